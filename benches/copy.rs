@@ -0,0 +1,71 @@
+//! Copy throughput for dense and sparse files, run with `cargo bench --bench copy`.
+//!
+//! Files are created in the directory given by `COPY_BENCH_DIR`, or the system temporary
+//! directory, so that different filesystems can be compared.
+
+use cosmic_files::operation::recursive::{Context, Method};
+use cosmic_files::operation::{Controller, Preserve};
+use std::error::Error;
+use std::fs::{self, File};
+use std::io::{Seek, SeekFrom, Write};
+use std::path::Path;
+use std::time::{Duration, Instant};
+
+const LEN: u64 = 256 * 1024 * 1024;
+const ITERATIONS: u32 = 5;
+
+fn create_dense(path: &Path) -> std::io::Result<()> {
+    let mut file = File::create(path)?;
+    let chunk = vec![0x42; 1024 * 1024];
+    for _ in 0..LEN / chunk.len() as u64 {
+        file.write_all(&chunk)?;
+    }
+    file.sync_all()
+}
+
+fn create_sparse(path: &Path) -> std::io::Result<()> {
+    let mut file = File::create(path)?;
+    file.set_len(LEN)?;
+    // A few small data segments spread through the file
+    for i in 0..4 {
+        file.seek(SeekFrom::Start(i * LEN / 4))?;
+        file.write_all(&[0x42; 4096])?;
+    }
+    file.sync_all()
+}
+
+async fn bench(name: &str, from: &Path, dir: &Path) -> Result<(), Box<dyn Error>> {
+    let to = dir.join(format!("{name}-copy"));
+    let mut total = Duration::ZERO;
+    for _ in 0..ITERATIONS {
+        _ = fs::remove_file(&to);
+        let start = Instant::now();
+        let mut context = Context::new(Controller::default()).preserve(Preserve::NONE);
+        context
+            .recursive_copy_or_move([(from.to_path_buf(), to.clone())], Method::Copy)
+            .await?;
+        total += start.elapsed();
+    }
+    fs::remove_file(&to)?;
+
+    let average = total / ITERATIONS;
+    let mib_per_sec = (LEN as f64 / (1024.0 * 1024.0)) / average.as_secs_f64();
+    println!("{name}: {average:?} per copy, {mib_per_sec:.0} MiB/s");
+    Ok(())
+}
+
+#[compio::main]
+async fn main() -> Result<(), Box<dyn Error>> {
+    let parent = std::env::var_os("COPY_BENCH_DIR").map_or_else(std::env::temp_dir, Into::into);
+    let dir = tempfile::tempdir_in(parent)?;
+
+    let dense = dir.path().join("dense");
+    create_dense(&dense)?;
+    bench("dense", &dense, dir.path()).await?;
+
+    let sparse = dir.path().join("sparse");
+    create_sparse(&sparse)?;
+    bench("sparse", &sparse, dir.path()).await?;
+
+    Ok(())
+}
@@ -1,16 +1,49 @@
 // SPDX-License-Identifier: GPL-3.0-only
 // Implementation of https://www.freedesktop.org/wiki/Specifications/file-manager-interface/
 
-#![allow(dead_code, non_snake_case)]
+#![allow(non_snake_case)]
 
+use cosmic_files::dbus::{DBUS_NAME, DBUS_PATH, FILE_MANAGER_INTERFACE};
 use std::process;
 
 pub struct FileManager;
 
 impl FileManager {
+    /// Passes the call on to the running file manager, returning false if there is none
+    fn forward(&self, method: &str, uris: &[&str], startup_id: &str) -> zbus::Result<bool> {
+        let connection = zbus::blocking::Connection::session()?;
+        let dbus = zbus::blocking::fdo::DBusProxy::new(&connection)?;
+        if !dbus.name_has_owner(DBUS_NAME.try_into()?)? {
+            return Ok(false);
+        }
+        connection.call_method(
+            Some(DBUS_NAME),
+            DBUS_PATH,
+            Some(FILE_MANAGER_INTERFACE),
+            method,
+            &(uris, startup_id),
+        )?;
+        Ok(true)
+    }
+
     //TODO: return error?
-    fn open(&self, uris: &[&str], _startup_id: &str) {
-        match process::Command::new("cosmic-files").args(uris).spawn() {
+    fn open(&self, method: &str, args: &[&str], uris: &[&str], startup_id: &str) {
+        match self.forward(method, uris, startup_id) {
+            Ok(true) => return,
+            Ok(false) => {}
+            Err(err) => {
+                log::warn!("failed to forward {method} to cosmic-files: {err}");
+            }
+        }
+
+        let mut command = process::Command::new("cosmic-files");
+        command.args(args).args(uris);
+        // Lets the new window take focus
+        if !startup_id.is_empty() {
+            command.env("XDG_ACTIVATION_TOKEN", startup_id);
+            command.env("DESKTOP_STARTUP_ID", startup_id);
+        }
+        match command.spawn() {
             Ok(mut child) => {
                 log::info!("spawned cosmic-files with id {:?}", child.id());
                 match child.wait() {
@@ -37,17 +70,17 @@ impl FileManager {
 #[zbus::interface(name = "org.freedesktop.FileManager1")]
 impl FileManager {
     fn ShowFolders(&self, URIs: Vec<&str>, StartupId: &str) {
-        log::warn!("ShowFolders {:?} {:?}", URIs, StartupId);
-        self.open(&URIs, StartupId)
+        log::info!("ShowFolders {:?} {:?}", URIs, StartupId);
+        self.open("ShowFolders", &[], &URIs, StartupId)
     }
 
     fn ShowItems(&self, URIs: Vec<&str>, StartupId: &str) {
-        log::warn!("ShowItems {:?} {:?}", URIs, StartupId);
-        self.open(&URIs, StartupId)
+        log::info!("ShowItems {:?} {:?}", URIs, StartupId);
+        self.open("ShowItems", &["--select"], &URIs, StartupId)
     }
 
     fn ShowItemProperties(&self, URIs: Vec<&str>, StartupId: &str) {
-        log::warn!("ShowItemProperties {:?} {:?}", URIs, StartupId);
-        self.open(&URIs, StartupId)
+        log::info!("ShowItemProperties {:?} {:?}", URIs, StartupId);
+        self.open("ShowItemProperties", &["--properties"], &URIs, StartupId)
     }
 }
@@ -0,0 +1,670 @@
+// SPDX-License-Identifier: GPL-3.0-only
+// Implementation of https://flatpak.github.io/xdg-desktop-portal/docs/doc-org.freedesktop.impl.portal.FileChooser.html
+
+use cosmic_files::dialog::{
+    DialogChoice, DialogChoiceOption, DialogFilter, DialogFilterPattern, DialogKind,
+};
+use std::ffi::OsStr;
+use std::os::unix::ffi::OsStrExt;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
+use tokio::sync::{mpsc, oneshot};
+use zbus::object_server::ObjectServer;
+use zbus::zvariant::{DeserializeDict, OwnedObjectPath, SerializeDict, Type};
+
+pub const DBUS_NAME: &str = "org.freedesktop.impl.portal.desktop.cosmic.files";
+pub const DBUS_PATH: &str = "/org/freedesktop/portal/desktop";
+
+pub const RESPONSE_SUCCESS: u32 = 0;
+pub const RESPONSE_CANCELLED: u32 = 1;
+pub const RESPONSE_OTHER: u32 = 2;
+
+/// Filter label and its patterns, each tagged 0 for a glob or 1 for a MIME type
+pub type Filter = (String, Vec<(u32, String)>);
+
+/// Choice ID, label, options as (ID, label) pairs and the initial selection.
+///
+/// A choice without options is a check box whose selection is "true" or "false".
+pub type Choice = (String, String, Vec<(String, String)>, String);
+
+#[derive(Debug, Default, DeserializeDict, SerializeDict, Type)]
+#[zvariant(signature = "a{sv}")]
+pub struct OpenFileOptions {
+    pub accept_label: Option<String>,
+    pub modal: Option<bool>,
+    pub multiple: Option<bool>,
+    pub directory: Option<bool>,
+    pub filters: Option<Vec<Filter>>,
+    pub current_filter: Option<Filter>,
+    pub choices: Option<Vec<Choice>>,
+    pub current_folder: Option<Vec<u8>>,
+}
+
+#[derive(Debug, Default, DeserializeDict, SerializeDict, Type)]
+#[zvariant(signature = "a{sv}")]
+pub struct SaveFileOptions {
+    pub accept_label: Option<String>,
+    pub modal: Option<bool>,
+    pub filters: Option<Vec<Filter>>,
+    pub current_filter: Option<Filter>,
+    pub choices: Option<Vec<Choice>>,
+    pub current_name: Option<String>,
+    pub current_folder: Option<Vec<u8>>,
+    pub current_file: Option<Vec<u8>>,
+}
+
+#[derive(Debug, Default, DeserializeDict, SerializeDict, Type)]
+#[zvariant(signature = "a{sv}")]
+pub struct SaveFilesOptions {
+    pub accept_label: Option<String>,
+    pub modal: Option<bool>,
+    pub choices: Option<Vec<Choice>>,
+    pub current_folder: Option<Vec<u8>>,
+    pub files: Option<Vec<Vec<u8>>>,
+}
+
+#[derive(Debug, Default, DeserializeDict, SerializeDict, Type)]
+#[zvariant(signature = "a{sv}")]
+pub struct Results {
+    pub uris: Vec<String>,
+    pub choices: Option<Vec<(String, String)>>,
+    pub current_filter: Option<Filter>,
+}
+
+/// A dialog requested over D-Bus, answered with [`PortalRequest::respond`]
+#[derive(Debug)]
+pub struct PortalRequest {
+    pub handle: OwnedObjectPath,
+    pub app_id: String,
+    pub title: String,
+    pub accept_label: Option<String>,
+    pub kind: DialogKind,
+    pub path_opt: Option<PathBuf>,
+    pub filters: Vec<DialogFilter>,
+    pub filter_selected: Option<usize>,
+    pub choices: Vec<DialogChoice>,
+    reply: Mutex<Option<oneshot::Sender<PortalResponse>>>,
+}
+
+impl PortalRequest {
+    /// Sends the response back to the caller. Only the first response is used.
+    pub fn respond(&self, response: PortalResponse) {
+        if let Some(reply) = self.reply.lock().unwrap().take() {
+            let _ = reply.send(response);
+        }
+    }
+}
+
+#[derive(Debug)]
+pub enum PortalResponse {
+    Cancelled,
+    Selected {
+        paths: Vec<PathBuf>,
+        choices: Vec<DialogChoice>,
+        filter: Option<DialogFilter>,
+    },
+}
+
+#[derive(Clone, Debug)]
+pub enum PortalEvent {
+    Request(Arc<PortalRequest>),
+    /// The caller closed the request with the given handle
+    Close(OwnedObjectPath),
+}
+
+/// Portal paths are NUL terminated byte strings
+fn path_from_bytes(bytes: &[u8]) -> Option<PathBuf> {
+    let bytes = bytes.strip_suffix(&[0]).unwrap_or(bytes);
+    (!bytes.is_empty()).then(|| PathBuf::from(OsStr::from_bytes(bytes)))
+}
+
+fn filter_from_portal((label, patterns): &Filter) -> DialogFilter {
+    DialogFilter {
+        label: label.clone(),
+        patterns: patterns
+            .iter()
+            .filter_map(|(kind, pattern)| match kind {
+                0 => Some(DialogFilterPattern::Glob(pattern.clone())),
+                1 => Some(DialogFilterPattern::Mime(pattern.clone())),
+                _ => {
+                    log::warn!("unsupported filter pattern kind {kind} for {pattern:?}");
+                    None
+                }
+            })
+            .collect(),
+    }
+}
+
+fn filter_to_portal(filter: &DialogFilter) -> Filter {
+    (
+        filter.label.clone(),
+        filter
+            .patterns
+            .iter()
+            .map(|pattern| match pattern {
+                DialogFilterPattern::Glob(glob) => (0, glob.clone()),
+                DialogFilterPattern::Mime(mime) => (1, mime.clone()),
+            })
+            .collect(),
+    )
+}
+
+/// Maps portal filters onto dialog filters and the index of the current one.
+///
+/// A current filter that is not in the list is appended, so it still applies.
+fn filters_from_portal(
+    filters: Option<Vec<Filter>>,
+    current_filter: Option<Filter>,
+) -> (Vec<DialogFilter>, Option<usize>) {
+    let mut filters: Vec<DialogFilter> = filters.iter().flatten().map(filter_from_portal).collect();
+    let selected = current_filter.map(|current| {
+        filters
+            .iter()
+            .position(|filter| filter.label == current.0)
+            .unwrap_or_else(|| {
+                filters.push(filter_from_portal(&current));
+                filters.len() - 1
+            })
+    });
+    (filters, selected)
+}
+
+fn choice_from_portal((id, label, options, initial): &Choice) -> DialogChoice {
+    if options.is_empty() {
+        DialogChoice::CheckBox {
+            id: id.clone(),
+            label: label.clone(),
+            value: initial == "true",
+        }
+    } else {
+        DialogChoice::ComboBox {
+            id: id.clone(),
+            label: label.clone(),
+            options: options
+                .iter()
+                .map(|(id, label)| DialogChoiceOption {
+                    id: id.clone(),
+                    label: label.clone(),
+                })
+                .collect(),
+            selected: options.iter().position(|(id, _)| id == initial),
+        }
+    }
+}
+
+fn choice_to_portal(choice: &DialogChoice) -> Option<(String, String)> {
+    match choice {
+        DialogChoice::CheckBox { id, value, .. } => Some((id.clone(), value.to_string())),
+        DialogChoice::ComboBox {
+            id,
+            options,
+            selected,
+            ..
+        } => Some((id.clone(), options.get((*selected)?)?.id.clone())),
+    }
+}
+
+fn choices_from_portal(choices: Option<Vec<Choice>>) -> Vec<DialogChoice> {
+    choices.iter().flatten().map(choice_from_portal).collect()
+}
+
+fn uri(path: &Path) -> Option<String> {
+    match url::Url::from_file_path(path) {
+        Ok(url) => Some(url.into()),
+        Err(()) => {
+            log::warn!("failed to convert {} to a URI", path.display());
+            None
+        }
+    }
+}
+
+fn results(response: Option<PortalResponse>) -> (u32, Results) {
+    match response {
+        Some(PortalResponse::Selected {
+            paths,
+            choices,
+            filter,
+        }) => (
+            RESPONSE_SUCCESS,
+            Results {
+                uris: paths.iter().filter_map(|path| uri(path)).collect(),
+                choices: Some(choices.iter().filter_map(choice_to_portal).collect()),
+                current_filter: filter.as_ref().map(filter_to_portal),
+            },
+        ),
+        Some(PortalResponse::Cancelled) => (RESPONSE_CANCELLED, Results::default()),
+        None => (RESPONSE_OTHER, Results::default()),
+    }
+}
+
+/// Request object exported at the handle of every pending dialog
+struct Request {
+    handle: OwnedObjectPath,
+    events: mpsc::UnboundedSender<PortalEvent>,
+}
+
+#[zbus::interface(name = "org.freedesktop.impl.portal.Request")]
+impl Request {
+    async fn close(&self) {
+        let _ = self.events.send(PortalEvent::Close(self.handle.clone()));
+    }
+}
+
+pub struct FileChooser {
+    events: mpsc::UnboundedSender<PortalEvent>,
+}
+
+impl FileChooser {
+    pub fn new(events: mpsc::UnboundedSender<PortalEvent>) -> Self {
+        Self { events }
+    }
+
+    #[allow(clippy::too_many_arguments)]
+    async fn run(
+        &self,
+        server: &ObjectServer,
+        handle: OwnedObjectPath,
+        app_id: String,
+        title: String,
+        accept_label: Option<String>,
+        kind: DialogKind,
+        path_opt: Option<PathBuf>,
+        (filters, filter_selected): (Vec<DialogFilter>, Option<usize>),
+        choices: Vec<DialogChoice>,
+    ) -> Option<PortalResponse> {
+        let request = Request {
+            handle: handle.clone(),
+            events: self.events.clone(),
+        };
+        if let Err(err) = server.at(&handle, request).await {
+            log::warn!("failed to export request {handle}: {err}");
+        }
+
+        let (reply, response) = oneshot::channel();
+        let request = PortalRequest {
+            handle: handle.clone(),
+            app_id,
+            title,
+            accept_label,
+            kind,
+            path_opt,
+            filters,
+            filter_selected,
+            choices,
+            reply: Mutex::new(Some(reply)),
+        };
+        let response = match self.events.send(PortalEvent::Request(Arc::new(request))) {
+            Ok(()) => response.await.ok(),
+            Err(err) => {
+                log::warn!("failed to queue request {handle}: {err}");
+                None
+            }
+        };
+
+        if let Err(err) = server.remove::<Request, _>(&handle).await {
+            log::warn!("failed to remove request {handle}: {err}");
+        }
+        response
+    }
+}
+
+#[zbus::interface(name = "org.freedesktop.impl.portal.FileChooser")]
+impl FileChooser {
+    async fn open_file(
+        &self,
+        #[zbus(object_server)] server: &ObjectServer,
+        handle: OwnedObjectPath,
+        app_id: String,
+        parent_window: String,
+        title: String,
+        options: OpenFileOptions,
+    ) -> (u32, Results) {
+        log::info!("open file for {app_id:?} with parent {parent_window:?}: {options:?}");
+        let kind = match (
+            options.directory.unwrap_or(false),
+            options.multiple.unwrap_or(false),
+        ) {
+            (false, false) => DialogKind::OpenFile,
+            (false, true) => DialogKind::OpenMultipleFiles,
+            (true, false) => DialogKind::OpenFolder,
+            (true, true) => DialogKind::OpenMultipleFolders,
+        };
+        let path_opt = options.current_folder.as_deref().and_then(path_from_bytes);
+        let response = self
+            .run(
+                server,
+                handle,
+                app_id,
+                title,
+                options.accept_label,
+                kind,
+                path_opt,
+                filters_from_portal(options.filters, options.current_filter),
+                choices_from_portal(options.choices),
+            )
+            .await;
+        results(response)
+    }
+
+    async fn save_file(
+        &self,
+        #[zbus(object_server)] server: &ObjectServer,
+        handle: OwnedObjectPath,
+        app_id: String,
+        parent_window: String,
+        title: String,
+        options: SaveFileOptions,
+    ) -> (u32, Results) {
+        log::info!("save file for {app_id:?} with parent {parent_window:?}: {options:?}");
+        // An existing file to save over takes precedence over the suggested folder and name
+        let current_file = options.current_file.as_deref().and_then(path_from_bytes);
+        let filename = current_file
+            .as_deref()
+            .and_then(Path::file_name)
+            .map(|name| name.to_string_lossy().into_owned())
+            .or(options.current_name)
+            .unwrap_or_default();
+        let path_opt = current_file
+            .as_deref()
+            .and_then(Path::parent)
+            .map(Path::to_path_buf)
+            .or_else(|| options.current_folder.as_deref().and_then(path_from_bytes));
+        let response = self
+            .run(
+                server,
+                handle,
+                app_id,
+                title,
+                options.accept_label,
+                DialogKind::SaveFile { filename },
+                path_opt,
+                filters_from_portal(options.filters, options.current_filter),
+                choices_from_portal(options.choices),
+            )
+            .await;
+        results(response)
+    }
+
+    async fn save_files(
+        &self,
+        #[zbus(object_server)] server: &ObjectServer,
+        handle: OwnedObjectPath,
+        app_id: String,
+        parent_window: String,
+        title: String,
+        options: SaveFilesOptions,
+    ) -> (u32, Results) {
+        log::info!("save files for {app_id:?} with parent {parent_window:?}: {options:?}");
+        let filenames = options
+            .files
+            .iter()
+            .flatten()
+            .filter_map(|file| path_from_bytes(file))
+            .filter_map(|file| Some(file.file_name()?.to_string_lossy().into_owned()))
+            .collect();
+        let path_opt = options.current_folder.as_deref().and_then(path_from_bytes);
+        let response = self
+            .run(
+                server,
+                handle,
+                app_id,
+                title,
+                options.accept_label,
+                DialogKind::SaveFiles { filenames },
+                path_opt,
+                (Vec::new(), None),
+                choices_from_portal(options.choices),
+            )
+            .await;
+        results(response)
+    }
+}
+
+/// Serves the file chooser on the connection built by `builder`.
+///
+/// Requests are sent to `events` and the connection must be kept alive to keep serving them.
+pub async fn serve(
+    builder: zbus::connection::Builder<'_>,
+    events: mpsc::UnboundedSender<PortalEvent>,
+) -> zbus::Result<zbus::Connection> {
+    builder
+        .name(DBUS_NAME)?
+        .serve_at(DBUS_PATH, FileChooser::new(events))?
+        .build()
+        .await
+}
+
+#[cfg(test)]
+mod tests {
+    use cosmic_files::dialog::{DialogChoice, DialogKind};
+    use std::path::PathBuf;
+    use tokio::sync::mpsc;
+    use zbus::zvariant::ObjectPath;
+
+    use super::{
+        DBUS_NAME, DBUS_PATH, OpenFileOptions, PortalEvent, PortalResponse, RESPONSE_CANCELLED,
+        RESPONSE_SUCCESS, Results, SaveFileOptions, SaveFilesOptions, choice_from_portal,
+        choice_to_portal, filters_from_portal, path_from_bytes, serve,
+    };
+    use cosmic_files::test_bus::PrivateBus;
+
+    async fn proxy(bus: &PrivateBus) -> zbus::Result<zbus::Proxy<'static>> {
+        let client = bus.connect()?.build().await?;
+        zbus::Proxy::new(
+            &client,
+            DBUS_NAME,
+            DBUS_PATH,
+            "org.freedesktop.impl.portal.FileChooser",
+        )
+        .await
+    }
+
+    fn handle() -> ObjectPath<'static> {
+        ObjectPath::from_static_str_unchecked("/org/freedesktop/portal/desktop/request/1_1/test")
+    }
+
+    #[test]
+    fn maps_paths_filters_and_choices() {
+        assert_eq!(path_from_bytes(b"/tmp/a\0"), Some(PathBuf::from("/tmp/a")));
+        assert_eq!(path_from_bytes(b"\0"), None);
+
+        let images = ("Images".to_string(), vec![(1, "image/*".to_string())]);
+        let (filters, selected) = filters_from_portal(
+            Some(vec![
+                ("All".to_string(), vec![(0, "*".to_string())]),
+                images.clone(),
+            ]),
+            Some(images),
+        );
+        assert_eq!(filters.len(), 2);
+        assert_eq!(selected, Some(1));
+
+        // A current filter outside the list is still applied
+        let text = ("Text".to_string(), vec![(0, "*.txt".to_string())]);
+        let (filters, selected) = filters_from_portal(None, Some(text));
+        assert_eq!(filters[0].label, "Text");
+        assert_eq!(selected, Some(0));
+
+        let combo = (
+            "encoding".to_string(),
+            "Encoding".to_string(),
+            vec![
+                ("utf8".to_string(), "UTF-8".to_string()),
+                ("latin1".to_string(), "Latin-1".to_string()),
+            ],
+            "latin1".to_string(),
+        );
+        let choice = choice_from_portal(&combo);
+        assert!(matches!(
+            choice,
+            DialogChoice::ComboBox {
+                selected: Some(1),
+                ..
+            }
+        ));
+        assert_eq!(
+            choice_to_portal(&choice),
+            Some(("encoding".to_string(), "latin1".to_string()))
+        );
+
+        let check = (
+            "reencode".to_string(),
+            "Re-encode".to_string(),
+            Vec::new(),
+            "true".to_string(),
+        );
+        let choice = choice_from_portal(&check);
+        assert!(matches!(choice, DialogChoice::CheckBox { value: true, .. }));
+        assert_eq!(
+            choice_to_portal(&choice),
+            Some(("reencode".to_string(), "true".to_string()))
+        );
+    }
+
+    #[tokio::test]
+    async fn open_file_over_private_bus() -> zbus::Result<()> {
+        let Some(bus) = PrivateBus::start() else {
+            return Ok(());
+        };
+        let (events, mut requests) = mpsc::unbounded_channel();
+        let _server = serve(bus.connect()?, events).await?;
+
+        let responder = tokio::spawn(async move {
+            let Some(PortalEvent::Request(request)) = requests.recv().await else {
+                panic!("expected a request");
+            };
+            assert_eq!(request.app_id, "com.example.App");
+            assert_eq!(request.title, "Pick images");
+            assert!(matches!(request.kind, DialogKind::OpenMultipleFiles));
+            assert_eq!(request.path_opt, Some(PathBuf::from("/tmp")));
+            assert_eq!(request.filter_selected, Some(1));
+            request.respond(PortalResponse::Selected {
+                paths: vec![PathBuf::from("/tmp/a b.png")],
+                choices: request.choices.clone(),
+                filter: request.filters.get(1).cloned(),
+            });
+        });
+
+        let images = ("Images".to_string(), vec![(1, "image/*".to_string())]);
+        let options = OpenFileOptions {
+            multiple: Some(true),
+            filters: Some(vec![
+                ("All".to_string(), vec![(0, "*".to_string())]),
+                images.clone(),
+            ]),
+            current_filter: Some(images),
+            choices: Some(vec![(
+                "reencode".to_string(),
+                "Re-encode".to_string(),
+                Vec::new(),
+                "true".to_string(),
+            )]),
+            current_folder: Some(b"/tmp\0".to_vec()),
+            ..Default::default()
+        };
+        let (response, results): (u32, Results) = proxy(&bus)
+            .await?
+            .call(
+                "OpenFile",
+                &(handle(), "com.example.App", "", "Pick images", options),
+            )
+            .await?;
+        responder.await.unwrap();
+
+        assert_eq!(response, RESPONSE_SUCCESS);
+        assert_eq!(results.uris, ["file:///tmp/a%20b.png"]);
+        assert_eq!(
+            results.choices,
+            Some(vec![("reencode".to_string(), "true".to_string())])
+        );
+        assert_eq!(
+            results.current_filter.map(|filter| filter.0),
+            Some("Images".to_string())
+        );
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn save_file_over_private_bus() -> zbus::Result<()> {
+        let Some(bus) = PrivateBus::start() else {
+            return Ok(());
+        };
+        let (events, mut requests) = mpsc::unbounded_channel();
+        let _server = serve(bus.connect()?, events).await?;
+
+        let responder = tokio::spawn(async move {
+            let Some(PortalEvent::Request(request)) = requests.recv().await else {
+                panic!("expected a request");
+            };
+            match &request.kind {
+                DialogKind::SaveFile { filename } => assert_eq!(filename, "notes.txt"),
+                kind => panic!("unexpected kind {kind:?}"),
+            }
+            assert_eq!(
+                request.path_opt,
+                Some(PathBuf::from("/home/user/Documents"))
+            );
+            request.respond(PortalResponse::Cancelled);
+        });
+
+        let options = SaveFileOptions {
+            current_name: Some("untitled.txt".to_string()),
+            current_folder: Some(b"/tmp\0".to_vec()),
+            current_file: Some(b"/home/user/Documents/notes.txt\0".to_vec()),
+            ..Default::default()
+        };
+        let (response, results): (u32, Results) = proxy(&bus)
+            .await?
+            .call("SaveFile", &(handle(), "com.example.App", "", "", options))
+            .await?;
+        responder.await.unwrap();
+
+        assert_eq!(response, RESPONSE_CANCELLED);
+        assert!(results.uris.is_empty());
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn save_files_over_private_bus() -> zbus::Result<()> {
+        let Some(bus) = PrivateBus::start() else {
+            return Ok(());
+        };
+        let (events, mut requests) = mpsc::unbounded_channel();
+        let _server = serve(bus.connect()?, events).await?;
+
+        let responder = tokio::spawn(async move {
+            let Some(PortalEvent::Request(request)) = requests.recv().await else {
+                panic!("expected a request");
+            };
+            match &request.kind {
+                DialogKind::SaveFiles { filenames } => assert_eq!(filenames, &["a.txt", "b.txt"]),
+                kind => panic!("unexpected kind {kind:?}"),
+            }
+            request.respond(PortalResponse::Selected {
+                paths: vec![
+                    PathBuf::from("/tmp/out/a.txt"),
+                    PathBuf::from("/tmp/out/b.txt"),
+                ],
+                choices: Vec::new(),
+                filter: None,
+            });
+        });
+
+        let options = SaveFilesOptions {
+            files: Some(vec![b"a.txt\0".to_vec(), b"/home/user/b.txt\0".to_vec()]),
+            ..Default::default()
+        };
+        let (response, results): (u32, Results) = proxy(&bus)
+            .await?
+            .call("SaveFiles", &(handle(), "com.example.App", "", "", options))
+            .await?;
+        responder.await.unwrap();
+
+        assert_eq!(response, RESPONSE_SUCCESS);
+        assert_eq!(
+            results.uris,
+            ["file:///tmp/out/a.txt", "file:///tmp/out/b.txt"]
+        );
+        Ok(())
+    }
+}
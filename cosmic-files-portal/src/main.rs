@@ -0,0 +1,220 @@
+// SPDX-License-Identifier: GPL-3.0-only
+
+use cosmic::app::{self, Core, Settings, Task};
+use cosmic::iced::futures::{self, SinkExt};
+use cosmic::iced::{Subscription, stream, window};
+use cosmic::{Application, Element, executor, widget};
+use cosmic_files::dialog::{Dialog, DialogMessage, DialogResult, DialogSettings};
+use std::any::TypeId;
+use std::collections::VecDeque;
+use std::sync::Arc;
+use tokio::sync::mpsc;
+use tracing_subscriber::layer::SubscriberExt;
+use tracing_subscriber::util::SubscriberInitExt;
+
+use file_chooser::{PortalEvent, PortalRequest, PortalResponse};
+
+mod file_chooser;
+
+fn main() -> Result<(), Box<dyn std::error::Error>> {
+    let log_format = tracing_subscriber::fmt::format()
+        .pretty()
+        .without_time()
+        .with_line_number(true)
+        .with_file(true)
+        .with_target(false)
+        .with_thread_names(true);
+
+    let log_layer = tracing_subscriber::fmt::Layer::default()
+        .with_writer(std::io::stderr)
+        .event_format(log_format);
+
+    tracing_subscriber::registry()
+        .with(tracing_subscriber::EnvFilter::from_env("RUST_LOG"))
+        .with(log_layer)
+        .init();
+
+    let settings = Settings::default()
+        .no_main_window(true)
+        .exit_on_close(false);
+    app::run::<App>(settings, ())?;
+    Ok(())
+}
+
+#[derive(Clone, Debug)]
+pub enum Message {
+    DialogMessage(DialogMessage),
+    DialogResult(DialogResult),
+    Portal(PortalEvent),
+}
+
+pub struct App {
+    core: Core,
+    /// Request shown in the open dialog. Dialogs are shown one at a time.
+    current_opt: Option<(Arc<PortalRequest>, Dialog<Message>)>,
+    queue: VecDeque<Arc<PortalRequest>>,
+}
+
+impl App {
+    fn next_request(&mut self) -> Task<Message> {
+        if self.current_opt.is_some() {
+            return Task::none();
+        }
+        let Some(request) = self.queue.pop_front() else {
+            return Task::none();
+        };
+
+        let mut settings = DialogSettings::new()
+            .kind(request.kind.clone())
+            .preview(true);
+        if !request.app_id.is_empty() {
+            settings = settings.app_id(request.app_id.clone());
+        }
+        if let Some(path) = &request.path_opt {
+            settings = settings.path(path.clone());
+        }
+        let (mut dialog, task) =
+            Dialog::new(settings, Message::DialogMessage, Message::DialogResult);
+        let mut tasks = vec![task];
+        if !request.title.is_empty() {
+            tasks.push(dialog.set_title(request.title.clone()));
+        }
+        if let Some(accept_label) = &request.accept_label {
+            dialog.set_accept_label(accept_label);
+        }
+        dialog.set_choices(request.choices.clone());
+        if !request.filters.is_empty() {
+            tasks.push(dialog.set_filters(request.filters.clone(), request.filter_selected));
+        }
+        self.current_opt = Some((request, dialog));
+        Task::batch(tasks)
+    }
+}
+
+impl Application for App {
+    type Executor = executor::Default;
+    type Flags = ();
+    type Message = Message;
+
+    const APP_ID: &'static str = "com.system76.CosmicFilesPortal";
+
+    fn core(&self) -> &Core {
+        &self.core
+    }
+
+    fn core_mut(&mut self) -> &mut Core {
+        &mut self.core
+    }
+
+    fn init(core: Core, _flags: Self::Flags) -> (Self, Task<Message>) {
+        (
+            Self {
+                core,
+                current_opt: None,
+                queue: VecDeque::new(),
+            },
+            Task::none(),
+        )
+    }
+
+    fn update(&mut self, message: Message) -> Task<Message> {
+        match message {
+            Message::DialogMessage(dialog_message) => {
+                if let Some((_, dialog)) = &mut self.current_opt {
+                    return dialog.update(dialog_message);
+                }
+            }
+            Message::DialogResult(result) => {
+                if let Some((request, dialog)) = self.current_opt.take() {
+                    let response = match result {
+                        DialogResult::Cancel => PortalResponse::Cancelled,
+                        DialogResult::Open(paths) => {
+                            let (filters, filter_selected) = dialog.filters();
+                            PortalResponse::Selected {
+                                paths,
+                                choices: dialog.choices().to_vec(),
+                                filter: filter_selected
+                                    .and_then(|index| filters.get(index))
+                                    .cloned(),
+                            }
+                        }
+                    };
+                    request.respond(response);
+                    return self.next_request();
+                }
+            }
+            Message::Portal(PortalEvent::Request(request)) => {
+                self.queue.push_back(request);
+                return self.next_request();
+            }
+            Message::Portal(PortalEvent::Close(handle)) => {
+                self.queue.retain(|request| {
+                    if request.handle == handle {
+                        request.respond(PortalResponse::Cancelled);
+                        false
+                    } else {
+                        true
+                    }
+                });
+                if let Some((request, dialog)) = self
+                    .current_opt
+                    .take_if(|(request, _)| request.handle == handle)
+                {
+                    request.respond(PortalResponse::Cancelled);
+                    return Task::batch([window::close(dialog.window_id()), self.next_request()]);
+                }
+            }
+        }
+
+        Task::none()
+    }
+
+    fn view_window(&self, window_id: window::Id) -> Element<'_, Message> {
+        match &self.current_opt {
+            Some((_, dialog)) => dialog.view(window_id),
+            None => widget::space::horizontal().into(),
+        }
+    }
+
+    fn view(&self) -> Element<'_, Message> {
+        widget::space::horizontal().into()
+    }
+
+    fn subscription(&self) -> Subscription<Message> {
+        struct PortalSubscription;
+
+        let mut subscriptions = vec![Subscription::run_with(
+            TypeId::of::<PortalSubscription>(),
+            |_| {
+                stream::channel(
+                    16,
+                    |mut output: futures::channel::mpsc::Sender<Message>| async move {
+                        let (events, mut requests) = mpsc::unbounded_channel();
+                        let connection_res = match zbus::connection::Builder::session() {
+                            Ok(builder) => file_chooser::serve(builder, events).await,
+                            Err(err) => Err(err),
+                        };
+                        // The connection has to be kept alive to keep serving requests
+                        let _connection = match connection_res {
+                            Ok(connection) => connection,
+                            Err(err) => {
+                                log::error!("failed to serve file chooser portal: {err}");
+                                return std::future::pending().await;
+                            }
+                        };
+                        while let Some(event) = requests.recv().await {
+                            if output.send(Message::Portal(event)).await.is_err() {
+                                break;
+                            }
+                        }
+                        std::future::pending().await
+                    },
+                )
+            },
+        )];
+        if let Some((_, dialog)) = &self.current_opt {
+            subscriptions.push(dialog.subscription());
+        }
+        Subscription::batch(subscriptions)
+    }
+}
@@ -206,6 +206,15 @@ impl Application for App {
             }
             column = column.push(button);
         }
+        {
+            let mut button = widget::button::standard("Save Files");
+            if self.dialog_opt.is_none() {
+                button = button.on_press(Message::DialogOpen(DialogKind::SaveFiles {
+                    filenames: vec!["test-1".to_string(), "test-2".to_string()],
+                }));
+            }
+            column = column.push(button);
+        }
         if let Some(result) = &self.result_opt {
             match result {
                 DialogResult::Cancel => {
@@ -22,6 +22,21 @@ trash-folder-icon = Trash folder icon
 icon-size-and-spacing = Icon size and spacing
 icon-size = Icon size
 grid-spacing = Grid spacing
+keep-aligned = Keep aligned
+keep-aligned-description = Pack icons into the grid without gaps
+stack-by-type = Stack files by type
+stack-documents = Documents ({$count})
+stack-images = Images ({$count})
+stack-music = Music ({$count})
+stack-videos = Videos ({$count})
+stack-archives = Archives ({$count})
+stack-other = Other files ({$count})
+desktop-output = Display {$output}
+desktop-output-folder = Folder
+desktop-output-sort = Sort
+desktop-folder = Desktop folder
+no-folder = No folder
+use-default = Default
 
 # List view
 name = Name
@@ -52,6 +67,20 @@ create-archive = Create archive
 copy-to-title = Select copy destination
 copy-to-button-label = Copy
 
+## Sync Folders Dialog
+sync-folders-title = Select folder to synchronize with
+sync-folders-button-label = Select
+sync-folders-description = Synchronize "{$from}" with "{$to}"
+sync-mirror = Mirror: make the second folder match the first, removing extra items
+sync-two-way = Two-way: copy missing items both ways and keep the newest version of each file
+sync-compare-contents = Compare file contents instead of modification times
+sync-comparing = Comparing folders...
+sync-up-to-date = The folders are already synchronized.
+sync-action-copy = Copy {$path}
+sync-action-update = Update {$path}
+sync-action-delete = Move {$path} to the trash
+synchronize = Synchronize
+
 ## Extract Dialog
 extract-password-required = Password required
 extract-to = Extract To...
@@ -80,6 +109,12 @@ name-hidden = Names starting with "." will be hidden
 name-invalid = Name cannot be "{$filename}"
 name-no-slashes = Name cannot contain slashes
 
+## New Tag Dialog
+create-new-tag = Create new tag
+tag-name = Tag name
+tag-name-invalid = Tag names cannot contain commas
+tag-name-whitespace = Tag names cannot contain spaces
+
 ## Open/Save Dialog
 cancel = Cancel
 create = Create
@@ -89,10 +124,12 @@ open-folder = Open folder
 open-in-new-tab = Open in new tab
 open-in-new-window = Open in new window
 open-item-location = Open item location
+follow-link = Follow link
 open-multiple-files = Open multiple files
 open-multiple-folders = Open multiple folders
 save = Save
 save-file = Save file
+save-files = Save files
 
 ## Open With Dialog
 open-with-title = How do you want to open "{$name}"?
@@ -106,6 +143,9 @@ context-action-confirm-warning = This will run on {$items} {$items ->
     *[other] items
   }.
 run = Run
+context-action-output-title = Output of "{$name}"
+context-action-no-output = The action finished without any output.
+close = Close
 
 ## Permanently delete Dialog
 selected-items = The {$items} selected items
@@ -125,12 +165,27 @@ sidebar-label = Sidebar label
 replace = Replace
 replace-title = "{$filename}" already exists in this location
 replace-warning = Do you want to replace it with the one you are saving? Replacing it will overwrite its content.
+replace-title-multiple = {$count} files already exist in this location
+replace-warning-multiple = Do you want to replace them with the ones you are saving? Replacing them will overwrite their content.
 replace-warning-operation = Do you want to replace it? Replacing it will overwrite its content.
 original-file = Original file
 replace-with = Replace with
 apply-to-all = Apply to all
 keep-both = Keep both
 skip = Skip
+replace-if-newer = Replace if newer
+replace-if-size-differs = Replace if size differs
+rename-existing = Rename existing
+compare = Compare
+files-identical = The files are identical.
+files-different = The files are different.
+merge = Merge
+merge-title = A folder named "{$filename}" already exists in this location
+merge-warning = Do you want to merge the folders? Files in both folders are handled as chosen below.
+original-folder = Original folder
+merge-with = Merge with
+merge-policy = Files in both folders
+merge-ask = Ask for each file
 
 ## Set as Executable and Launch Dialog
 set-executable-and-launch = Set as executable and launch
@@ -160,6 +215,18 @@ read-write = Read and write
 ### Mode 7
 read-write-execute = Read, write, and execute
 
+## Properties Dialog
+properties = Properties
+apply = Apply
+apply-to-contents = Apply to enclosed files and folders
+folder-access = Folder access
+file-access = File access
+unchanged = Unchanged
+user-not-found = No user with that name exists
+group-not-found = No group with that name exists
+special-bits = Special bits: {$bits}
+access-control-list = Access control list
+
 ## Favorite Path Error Dialog
 favorite-path-error = Error opening directory
 favorite-path-error-description =
@@ -212,7 +279,29 @@ progress-cancelled = {$percent}%, cancelled
 progress-failed = {$percent}%, failed
 progress-paused = {$percent}%, paused
 failed = Failed
+verification-failed = {$items} {$items ->
+        [one] copy does
+        *[other] copies do
+    } not match the original. The originals have been kept.
 complete = Complete
+queued = Queued, position {$position}
+move-earlier = Move earlier
+move-later = Move later
+interrupted-operations = Resume interrupted operations?
+interrupted-operations-body = These operations did not finish before the file manager last closed.
+discard = Discard
+transfer-files = {$done} of {$total} {$total ->
+        [one] file
+        *[other] files
+    }
+transfer-stats = {$speed}/s, {$eta} left, {$files}
+transfer-current = Copying {$name}
+file-transfer = {$size} in {$duration}
+show-file-history = Show copied files
+hide-file-history = Hide copied files
+unlimited = Unlimited
+bandwidth-limit = {$speed}/s
+bandwidth-limit-tooltip = Limit transfer speed
 compressing = Compressing {$items} {$items ->
         [one] item
         *[other] items
@@ -222,6 +311,7 @@ compressed = Compressed {$items} {$items ->
         *[other] items
     } from "{$from}" to "{$to}"
 copy_noun = Copy
+link_noun = Link
 pasted-image = Pasted Image
 pasted-text = Pasted Text
 pasted-video = Pasted Video
@@ -257,6 +347,59 @@ setting-executable-and-launching = Setting "{$name}" as executable and launching
 set-executable-and-launched = Set "{$name}" as executable and launched
 setting-permissions = Setting permissions for "{$name}" to {$mode}
 set-permissions = Set permissions for "{$name}" to {$mode}
+setting-properties = Setting properties of {$items} {$items ->
+        [one] item
+        *[other] items
+    } ({$progress})...
+set-properties = Set properties of {$items} {$items ->
+        [one] item
+        *[other] items
+    }
+setting-tag = {$enabled ->
+        [true] Adding
+        *[other] Removing
+    } tag "{$tag}" {$enabled ->
+        [true] to
+        *[other] from
+    } {$items} {$items ->
+        [one] item
+        *[other] items
+    }
+set-tag = {$enabled ->
+        [true] Added
+        *[other] Removed
+    } tag "{$tag}" {$enabled ->
+        [true] to
+        *[other] from
+    } {$items} {$items ->
+        [one] item
+        *[other] items
+    }
+synchronizing = Synchronizing "{$from}" with "{$to}" ({$progress})...
+synchronized = Synchronized "{$from}" with "{$to}"
+running-context-action = Running "{$name}" on {$items} {$items ->
+        [one] item
+        *[other] items
+    } ({$progress})...
+ran-context-action = Ran "{$name}" on {$items} {$items ->
+        [one] item
+        *[other] items
+    }
+linking = Creating {$hard ->
+        [true] hard links
+        *[other] links
+    } to {$items} {$items ->
+        [one] item
+        *[other] items
+    } from "{$from}" in "{$to}" ({$progress})...
+linked = Created {$hard ->
+        [true] hard links
+        *[other] links
+    } to {$items} {$items ->
+        [one] item
+        *[other] items
+    } from "{$from}" in "{$to}"
+hard-link-folder = Cannot create a hard link to the folder "{$name}"
 moving = Moving {$items} {$items ->
         [one] item
         *[other] items
@@ -291,6 +434,18 @@ restored = Restored {$items} {$items ->
         [one] item
         *[other] items
     } from {trash}
+transforming-image = {$transform ->
+        [rotate-left] Rotating "{$name}" left
+        [rotate-right] Rotating "{$name}" right
+        [flip-horizontal] Flipping "{$name}" horizontally
+        *[flip-vertical] Flipping "{$name}" vertically
+    }
+transformed-image = {$transform ->
+        [rotate-left] Rotated "{$name}" left
+        [rotate-right] Rotated "{$name}" right
+        [flip-horizontal] Flipped "{$name}" horizontally
+        *[flip-vertical] Flipped "{$name}" vertically
+    }
 unknown-folder = unknown folder
 
 ## Open with
@@ -303,18 +458,35 @@ show-details = Show details
 type = Type: {$mime}
 items = Items: {$items}
 item-size = Size: {$size}
+item-duration = Duration: {$duration}
 item-created = Created: {$created}
 item-modified = Modified: {$modified}
 item-accessed = Accessed: {$accessed}
+link-target = Link to: {$target}
+broken-link = The link target does not exist
 calculating = Calculating...
 checksum = {$kind} checksum
 calculate = Calculate
 error = Error
+load-more = Load more
 
 ## Settings
 settings = Settings
 single-click = Single click to open
 show-recents = Recents folder in the sidebar
+verify-copies = Verify copied files
+verify-copies-description = Compare checksums after copying. Moved files are only removed once their copies match.
+resume-copies = Resume interrupted copies
+resume-copies-description = Large files are copied to a hidden file first, so that copies can continue where they stopped.
+
+### Preserve
+preserve = Keep when copying
+preserve-times = Modification times
+preserve-permissions = Permissions
+preserve-ownership = Owner and group
+preserve-ownership-description = Only possible when running as an administrator.
+preserve-xattrs = Extended attributes
+preserve-xattrs-description = Includes tags and other information stored by apps.
 
 ### Appearance
 appearance = Appearance
@@ -339,17 +511,22 @@ eject = Eject
 extract-here = Extract
 new-file = New file...
 new-folder = New folder...
+new-tag = New tag...
 open-in-terminal = Open in terminal
 move-to = Move to...
 move-to-trash = Move to trash
 restore-from-trash = Restore from trash
 remove-from-sidebar = Remove from sidebar
 change-sidebar-label = Change sidebar label
+remove-tag = Remove tag
 sort-by-name = Sort by name
 sort-by-modified = Sort by modified
 sort-by-size = Sort by size
 sort-by-trashed = Sort by delete time
 remove-from-recents = Remove from recents
+arrange-by-name = Arrange by name
+arrange-by-type = Arrange by type
+arrange-by-modified = Arrange by date
 
 ## Desktop
 change-wallpaper = Change wallpaper...
@@ -363,6 +540,7 @@ file = File
 new-tab = New tab
 new-window = New window
 reload-folder = Reload folder
+sync-folders = Synchronize folders...
 rename = Rename...
 close-tab = Close tab
 quit = Quit
@@ -373,6 +551,8 @@ cut = Cut
 copy = Copy
 copy-path = Copy path
 paste = Paste
+paste-link = Paste as link
+paste-hard-link = Paste as hard link
 select-all = Select all
 
 ## View
@@ -388,6 +568,23 @@ gallery-preview = Gallery preview
 menu-settings = Settings...
 menu-about = About COSMIC Files...
 
+## Gallery
+slideshow = Slideshow
+zoom-fit = Fit
+zoom-fill = Fill
+zoom-original = Original size
+rotate-left = Rotate left
+rotate-right = Rotate right
+flip-horizontal = Flip horizontally
+flip-vertical = Flip vertically
+transform-drops-metadata = The image is saved again without its metadata
+image-info = Image information
+camera = Camera
+exposure = Exposure
+location = Location
+date-taken = Date taken
+no-image-info = No image information
+
 ## Sort
 sort = Sort
 sort-a-z = A-Z
@@ -3,7 +3,7 @@
 
 use cosmic::app::{self, Core, Task, context_drawer};
 use cosmic::core::Auto;
-use cosmic::cosmic_config::{self, ConfigSet};
+use cosmic::cosmic_config::{self, ConfigGet, ConfigSet};
 use cosmic::iced::clipboard::dnd::DndAction;
 use cosmic::iced::core::SmolStr;
 use cosmic::iced::core::widget::operation::focusable::unfocus;
@@ -42,8 +42,10 @@ use mime_guess::Mime;
 use notify_debouncer_full::notify::{self, RecommendedWatcher};
 use notify_debouncer_full::{DebouncedEvent, Debouncer, RecommendedCache, new_debouncer};
 use rustc_hash::{FxHashMap, FxHashSet};
+use serde::{Deserialize, Serialize};
 use slotmap::Key as SlotMapKey;
 use std::any::TypeId;
+use std::borrow::Cow;
 use std::collections::{BTreeMap, BTreeSet, HashMap, VecDeque};
 use std::future::Future;
 use std::num::NonZeroU16;
@@ -62,9 +64,10 @@ use crate::clipboard::{
     ClipboardPasteText, ClipboardPasteVideo,
 };
 use crate::config::{
-    AppTheme, Config, DesktopConfig, Favorite, IconSizes, State, TIME_CONFIG_ID, TabConfig,
-    TimeConfig, TypeToSearch,
+    AppTheme, Config, DesktopConfig, DesktopFolder, DesktopOutputConfig, Favorite, IconSizes,
+    State, TIME_CONFIG_ID, TabConfig, Tag, TagColor, TimeConfig, TypeToSearch,
 };
+use crate::context_action::ContextActionOutput;
 use crate::dialog::{Dialog, DialogKind, DialogMessage, DialogResult, DialogSettings};
 use crate::key_bind::key_binds;
 use crate::localize::LANGUAGE_SORTER;
@@ -73,8 +76,9 @@ use crate::mounter::{
     MOUNTERS, MounterAuth, MounterItem, MounterItems, MounterKey, MounterMessage,
 };
 use crate::operation::{
-    Controller, Operation, OperationError, OperationErrorType, OperationSelection, ReplaceResult,
-    copy_unique_path,
+    Controller, ControllerState, MergePolicy, Operation, OperationError, OperationErrorType,
+    OperationQueue, OperationSelection, PermissionsChange, Preserve, ReplaceResult, SyncAction,
+    SyncMode, TransferStats, copy_unique_path, permissions, queue,
 };
 use crate::spawn_detached::spawn_detached;
 use crate::tab::{
@@ -112,12 +116,52 @@ static MOUNT_ERROR_TRY_AGAIN_BUTTON_ID: LazyLock<widget::Id> =
 pub(crate) static REPLACE_BUTTON_ID: LazyLock<widget::Id> =
     LazyLock::new(|| widget::Id::new("replace-button"));
 
+/// Bandwidth limits in bytes per second that copies and moves can be throttled to
+const BANDWIDTH_LIMITS: [Option<u64>; 6] = [
+    None,
+    Some(1_000_000),
+    Some(5_000_000),
+    Some(10_000_000),
+    Some(50_000_000),
+    Some(100_000_000),
+];
+
+/// Icon sizes that an output can use instead of the desktop icon size
+const DESKTOP_ICON_SIZES: [Option<u16>; 7] = [
+    None,
+    Some(50),
+    Some(75),
+    Some(100),
+    Some(150),
+    Some(200),
+    Some(300),
+];
+
+/// Sorts that an output can start with instead of sorting by name
+const DESKTOP_SORTS: [Option<(HeadingOptions, bool)>; 4] = [
+    None,
+    Some((HeadingOptions::Name, true)),
+    Some((HeadingOptions::Modified, false)),
+    Some((HeadingOptions::Size, true)),
+];
+
 #[derive(Clone, Debug)]
 pub enum Mode {
     App,
     Desktop,
 }
 
+/// How paths sent by other programs are shown, following `org.freedesktop.FileManager1`
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum ShowMode {
+    /// Open folders, or the parent folders of files with the files selected
+    Folders,
+    /// Open the parent folders with the items selected
+    Items,
+    /// Like [`ShowMode::Items`], and open the details pane
+    ItemProperties,
+}
+
 #[derive(Clone, Debug)]
 pub struct Flags {
     pub config_handler: Option<cosmic_config::Config>,
@@ -127,6 +171,7 @@ pub struct Flags {
     pub mode: Mode,
     pub locations: Vec<Location>,
     pub uris: Vec<url::Url>,
+    pub show_mode: ShowMode,
 }
 
 #[derive(Clone, Copy, Debug, Eq, PartialEq)]
@@ -141,6 +186,8 @@ pub enum Action {
     CosmicSettingsDesktop,
     CosmicSettingsDisplays,
     CosmicSettingsWallpaper,
+    DesktopArrange(tab::DesktopArrange),
+    DesktopKeepAligned,
     DesktopViewOptions,
     Delete,
     EditHistory,
@@ -151,6 +198,7 @@ pub enum Action {
     ExecEntryAction(usize),
     ExtractHere,
     ExtractTo,
+    FollowLink,
     Gallery,
     HistoryNext,
     HistoryPrevious,
@@ -164,6 +212,7 @@ pub enum Action {
     MoveTo,
     NewFile,
     NewFolder,
+    NewTag,
     Open,
     OpenInNewTab,
     OpenInNewWindow,
@@ -172,8 +221,11 @@ pub enum Action {
     OpenWith,
     RunContextAction(usize),
     Paste,
+    PasteHardLink,
+    PasteLink,
     PermanentlyDelete,
     Preview,
+    Properties,
     Reload,
     RemoveFromRecents,
     Rename,
@@ -184,6 +236,7 @@ pub enum Action {
     SelectAll,
     SetSort(HeadingOptions, bool),
     Settings,
+    SyncFolders,
     TabClose,
     TabNew,
     TabNext,
@@ -193,6 +246,7 @@ pub enum Action {
     ToggleFoldersFirst,
     ToggleShowHidden,
     ToggleSort(HeadingOptions),
+    ToggleTag(usize),
     WindowClose,
     WindowNew,
     ZoomDefault,
@@ -215,6 +269,10 @@ impl Action {
             Self::CosmicSettingsDisplays => Message::CosmicSettings("displays"),
             Self::CosmicSettingsWallpaper => Message::CosmicSettings("wallpaper"),
             Self::Delete => Message::Delete(entity_opt),
+            Self::DesktopArrange(arrange) => {
+                Message::TabMessage(entity_opt, tab::Message::DesktopArrange(*arrange))
+            }
+            Self::DesktopKeepAligned => Message::DesktopKeepAligned,
             Self::DesktopViewOptions => Message::DesktopViewOptions,
             Self::EditHistory => Message::ToggleContextPage(ContextPage::EditHistory),
             Self::EditLocation => Message::TabMessage(entity_opt, tab::Message::EditLocationEnable),
@@ -226,6 +284,7 @@ impl Action {
             Self::ExecEntryAction(action) => {
                 Message::TabMessage(entity_opt, tab::Message::ExecEntryAction(None, *action))
             }
+            Self::FollowLink => Message::TabMessage(entity_opt, tab::Message::FollowLink(None)),
             Self::Gallery => Message::TabMessage(entity_opt, tab::Message::GalleryToggle),
             Self::HistoryNext => Message::TabMessage(entity_opt, tab::Message::GoNext),
             Self::HistoryPrevious => Message::TabMessage(entity_opt, tab::Message::GoPrevious),
@@ -239,6 +298,7 @@ impl Action {
             Self::MoveTo => Message::MoveTo(entity_opt),
             Self::NewFile => Message::NewItem(entity_opt, false),
             Self::NewFolder => Message::NewItem(entity_opt, true),
+            Self::NewTag => Message::NewTag(entity_opt),
             Self::Open => Message::TabMessage(entity_opt, tab::Message::Open(None)),
             Self::OpenInNewTab => Message::OpenInNewTab(entity_opt),
             Self::OpenInNewWindow => Message::OpenInNewWindow(entity_opt),
@@ -249,8 +309,11 @@ impl Action {
                 Message::TabMessage(entity_opt, tab::Message::RunContextAction(*action))
             }
             Self::Paste => Message::Paste(entity_opt),
+            Self::PasteHardLink => Message::PasteLink(entity_opt, true),
+            Self::PasteLink => Message::PasteLink(entity_opt, false),
             Self::PermanentlyDelete => Message::PermanentlyDelete(entity_opt),
             Self::Preview => Message::Preview(entity_opt),
+            Self::Properties => Message::Properties(entity_opt),
             Self::Reload => Message::TabMessage(entity_opt, tab::Message::Reload),
             Self::RemoveFromRecents => Message::RemoveFromRecents(entity_opt),
             Self::Rename => Message::Rename(entity_opt),
@@ -263,6 +326,7 @@ impl Action {
                 Message::TabMessage(entity_opt, tab::Message::SetSort(*sort, *dir))
             }
             Self::Settings => Message::ToggleContextPage(ContextPage::Settings),
+            Self::SyncFolders => Message::SyncFolders(entity_opt),
             Self::TabClose => Message::TabClose(entity_opt),
             Self::TabNew => Message::TabNew,
             Self::TabNext => Message::TabNext,
@@ -274,6 +338,7 @@ impl Action {
             Self::ToggleSort(sort) => {
                 Message::TabMessage(entity_opt, tab::Message::ToggleSort(*sort))
             }
+            Self::ToggleTag(tag) => Message::ToggleTag(entity_opt, *tag),
             Self::WindowClose => Message::WindowClose,
             Self::WindowNew => Message::WindowNew,
             Self::ZoomDefault => Message::ZoomDefault(entity_opt),
@@ -320,6 +385,7 @@ pub enum NavMenuAction {
     OpenInNewWindow(segmented_button::Entity),
     Preview(segmented_button::Entity),
     RunContextAction(segmented_button::Entity, usize),
+    RemoveTag(segmented_button::Entity),
     RemoveFromSidebar(segmented_button::Entity),
     ChangeSidebarLabel(segmented_button::Entity),
 }
@@ -348,6 +414,8 @@ pub enum Message {
     Cut(Option<Entity>),
     Delete(Option<Entity>),
     DesktopConfig(DesktopConfig),
+    DesktopKeepAligned,
+    DesktopOutputConfig(DesktopOutputConfig),
     DesktopViewOptions,
     DesktopDialogs(bool),
     DialogCancel,
@@ -357,6 +425,8 @@ pub enum Message {
     DialogPush(DialogPage, Option<widget::Id>),
     DialogUpdate(DialogPage),
     DialogUpdateComplete(DialogPage),
+    #[cfg(feature = "dbus")]
+    Dbus(crate::dbus::Request),
     ExtractHere(Option<Entity>),
     ExtractTo(Option<Entity>),
     ExtractToResult(DialogResult),
@@ -385,8 +455,11 @@ pub enum Message {
     NetworkDriveSubmit,
     NetworkResult(MounterKey, String, Result<bool, String>),
     NewItem(Option<Entity>, bool),
+    NewTag(Option<Entity>),
     #[cfg(feature = "notify")]
     Notification(Arc<Mutex<notify_rust::NotificationHandle>>),
+    #[cfg(feature = "notify")]
+    NotificationUpdate,
     NotifyEvents(Vec<DebouncedEvent>),
     NotifyWatcher(WatcherWrapper),
     OpenTerminal(Option<Entity>),
@@ -403,6 +476,7 @@ pub enum Message {
     PasteContents(PathBuf, ClipboardPaste),
     PasteImage(PathBuf),
     PasteImageContents(PathBuf, ClipboardPasteImage),
+    PasteLink(Option<Entity>, bool),
     PasteText(PathBuf),
     PasteTextContents(PathBuf, ClipboardPasteText),
     PasteVideo(PathBuf),
@@ -417,18 +491,25 @@ pub enum Message {
     PendingCancelAll,
     PendingComplete(u64, OperationSelection),
     PendingDismiss,
+    PendingBandwidthLimit(u64, Option<u64>),
     PendingError(u64, OperationError),
+    PendingExpand(u64),
     PendingResults(Vec<(u64, OperationSelection)>, Vec<(u64, OperationError)>),
     PendingPause(u64, bool),
     PendingPauseAll(bool),
+    PendingReorder(u64, bool),
+    PendingRetry(u64),
     PermanentlyDelete(Option<Entity>),
     Preview(Option<Entity>),
+    Properties(Option<Entity>),
     ReloadMimeAppCache,
     ReorderTab(ReorderEvent),
     RescanRecents,
     RescanTrash,
     RemoveFromRecents(Option<Entity>),
     Rename(Option<Entity>),
+    ReplaceChecksums(PathBuf, tab::ChecksumState),
+    ReplaceCompare,
     ReplaceResult(ReplaceResult),
     RestoreFromTrash(Option<Entity>),
     SaveSortNames,
@@ -438,9 +519,17 @@ pub enum Message {
     SearchInput(String),
     SetShowDetails(bool),
     SetShowRecents(bool),
+    Show(ShowMode, Vec<PathBuf>, String),
     SetTypeToSearch(TypeToSearch),
+    SetPreserve(Preserve),
+    SetResumeCopies(bool),
+    SetVerifyCopies(bool),
     SystemThemeModeChange,
     Size(window::Id, Size),
+    SyncCompare,
+    SyncFolders(Option<Entity>),
+    SyncFoldersResult(DialogResult),
+    SyncPlan(SyncMode, bool, Result<Vec<SyncAction>, String>),
     TabActivate(Entity),
     TabNext,
     TabPrev,
@@ -460,6 +549,7 @@ pub enum Message {
     ToggleContextPage(ContextPage),
     ToggleFoldersFirst,
     ToggleShowHidden,
+    ToggleTag(Option<Entity>, usize),
     Undo(usize),
     UndoTrash(widget::ToastId, Arc<[PathBuf]>),
     UndoTrashStart(Vec<TrashItem>),
@@ -496,7 +586,7 @@ pub enum ContextPage {
     Settings,
 }
 
-#[derive(Clone, Copy, Debug, Default, Eq, Hash, PartialEq)]
+#[derive(Clone, Copy, Debug, Default, Deserialize, Eq, Hash, PartialEq, Serialize)]
 pub enum ArchiveType {
     Tgz,
     #[default]
@@ -534,6 +624,7 @@ pub enum DialogPage {
     EmptyTrash,
     FailedOperation(u64),
     FailedOperations(Vec<u64>),
+    InterruptedOperations(Box<[Operation]>),
     ExtractPassword {
         id: u64,
         password: String,
@@ -559,10 +650,19 @@ pub enum DialogPage {
         name: String,
         dir: bool,
     },
+    NewTag {
+        paths: Box<[PathBuf]>,
+        name: String,
+        color: TagColor,
+    },
     RunContextAction {
         action: usize,
         paths: Box<[PathBuf]>,
     },
+    ContextActionOutput {
+        name: String,
+        output: ContextActionOutput,
+    },
     OpenWith {
         path: PathBuf,
         mime: mime_guess::Mime,
@@ -592,8 +692,13 @@ pub enum DialogPage {
         multiple: bool,
         apply_to_all: bool,
         conflict_count: usize,
+        /// Show both items side by side along with their checksums
+        compare: bool,
+        /// Policy for conflicts inside folders that are merged
+        merge_policy: MergePolicy,
         tx: mpsc::Sender<ReplaceResult>,
     },
+    Properties(PropertiesPage),
     SetExecutableAndLaunch {
         path: PathBuf,
     },
@@ -601,6 +706,131 @@ pub enum DialogPage {
         path: PathBuf,
         entity: Entity,
     },
+    Sync {
+        source: PathBuf,
+        destination: PathBuf,
+        mode: SyncMode,
+        /// Compare the checksums of files with the same size
+        hash: bool,
+        comparing: bool,
+        plan: Option<Result<Vec<SyncAction>, String>>,
+    },
+}
+
+#[derive(Clone, Debug)]
+pub struct PropertiesPage {
+    pub paths: Box<[PathBuf]>,
+    /// Name of the new owner, empty if there are multiple owners that should be kept
+    pub owner: String,
+    /// Name of the new group, empty if there are multiple groups that should be kept
+    pub group: String,
+    pub change: PermissionsChange,
+    /// Access bits that are the same for all files, shown until they are changed
+    pub files_common: permissions::ModeChange,
+    /// Access bits that are the same for all directories
+    pub dirs_common: permissions::ModeChange,
+    pub has_dirs: bool,
+    pub has_files: bool,
+    pub special_bits: Vec<&'static str>,
+    /// Formatted ACL entries, only read when a single item is selected
+    pub acl: Vec<String>,
+}
+
+impl PropertiesPage {
+    pub fn new(paths: Box<[PathBuf]>) -> Self {
+        use std::os::unix::fs::MetadataExt;
+
+        let mut owners = BTreeSet::new();
+        let mut groups = BTreeSet::new();
+        let mut file_modes = BTreeSet::new();
+        let mut dir_modes = BTreeSet::new();
+        let mut special = 0;
+        for path in &paths {
+            match fs::symlink_metadata(path) {
+                Ok(metadata) => {
+                    owners.insert(metadata.uid());
+                    groups.insert(metadata.gid());
+                    if metadata.is_dir() {
+                        dir_modes.insert(metadata.mode() & 0o777);
+                    } else {
+                        file_modes.insert(metadata.mode() & 0o777);
+                    }
+                    special |= metadata.mode() & 0o7000;
+                }
+                Err(err) => {
+                    log::warn!("failed to read metadata of {}: {}", path.display(), err);
+                }
+            }
+        }
+
+        // Only show mode parts that are the same for all items
+        let common_mode = |modes: &BTreeSet<u32>| {
+            let mut change = permissions::ModeChange::default();
+            for shift in [
+                tab::MODE_SHIFT_USER,
+                tab::MODE_SHIFT_GROUP,
+                tab::MODE_SHIFT_OTHER,
+            ] {
+                let mut parts = modes.iter().map(|mode| (mode >> shift) & 0o7);
+                if let Some(part) = parts.next()
+                    && parts.all(|x| x == part)
+                {
+                    change = change.with_part(shift, part);
+                }
+            }
+            change
+        };
+
+        let mut acl = Vec::new();
+        if let [path] = &*paths {
+            acl.extend(
+                permissions::read_acl(path, false)
+                    .iter()
+                    .map(ToString::to_string),
+            );
+            acl.extend(
+                permissions::read_acl(path, true)
+                    .iter()
+                    .map(|entry| format!("default:{entry}")),
+            );
+        }
+
+        // Names are only shown if they are the same for all items
+        let owner = owners
+            .first()
+            .filter(|_| owners.len() == 1)
+            .and_then(|&uid| uzers::get_user_by_uid(uid))
+            .and_then(|user| user.name().to_str().map(ToOwned::to_owned))
+            .unwrap_or_default();
+        let group = groups
+            .first()
+            .filter(|_| groups.len() == 1)
+            .and_then(|&gid| uzers::get_group_by_gid(gid))
+            .and_then(|group| group.name().to_str().map(ToOwned::to_owned))
+            .unwrap_or_default();
+
+        Self {
+            owner,
+            group,
+            // Nothing is changed until the user picks new access
+            change: PermissionsChange::default(),
+            files_common: common_mode(&file_modes),
+            dirs_common: common_mode(&dir_modes),
+            has_dirs: !dir_modes.is_empty(),
+            has_files: !file_modes.is_empty(),
+            special_bits: permissions::special_bits(special),
+            acl,
+            paths,
+        }
+    }
+
+    fn owner_id(&self) -> Option<u32> {
+        uzers::get_user_by_name(&self.owner).map(|user| user.uid())
+    }
+
+    fn group_id(&self) -> Option<u32> {
+        uzers::get_group_by_name(&self.group).map(|group| group.gid())
+    }
 }
 
 pub struct DialogPages {
@@ -668,6 +898,8 @@ impl DialogPages {
 
 pub struct FavoriteIndex(usize);
 
+pub struct TagIndex(usize);
+
 pub struct MounterData(MounterKey, MounterItem);
 
 #[derive(Clone, Debug)]
@@ -729,6 +961,12 @@ pub struct App {
     state: State,
     mode: Mode,
     app_themes: Vec<String>,
+    bandwidth_limits: Vec<String>,
+    desktop_folders: Vec<DesktopFolder>,
+    desktop_folder_names: Vec<String>,
+    desktop_icon_sizes: Vec<String>,
+    desktop_sorts: Vec<String>,
+    merge_policies: Vec<String>,
     compio_tx: mpsc::Sender<Pin<Box<dyn Future<Output = ()> + Send>>>,
     context_page: ContextPage,
     dialog_pages: DialogPages,
@@ -750,6 +988,8 @@ pub struct App {
     progress_operations: BTreeSet<u64>,
     complete_operations: BTreeMap<u64, Operation>,
     failed_operations: BTreeMap<u64, (Operation, Controller, String)>,
+    operation_queue: OperationQueue,
+    expanded_operation: Option<u64>,
     scrollable_id: widget::Id,
     search_id: widget::Id,
     size: Option<Size>,
@@ -776,6 +1016,50 @@ pub struct App {
     clipboard_cache: ClipboardCache,
 }
 
+fn operation_paused(pending_operations: &BTreeMap<u64, (Operation, Controller)>, id: u64) -> bool {
+    pending_operations
+        .get(&id)
+        .is_some_and(|(_, controller)| controller.is_paused())
+}
+
+/// Speed, time remaining and the file being transferred.
+fn transfer_stats_text(stats: &TransferStats) -> String {
+    match stats.current_name() {
+        Some(name) => format!(
+            "{}\n{}",
+            stats.summary(),
+            fl!("transfer-current", name = name)
+        ),
+        None => stats.summary(),
+    }
+}
+
+/// Starts another process showing each path in its own window.
+fn open_in_new_window(paths: &[PathBuf]) {
+    if paths.is_empty() {
+        return;
+    }
+    match env::current_exe() {
+        Ok(exe) => {
+            for path in paths {
+                match process::Command::new(&exe)
+                    .arg("--new-window")
+                    .arg(path)
+                    .spawn()
+                {
+                    Ok(_child) => {}
+                    Err(err) => {
+                        log::error!("failed to execute {}: {}", exe.display(), err);
+                    }
+                }
+            }
+        }
+        Err(err) => {
+            log::error!("failed to get current executable path: {err}");
+        }
+    }
+}
+
 impl App {
     /// Returns true if the clipboard cache contains pasteable content
     fn clipboard_has_content(&self) -> bool {
@@ -1074,6 +1358,15 @@ impl App {
         )
     }
 
+    fn sync_folders(&mut self, source: &Path) -> Task<Message> {
+        self.destination_selection_dialog(
+            &[source],
+            Message::SyncFoldersResult,
+            fl!("sync-folders-title"),
+            fl!("sync-folders-button-label"),
+        )
+    }
+
     #[cfg(all(feature = "wayland", feature = "desktop-applet"))]
     fn handle_overlap(&mut self) {
         let mut overlaps: FxHashMap<_, _> = self
@@ -1188,6 +1481,11 @@ impl App {
                 tab::Mode::Desktop
             }
         };
+        if let Location::Desktop(_, output, _) = &location
+            && let Some(positions) = self.state.desktop_positions.get(output)
+        {
+            tab.desktop_positions = positions.clone();
+        }
 
         let entity = self
             .tab_model
@@ -1228,6 +1526,51 @@ impl App {
         .1
     }
 
+    /// Opens tabs for paths sent by other programs and raises the window
+    fn show(&mut self, mode: ShowMode, paths: Vec<PathBuf>, startup_id: String) -> Task<Message> {
+        let mut tasks = Vec::new();
+        // Items in the same folder share one tab
+        let mut parents = FxOrderMap::<PathBuf, Vec<PathBuf>>::default();
+        for path in paths {
+            match path.parent() {
+                Some(parent) if mode != ShowMode::Folders || !path.is_dir() => {
+                    parents
+                        .entry(parent.to_path_buf())
+                        .or_default()
+                        .push(path.clone());
+                }
+                _ => tasks.push(self.open_tab(Location::Path(path), true, None)),
+            }
+        }
+        for (parent, selection_paths) in parents {
+            tasks.push(self.open_tab(Location::Path(parent), true, Some(selection_paths)));
+        }
+        if tasks.is_empty() {
+            tasks.push(self.open_tab(Location::Path(home_dir()), true, None));
+        }
+
+        if mode == ShowMode::ItemProperties {
+            self.context_page = ContextPage::Preview(None, PreviewKind::Selected);
+            self.core.window.show_context = true;
+            tasks.push(cosmic::task::message(Message::SetShowDetails(true)));
+        }
+
+        if let Some(window_id) = self.core.main_window_id() {
+            #[cfg(feature = "wayland")]
+            if !startup_id.is_empty() && crate::is_wayland() {
+                tasks.push(
+                    cosmic::iced::platform_specific::shell::wayland::commands::activation::activate(
+                        window_id, startup_id,
+                    ),
+                );
+                return Task::batch(tasks);
+            }
+            let _ = startup_id;
+            tasks.push(window::gain_focus(window_id));
+        }
+        Task::batch(tasks)
+    }
+
     // This wrapper ensures that local folders use trash and remote folders permanently delete with a dialog
     fn delete(&mut self, paths: impl IntoIterator<Item = PathBuf>) -> Task<Message> {
         let mut dialog_paths = Vec::new();
@@ -1276,12 +1619,36 @@ impl App {
         self.pending_operations
             .insert(id, (operation.clone(), controller.clone()));
 
+        // Long running operations wait for others using the same devices to finish
+        let start_rx = if operation.show_progress_notification() {
+            let pending_operations = &self.pending_operations;
+            self.operation_queue.push(id, operation.devices(), |id| {
+                operation_paused(pending_operations, id)
+            })
+        } else {
+            None
+        };
+        self.save_operation_queue();
+
         // Use a task to send operations to the compio runtime thread.
         cosmic::Task::stream(cosmic::iced::stream::channel(4, move |msg_tx| async move {
             let (tx, rx) = tokio::sync::oneshot::channel();
 
             let msg_tx = Arc::new(tokio::sync::Mutex::new(msg_tx));
 
+            // The start signal is dropped if the operation is cancelled while queued
+            if let Some(start_rx) = start_rx
+                && start_rx.await.is_err()
+            {
+                let err = OperationError::from_state(ControllerState::Cancelled, &controller);
+                let _ = msg_tx
+                    .lock()
+                    .await
+                    .send(Message::PendingError(id, err))
+                    .await;
+                return;
+            }
+
             let msg_tx_clone = msg_tx.clone();
 
             _ = compio_tx
@@ -1302,6 +1669,80 @@ impl App {
         .map(cosmic::Action::App)
     }
 
+    /// Run a context action on the paths, as an operation if the preset is tracked
+    fn run_context_action(&mut self, action: usize, paths: Vec<PathBuf>) -> Task<Message> {
+        let Some(preset) = self.config.context_actions.get(action) else {
+            log::warn!("invalid context action index `{action}`");
+            return Task::none();
+        };
+        if preset.track {
+            let operation = Operation::ContextAction {
+                name: preset.name.clone(),
+                steps: preset.steps.clone(),
+                paths,
+            };
+            self.operation(operation)
+        } else {
+            preset.run(&paths);
+            Task::none()
+        }
+    }
+
+    /// Run a failed or cancelled operation again. Copies of large files continue from where the
+    /// previous attempt stopped.
+    fn retry_operation(&mut self, id: u64) -> Task<Message> {
+        match self.failed_operations.remove(&id) {
+            Some((operation, _, _)) => self.operation(operation),
+            None => Task::none(),
+        }
+    }
+
+    /// Start queued operations whose devices are no longer in use.
+    fn start_queued_operations(&mut self) {
+        let pending_operations = &self.pending_operations;
+        self.operation_queue
+            .start_ready(|id| operation_paused(pending_operations, id));
+    }
+
+    /// Save unfinished operations so that they can be resumed if the app exits before they finish.
+    fn save_operation_queue(&mut self) {
+        if !matches!(self.mode, Mode::App) {
+            return;
+        }
+        let mut operations: Vec<_> = self
+            .pending_operations
+            .iter()
+            .filter(|(_, (op, _))| op.persistent())
+            .collect();
+        // Running operations first, then queued operations in the order they will start
+        operations.sort_by_key(|(id, _)| self.operation_queue.position(**id));
+        let operation_queue: Vec<_> = operations
+            .into_iter()
+            .map(|(_, (op, _))| op.clone())
+            .collect();
+        let pid = process::id();
+        let saved = self.state.operation_queue.get(&pid);
+        if saved.map_or(&[][..], Vec::as_slice) == operation_queue.as_slice() {
+            return;
+        }
+        // Other processes save their own queues under their IDs, so reload before writing
+        if let Some(state_handler) = self.state_handler.as_ref()
+            && let Ok(saved) = state_handler.get("operation_queue")
+        {
+            self.state.operation_queue = saved;
+        }
+        if operation_queue.is_empty() {
+            self.state.operation_queue.remove(&pid);
+        } else {
+            self.state.operation_queue.insert(pid, operation_queue);
+        }
+        if let Some(state_handler) = self.state_handler.as_ref()
+            && let Err(err) = state_handler.set("operation_queue", &self.state.operation_queue)
+        {
+            log::warn!("Failed to save operation queue: {err:?}");
+        }
+    }
+
     /// Will join operations together into a single task that will return a single
     /// Message::PendingResults message when all operations are complete.
     fn join_operations(&mut self, operations: Vec<Operation>) -> Task<Message> {
@@ -1342,6 +1783,7 @@ impl App {
         for (id, op_sel_pending) in completed {
             op_sel.ignored.extend(op_sel_pending.ignored);
             op_sel.selected.extend(op_sel_pending.selected);
+            self.operation_queue.finish(id);
             if let Some((op, _)) = self.pending_operations.remove(&id) {
                 // Show toast for some operations
                 if let Some(description) = op.toast() {
@@ -1388,9 +1830,16 @@ impl App {
                     commands.push(self.rescan_recents());
                 }
 
+                // Context actions may change anything around the paths they ran on
+                if let Operation::ContextAction { ref paths, .. } = op {
+                    commands.push(self.rescan_paths(paths));
+                }
+
                 self.complete_operations.insert(id, op);
             }
         }
+        self.start_queued_operations();
+        self.save_operation_queue();
         // Close progress notification if all relevant operations are finished
         if !self
             .pending_operations
@@ -1413,11 +1862,17 @@ impl App {
         let mut tasks = Vec::new();
         let mut failed = Vec::new();
         for (id, err) in errors.into_iter() {
+            self.operation_queue.finish(id);
             if let Some((op, controller)) = self.pending_operations.remove(&id) {
                 // Only show dialog if not cancelled
                 if !controller.is_cancelled() {
                     match err.kind {
                         OperationErrorType::Generic(_) => failed.push(id),
+                        // Mismatched files are listed on their own
+                        OperationErrorType::VerificationFailed(_) => {
+                            tasks
+                                .push(self.dialog_pages.push_back(DialogPage::FailedOperation(id)));
+                        }
                         OperationErrorType::PasswordRequired => {
                             tasks.push(self.dialog_pages.push_back(DialogPage::ExtractPassword {
                                 id,
@@ -1429,6 +1884,10 @@ impl App {
 
                 // Remove from progress
                 self.progress_operations.remove(&id);
+                // A failed or cancelled context action may still have changed some paths
+                if let Operation::ContextAction { ref paths, .. } = op {
+                    tasks.push(self.rescan_paths(paths));
+                }
                 self.failed_operations
                     .insert(id, (op, controller, err.to_string()));
             }
@@ -1440,6 +1899,8 @@ impl App {
             );
             tasks.push(widget::text_input::focus(self.dialog_text_input.clone()));
         }
+        self.start_queued_operations();
+        self.save_operation_queue();
 
         // Close progress notification if all relevant operations are finished
         if !self
@@ -1593,6 +2054,30 @@ impl App {
         Task::batch(commands)
     }
 
+    /// Reload tabs showing any of the paths, the folders containing them or folders inside them
+    fn rescan_paths(&mut self, paths: &[PathBuf]) -> Task<Message> {
+        let needs_reload: Box<[_]> = self
+            .tab_model
+            .iter()
+            .filter_map(|entity| {
+                let tab = self.tab_model.data::<Tab>(entity)?;
+                let tab_path = tab.location.path_opt()?;
+                paths
+                    .iter()
+                    .any(|path| {
+                        tab_path.starts_with(path) || path.parent() == Some(tab_path.as_path())
+                    })
+                    .then_some((entity, tab.location.clone()))
+            })
+            .collect();
+
+        let commands = needs_reload
+            .into_iter()
+            .map(|(entity, location)| self.update_tab(entity, location, None));
+
+        Task::batch(commands)
+    }
+
     fn search_get(&self) -> Option<&str> {
         let entity = self.tab_model.active();
         let tab = self.tab_model.data::<Tab>(entity)?;
@@ -1710,10 +2195,11 @@ impl App {
         let needs_reload: Box<[_]> = (self.tab_model.iter())
             .filter_map(|entity| {
                 let tab = self.tab_model.data::<Tab>(entity)?;
-                if let Location::Desktop(path, output, _) = &tab.location {
+                if let Location::Desktop(_, output, _) = &tab.location {
+                    let (path, desktop_config) = self.config.desktop.for_output(output);
                     Some((
                         entity,
-                        Location::Desktop(path.clone(), output.clone(), self.config.desktop),
+                        Location::Desktop(path, output.clone(), desktop_config),
                     ))
                 } else {
                     None
@@ -1721,13 +2207,22 @@ impl App {
             })
             .collect();
 
-        let mut commands = Vec::with_capacity(needs_reload.len());
+        let mut commands = Vec::with_capacity(needs_reload.len() + 1);
         for (entity, location) in needs_reload {
             if let Some(tab) = self.tab_model.data_mut::<Tab>(entity) {
+                if let Location::Desktop(_, output, desktop_config) = &location
+                    && let Some((sort_name, sort_direction)) =
+                        desktop_config.output(output).and_then(|x| x.sort)
+                {
+                    tab.sort_name = sort_name;
+                    tab.sort_direction = sort_direction;
+                }
                 tab.location = location.clone();
             }
             commands.push(self.update_tab(entity, location, None));
         }
+        // The folder shown on an output may have changed
+        commands.push(self.update_watcher());
         Task::batch(commands)
     }
 
@@ -1774,9 +2269,7 @@ impl App {
 
         for (favorite_i, favorite) in self.config.favorites.iter().enumerate() {
             if let Some(path) = favorite.path_opt() {
-                let name = favorite
-                    .display_name()
-                    .unwrap_or_else(|| fl!("filesystem"));
+                let name = favorite.display_name().unwrap_or_else(|| fl!("filesystem"));
                 nav_model = nav_model.insert(move |b| {
                     b.text(name.clone())
                         .icon(
@@ -1798,6 +2291,23 @@ impl App {
             }
         }
 
+        for (tag_i, tag) in self.config.tags.iter().enumerate() {
+            let location = Location::Search(
+                SearchLocation::Path(home_dir()),
+                crate::tag::search_term(&tag.name),
+                self.config.tab.show_hidden,
+                Instant::now(),
+            );
+            nav_model = nav_model.insert(move |b| {
+                let b = b
+                    .text(tag.name.clone())
+                    .icon(icon::icon(tag.color.icon(16)).size(16))
+                    .data(location)
+                    .data(TagIndex(tag_i));
+                if tag_i == 0 { b.divider_above() } else { b }
+            });
+        }
+
         nav_model = nav_model.insert(|b| {
             b.text(fl!("trash"))
                 .icon(icon::icon(Trash::icon_symbolic(16)))
@@ -1979,7 +2489,7 @@ impl App {
         let cosmic_theme::Spacing {
             space_m, space_l, ..
         } = theme::spacing();
-        let config = self.config.desktop;
+        let config = &self.config.desktop;
 
         let show_on_desktop = settings::section()
             .title(fl!("show-on-desktop"))
@@ -1989,7 +2499,7 @@ impl App {
                     move |show_content| {
                         Message::DesktopConfig(DesktopConfig {
                             show_content,
-                            ..config
+                            ..config.clone()
                         })
                     },
                 ),
@@ -1999,7 +2509,7 @@ impl App {
                 move |show_mounted_drives| {
                     Message::DesktopConfig(DesktopConfig {
                         show_mounted_drives,
-                        ..config
+                        ..config.clone()
                     })
                 },
             ))
@@ -2008,7 +2518,16 @@ impl App {
                 move |show_trash| {
                     Message::DesktopConfig(DesktopConfig {
                         show_trash,
-                        ..config
+                        ..config.clone()
+                    })
+                },
+            ))
+            .add(settings::item::builder(fl!("stack-by-type")).toggler(
+                config.stacks,
+                move |stacks| {
+                    Message::DesktopConfig(DesktopConfig {
+                        stacks,
+                        ..config.clone()
                     })
                 },
             ));
@@ -2024,7 +2543,7 @@ impl App {
                         widget::slider(50..=500, icon_size.get(), move |new_value| {
                             Message::DesktopConfig(DesktopConfig {
                                 icon_size: NonZeroU16::new(new_value).unwrap_or(icon_size),
-                                ..config
+                                ..config.clone()
                             })
                         })
                         .step(25u16),
@@ -2037,21 +2556,113 @@ impl App {
                         widget::slider(50..=500, grid_spacing.get(), move |new_value| {
                             Message::DesktopConfig(DesktopConfig {
                                 grid_spacing: NonZeroU16::new(new_value).unwrap_or(grid_spacing),
-                                ..config
+                                ..config.clone()
                             })
                         })
                         .step(25u16),
                     ),
+            )
+            .add(
+                settings::item::builder(fl!("keep-aligned"))
+                    .description(fl!("keep-aligned-description"))
+                    .toggler(config.keep_aligned, move |keep_aligned| {
+                        Message::DesktopConfig(DesktopConfig {
+                            keep_aligned,
+                            ..config.clone()
+                        })
+                    }),
             );
 
-        widget::column::with_capacity(2)
+        let outputs = self.tab_model.iter().filter_map(|entity| {
+            match &self.tab_model.data::<Tab>(entity)?.location {
+                Location::Desktop(_, output, _) if !output.is_empty() => Some(output),
+                _ => None,
+            }
+        });
+
+        let mut column = widget::column::with_capacity(3)
             .padding([0, space_l, space_l, space_l])
             .spacing(space_m)
             .push(show_on_desktop)
-            .push(icon_size_and_spacing)
+            .push(icon_size_and_spacing);
+        for output in outputs {
+            column = column.push(self.desktop_output_options(output));
+        }
+        widget::scrollable(column).into()
+    }
+
+    fn desktop_output_options(&self, output: &str) -> Element<'_, Message> {
+        let output_config = self
+            .config
+            .desktop
+            .output(output)
+            .cloned()
+            .unwrap_or_else(|| DesktopOutputConfig {
+                output: output.to_string(),
+                ..Default::default()
+            });
+
+        let folders = self.desktop_folders.clone();
+        let folder_config = output_config.clone();
+        let icon_size_config = output_config.clone();
+        let sort_config = output_config.clone();
+        settings::section()
+            .title(fl!("desktop-output", output = output))
+            .add(
+                settings::item::builder(fl!("desktop-output-folder")).control(widget::dropdown(
+                    &self.desktop_folder_names,
+                    folders.iter().position(|x| *x == output_config.folder),
+                    move |index| {
+                        Message::DesktopOutputConfig(DesktopOutputConfig {
+                            folder: folders[index].clone(),
+                            ..folder_config.clone()
+                        })
+                    },
+                )),
+            )
+            .add(
+                settings::item::builder(fl!("icon-size")).control(widget::dropdown(
+                    &self.desktop_icon_sizes,
+                    DESKTOP_ICON_SIZES
+                        .iter()
+                        .position(|x| *x == output_config.icon_size.map(NonZeroU16::get)),
+                    move |index| {
+                        Message::DesktopOutputConfig(DesktopOutputConfig {
+                            icon_size: DESKTOP_ICON_SIZES[index].and_then(NonZeroU16::new),
+                            ..icon_size_config.clone()
+                        })
+                    },
+                )),
+            )
+            .add(
+                settings::item::builder(fl!("desktop-output-sort")).control(widget::dropdown(
+                    &self.desktop_sorts,
+                    DESKTOP_SORTS.iter().position(|x| *x == output_config.sort),
+                    move |index| {
+                        Message::DesktopOutputConfig(DesktopOutputConfig {
+                            sort: DESKTOP_SORTS[index],
+                            ..sort_config.clone()
+                        })
+                    },
+                )),
+            )
             .into()
     }
 
+    /// Folders that outputs can show, from the sidebar favorites
+    fn update_desktop_folders(&mut self) {
+        let mut folders = vec![(DesktopFolder::Desktop, fl!("desktop-folder"))];
+        for favorite in &self.config.favorites {
+            if let Some(name) = favorite.display_name()
+                && !matches!(favorite, Favorite::Network { .. })
+            {
+                folders.push((DesktopFolder::Favorite(favorite.clone()), name));
+            }
+        }
+        folders.push((DesktopFolder::Empty, fl!("no-folder")));
+        (self.desktop_folders, self.desktop_folder_names) = folders.into_iter().unzip();
+    }
+
     fn edit_history(&self) -> Element<'_, Message> {
         let cosmic_theme::Spacing { space_m, .. } = theme::spacing();
 
@@ -2064,60 +2675,165 @@ impl App {
             let mut section = widget::settings::section().title(fl!("pending"));
             for (id, (op, controller)) in self.pending_operations.iter().rev() {
                 let progress = controller.progress();
-                section = section.add(widget::column::with_children([
-                    widget::row::with_children([
-                        widget::determinate_linear(progress)
-                            .width(Length::Fill)
-                            .girth(progress_bar_height)
-                            .into(),
-                        if controller.is_paused() {
-                            widget::tooltip(
-                                widget::button::icon(icon::from_name(
-                                    "media-playback-start-symbolic",
-                                ))
-                                .on_press(Message::PendingPause(*id, false))
+                let queued = self.operation_queue.position(*id);
+                let mut row = Vec::with_capacity(6);
+                row.push(
+                    widget::determinate_linear(progress)
+                        .width(Length::Fill)
+                        .girth(progress_bar_height)
+                        .into(),
+                );
+                if let Some((index, len)) = queued {
+                    row.push(
+                        widget::tooltip(
+                            widget::button::icon(icon::from_name("go-up-symbolic"))
+                                .on_press_maybe(
+                                    (index > 0).then_some(Message::PendingReorder(*id, true)),
+                                )
                                 .padding(8),
-                                widget::text::body(fl!("resume")),
-                                widget::tooltip::Position::Top,
-                            )
-                            .into()
-                        } else {
-                            widget::tooltip(
-                                widget::button::icon(icon::from_name(
-                                    "media-playback-pause-symbolic",
-                                ))
-                                .on_press(Message::PendingPause(*id, true))
+                            widget::text::body(fl!("move-earlier")),
+                            widget::tooltip::Position::Top,
+                        )
+                        .into(),
+                    );
+                    row.push(
+                        widget::tooltip(
+                            widget::button::icon(icon::from_name("go-down-symbolic"))
+                                .on_press_maybe(
+                                    (index + 1 < len)
+                                        .then_some(Message::PendingReorder(*id, false)),
+                                )
                                 .padding(8),
-                                widget::text::body(fl!("pause")),
-                                widget::tooltip::Position::Top,
-                            )
-                            .into()
-                        },
+                            widget::text::body(fl!("move-later")),
+                            widget::tooltip::Position::Top,
+                        )
+                        .into(),
+                    );
+                }
+                if matches!(op, Operation::Copy { .. } | Operation::Move { .. }) {
+                    let id = *id;
+                    let limit = controller.bandwidth_limit();
+                    row.push(
                         widget::tooltip(
-                            widget::button::icon(icon::from_name("window-close-symbolic"))
-                                .on_press(Message::PendingCancel(*id))
+                            widget::dropdown(
+                                &self.bandwidth_limits,
+                                BANDWIDTH_LIMITS.iter().position(|x| *x == limit),
+                                move |index| {
+                                    Message::PendingBandwidthLimit(id, BANDWIDTH_LIMITS[index])
+                                },
+                            ),
+                            widget::text::body(fl!("bandwidth-limit-tooltip")),
+                            widget::tooltip::Position::Top,
+                        )
+                        .into(),
+                    );
+                }
+                row.extend([
+                    if controller.is_paused() {
+                        widget::tooltip(
+                            widget::button::icon(icon::from_name("media-playback-start-symbolic"))
+                                .on_press(Message::PendingPause(*id, false))
                                 .padding(8),
-                            widget::text::body(fl!("cancel")),
+                            widget::text::body(fl!("resume")),
                             widget::tooltip::Position::Top,
                         )
+                        .into()
+                    } else {
+                        widget::tooltip(
+                            widget::button::icon(icon::from_name("media-playback-pause-symbolic"))
+                                .on_press(Message::PendingPause(*id, true))
+                                .padding(8),
+                            widget::text::body(fl!("pause")),
+                            widget::tooltip::Position::Top,
+                        )
+                        .into()
+                    },
+                    widget::tooltip(
+                        widget::button::icon(icon::from_name("window-close-symbolic"))
+                            .on_press(Message::PendingCancel(*id))
+                            .padding(8),
+                        widget::text::body(fl!("cancel")),
+                        widget::tooltip::Position::Top,
+                    )
+                    .into(),
+                ]);
+                let text = op.pending_text(progress, controller.state());
+                let mut column = vec![
+                    widget::row::with_children(row)
+                        .align_y(Alignment::Center)
                         .into(),
-                    ])
-                    .align_y(Alignment::Center)
+                    widget::text::body(match queued {
+                        Some((index, _)) => {
+                            format!("{text}\n{}", fl!("queued", position = index + 1))
+                        }
+                        None => text,
+                    })
                     .into(),
-                    widget::text::body(op.pending_text(progress, controller.state())).into(),
-                ]));
+                ];
+                let expanded = self.expanded_operation == Some(*id);
+                if let Some(stats) = controller.with_stats(|stats| {
+                    let mut stats_column = vec![
+                        widget::text::caption(transfer_stats_text(stats)).into(),
+                        widget::button::link(if expanded {
+                            fl!("hide-file-history")
+                        } else {
+                            fl!("show-file-history")
+                        })
+                        .on_press(Message::PendingExpand(*id))
+                        .padding(0)
+                        .into(),
+                    ];
+                    if expanded {
+                        stats_column.extend(stats.history.iter().rev().map(|file| {
+                            let name = file.path.file_name().map_or_else(
+                                || file.path.display().to_string(),
+                                |name| name.to_string_lossy().into_owned(),
+                            );
+                            widget::row::with_children([
+                                widget::text::caption(name).width(Length::Fill).into(),
+                                widget::text::caption(fl!(
+                                    "file-transfer",
+                                    size = tab::format_size(file.bytes),
+                                    duration = tab::format_duration(file.duration)
+                                ))
+                                .into(),
+                            ])
+                            .into()
+                        }));
+                    }
+                    widget::column::with_children(stats_column).into()
+                }) {
+                    column.push(stats);
+                }
+                section = section.add(widget::column::with_children(column));
             }
             children.push(section.into());
         }
 
         if !self.failed_operations.is_empty() {
             let mut section = widget::settings::section().title(fl!("failed"));
-            for (op, controller, error) in self.failed_operations.values().rev() {
+            for (id, (op, controller, error)) in self.failed_operations.iter().rev() {
                 let progress = controller.progress();
-                section = section.add(widget::column::with_children([
-                    widget::text::body(op.pending_text(progress, controller.state())).into(),
-                    widget::text::body(error).into(),
-                ]));
+                section = section.add(
+                    widget::row::with_children([
+                        widget::column::with_children([
+                            widget::text::body(op.pending_text(progress, controller.state()))
+                                .into(),
+                            widget::text::body(error).into(),
+                        ])
+                        .width(Length::Fill)
+                        .into(),
+                        widget::tooltip(
+                            widget::button::icon(icon::from_name("view-refresh-symbolic"))
+                                .on_press(Message::PendingRetry(*id))
+                                .padding(8),
+                            widget::text::body(fl!("try-again")),
+                            widget::tooltip::Position::Top,
+                        )
+                        .into(),
+                    ])
+                    .align_y(Alignment::Center),
+                );
             }
             children.push(section.into());
         }
@@ -2213,6 +2929,7 @@ impl App {
 
     fn settings(&self) -> Element<'_, Message> {
         let tab_config = self.config.tab;
+        let preserve = self.config.preserve;
 
         // TODO: Should dialog be updated here too?
         settings::view_column(vec![
@@ -2276,6 +2993,53 @@ impl App {
                     settings::item::builder(fl!("show-recents"))
                         .toggler(self.config.show_recents, Message::SetShowRecents)
                 })
+                .add({
+                    settings::item::builder(fl!("verify-copies"))
+                        .description(fl!("verify-copies-description"))
+                        .toggler(self.config.verify_copies, Message::SetVerifyCopies)
+                })
+                .add({
+                    settings::item::builder(fl!("resume-copies"))
+                        .description(fl!("resume-copies-description"))
+                        .toggler(self.config.resume_copies, Message::SetResumeCopies)
+                })
+                .into(),
+            settings::section()
+                .title(fl!("preserve"))
+                .add({
+                    settings::item::builder(fl!("preserve-times"))
+                        .toggler(preserve.times, move |times| {
+                            Message::SetPreserve(Preserve { times, ..preserve })
+                        })
+                })
+                .add({
+                    settings::item::builder(fl!("preserve-permissions")).toggler(
+                        preserve.permissions,
+                        move |permissions| {
+                            Message::SetPreserve(Preserve {
+                                permissions,
+                                ..preserve
+                            })
+                        },
+                    )
+                })
+                .add({
+                    settings::item::builder(fl!("preserve-ownership"))
+                        .description(fl!("preserve-ownership-description"))
+                        .toggler(preserve.ownership, move |ownership| {
+                            Message::SetPreserve(Preserve {
+                                ownership,
+                                ..preserve
+                            })
+                        })
+                })
+                .add({
+                    settings::item::builder(fl!("preserve-xattrs"))
+                        .description(fl!("preserve-xattrs-description"))
+                        .toggler(preserve.xattrs, move |xattrs| {
+                            Message::SetPreserve(Preserve { xattrs, ..preserve })
+                        })
+                })
                 .into(),
         ])
         .into()
@@ -2386,6 +3150,30 @@ impl Application for App {
         }
 
         let app_themes = vec![fl!("match-desktop"), fl!("dark"), fl!("light")];
+        let bandwidth_limits = BANDWIDTH_LIMITS
+            .iter()
+            .map(|limit| match limit {
+                Some(limit) => fl!("bandwidth-limit", speed = tab::format_size(*limit)),
+                None => fl!("unlimited"),
+            })
+            .collect();
+        let desktop_icon_sizes = DESKTOP_ICON_SIZES
+            .iter()
+            .map(|size| match size {
+                Some(size) => format!("{size}%"),
+                None => fl!("use-default"),
+            })
+            .collect();
+        let desktop_sorts = DESKTOP_SORTS
+            .iter()
+            .map(|sort| match sort {
+                Some((HeadingOptions::Modified, _)) => fl!("sort-by-modified"),
+                Some((HeadingOptions::Size, _)) => fl!("sort-by-size"),
+                Some(_) => fl!("sort-by-name"),
+                None => fl!("use-default"),
+            })
+            .collect();
+        let merge_policies = MergePolicy::ALL.iter().map(|x| x.label()).collect();
 
         let key_binds = key_binds(&match flags.mode {
             Mode::App => tab::Mode::App,
@@ -2440,6 +3228,12 @@ impl Application for App {
             state: flags.state,
             mode: flags.mode,
             app_themes,
+            bandwidth_limits,
+            desktop_folders: Vec::new(),
+            desktop_folder_names: Vec::new(),
+            desktop_icon_sizes,
+            desktop_sorts,
+            merge_policies,
             compio_tx,
             context_page: ContextPage::Preview(None, PreviewKind::Selected),
             dialog_pages: DialogPages::new(),
@@ -2461,6 +3255,8 @@ impl Application for App {
             progress_operations: BTreeSet::new(),
             complete_operations: BTreeMap::new(),
             failed_operations: BTreeMap::new(),
+            operation_queue: OperationQueue::default(),
+            expanded_operation: None,
             scrollable_id: widget::Id::new("File Scrollable"),
             search_id: widget::Id::new("File Search"),
             size: None,
@@ -2486,19 +3282,59 @@ impl Application for App {
 
         let mut commands = vec![app.update_config(), app.update(Message::CheckClipboard)];
 
-        for location in flags.locations {
-            if let Some(path) = location.path_opt()
-                && path.is_file()
-                && let Some(parent) = path.parent()
-            {
-                commands.push(app.open_tab(
-                    Location::Path(parent.to_path_buf()),
-                    true,
-                    Some(vec![path.clone()]),
-                ));
-                continue;
+        // Offer to resume operations that were not finished when the app last exited
+        if matches!(app.mode, Mode::App) {
+            // Take over the queues of processes that are no longer running, so that they are only
+            // offered once and stay saved until the dialog is answered
+            let pid = process::id();
+            let mut operations = Vec::new();
+            app.state.operation_queue.retain(|queue_pid, queue| {
+                if *queue_pid == pid || !queue::process_running(*queue_pid) {
+                    operations.append(queue);
+                    false
+                } else {
+                    true
+                }
+            });
+            if !operations.is_empty() {
+                app.state.operation_queue.insert(pid, operations.clone());
+                if let Some(state_handler) = app.state_handler.as_ref()
+                    && let Err(err) =
+                        state_handler.set("operation_queue", &app.state.operation_queue)
+                {
+                    log::warn!("Failed to save operation queue: {err:?}");
+                }
+                commands.push(
+                    app.dialog_pages
+                        .push_back(DialogPage::InterruptedOperations(
+                            operations.into_boxed_slice(),
+                        )),
+                );
             }
-            commands.push(app.open_tab(location, true, None));
+        }
+
+        if flags.show_mode == ShowMode::Folders {
+            for location in flags.locations {
+                if let Some(path) = location.path_opt()
+                    && path.is_file()
+                    && let Some(parent) = path.parent()
+                {
+                    commands.push(app.open_tab(
+                        Location::Path(parent.to_path_buf()),
+                        true,
+                        Some(vec![path.clone()]),
+                    ));
+                    continue;
+                }
+                commands.push(app.open_tab(location, true, None));
+            }
+        } else {
+            let paths = flags
+                .locations
+                .iter()
+                .filter_map(|location| location.path_opt().cloned())
+                .collect();
+            commands.push(app.show(flags.show_mode, paths, String::new()));
         }
         for location in flags.uris {
             if let Some(e) = app.nav_model.iter().find(|e| {
@@ -2568,6 +3404,7 @@ impl Application for App {
     fn nav_context_menu(&self) -> Option<Vec<widget::menu::Tree<cosmic::Action<Self::Message>>>> {
         let items = self.nav_model.iter().map(|entity| {
             let favorite_index_opt = self.nav_model.data::<FavoriteIndex>(entity);
+            let tag_index_opt = self.nav_model.data::<TagIndex>(entity);
             let location_opt = self.nav_model.data::<Location>(entity);
 
             let mut items: Vec<widget::menu::Item<NavMenuAction, String>> = Vec::with_capacity(7);
@@ -2598,22 +3435,39 @@ impl Application for App {
                     NavMenuAction::OpenInNewWindow(entity),
                 ));
             }
-            if let Some(path) = location_opt.and_then(Location::path_opt) {
-                let selected_dir = usize::from(path.is_dir());
-                let action_items: Vec<_> = self
-                    .config
-                    .context_actions
-                    .iter()
-                    .enumerate()
-                    .filter(|(_, action)| action.matches_selection(1, selected_dir))
-                    .map(|(i, action)| {
-                        cosmic::widget::menu::Item::Button(
-                            action.name.clone(),
-                            None,
-                            NavMenuAction::RunContextAction(entity, i),
-                        )
-                    })
-                    .collect();
+            if let Some(path) = location_opt.and_then(Location::path_opt)
+                && tag_index_opt.is_none()
+            {
+                let name = path
+                    .file_name()
+                    .map(|name| name.to_string_lossy())
+                    .unwrap_or_default();
+                let mime = mime_icon::mime_for_path(path, None, false);
+                let selected = [context_action::SelectedItem {
+                    name: &name,
+                    mime: &mime,
+                    is_dir: path.is_dir(),
+                }];
+                let action_item = |i: usize| {
+                    cosmic::widget::menu::Item::Button(
+                        self.config.context_actions[i].name.clone(),
+                        None,
+                        NavMenuAction::RunContextAction(entity, i),
+                    )
+                };
+                let mut action_items = Vec::new();
+                for (submenu, indexes) in
+                    context_action::grouped(&self.config.context_actions, &selected)
+                {
+                    if submenu.is_empty() {
+                        action_items.extend(indexes.into_iter().map(action_item));
+                    } else {
+                        action_items.push(cosmic::widget::menu::Item::Folder(
+                            submenu,
+                            indexes.into_iter().map(action_item).collect(),
+                        ));
+                    }
+                }
 
                 if !action_items.is_empty() {
                     items.push(cosmic::widget::menu::Item::Divider);
@@ -2641,6 +3495,13 @@ impl Application for App {
                     NavMenuAction::RemoveFromSidebar(entity),
                 ));
             }
+            if tag_index_opt.is_some() {
+                items.push(cosmic::widget::menu::Item::Button(
+                    fl!("remove-tag"),
+                    None,
+                    NavMenuAction::RemoveTag(entity),
+                ));
+            }
 
             if matches!(location_opt, Some(Location::Recents)) && tab::has_recents() {
                 items.push(cosmic::widget::menu::Item::Button(
@@ -2762,7 +3623,17 @@ impl Application for App {
             };
 
             if should_open {
-                let message = Message::TabMessage(None, tab::Message::Location(location.clone()));
+                let location = match location {
+                    // Restart searches from the sidebar
+                    Location::Search(search_location, term, show_hidden, _) => Location::Search(
+                        search_location.clone(),
+                        term.clone(),
+                        *show_hidden,
+                        Instant::now(),
+                    ),
+                    _ => location.clone(),
+                };
+                let message = Message::TabMessage(None, tab::Message::Location(location));
                 return self.update(message);
             }
         }
@@ -3014,6 +3885,9 @@ impl Application for App {
                             return self.operation(Operation::Copy {
                                 paths: file_paths.to_vec(),
                                 to: selected_paths[0].clone(),
+                                preserve: self.config.preserve,
+                                verify: self.config.verify_copies,
+                                resumable: self.config.resume_copies,
                             });
                         }
                     }
@@ -3084,12 +3958,24 @@ impl Application for App {
                     return self.update_desktop();
                 }
             }
+            Message::DesktopKeepAligned => {
+                return self.update(Message::DesktopConfig(DesktopConfig {
+                    keep_aligned: !self.config.desktop.keep_aligned,
+                    ..self.config.desktop.clone()
+                }));
+            }
+            Message::DesktopOutputConfig(output_config) => {
+                let mut config = self.config.desktop.clone();
+                config.set_output(output_config);
+                return self.update(Message::DesktopConfig(config));
+            }
             Message::DesktopViewOptions => {
+                self.update_desktop_folders();
                 let mut settings = window::Settings {
                     decorations: true,
                     min_size: Some(Size::new(360.0, 180.0)),
                     resizable: true,
-                    size: Size::new(480.0, 444.0),
+                    size: Size::new(480.0, 640.0),
                     transparent: true,
                     ..Default::default()
                 };
@@ -3144,7 +4030,23 @@ impl Application for App {
                 }
             }
             Message::DialogCancel => {
-                if let Some((_page, task)) = self.dialog_pages.pop_front() {
+                if let Some((page, task)) = self.dialog_pages.pop_front() {
+                    // Discard interrupted operations instead of offering them again
+                    if let DialogPage::InterruptedOperations(operations) = page {
+                        self.save_operation_queue();
+                        return Task::batch([
+                            task,
+                            Task::future(async move {
+                                _ = tokio::task::spawn_blocking(move || {
+                                    for operation in operations {
+                                        operation.discard_resume_state();
+                                    }
+                                })
+                                .await;
+                                cosmic::action::none()
+                            }),
+                        ]);
+                    }
                     return task;
                 }
             }
@@ -3173,22 +4075,29 @@ impl Application for App {
                             tasks.push(self.operation(Operation::EmptyTrash));
                         }
                         DialogPage::FailedOperation(id) => {
-                            log::warn!("TODO: retry operation {id}");
+                            tasks.push(self.retry_operation(id));
                         }
-                        DialogPage::FailedOperations(_ids) => {
-                            log::warn!("TODO: retry operations");
+                        DialogPage::FailedOperations(ids) => {
+                            for id in ids {
+                                tasks.push(self.retry_operation(id));
+                            }
+                        }
+                        DialogPage::InterruptedOperations(operations) => {
+                            for operation in operations {
+                                tasks.push(self.operation(operation));
+                            }
                         }
                         DialogPage::ExtractPassword { id, password } => {
-                            let (operation, _, _err) = self.failed_operations.get(&id).unwrap();
-                            let new_op = match &operation {
-                                Operation::Extract { to, paths, .. } => Operation::Extract {
-                                    to: to.clone(),
-                                    paths: paths.clone(),
+                            // The operation may have been retried from the edit history
+                            if let Some((Operation::Extract { to, paths, .. }, _, _err)) =
+                                self.failed_operations.remove(&id)
+                            {
+                                tasks.push(self.operation(Operation::Extract {
+                                    to,
+                                    paths,
                                     password: Some(password),
-                                },
-                                _ => unreachable!(),
-                            };
-                            tasks.push(self.operation(new_op));
+                                }));
+                            }
                         }
                         DialogPage::MountError {
                             mounter_key,
@@ -3227,9 +4136,40 @@ impl Application for App {
                                 Operation::NewFile { path }
                             }));
                         }
+                        DialogPage::NewTag { paths, name, color } => {
+                            let name = name.trim().to_string();
+                            if !self.config.tags.iter().any(|tag| tag.name == name) {
+                                let mut tags = self.config.tags.clone();
+                                tags.push(Tag {
+                                    name: name.clone(),
+                                    color,
+                                });
+                                config_set!(tags, tags);
+                                tasks.push(self.update_config());
+                            }
+                            if !paths.is_empty() {
+                                tasks.push(self.operation(Operation::SetTag {
+                                    paths: paths.into_vec(),
+                                    tag: name,
+                                    enabled: true,
+                                }));
+                            }
+                        }
+                        DialogPage::Properties(page) => {
+                            let change = PermissionsChange {
+                                owner: page.owner_id(),
+                                group: page.group_id(),
+                                ..page.change
+                            };
+                            tasks.push(self.operation(Operation::SetProperties {
+                                paths: page.paths.into_vec(),
+                                change,
+                            }));
+                        }
                         DialogPage::RunContextAction { action, paths } => {
-                            context_action::run(&self.config.context_actions, action, &paths);
+                            tasks.push(self.run_context_action(action, paths.into_vec()));
                         }
+                        DialogPage::ContextActionOutput { .. } => {}
                         DialogPage::OpenWith {
                             path,
                             mime,
@@ -3319,6 +4259,21 @@ impl Application for App {
                                 tasks.push(self.update_config());
                             }
                         }
+                        DialogPage::Sync {
+                            source,
+                            destination,
+                            mode,
+                            plan: Some(Ok(actions)),
+                            ..
+                        } if !actions.is_empty() => {
+                            tasks.push(self.operation(Operation::Sync {
+                                source,
+                                destination,
+                                mode,
+                                actions,
+                            }));
+                        }
+                        DialogPage::Sync { .. } => {}
                     }
                     return Task::batch(tasks);
                 }
@@ -3335,6 +4290,53 @@ impl Application for App {
                     self.update(Message::DialogComplete),
                 ]);
             }
+            #[cfg(feature = "dbus")]
+            Message::Dbus(request) => match request {
+                crate::dbus::Request::OpenWindow(path) => open_in_new_window(&[path]),
+                crate::dbus::Request::Operation(mut operation, tx) => {
+                    if let Operation::Copy {
+                        preserve,
+                        verify,
+                        resumable,
+                        ..
+                    }
+                    | Operation::Move {
+                        preserve,
+                        verify,
+                        resumable,
+                        ..
+                    } = &mut operation
+                    {
+                        *preserve = self.config.preserve;
+                        *verify = self.config.verify_copies;
+                        *resumable = self.config.resume_copies;
+                    }
+                    // Operations are numbered in the order they start
+                    if let Err(err) = tx.try_send(self.pending_operation_id) {
+                        log::warn!("failed to reply with operation ID: {err}");
+                    }
+                    return self.operation(operation);
+                }
+                crate::dbus::Request::PendingOperations(tx) => {
+                    let statuses = self
+                        .pending_operations
+                        .iter()
+                        .map(|(id, (op, controller))| {
+                            let progress = controller.progress();
+                            let state = controller.state();
+                            (
+                                *id,
+                                op.pending_text(progress, state),
+                                f64::from(progress),
+                                format!("{state:?}"),
+                            )
+                        })
+                        .collect();
+                    if let Err(err) = tx.try_send(statuses) {
+                        log::warn!("failed to reply with pending operations: {err}");
+                    }
+                }
+            },
             Message::ExtractHere(entity_opt) => {
                 let paths: Box<[_]> = self.selected_paths(entity_opt).collect();
                 if let Some(destination) = paths
@@ -3608,6 +4610,9 @@ impl Application for App {
                                 paths: file_paths.to_vec(),
                                 to: selected_paths[0].clone(),
                                 cross_device_copy: false,
+                                preserve: self.config.preserve,
+                                verify: self.config.verify_copies,
+                                resumable: self.config.resume_copies,
                             });
                         }
                     }
@@ -3685,9 +4690,58 @@ impl Application for App {
                     ]);
                 }
             }
+            Message::Properties(entity_opt) => {
+                let paths: Box<[_]> = self.selected_paths(entity_opt).collect();
+                if !paths.is_empty() {
+                    return self
+                        .dialog_pages
+                        .push_back(DialogPage::Properties(PropertiesPage::new(paths)));
+                }
+            }
+            Message::NewTag(entity_opt) => {
+                let paths: Box<[_]> = self.selected_paths(entity_opt).collect();
+                return Task::batch([
+                    self.dialog_pages.push_back(DialogPage::NewTag {
+                        paths,
+                        name: String::new(),
+                        color: TagColor::default(),
+                    }),
+                    widget::text_input::focus(self.dialog_text_input.clone()),
+                ]);
+            }
             #[cfg(feature = "notify")]
             Message::Notification(notification) => {
                 self.notification_opt = Some(notification);
+                return self.update(Message::NotificationUpdate);
+            }
+            #[cfg(feature = "notify")]
+            Message::NotificationUpdate => {
+                if let Some(notification_arc) = self.notification_opt.clone() {
+                    let body = self
+                        .pending_operations
+                        .values()
+                        .filter(|(op, _)| op.show_progress_notification())
+                        .map(|(op, controller)| {
+                            let text = op.pending_text(controller.progress(), controller.state());
+                            match controller.with_stats(transfer_stats_text) {
+                                Some(stats) => format!("{text}\n{stats}"),
+                                None => text,
+                            }
+                        })
+                        .collect::<Vec<_>>()
+                        .join("\n");
+                    return Task::future(async move {
+                        tokio::task::spawn_blocking(move || {
+                            if let Ok(mut notification) = notification_arc.lock() {
+                                notification.body(&body);
+                                notification.update();
+                            }
+                        })
+                        .await
+                        .unwrap();
+                        cosmic::action::none()
+                    });
+                }
             }
             Message::NotifyEvents(events) => {
                 log::debug!("{events:?}");
@@ -3817,20 +4871,13 @@ impl Application for App {
                         .map(|path| self.open_tab(Location::Path(path), false, None)),
                 );
             }
-            Message::OpenInNewWindow(entity_opt) => match env::current_exe() {
-                Ok(exe) => self
+            Message::OpenInNewWindow(entity_opt) => {
+                let paths: Vec<_> = self
                     .selected_paths(entity_opt)
                     .filter(|p| p.is_dir())
-                    .for_each(|path| match process::Command::new(&exe).arg(path).spawn() {
-                        Ok(_child) => {}
-                        Err(err) => {
-                            log::error!("failed to execute {}: {}", exe.display(), err);
-                        }
-                    }),
-                Err(err) => {
-                    log::error!("failed to get current executable path: {err}");
-                }
-            },
+                    .collect();
+                open_in_new_window(&paths);
+            }
             Message::OpenItemLocation(entity_opt) => {
                 let selected_paths: Box<[_]> = self.selected_paths(entity_opt).collect();
                 return Task::batch(selected_paths.into_iter().filter_map(|path| {
@@ -3983,15 +5030,57 @@ impl Application for App {
                         ClipboardKind::Copy => self.operation(Operation::Copy {
                             paths: contents.paths,
                             to,
+                            preserve: self.config.preserve,
+                            verify: self.config.verify_copies,
+                            resumable: self.config.resume_copies,
                         }),
                         ClipboardKind::Cut { is_dnd } => self.operation(Operation::Move {
                             paths: contents.paths,
                             to,
                             cross_device_copy: is_dnd,
+                            preserve: self.config.preserve,
+                            verify: self.config.verify_copies,
+                            resumable: self.config.resume_copies,
+                        }),
+                        ClipboardKind::Link { hard } => self.operation(Operation::Link {
+                            paths: contents.paths,
+                            to,
+                            hard,
                         }),
                     };
                 }
             }
+            Message::PasteLink(entity_opt, hard) => {
+                let entity = entity_opt.unwrap_or_else(|| self.tab_model.active());
+                if let Some(tab) = self.tab_model.data::<Tab>(entity)
+                    && let Some(path) = tab.location.path_opt()
+                {
+                    let to = path.clone();
+                    let kind = ClipboardKind::Link { hard };
+                    // Use cached clipboard data if available (needed for Wayland popups)
+                    if let ClipboardCache::Files(contents) = &self.clipboard_cache
+                        && !contents.paths.is_empty()
+                    {
+                        let contents = ClipboardPaste {
+                            kind,
+                            paths: contents.paths.clone(),
+                        };
+                        return self.update(Message::PasteContents(to, contents));
+                    }
+                    return clipboard::read_data::<ClipboardPaste>().map(move |contents_opt| {
+                        match contents_opt {
+                            Some(contents) => cosmic::action::app(Message::PasteContents(
+                                to.clone(),
+                                ClipboardPaste {
+                                    kind,
+                                    paths: contents.paths,
+                                },
+                            )),
+                            None => cosmic::action::none(),
+                        }
+                    });
+                }
+            }
             Message::PasteImage(to) => {
                 return clipboard::read_data::<ClipboardPasteImage>().map(move |contents_opt| {
                     match contents_opt {
@@ -4160,11 +5249,15 @@ impl Application for App {
                     controller.cancel();
                     self.progress_operations.remove(&id);
                 }
+                if self.operation_queue.cancel(id) {
+                    self.start_queued_operations();
+                }
             }
             Message::PendingCancelAll => {
                 for (id, (_, controller)) in &self.pending_operations {
                     controller.cancel();
                     self.progress_operations.remove(id);
+                    self.operation_queue.cancel(*id);
                 }
             }
             Message::PendingComplete(id, op_sel) => {
@@ -4190,6 +5283,8 @@ impl Application for App {
                         controller.unpause();
                     }
                 }
+                // Pausing a queued operation lets the ones after it start
+                self.start_queued_operations();
             }
             Message::PendingPauseAll(pause) => {
                 for (_, controller) in self.pending_operations.values() {
@@ -4199,6 +5294,27 @@ impl Application for App {
                         controller.unpause();
                     }
                 }
+                self.start_queued_operations();
+            }
+            Message::PendingReorder(id, earlier) => {
+                self.operation_queue.reorder(id, earlier);
+                self.start_queued_operations();
+                self.save_operation_queue();
+            }
+            Message::PendingRetry(id) => {
+                return self.retry_operation(id);
+            }
+            Message::PendingBandwidthLimit(id, limit) => {
+                if let Some((_, controller)) = self.pending_operations.get(&id) {
+                    controller.set_bandwidth_limit(limit);
+                }
+            }
+            Message::PendingExpand(id) => {
+                if self.expanded_operation == Some(id) {
+                    self.expanded_operation = None;
+                } else {
+                    self.expanded_operation = Some(id);
+                }
             }
             Message::PermanentlyDelete(entity_opt) => {
                 let paths: Box<[_]> = self.selected_paths(entity_opt).collect();
@@ -4328,6 +5444,39 @@ impl Application for App {
                     }
                 }
             }
+            Message::ReplaceChecksums(path, checksums) => {
+                if let Some(DialogPage::Replace { from, to, .. }) = self.dialog_pages.front_mut() {
+                    for item in [from, to] {
+                        if item.path_opt() == Some(&path) {
+                            item.checksums = checksums.clone();
+                        }
+                    }
+                }
+            }
+            Message::ReplaceCompare => {
+                if let Some(DialogPage::Replace {
+                    from, to, compare, ..
+                }) = self.dialog_pages.front_mut()
+                {
+                    *compare = true;
+                    let mut tasks = Vec::with_capacity(2);
+                    for item in [from, to] {
+                        if !item.metadata.is_dir()
+                            && let Some(path) = item.path_opt().cloned()
+                        {
+                            item.checksums = tab::ChecksumState::Calculating;
+                            tasks.push(Task::future(async move {
+                                let checksums = match tab::calculate_checksums(&path).await {
+                                    Ok(checksums) => tab::ChecksumState::Calculated(checksums),
+                                    Err(err) => tab::ChecksumState::Error(err),
+                                };
+                                cosmic::action::app(Message::ReplaceChecksums(path, checksums))
+                            }));
+                        }
+                    }
+                    return Task::batch(tasks);
+                }
+            }
             Message::ReplaceResult(replace_result) => {
                 if let Some((dialog_page, task)) = self.dialog_pages.pop_front() {
                     match dialog_page {
@@ -4396,13 +5545,111 @@ impl Application for App {
                 config_set!(show_recents, show_recents);
                 return self.update_config();
             }
+            Message::Show(mode, paths, startup_id) => {
+                return self.show(mode, paths, startup_id);
+            }
             Message::SetTypeToSearch(type_to_search) => {
                 config_set!(type_to_search, type_to_search);
                 return self.update_config();
             }
+            Message::SetPreserve(preserve) => {
+                config_set!(preserve, preserve);
+                return self.update_config();
+            }
+            Message::SetResumeCopies(resume_copies) => {
+                config_set!(resume_copies, resume_copies);
+                return self.update_config();
+            }
+            Message::SetVerifyCopies(verify_copies) => {
+                config_set!(verify_copies, verify_copies);
+                return self.update_config();
+            }
             Message::SystemThemeModeChange => {
                 return self.update_config();
             }
+            Message::SyncCompare => {
+                if let Some(DialogPage::Sync {
+                    source,
+                    destination,
+                    mode,
+                    hash,
+                    comparing,
+                    plan,
+                }) = self.dialog_pages.front_mut()
+                {
+                    *comparing = true;
+                    *plan = None;
+                    let (source, destination, mode, hash) =
+                        (source.clone(), destination.clone(), *mode, *hash);
+                    return Task::future(async move {
+                        let plan = tokio::task::spawn_blocking(move || {
+                            crate::operation::sync::plan(&source, &destination, mode, hash)
+                                .map_err(|err| err.to_string())
+                        })
+                        .await
+                        .unwrap_or_else(|err| Err(err.to_string()));
+                        cosmic::action::app(Message::SyncPlan(mode, hash, plan))
+                    });
+                }
+            }
+            Message::SyncFolders(entity_opt) => {
+                let entity = entity_opt.unwrap_or_else(|| self.tab_model.active());
+                let selected: Box<[_]> = self.selected_paths(Some(entity)).collect();
+                let source = match &*selected {
+                    [path] if path.is_dir() => Some(path.clone()),
+                    _ => self
+                        .tab_model
+                        .data::<Tab>(entity)
+                        .and_then(|tab| tab.location.path_opt().cloned()),
+                };
+                if let Some(source) = source {
+                    return self.sync_folders(&source);
+                }
+            }
+            Message::SyncFoldersResult(result) => {
+                if let DialogResult::Open(selected_paths) = result {
+                    let mut source = None;
+                    if let Some(file_dialog) = &self.file_dialog_opt
+                        && let Some(window) = self.windows.remove(&file_dialog.window_id())
+                        && let WindowKind::FileDialog(Some(paths)) = window.kind
+                    {
+                        source = paths.first().cloned();
+                    }
+                    if let Some(source) = source
+                        && let Some(destination) = selected_paths.first()
+                    {
+                        self.file_dialog_opt = None;
+                        return self.push_dialog(
+                            DialogPage::Sync {
+                                source,
+                                destination: destination.clone(),
+                                mode: SyncMode::default(),
+                                hash: false,
+                                comparing: false,
+                                plan: None,
+                            },
+                            None,
+                        );
+                    }
+                }
+                self.file_dialog_opt = None;
+            }
+            Message::SyncPlan(plan_mode, plan_hash, result) => {
+                // Plans for options that were changed while comparing are dropped
+                if let Some(DialogPage::Sync {
+                    mode,
+                    hash,
+                    comparing,
+                    plan,
+                    ..
+                }) = self.dialog_pages.front_mut()
+                    && *mode == plan_mode
+                    && *hash == plan_hash
+                {
+                    *comparing = false;
+                    *plan = Some(result);
+                }
+            }
             Message::TabActivate(entity) => {
                 let mut tasks = vec![self.close_context_menus()];
 
@@ -4496,6 +5743,30 @@ impl Application for App {
                 config.folders_first = !config.folders_first;
                 return self.update(Message::TabConfig(config));
             }
+            Message::ToggleTag(entity_opt, tag_i) => {
+                let Some(tag) = self.config.tags.get(tag_i) else {
+                    log::warn!("invalid tag index `{tag_i}`");
+                    return Task::none();
+                };
+                let entity = entity_opt.unwrap_or_else(|| self.tab_model.active());
+                // Remove the tag only if every selected item has it
+                let enabled = !self
+                    .tab_model
+                    .data::<Tab>(entity)
+                    .and_then(|tab| tab.items_opt())
+                    .is_some_and(|items| {
+                        items
+                            .iter()
+                            .filter(|item| item.selected)
+                            .all(|item| item.tags.contains(&tag.name))
+                    });
+                let operation = Operation::SetTag {
+                    paths: self.selected_paths(entity_opt).collect(),
+                    tag: tag.name.clone(),
+                    enabled,
+                };
+                return self.operation(operation);
+            }
             Message::ToggleShowHidden => {
                 let mut config = self.config.tab;
                 config.show_hidden = !config.show_hidden;
@@ -4669,11 +5940,8 @@ impl Application for App {
                                         Some(CONFIRM_CONTEXT_ACTION_BUTTON_ID.clone()),
                                     ));
                                 } else {
-                                    context_action::run(
-                                        &self.config.context_actions,
-                                        action,
-                                        &paths,
-                                    );
+                                    commands
+                                        .push(self.run_context_action(action, paths.into_vec()));
                                 }
                             } else {
                                 log::warn!("invalid context action index `{action}`");
@@ -4735,6 +6003,20 @@ impl Application for App {
                                 ),
                             );
                         }
+                        tab::Command::SetDesktopPositions(output, positions) => {
+                            self.state.desktop_positions.insert(output, positions);
+                            if let Some(state_handler) = self.state_handler.as_ref()
+                                && let Err(err) = state_handler
+                                    .set("desktop_positions", &self.state.desktop_positions)
+                            {
+                                log::warn!("Failed to save desktop positions: {err:?}");
+                            }
+                        }
+                        tab::Command::TransformImage(path, transform) => {
+                            commands.push(
+                                self.operation(Operation::TransformImage { path, transform }),
+                            );
+                        }
                         tab::Command::WindowDrag => {
                             if let Some(window_id) = self.core.main_window_id() {
                                 commands.push(window::drag(window_id));
@@ -4827,7 +6109,7 @@ impl Application for App {
                         tasks.push(clipboard::read_data::<ClipboardPaste>().map(|p| {
                             cosmic::action::app(Message::CutPaths(match p {
                                 Some(s) => match s.kind {
-                                    ClipboardKind::Copy => Vec::new(),
+                                    ClipboardKind::Copy | ClipboardKind::Link { .. } => Vec::new(),
                                     ClipboardKind::Cut { .. } => s.paths,
                                 },
                                 None => Vec::new(),
@@ -5012,7 +6294,8 @@ impl Application for App {
                     let kind = match action {
                         DndAction::Move => ClipboardKind::Cut { is_dnd: true },
                         _ => ClipboardKind::Copy,
-                    };
+                    }
+                    .with_modifiers(self.modifiers);
                     let ret = match location {
                         Location::Path(p) => self.update(Message::PasteContents(
                             p.clone(),
@@ -5074,7 +6357,8 @@ impl Application for App {
                     let kind = match action {
                         DndAction::Move => ClipboardKind::Cut { is_dnd: true },
                         _ => ClipboardKind::Copy,
-                    };
+                    }
+                    .with_modifiers(self.modifiers);
                     let ret = match &tab.location {
                         Location::Trash if matches!(action, DndAction::Move) => {
                             self.delete(data.paths)
@@ -5205,7 +6489,7 @@ impl Application for App {
                                     Some(CONFIRM_CONTEXT_ACTION_BUTTON_ID.clone()),
                                 );
                             }
-                            context_action::run(&self.config.context_actions, action, &paths);
+                            return self.run_context_action(action, paths);
                         } else {
                             log::warn!("invalid context action index `{action}`");
                         }
@@ -5297,6 +6581,16 @@ impl Application for App {
                     }
                 }
 
+                NavMenuAction::RemoveTag(entity) => {
+                    // Files keep the tag, only the sidebar entry and color are removed
+                    if let Some(TagIndex(tag_i)) = self.nav_model.data::<TagIndex>(entity) {
+                        let mut tags = self.config.tags.clone();
+                        tags.remove(*tag_i);
+                        config_set!(tags, tags);
+                        return self.update_config();
+                    }
+                }
+
                 NavMenuAction::RemoveFromSidebar(entity) => {
                     if let Some(FavoriteIndex(favorite_i)) =
                         self.nav_model.data::<FavoriteIndex>(entity)
@@ -5309,16 +6603,10 @@ impl Application for App {
                 }
 
                 NavMenuAction::ChangeSidebarLabel(entity) => {
-                    if let Some(favorite) = self
-                        .nav_model
-                        .data::<FavoriteIndex>(entity)
-                        .and_then(|FavoriteIndex(favorite_i)| {
-                            self.config.favorites.get(*favorite_i)
-                        })
-                    {
-                        let label = favorite
-                            .display_name()
-                            .unwrap_or_else(|| fl!("filesystem"));
+                    if let Some(favorite) = self.nav_model.data::<FavoriteIndex>(entity).and_then(
+                        |FavoriteIndex(favorite_i)| self.config.favorites.get(*favorite_i),
+                    ) {
+                        let label = favorite.display_name().unwrap_or_else(|| fl!("filesystem"));
                         return Task::batch([
                             self.dialog_pages
                                 .push_back(DialogPage::ChangeSidebarLabel { entity, label }),
@@ -5367,8 +6655,9 @@ impl Application for App {
                             }
                         };
 
+                        let (path, desktop_config) = self.config.desktop.for_output(&display);
                         let (entity, command) = self.open_tab_entity(
-                            Location::Desktop(crate::desktop_dir(), display, self.config.desktop),
+                            Location::Desktop(path, display, desktop_config),
                             false,
                             None,
                             widget::Id::unique(),
@@ -5754,8 +7043,11 @@ impl Application for App {
                     .title("Failed operation")
                     .body(format!("{operation:#?}\n{err}"))
                     .icon(icon::from_name("dialog-error").size(64))
-                    //TODO: retry action
                     .primary_action(
+                        widget::button::suggested(fl!("try-again"))
+                            .on_press(Message::DialogComplete),
+                    )
+                    .secondary_action(
                         widget::button::standard(fl!("cancel")).on_press(Message::DialogCancel),
                     )
             }
@@ -5773,11 +7065,33 @@ impl Application for App {
                     .title("Failed operations")
                     .body(errors.join("\n\n"))
                     .icon(icon::from_name("dialog-error").size(64))
-                    //TODO: retry action
                     .primary_action(
+                        widget::button::suggested(fl!("try-again"))
+                            .on_press(Message::DialogComplete),
+                    )
+                    .secondary_action(
                         widget::button::standard(fl!("cancel")).on_press(Message::DialogCancel),
                     )
             }
+            DialogPage::InterruptedOperations(operations) => {
+                let mut column = widget::column::with_capacity(operations.len());
+                for operation in operations {
+                    column = column.push(widget::text::body(
+                        operation.pending_text(0.0, ControllerState::Paused),
+                    ));
+                }
+                widget::dialog()
+                    .title(fl!("interrupted-operations"))
+                    .body(fl!("interrupted-operations-body"))
+                    .icon(icon::from_name("dialog-warning").size(64))
+                    .control(widget::scrollable(column).height(Length::Shrink))
+                    .primary_action(
+                        widget::button::suggested(fl!("resume")).on_press(Message::DialogComplete),
+                    )
+                    .secondary_action(
+                        widget::button::standard(fl!("discard")).on_press(Message::DialogCancel),
+                    )
+            }
             DialogPage::ExtractPassword { id, password } => widget::dialog()
                 .title(fl!("extract-password-required"))
                 .icon(icon::from_name("dialog-error").size(64))
@@ -6044,6 +7358,25 @@ impl Application for App {
                         widget::button::standard(fl!("cancel")).on_press(Message::DialogCancel),
                     )
             }
+            DialogPage::ContextActionOutput { name, output } => {
+                let mut column = widget::column::with_capacity(2).spacing(space_xxs);
+                if output.is_empty() {
+                    column = column.push(widget::text::body(fl!("context-action-no-output")));
+                }
+                for text in [&output.stdout, &output.stderr] {
+                    if !text.trim().is_empty() {
+                        column = column.push(widget::text::monotext(text.trim_end()));
+                    }
+                }
+
+                widget::dialog()
+                    .title(fl!("context-action-output-title", name = name.as_str()))
+                    .icon(icon::from_name("utilities-terminal").size(64))
+                    .control(widget::scrollable(column).height(Length::Shrink))
+                    .primary_action(
+                        widget::button::suggested(fl!("close")).on_press(Message::DialogComplete),
+                    )
+            }
             DialogPage::OpenWith {
                 path,
                 mime,
@@ -6254,6 +7587,64 @@ impl Application for App {
                         .spacing(space_xxs),
                     )
             }
+            DialogPage::NewTag { paths, name, color } => {
+                let exists = self.config.tags.iter().any(|tag| tag.name == name.trim());
+                // Tag searches end at the first whitespace, so names cannot contain any
+                let has_whitespace = name.trim().contains(char::is_whitespace);
+                let invalid = name.trim().is_empty() || name.contains(',') || has_whitespace;
+                let complete_maybe = if invalid || (exists && paths.is_empty()) {
+                    None
+                } else {
+                    Some(Message::DialogComplete)
+                };
+
+                let mut colors = widget::row::with_capacity(TagColor::ALL.len()).spacing(space_xxs);
+                for tag_color in TagColor::ALL {
+                    colors = colors.push(
+                        widget::button::icon(tag_color.icon(24))
+                            .selected(tag_color == *color)
+                            .on_press(Message::DialogUpdate(DialogPage::NewTag {
+                                paths: paths.clone(),
+                                name: name.clone(),
+                                color: tag_color,
+                            })),
+                    );
+                }
+
+                let mut column = widget::column::with_capacity(4).spacing(space_xxs);
+                column = column.push(widget::text::body(fl!("tag-name")));
+                column = column.push(
+                    widget::text_input("", name.as_str())
+                        .id(self.dialog_text_input.clone())
+                        .on_input(move |name| {
+                            Message::DialogUpdate(DialogPage::NewTag {
+                                paths: paths.clone(),
+                                name,
+                                color: *color,
+                            })
+                        })
+                        .on_submit_maybe(
+                            complete_maybe.clone().map(|maybe| move |_| maybe.clone()),
+                        ),
+                );
+                if name.contains(',') {
+                    column = column.push(widget::text::body(fl!("tag-name-invalid")));
+                }
+                if has_whitespace {
+                    column = column.push(widget::text::body(fl!("tag-name-whitespace")));
+                }
+                column = column.push(colors);
+
+                widget::dialog()
+                    .title(fl!("create-new-tag"))
+                    .primary_action(
+                        widget::button::suggested(fl!("save")).on_press_maybe(complete_maybe),
+                    )
+                    .secondary_action(
+                        widget::button::standard(fl!("cancel")).on_press(Message::DialogCancel),
+                    )
+                    .control(column)
+            }
             DialogPage::RenameItem {
                 from,
                 parent,
@@ -6336,63 +7727,302 @@ impl Application for App {
                 multiple,
                 apply_to_all,
                 conflict_count,
+                compare,
+                merge_policy,
                 tx,
             } => {
                 let military_time = self.config.tab.military_time;
-                let dialog = widget::dialog()
-                    .title(fl!("replace-title", filename = to.name.as_str()))
-                    .body(fl!("replace-warning-operation"))
-                    .control(
-                        to.replace_view(fl!("original-file"), military_time)
-                            .map(|x| Message::TabMessage(None, x)),
-                    )
-                    .control(
-                        from.replace_view(fl!("replace-with"), military_time)
-                            .map(|x| Message::TabMessage(None, x)),
-                    )
-                    .primary_action(
-                        widget::button::suggested(fl!("replace"))
-                            .on_press(Message::ReplaceResult(ReplaceResult::Replace(
-                                *apply_to_all,
-                            )))
-                            .id(REPLACE_BUTTON_ID.clone()),
-                    );
-                if *multiple {
-                    dialog
+                if to.metadata.is_dir() {
+                    widget::dialog()
+                        .title(fl!("merge-title", filename = to.name.as_str()))
+                        .body(fl!("merge-warning"))
                         .control(
-                            widget::checkbox(*apply_to_all)
-                                .label(format!("{} ({})", fl!("apply-to-all"), *conflict_count))
-                                .on_toggle(|apply_to_all| {
-                                    Message::DialogUpdate(DialogPage::Replace {
-                                        from: from.clone(),
-                                        to: to.clone(),
-                                        multiple: *multiple,
-                                        apply_to_all,
-                                        conflict_count: *conflict_count,
-                                        tx: tx.clone(),
-                                    })
-                                }),
+                            to.replace_view(fl!("original-folder"), military_time)
+                                .map(|x| Message::TabMessage(None, x)),
+                        )
+                        .control(
+                            from.replace_view(fl!("merge-with"), military_time)
+                                .map(|x| Message::TabMessage(None, x)),
+                        )
+                        .control(
+                            widget::settings::item::builder(fl!("merge-policy")).control(
+                                widget::dropdown(
+                                    &self.merge_policies,
+                                    MergePolicy::ALL.iter().position(|x| x == merge_policy),
+                                    |index| {
+                                        Message::DialogUpdate(DialogPage::Replace {
+                                            from: from.clone(),
+                                            to: to.clone(),
+                                            multiple: *multiple,
+                                            apply_to_all: *apply_to_all,
+                                            conflict_count: *conflict_count,
+                                            compare: *compare,
+                                            merge_policy: MergePolicy::ALL[index],
+                                            tx: tx.clone(),
+                                        })
+                                    },
+                                ),
+                            ),
+                        )
+                        .control(
+                            widget::button::link(fl!("keep-both"))
+                                .on_press(Message::ReplaceResult(ReplaceResult::KeepBoth))
+                                .padding(0),
+                        )
+                        .primary_action(
+                            widget::button::suggested(fl!("merge"))
+                                .on_press(Message::ReplaceResult(ReplaceResult::Merge(
+                                    *merge_policy,
+                                )))
+                                .id(REPLACE_BUTTON_ID.clone()),
                         )
                         .secondary_action(
-                            widget::button::standard(fl!("skip")).on_press(Message::ReplaceResult(
-                                ReplaceResult::Skip(*apply_to_all),
-                            )),
+                            widget::button::standard(fl!("skip"))
+                                .on_press(Message::ReplaceResult(ReplaceResult::Skip(false))),
                         )
                         .tertiary_action(
                             widget::button::text(fl!("cancel"))
                                 .on_press(Message::ReplaceResult(ReplaceResult::Cancel)),
                         )
                 } else {
-                    dialog
-                        .secondary_action(
-                            widget::button::standard(fl!("cancel"))
-                                .on_press(Message::ReplaceResult(ReplaceResult::Cancel)),
-                        )
-                        .tertiary_action(
-                            widget::button::text(fl!("keep-both"))
-                                .on_press(Message::ReplaceResult(ReplaceResult::KeepBoth)),
+                    let to_view = to
+                        .replace_view(fl!("original-file"), military_time)
+                        .map(|x| Message::TabMessage(None, x));
+                    let from_view = from
+                        .replace_view(fl!("replace-with"), military_time)
+                        .map(|x| Message::TabMessage(None, x));
+                    let mut dialog = widget::dialog()
+                        .title(fl!("replace-title", filename = to.name.as_str()))
+                        .body(fl!("replace-warning-operation"));
+                    if *compare {
+                        dialog = dialog.control(
+                            widget::row::with_children([
+                                widget::container(to_view).width(Length::Fill).into(),
+                                widget::container(from_view).width(Length::Fill).into(),
+                            ])
+                            .spacing(space_xxs),
+                        );
+                        if let (
+                            tab::ChecksumState::Calculated(to_checksums),
+                            tab::ChecksumState::Calculated(from_checksums),
+                        ) = (&to.checksums, &from.checksums)
+                        {
+                            dialog = dialog.control(widget::text::body(
+                                if to_checksums.sha256 == from_checksums.sha256 {
+                                    fl!("files-identical")
+                                } else {
+                                    fl!("files-different")
+                                },
+                            ));
+                        }
+                    } else {
+                        dialog = dialog.control(to_view).control(from_view);
+                    }
+                    let dialog = dialog
+                        .control(
+                            widget::flex_row(vec![
+                                widget::button::link(fl!("compare"))
+                                    .on_press_maybe((!*compare).then_some(Message::ReplaceCompare))
+                                    .padding(0)
+                                    .into(),
+                                widget::button::link(fl!("replace-if-newer"))
+                                    .on_press(Message::ReplaceResult(
+                                        ReplaceResult::ReplaceIfNewer(*apply_to_all),
+                                    ))
+                                    .padding(0)
+                                    .into(),
+                                widget::button::link(fl!("replace-if-size-differs"))
+                                    .on_press(Message::ReplaceResult(
+                                        ReplaceResult::ReplaceIfSizeDiffers(*apply_to_all),
+                                    ))
+                                    .padding(0)
+                                    .into(),
+                                widget::button::link(fl!("rename-existing"))
+                                    .on_press(Message::ReplaceResult(
+                                        ReplaceResult::RenameExisting(*apply_to_all),
+                                    ))
+                                    .padding(0)
+                                    .into(),
+                            ])
+                            .column_spacing(space_s),
                         )
+                        .primary_action(
+                            widget::button::suggested(fl!("replace"))
+                                .on_press(Message::ReplaceResult(ReplaceResult::Replace(
+                                    *apply_to_all,
+                                )))
+                                .id(REPLACE_BUTTON_ID.clone()),
+                        );
+                    if *multiple {
+                        dialog
+                            .control(
+                                widget::checkbox(*apply_to_all)
+                                    .label(format!("{} ({})", fl!("apply-to-all"), *conflict_count))
+                                    .on_toggle(|apply_to_all| {
+                                        Message::DialogUpdate(DialogPage::Replace {
+                                            from: from.clone(),
+                                            to: to.clone(),
+                                            multiple: *multiple,
+                                            apply_to_all,
+                                            conflict_count: *conflict_count,
+                                            compare: *compare,
+                                            merge_policy: *merge_policy,
+                                            tx: tx.clone(),
+                                        })
+                                    }),
+                            )
+                            .secondary_action(widget::button::standard(fl!("skip")).on_press(
+                                Message::ReplaceResult(ReplaceResult::Skip(*apply_to_all)),
+                            ))
+                            .tertiary_action(
+                                widget::button::text(fl!("cancel"))
+                                    .on_press(Message::ReplaceResult(ReplaceResult::Cancel)),
+                            )
+                    } else {
+                        dialog
+                            .secondary_action(
+                                widget::button::standard(fl!("cancel"))
+                                    .on_press(Message::ReplaceResult(ReplaceResult::Cancel)),
+                            )
+                            .tertiary_action(
+                                widget::button::text(fl!("keep-both"))
+                                    .on_press(Message::ReplaceResult(ReplaceResult::KeepBoth)),
+                            )
+                    }
+                }
+            }
+            DialogPage::Properties(page) => {
+                let mut dialog = widget::dialog().title(fl!("properties"));
+
+                let complete_maybe = if !page.owner.is_empty() && page.owner_id().is_none() {
+                    dialog = dialog.tertiary_action(widget::text::body(fl!("user-not-found")));
+                    None
+                } else if !page.group.is_empty() && page.group_id().is_none() {
+                    dialog = dialog.tertiary_action(widget::text::body(fl!("group-not-found")));
+                    None
+                } else {
+                    Some(Message::DialogComplete)
+                };
+
+                let page_owner = page.clone();
+                let page_group = page.clone();
+                dialog = dialog
+                    .primary_action(
+                        widget::button::suggested(fl!("apply")).on_press_maybe(complete_maybe),
+                    )
+                    .secondary_action(
+                        widget::button::standard(fl!("cancel")).on_press(Message::DialogCancel),
+                    )
+                    .control(
+                        widget::column::with_children([
+                            widget::text::body(fl!("owner")).into(),
+                            widget::text_input(fl!("mixed"), page.owner.as_str())
+                                .on_input(move |owner| {
+                                    Message::DialogUpdate(DialogPage::Properties(PropertiesPage {
+                                        owner,
+                                        ..page_owner.clone()
+                                    }))
+                                })
+                                .into(),
+                            widget::text::body(fl!("group")).into(),
+                            widget::text_input(fl!("mixed"), page.group.as_str())
+                                .on_input(move |group| {
+                                    Message::DialogUpdate(DialogPage::Properties(PropertiesPage {
+                                        group,
+                                        ..page_group.clone()
+                                    }))
+                                })
+                                .into(),
+                        ])
+                        .spacing(space_xxs),
+                    );
+
+                if page.has_dirs {
+                    let page = page.clone();
+                    dialog =
+                        dialog.control(settings::item::builder(fl!("apply-to-contents")).toggler(
+                            page.change.recursive,
+                            move |recursive| {
+                                let mut page = page.clone();
+                                page.change.recursive = recursive;
+                                Message::DialogUpdate(DialogPage::Properties(page))
+                            },
+                        ));
+                }
+
+                // Sections of access dropdowns for either directories or other items
+                let access_section = |title: String, dirs: bool| {
+                    let (change, common, present) = if dirs {
+                        (page.change.dirs, page.dirs_common, page.has_dirs)
+                    } else {
+                        (page.change.files, page.files_common, page.has_files)
+                    };
+                    let placeholder = if present {
+                        fl!("mixed")
+                    } else {
+                        fl!("unchanged")
+                    };
+                    let mut section = settings::section().title(title);
+                    for (label, shift) in [
+                        (fl!("owner"), tab::MODE_SHIFT_USER),
+                        (fl!("group"), tab::MODE_SHIFT_GROUP),
+                        (fl!("other"), tab::MODE_SHIFT_OTHER),
+                    ] {
+                        let page = page.clone();
+                        section = section.add(
+                            settings::item::builder(label).control(
+                                widget::dropdown(
+                                    Cow::Borrowed(tab::MODE_NAMES.as_slice()),
+                                    change
+                                        .shown_part(shift, common.part(shift))
+                                        .map(|bits| bits as usize),
+                                    move |selected| {
+                                        let mut page = page.clone();
+                                        let bits = selected.try_into().unwrap();
+                                        let current = common.part(shift);
+                                        if dirs {
+                                            page.change.dirs = page
+                                                .change
+                                                .dirs
+                                                .with_toggled_part(shift, bits, current);
+                                        } else {
+                                            page.change.files = page
+                                                .change
+                                                .files
+                                                .with_toggled_part(shift, bits, current);
+                                        }
+                                        Message::DialogUpdate(DialogPage::Properties(page))
+                                    },
+                                )
+                                .placeholder(placeholder.clone()),
+                            ),
+                        );
+                    }
+                    section
+                };
+                if page.has_dirs {
+                    dialog = dialog.control(access_section(fl!("folder-access"), true));
+                }
+                if page.has_files || (page.has_dirs && page.change.recursive) {
+                    dialog = dialog.control(access_section(fl!("file-access"), false));
+                }
+
+                if !page.special_bits.is_empty() {
+                    dialog = dialog.control(widget::text::body(fl!(
+                        "special-bits",
+                        bits = page.special_bits.join(", ")
+                    )));
+                }
+
+                if !page.acl.is_empty() {
+                    let mut section = settings::section().title(fl!("access-control-list"));
+                    for entry in &page.acl {
+                        section = section.add(widget::text::monotext(entry.as_str()));
+                    }
+                    dialog = dialog.control(section);
                 }
+
+                dialog
             }
             DialogPage::SetExecutableAndLaunch { path } => {
                 let name = match path.file_name() {
@@ -6432,6 +8062,98 @@ impl Application for App {
                 .secondary_action(
                     widget::button::standard(fl!("keep")).on_press(Message::DialogCancel),
                 ),
+            DialogPage::Sync {
+                source,
+                destination,
+                mode,
+                hash,
+                comparing,
+                plan,
+            } => {
+                let update = |mode: SyncMode, hash: bool| {
+                    Message::DialogUpdate(DialogPage::Sync {
+                        source: source.clone(),
+                        destination: destination.clone(),
+                        mode,
+                        hash,
+                        comparing: false,
+                        plan: None,
+                    })
+                };
+                let mut options = widget::column::with_capacity(3).spacing(space_xxs);
+                for (value, label) in [
+                    (SyncMode::Mirror, fl!("sync-mirror")),
+                    (SyncMode::TwoWay, fl!("sync-two-way")),
+                ] {
+                    options = options.push(widget::radio(
+                        widget::text::body(label),
+                        value,
+                        Some(*mode),
+                        move |mode| update(mode, *hash),
+                    ));
+                }
+                options = options.push(
+                    widget::checkbox(*hash)
+                        .label(fl!("sync-compare-contents"))
+                        .on_toggle(move |hash| update(*mode, hash)),
+                );
+
+                let mut dialog = widget::dialog()
+                    .title(fl!("sync-folders-title"))
+                    .body(fl!(
+                        "sync-folders-description",
+                        from = source.display().to_string(),
+                        to = destination.display().to_string()
+                    ))
+                    .control(options)
+                    .secondary_action(
+                        widget::button::standard(fl!("cancel")).on_press(Message::DialogCancel),
+                    );
+                match plan {
+                    None => {
+                        let mut button = widget::button::suggested(fl!("compare"));
+                        if !*comparing {
+                            button = button.on_press(Message::SyncCompare);
+                        }
+                        dialog = dialog.primary_action(button);
+                        if *comparing {
+                            dialog = dialog.control(widget::text::body(fl!("sync-comparing")));
+                        }
+                    }
+                    Some(Ok(actions)) => {
+                        let mut button = widget::button::suggested(fl!("synchronize"));
+                        if actions.is_empty() {
+                            dialog = dialog.control(widget::text::body(fl!("sync-up-to-date")));
+                        } else {
+                            button = button.on_press(Message::DialogComplete);
+                            let mut column = widget::column::with_capacity(actions.len());
+                            for action in actions {
+                                let path = action.target().display().to_string();
+                                column = column.push(widget::text::body(match action {
+                                    SyncAction::Copy { .. } => fl!("sync-action-copy", path = path),
+                                    SyncAction::Update { .. } => {
+                                        fl!("sync-action-update", path = path)
+                                    }
+                                    SyncAction::Delete { .. } => {
+                                        fl!("sync-action-delete", path = path)
+                                    }
+                                }));
+                            }
+                            dialog = dialog.control(
+                                widget::scrollable(column.spacing(space_xxs))
+                                    .height(Length::Fixed(240.0)),
+                            );
+                        }
+                        dialog = dialog.primary_action(button);
+                    }
+                    Some(Err(err)) => {
+                        dialog = dialog
+                            .control(widget::text::body(err.as_str()))
+                            .primary_action(widget::button::suggested(fl!("synchronize")));
+                    }
+                }
+                dialog
+            }
         };
         Some(dialog.into())
     }
@@ -6446,6 +8168,7 @@ impl Application for App {
         } = theme::spacing();
 
         let mut title = String::new();
+        let mut stats_opt = None;
         let mut total_progress = 0.0;
         let mut count = 0;
         let mut all_paused = true;
@@ -6457,6 +8180,7 @@ impl Application for App {
                 let progress = controller.progress();
                 if title.is_empty() {
                     title = op.pending_text(progress, controller.state());
+                    stats_opt = controller.with_stats(transfer_stats_text);
                 }
                 total_progress += progress;
                 count += 1;
@@ -6472,6 +8196,10 @@ impl Application for App {
         }
         let finished = count - running;
         total_progress /= count as f32;
+        if running > 1 {
+            // The statistics only describe the first operation
+            stats_opt = None;
+        }
         if running >= 1 && (running > 1 || finished > 0) {
             if finished > 0 {
                 title = fl!(
@@ -6529,6 +8257,7 @@ impl Application for App {
             .align_y(Alignment::Center)
             .into(),
             widget::text::body(title).into(),
+            widget::text::caption(stats_opt.unwrap_or_default()).into(),
             widget::space::vertical().height(space_s).into(),
             widget::row::with_children([
                 widget::button::link(fl!("details"))
@@ -6650,6 +8379,7 @@ impl Application for App {
                     &self.modifiers,
                     self.clipboard_has_content(),
                     &self.config.context_actions,
+                    &self.config.tags,
                 )
                 .map(move |message| Message::TabMessage(Some(entity), message));
             tab_column = tab_column.push(tab_view);
@@ -6679,6 +8409,7 @@ impl Application for App {
                                 &window.modifiers,
                                 self.clipboard_has_content(),
                                 &self.config.context_actions,
+                                &self.config.tags,
                             )
                             .map(|x| Message::TabMessage(Some(*entity), x)),
                             id.clone(),
@@ -6697,6 +8428,7 @@ impl Application for App {
                                 &window.modifiers,
                                 self.clipboard_has_content(),
                                 &self.config.context_actions,
+                                &self.config.tags,
                             )
                             .map(move |message| Message::TabMessage(Some(*entity), message)),
                         None => widget::space::vertical().into(),
@@ -7164,9 +8896,53 @@ impl Application for App {
                         },
                     ));
                 }
+                #[cfg(feature = "notify")]
+                if self.notification_opt.is_some() {
+                    subscriptions.push(
+                        cosmic::iced::time::every(Duration::from_secs(2))
+                            .map(|_| Message::NotificationUpdate),
+                    );
+                }
             }
         }
 
+        // Serve the session bus interfaces, so later launches reuse this instance and scripts can
+        // drive it
+        #[cfg(feature = "dbus")]
+        if matches!(self.mode, Mode::App) {
+            struct DbusSubscription;
+            subscriptions.push(Subscription::run_with(
+                TypeId::of::<DbusSubscription>(),
+                |_| {
+                    stream::channel(
+                        16,
+                        |mut output: futures::channel::mpsc::Sender<Message>| async move {
+                            let (messages, mut messages_rx) =
+                                tokio::sync::mpsc::unbounded_channel();
+                            let connection_res = match zbus::connection::Builder::session() {
+                                Ok(builder) => crate::dbus::serve(builder, messages).await,
+                                Err(err) => Err(err),
+                            };
+                            // The connection has to be kept alive to keep serving requests
+                            let _connection = match connection_res {
+                                Ok(connection) => connection,
+                                Err(err) => {
+                                    log::info!("not serving {}: {err}", crate::dbus::DBUS_NAME);
+                                    return std::future::pending().await;
+                                }
+                            };
+                            while let Some(message) = messages_rx.recv().await {
+                                if output.send(message).await.is_err() {
+                                    break;
+                                }
+                            }
+                            std::future::pending().await
+                        },
+                    )
+                },
+            ));
+        }
+
         let mut selected_previews = Vec::new();
         match self.mode {
             Mode::App => {
@@ -0,0 +1,864 @@
+// SPDX-License-Identifier: GPL-3.0-only
+
+//! Headless file operations for scripts, such as `cosmic-files copy <src>... <dst>`.
+//!
+//! Operations are performed exactly like in the app, and conflicts are resolved with the same
+//! choices as the replace dialog, either from `--replace` or by asking on the terminal.
+
+use cosmic::iced::futures::StreamExt;
+use cosmic::iced::futures::channel::mpsc;
+use cosmic::iced::futures::future::{self, Either};
+use serde::Serialize;
+use std::io::{self, BufRead, IsTerminal, Write};
+use std::path::{Path, PathBuf};
+use std::pin::pin;
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::sync::Mutex as TokioMutex;
+
+use crate::app::{ArchiveType, DialogPage, Message};
+use crate::config::Config;
+use crate::fl;
+use crate::operation::{
+    Controller, ControllerState, MergePolicy, Operation, OperationSelection, Preserve,
+    ReplaceResult,
+};
+
+pub const EXIT_SUCCESS: i32 = 0;
+/// The operation failed
+pub const EXIT_FAILURE: i32 = 1;
+/// The arguments were invalid
+pub const EXIT_USAGE: i32 = 2;
+/// The operation was cancelled, for example because of a conflict
+pub const EXIT_CANCELLED: i32 = 3;
+
+const PROGRESS_INTERVAL: Duration = Duration::from_millis(100);
+const PROGRESS_WIDTH: usize = 30;
+
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum Command {
+    Compress,
+    Copy,
+    Extract,
+    Move,
+    Trash,
+}
+
+impl Command {
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name {
+            "compress" => Some(Self::Compress),
+            "copy" => Some(Self::Copy),
+            "extract" => Some(Self::Extract),
+            "move" => Some(Self::Move),
+            "trash" => Some(Self::Trash),
+            _ => None,
+        }
+    }
+
+    pub const fn name(self) -> &'static str {
+        match self {
+            Self::Compress => "compress",
+            Self::Copy => "copy",
+            Self::Extract => "extract",
+            Self::Move => "move",
+            Self::Trash => "trash",
+        }
+    }
+
+    const fn usage(self) -> &'static str {
+        match self {
+            Self::Compress => {
+                "usage: cosmic-files compress [--json] [--format=zip|tgz] [--password] \
+                 <src>... <archive>"
+            }
+            Self::Copy => {
+                "usage: cosmic-files copy [--json] [--replace=POLICY] [--no-preserve=LIST] \
+                 <src>... <dst>"
+            }
+            Self::Extract => {
+                "usage: cosmic-files extract [--json] [--replace=POLICY] [--password] \
+                 <archive>... <dst>"
+            }
+            Self::Move => {
+                "usage: cosmic-files move [--json] [--replace=POLICY] [--no-preserve=LIST] \
+                 <src>... <dst>"
+            }
+            Self::Trash => "usage: cosmic-files trash [--json] <path>...",
+        }
+    }
+}
+
+/// How conflicts with existing files are resolved, given with `--replace`
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
+pub enum ReplacePolicy {
+    /// Ask on the terminal, or cancel if there is none
+    #[default]
+    Ask,
+    Replace,
+    ReplaceIfNewer,
+    ReplaceIfSizeDiffers,
+    RenameExisting,
+    KeepBoth,
+    Skip,
+    Cancel,
+}
+
+impl ReplacePolicy {
+    fn from_name(name: &str) -> Option<Self> {
+        match name {
+            "ask" => Some(Self::Ask),
+            "replace" => Some(Self::Replace),
+            "newer" => Some(Self::ReplaceIfNewer),
+            "size-differs" => Some(Self::ReplaceIfSizeDiffers),
+            "rename-existing" => Some(Self::RenameExisting),
+            "keep-both" => Some(Self::KeepBoth),
+            "skip" => Some(Self::Skip),
+            "cancel" => Some(Self::Cancel),
+            _ => None,
+        }
+    }
+
+    /// Result for a conflict, or `None` to ask. `apply_to_all` resolves the remaining conflicts
+    /// the same way.
+    ///
+    /// Folders are merged for the policies that replace files, so that the files inside them are
+    /// resolved by the same policy.
+    const fn replace_result(self, is_dir: bool, apply_to_all: bool) -> Option<ReplaceResult> {
+        Some(match self {
+            Self::Ask => return None,
+            Self::KeepBoth => ReplaceResult::KeepBoth,
+            Self::Skip => ReplaceResult::Skip(apply_to_all),
+            Self::Cancel => ReplaceResult::Cancel,
+            _ if is_dir => ReplaceResult::Merge(MergePolicy::Ask),
+            Self::Replace => ReplaceResult::Replace(apply_to_all),
+            Self::ReplaceIfNewer => ReplaceResult::ReplaceIfNewer(apply_to_all),
+            Self::ReplaceIfSizeDiffers => ReplaceResult::ReplaceIfSizeDiffers(apply_to_all),
+            Self::RenameExisting => ReplaceResult::RenameExisting(apply_to_all),
+        })
+    }
+}
+
+/// Parse the comma separated list of `--no-preserve`, using the names of `cp`. Everything that is
+/// not listed is preserved.
+fn parse_no_preserve(list: &str) -> Result<Preserve, String> {
+    let mut preserve = Preserve::ALL;
+    for name in list.split(',') {
+        match name {
+            "all" => preserve = Preserve::NONE,
+            "timestamps" => preserve.times = false,
+            "mode" => preserve.permissions = false,
+            "ownership" => preserve.ownership = false,
+            "xattr" => preserve.xattrs = false,
+            _ => return Err(format!("invalid attribute {name:?}")),
+        }
+    }
+    Ok(preserve)
+}
+
+/// Parse an answer to the replace prompt. Upper case answers apply to all remaining conflicts.
+fn parse_answer(answer: &str, is_dir: bool) -> Option<(ReplacePolicy, bool)> {
+    let mut chars = answer.trim().chars();
+    let c = chars.next()?;
+    if chars.next().is_some() {
+        return None;
+    }
+    let policy = match (c.to_ascii_lowercase(), is_dir) {
+        ('m', true) => ReplacePolicy::Replace,
+        ('r', false) => ReplacePolicy::Replace,
+        ('n', false) => ReplacePolicy::ReplaceIfNewer,
+        ('d', false) => ReplacePolicy::ReplaceIfSizeDiffers,
+        ('e', false) => ReplacePolicy::RenameExisting,
+        ('k', _) => ReplacePolicy::KeepBoth,
+        ('s', _) => ReplacePolicy::Skip,
+        ('c', _) => ReplacePolicy::Cancel,
+        _ => return None,
+    };
+    // Merging a folder only applies to that folder, its files are asked for separately
+    Some((policy, c.is_ascii_uppercase() && !is_dir))
+}
+
+#[derive(Debug, Default, Eq, PartialEq)]
+struct Options {
+    json: bool,
+    replace: ReplacePolicy,
+    /// Metadata to preserve from `--no-preserve`, instead of the settings
+    preserve: Option<Preserve>,
+    archive_type: Option<ArchiveType>,
+    /// Read the password of the archive from standard input
+    read_password: bool,
+    password: Option<String>,
+    paths: Vec<PathBuf>,
+}
+
+impl Options {
+    fn parse(command: Command, args: &[String]) -> Result<Self, String> {
+        let mut options = Self::default();
+        let mut args = args.iter();
+        while let Some(arg) = args.next() {
+            if arg == "--" {
+                options.paths.extend(args.by_ref().map(PathBuf::from));
+                break;
+            } else if arg == "--json" {
+                options.json = true;
+            } else if let Some(value) = arg.strip_prefix("--replace=")
+                && command != Command::Compress
+                && command != Command::Trash
+            {
+                options.replace = ReplacePolicy::from_name(value)
+                    .ok_or_else(|| format!("invalid replace policy {value:?}"))?;
+            } else if let Some(value) = arg.strip_prefix("--no-preserve=")
+                && matches!(command, Command::Copy | Command::Move)
+            {
+                options.preserve = Some(parse_no_preserve(value)?);
+            } else if let Some(value) = arg.strip_prefix("--format=")
+                && command == Command::Compress
+            {
+                options.archive_type = Some(
+                    ArchiveType::all()
+                        .iter()
+                        .copied()
+                        .find(|archive_type| &archive_type.extension()[1..] == value)
+                        .ok_or_else(|| format!("invalid archive format {value:?}"))?,
+                );
+            } else if arg == "--password" && matches!(command, Command::Compress | Command::Extract)
+            {
+                options.read_password = true;
+            } else if arg.starts_with("--") {
+                return Err(format!("unknown option {arg:?}"));
+            } else {
+                options.paths.push(PathBuf::from(arg));
+            }
+        }
+        Ok(options)
+    }
+
+    /// Operation on absolute paths, as the app performs them
+    fn operation(&self, command: Command, config: &Config) -> Result<Operation, String> {
+        let mut paths = self
+            .paths
+            .iter()
+            .map(|path| {
+                std::path::absolute(path).map_err(|err| format!("{}: {err}", path.display()))
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+        let to = if command == Command::Trash {
+            None
+        } else {
+            paths.pop()
+        };
+        if paths.is_empty() {
+            return Err("missing paths".to_string());
+        }
+        for path in &paths {
+            if let Err(err) = path.symlink_metadata() {
+                return Err(format!("{}: {err}", path.display()));
+            }
+        }
+        let to_dir = || match to.clone() {
+            Some(to) if to.is_dir() => Ok(to),
+            Some(to) => Err(format!("{} is not a folder", to.display())),
+            None => Err("missing destination".to_string()),
+        };
+
+        Ok(match command {
+            Command::Compress => {
+                let Some(mut to) = to else {
+                    return Err("missing archive".to_string());
+                };
+                let name = to
+                    .file_name()
+                    .map(|name| name.to_string_lossy().to_lowercase());
+                let detected = ArchiveType::all().iter().copied().find(|archive_type| {
+                    name.as_ref()
+                        .is_some_and(|name| name.ends_with(archive_type.extension()))
+                });
+                let archive_type = match (self.archive_type, detected) {
+                    (Some(archive_type), Some(detected)) if archive_type == detected => {
+                        archive_type
+                    }
+                    (Some(archive_type), _) => {
+                        to.as_mut_os_string().push(archive_type.extension());
+                        archive_type
+                    }
+                    (None, Some(detected)) => detected,
+                    (None, None) => {
+                        let archive_type = ArchiveType::default();
+                        to.as_mut_os_string().push(archive_type.extension());
+                        archive_type
+                    }
+                };
+                if to.symlink_metadata().is_ok() {
+                    return Err(format!("{} already exists", to.display()));
+                }
+                Operation::Compress {
+                    paths,
+                    to,
+                    archive_type,
+                    password: self.password.clone(),
+                }
+            }
+            Command::Copy => Operation::Copy {
+                paths,
+                to: to_dir()?,
+                preserve: self.preserve.unwrap_or(config.preserve),
+                verify: config.verify_copies,
+                resumable: config.resume_copies,
+            },
+            Command::Extract => Operation::Extract {
+                paths: paths.into_boxed_slice(),
+                to: to_dir()?,
+                password: self.password.clone(),
+            },
+            Command::Move => Operation::Move {
+                paths,
+                to: to_dir()?,
+                cross_device_copy: false,
+                preserve: self.preserve.unwrap_or(config.preserve),
+                verify: config.verify_copies,
+                resumable: config.resume_copies,
+            },
+            Command::Trash => Operation::Delete { paths },
+        })
+    }
+}
+
+#[derive(Clone, Copy, Debug, Eq, PartialEq, Serialize)]
+#[serde(rename_all = "kebab-case")]
+enum Status {
+    Complete,
+    Failed,
+    Cancelled,
+}
+
+impl Status {
+    const fn exit_code(self) -> i32 {
+        match self {
+            Self::Complete => EXIT_SUCCESS,
+            Self::Failed => EXIT_FAILURE,
+            Self::Cancelled => EXIT_CANCELLED,
+        }
+    }
+}
+
+/// Result printed with `--json`
+#[derive(Debug, Serialize)]
+struct Report {
+    command: &'static str,
+    status: Status,
+    message: String,
+    /// Paths created by the operation
+    paths: Vec<String>,
+}
+
+/// Progress bar on standard error, if it is a terminal
+struct Terminal {
+    progress: bool,
+    drawn: bool,
+}
+
+impl Terminal {
+    fn draw(&mut self, operation: &Operation, controller: &Controller) {
+        if !self.progress {
+            return;
+        }
+        let ratio = controller.progress();
+        let mut line = format!(
+            "{} {}",
+            progress_bar(ratio),
+            operation.pending_text(ratio, controller.state())
+        );
+        if let Some(summary) = controller.with_stats(|stats| stats.summary()) {
+            line.push_str(" - ");
+            line.push_str(&summary);
+        }
+        eprint!("\r\x1b[2K{line}");
+        let _ = io::stderr().flush();
+        self.drawn = true;
+    }
+
+    fn clear(&mut self) {
+        if self.drawn {
+            eprint!("\r\x1b[2K");
+            let _ = io::stderr().flush();
+            self.drawn = false;
+        }
+    }
+}
+
+fn progress_bar(ratio: f32) -> String {
+    let filled = ((ratio.clamp(0.0, 1.0) * PROGRESS_WIDTH as f32) as usize).min(PROGRESS_WIDTH);
+    format!(
+        "[{}{}] {:3}%",
+        "#".repeat(filled),
+        " ".repeat(PROGRESS_WIDTH - filled),
+        (ratio.clamp(0.0, 1.0) * 100.0) as u32
+    )
+}
+
+/// Ask how to resolve a conflict, returning `None` at the end of input
+fn prompt(to: &Path, is_dir: bool, conflict_count: usize) -> Option<(ReplacePolicy, bool)> {
+    let question = if is_dir {
+        format!(
+            "{} already exists. [m]erge, [k]eep both, [s]kip, [c]ancel? ",
+            to.display()
+        )
+    } else {
+        format!(
+            "{} already exists. [r]eplace, replace if [n]ewer, replace if size [d]iffers, \
+             r[e]name existing, [k]eep both, [s]kip, [c]ancel (upper case for all {conflict_count})? ",
+            to.display()
+        )
+    };
+    let mut stdin = io::stdin().lock();
+    loop {
+        eprint!("{question}");
+        let _ = io::stderr().flush();
+        let mut answer = String::new();
+        match stdin.read_line(&mut answer) {
+            Ok(0) | Err(_) => return None,
+            Ok(_) => {}
+        }
+        if let Some(answer) = parse_answer(&answer, is_dir) {
+            return Some(answer);
+        }
+    }
+}
+
+/// Read a password from the terminal without echoing it, or from the first line of standard input
+/// if it is not a terminal. Passwords are not accepted as arguments, as those are visible to other
+/// users.
+fn read_password() -> io::Result<String> {
+    let mut stdin = io::stdin().lock();
+    let terminal = stdin.is_terminal();
+    if terminal {
+        eprint!("Password: ");
+        io::stderr().flush()?;
+    }
+    #[cfg(unix)]
+    let echo = if terminal { disable_echo() } else { None };
+    let mut password = String::new();
+    let result = stdin.read_line(&mut password);
+    #[cfg(unix)]
+    if let Some(termios) = echo {
+        use std::os::fd::AsRawFd;
+        // SAFETY: the settings were read from the same terminal
+        unsafe { libc::tcsetattr(io::stdin().as_raw_fd(), libc::TCSANOW, &termios) };
+    }
+    if terminal {
+        eprintln!();
+    }
+    if result? == 0 {
+        return Err(io::Error::new(
+            io::ErrorKind::UnexpectedEof,
+            "no password given",
+        ));
+    }
+    Ok(password.trim_end_matches(['\r', '\n']).to_string())
+}
+
+/// Stop echoing input on the terminal, returning the settings to restore
+#[cfg(unix)]
+fn disable_echo() -> Option<libc::termios> {
+    use std::os::fd::AsRawFd;
+    let fd = io::stdin().as_raw_fd();
+    let mut termios = std::mem::MaybeUninit::uninit();
+    // SAFETY: `termios` is initialized when `tcgetattr` succeeds
+    unsafe {
+        if libc::tcgetattr(fd, termios.as_mut_ptr()) != 0 {
+            return None;
+        }
+        let original = termios.assume_init();
+        let mut silent = original;
+        silent.c_lflag &= !libc::ECHO;
+        (libc::tcsetattr(fd, libc::TCSANOW, &silent) == 0).then_some(original)
+    }
+}
+
+/// Perform an operation, resolving conflicts with `replace` and drawing progress if `progress`
+async fn perform(
+    operation: Operation,
+    mut replace: ReplacePolicy,
+    progress: bool,
+) -> (Operation, Controller, Result<OperationSelection, String>) {
+    let controller = Controller::default();
+    let (msg_tx, mut msg_rx) = mpsc::channel(1);
+    let msg_tx = Arc::new(TokioMutex::new(msg_tx));
+    let interactive = io::stdin().is_terminal();
+    let mut terminal = Terminal {
+        progress,
+        drawn: false,
+    };
+
+    let mut perform = pin!(operation.clone().perform(&msg_tx, controller.clone()));
+    let result = loop {
+        let message = pin!(msg_rx.next());
+        let tick = pin!(compio::time::sleep(PROGRESS_INTERVAL));
+        match future::select(perform.as_mut(), future::select(message, tick)).await {
+            Either::Left((result, _)) => break result.map_err(|err| err.to_string()),
+            Either::Right((Either::Left((Some(message), _)), _)) => {
+                let Message::DialogPush(
+                    DialogPage::Replace {
+                        to,
+                        conflict_count,
+                        tx,
+                        ..
+                    },
+                    _,
+                ) = message
+                else {
+                    log::warn!("unexpected message from operation: {message:?}");
+                    continue;
+                };
+                let is_dir = to.metadata.is_dir();
+                let to_path = to
+                    .path_opt()
+                    .cloned()
+                    .unwrap_or_else(|| PathBuf::from(&to.name));
+                // Policies given on the command line resolve every conflict
+                let replace_result = match replace.replace_result(is_dir, true) {
+                    Some(replace_result) => replace_result,
+                    None if interactive => {
+                        terminal.clear();
+                        let answer = compio::runtime::spawn_blocking(move || {
+                            prompt(&to_path, is_dir, conflict_count)
+                        })
+                        .await
+                        .ok()
+                        .flatten();
+                        match answer {
+                            Some((policy, apply_to_all)) => {
+                                if apply_to_all {
+                                    replace = policy;
+                                }
+                                policy
+                                    .replace_result(is_dir, apply_to_all)
+                                    .unwrap_or(ReplaceResult::Cancel)
+                            }
+                            None => ReplaceResult::Cancel,
+                        }
+                    }
+                    None => {
+                        terminal.clear();
+                        eprintln!(
+                            "{} already exists, use --replace to resolve conflicts without a terminal",
+                            to_path.display()
+                        );
+                        ReplaceResult::Cancel
+                    }
+                };
+                // The operation stops without an error, so it is marked as cancelled here
+                if replace_result == ReplaceResult::Cancel {
+                    controller.cancel();
+                }
+                let _ = tx.send(replace_result).await;
+            }
+            Either::Right((Either::Left((None, _)), _)) => {}
+            Either::Right((Either::Right(_), _)) => terminal.draw(&operation, &controller),
+        }
+    };
+    terminal.clear();
+    (operation, controller, result)
+}
+
+/// Run a command with the arguments after its name, returning the exit code
+pub fn run(command: Command, args: &[String], config: &Config) -> i32 {
+    if args.iter().any(|arg| arg == "--help" || arg == "-h") {
+        println!("{}", command.usage());
+        println!(
+            "POLICY is one of ask, replace, newer, size-differs, rename-existing, keep-both, skip \
+             or cancel"
+        );
+        if matches!(command, Command::Copy | Command::Move) {
+            println!(
+                "LIST is a comma separated list of timestamps, mode, ownership, xattr or all, \
+                 which are not kept regardless of the settings"
+            );
+        }
+        if matches!(command, Command::Compress | Command::Extract) {
+            println!("--password reads the password from the terminal or standard input");
+        }
+        return EXIT_SUCCESS;
+    }
+    let (options, operation) = match Options::parse(command, args)
+        .and_then(|mut options| {
+            if options.read_password {
+                options.password = Some(read_password().map_err(|err| err.to_string())?);
+            }
+            Ok(options)
+        })
+        .and_then(|options| options.operation(command, config).map(|op| (options, op)))
+    {
+        Ok(ok) => ok,
+        Err(err) => {
+            eprintln!("cosmic-files {}: {err}", command.name());
+            eprintln!("{}", command.usage());
+            return EXIT_USAGE;
+        }
+    };
+
+    // Some operations run blocking work on tokio, as they do in the app
+    let tokio_runtime = match tokio::runtime::Builder::new_current_thread()
+        .enable_all()
+        .build()
+    {
+        Ok(runtime) => runtime,
+        Err(err) => {
+            eprintln!(
+                "cosmic-files {}: failed to start runtime: {err}",
+                command.name()
+            );
+            return EXIT_FAILURE;
+        }
+    };
+    let _tokio = tokio_runtime.enter();
+    let compio_runtime = match compio::runtime::RuntimeBuilder::new().build() {
+        Ok(runtime) => runtime,
+        Err(err) => {
+            eprintln!(
+                "cosmic-files {}: failed to start runtime: {err}",
+                command.name()
+            );
+            return EXIT_FAILURE;
+        }
+    };
+
+    let progress = !options.json && io::stderr().is_terminal();
+    let (operation, controller, result) =
+        compio_runtime.block_on(perform(operation, options.replace, progress));
+    let report = report(command, &operation, &controller, result);
+
+    if options.json {
+        match serde_json::to_string(&report) {
+            Ok(json) => println!("{json}"),
+            Err(err) => {
+                eprintln!("cosmic-files {}: {err}", command.name());
+                return EXIT_FAILURE;
+            }
+        }
+    } else if report.status == Status::Complete {
+        println!("{}", report.message);
+    } else {
+        eprintln!("cosmic-files {}: {}", command.name(), report.message);
+    }
+    report.status.exit_code()
+}
+
+fn report(
+    command: Command,
+    operation: &Operation,
+    controller: &Controller,
+    result: Result<OperationSelection, String>,
+) -> Report {
+    let cancelled = controller.state() == ControllerState::Cancelled;
+    let (status, message, paths) = match result {
+        Ok(_) if cancelled => (Status::Cancelled, fl!("cancelled"), Vec::new()),
+        Ok(op_sel) => (
+            Status::Complete,
+            operation.completed_text(),
+            op_sel.selected,
+        ),
+        Err(err) if cancelled => (Status::Cancelled, err, Vec::new()),
+        Err(err) => (Status::Failed, err, Vec::new()),
+    };
+    Report {
+        command: command.name(),
+        status,
+        message,
+        paths: paths
+            .iter()
+            .map(|path| path.to_string_lossy().into_owned())
+            .collect(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::fs;
+    use std::path::PathBuf;
+    use test_log::test;
+
+    use super::{
+        Command, EXIT_CANCELLED, Options, ReplacePolicy, Status, parse_answer, perform,
+        progress_bar, report,
+    };
+    use crate::app::ArchiveType;
+    use crate::config::Config;
+    use crate::operation::{MergePolicy, Operation, Preserve, ReplaceResult};
+
+    fn args(args: &[&str]) -> Vec<String> {
+        args.iter().map(|arg| arg.to_string()).collect()
+    }
+
+    #[test]
+    fn parse_options() {
+        let options = Options::parse(
+            Command::Copy,
+            &args(&["--json", "--replace=newer", "a", "--", "--b", "dst"]),
+        )
+        .unwrap();
+        assert!(options.json);
+        assert_eq!(options.replace, ReplacePolicy::ReplaceIfNewer);
+        assert_eq!(
+            options.paths,
+            [
+                PathBuf::from("a"),
+                PathBuf::from("--b"),
+                PathBuf::from("dst")
+            ]
+        );
+
+        let options = Options::parse(
+            Command::Compress,
+            &args(&["--format=tgz", "--password", "a", "b"]),
+        )
+        .unwrap();
+        assert_eq!(options.archive_type, Some(ArchiveType::Tgz));
+        assert!(options.read_password);
+        assert_eq!(options.password, None);
+
+        let options = Options::parse(
+            Command::Move,
+            &args(&["--no-preserve=mode,xattr", "a", "b"]),
+        )
+        .unwrap();
+        assert_eq!(
+            options.preserve,
+            Some(Preserve {
+                permissions: false,
+                xattrs: false,
+                ..Preserve::ALL
+            })
+        );
+        let options =
+            Options::parse(Command::Copy, &args(&["--no-preserve=all", "a", "b"])).unwrap();
+        assert_eq!(options.preserve, Some(Preserve::NONE));
+        assert!(Options::parse(Command::Copy, &args(&["--no-preserve=links", "a", "b"])).is_err());
+        assert!(Options::parse(Command::Trash, &args(&["--no-preserve=mode", "a"])).is_err());
+
+        // Passwords in arguments would be visible to other users
+        assert!(Options::parse(Command::Extract, &args(&["--password=secret", "a", "b"])).is_err());
+
+        // Options only apply to the commands that use them
+        assert!(Options::parse(Command::Trash, &args(&["--replace=skip", "a"])).is_err());
+        assert!(Options::parse(Command::Copy, &args(&["--format=zip", "a", "b"])).is_err());
+        assert!(Options::parse(Command::Copy, &args(&["--replace=always", "a", "b"])).is_err());
+    }
+
+    #[test]
+    fn compress_appends_extension() {
+        let dir = tempfile::tempdir().unwrap();
+        let src = dir.path().join("src");
+        fs::write(&src, "").unwrap();
+        let config = Config::default();
+
+        let options = Options {
+            paths: vec![src.clone(), dir.path().join("archive.TGZ")],
+            ..Default::default()
+        };
+        match options.operation(Command::Compress, &config).unwrap() {
+            Operation::Compress {
+                to, archive_type, ..
+            } => {
+                assert_eq!(to, dir.path().join("archive.TGZ"));
+                assert_eq!(archive_type, ArchiveType::Tgz);
+            }
+            op => panic!("unexpected operation {op:?}"),
+        }
+
+        let options = Options {
+            archive_type: Some(ArchiveType::Zip),
+            paths: vec![src, dir.path().join("archive")],
+            ..Default::default()
+        };
+        match options.operation(Command::Compress, &config).unwrap() {
+            Operation::Compress { to, .. } => assert_eq!(to, dir.path().join("archive.zip")),
+            op => panic!("unexpected operation {op:?}"),
+        }
+
+        // Copies need an existing destination folder
+        let options = Options {
+            paths: vec![dir.path().join("missing"), dir.path().to_path_buf()],
+            ..Default::default()
+        };
+        assert!(options.operation(Command::Copy, &config).is_err());
+    }
+
+    #[test]
+    fn replace_answers_and_policies() {
+        assert_eq!(
+            parse_answer("r\n", false),
+            Some((ReplacePolicy::Replace, false))
+        );
+        assert_eq!(parse_answer("S", false), Some((ReplacePolicy::Skip, true)));
+        assert_eq!(
+            parse_answer("M", true),
+            Some((ReplacePolicy::Replace, false))
+        );
+        assert_eq!(parse_answer("r", true), None);
+        assert_eq!(parse_answer("replace", false), None);
+
+        assert_eq!(
+            ReplacePolicy::Replace.replace_result(true, true),
+            Some(ReplaceResult::Merge(MergePolicy::Ask))
+        );
+        assert_eq!(
+            ReplacePolicy::ReplaceIfNewer.replace_result(false, true),
+            Some(ReplaceResult::ReplaceIfNewer(true))
+        );
+        assert_eq!(
+            ReplacePolicy::Skip.replace_result(true, true),
+            Some(ReplaceResult::Skip(true))
+        );
+        // Answers for a single conflict do not apply to the others
+        assert_eq!(
+            ReplacePolicy::Replace.replace_result(false, false),
+            Some(ReplaceResult::Replace(false))
+        );
+        assert_eq!(
+            ReplacePolicy::Skip.replace_result(false, false),
+            Some(ReplaceResult::Skip(false))
+        );
+        assert_eq!(ReplacePolicy::Ask.replace_result(false, true), None);
+    }
+
+    #[test]
+    fn progress_bar_is_clamped() {
+        assert_eq!(progress_bar(0.5).len(), progress_bar(1.5).len());
+        assert!(progress_bar(0.5).ends_with(" 50%"));
+        assert!(progress_bar(-1.0).ends_with("  0%"));
+    }
+
+    #[test(compio::test)]
+    async fn copy_resolves_conflicts_with_policy() {
+        let dir = tempfile::tempdir().unwrap();
+        let src = dir.path().join("src");
+        let dst = dir.path().join("dst");
+        fs::create_dir_all(src.join("folder")).unwrap();
+        fs::create_dir_all(dst.join("folder")).unwrap();
+        fs::write(src.join("file"), "new").unwrap();
+        fs::write(dst.join("file"), "old").unwrap();
+        fs::write(src.join("folder/inner"), "new").unwrap();
+        fs::write(dst.join("folder/inner"), "old").unwrap();
+
+        let copy = Operation::Copy {
+            paths: vec![src.join("file"), src.join("folder")],
+            to: dst.clone(),
+            preserve: Default::default(),
+            verify: false,
+            resumable: false,
+        };
+
+        // Folders are merged, so files inside them are replaced too
+        let (operation, controller, result) =
+            perform(copy.clone(), ReplacePolicy::Replace, false).await;
+        let copied = report(Command::Copy, &operation, &controller, result);
+        assert_eq!(copied.status, Status::Complete);
+        assert_eq!(fs::read_to_string(dst.join("file")).unwrap(), "new");
+        assert_eq!(fs::read_to_string(dst.join("folder/inner")).unwrap(), "new");
+
+        let (operation, controller, result) = perform(copy, ReplacePolicy::Cancel, false).await;
+        let cancelled = report(Command::Copy, &operation, &controller, result);
+        assert_eq!(cancelled.status.exit_code(), EXIT_CANCELLED);
+    }
+}
@@ -2,6 +2,7 @@
 // SPDX-License-Identifier: GPL-3.0-only
 
 use cosmic::iced::clipboard::mime::{AllowedMimeTypes, AsMimeTypes};
+use cosmic::iced::keyboard::Modifiers;
 use std::borrow::Cow;
 use std::error::Error;
 use std::path::{Path, PathBuf};
@@ -12,6 +13,21 @@ use url::Url;
 pub enum ClipboardKind {
     Copy,
     Cut { is_dnd: bool },
+    Link { hard: bool },
+}
+
+impl ClipboardKind {
+    /// Kind of a drop made while holding `modifiers`. Holding Ctrl and Shift creates links, and
+    /// also holding Alt creates hard links.
+    pub fn with_modifiers(self, modifiers: Modifiers) -> Self {
+        if modifiers.control() && modifiers.shift() {
+            Self::Link {
+                hard: modifiers.alt(),
+            }
+        } else {
+            self
+        }
+    }
 }
 
 #[derive(Clone, Debug)]
@@ -34,7 +50,7 @@ impl ClipboardCopy {
         let mut text_plain = String::new();
         let mut text_uri_list = String::new();
         let mut x_special_gnome_copied_files = match kind {
-            ClipboardKind::Copy => "copy",
+            ClipboardKind::Copy | ClipboardKind::Link { .. } => "copy",
             ClipboardKind::Cut { .. } => "cut",
         }
         .to_string();
@@ -1,6 +1,7 @@
 // SPDX-License-Identifier: GPL-3.0-only
 
 use std::any::TypeId;
+use std::collections::BTreeMap;
 use std::num::NonZeroU16;
 use std::path::PathBuf;
 
@@ -12,9 +13,11 @@ use serde::{Deserialize, Serialize};
 
 use crate::FxOrderMap;
 use crate::app::App;
+use crate::operation::{Operation, Preserve};
 use crate::tab::{HeadingOptions, Location, View};
 
 pub use crate::context_action::{ContextActionPreset, ContextActionSelection};
+pub use crate::tag::{Tag, TagColor};
 
 pub const CONFIG_VERSION: u64 = 1;
 
@@ -56,7 +59,7 @@ impl AppTheme {
     }
 }
 
-#[derive(Clone, Debug, Deserialize, Eq, PartialEq, Serialize)]
+#[derive(Clone, Debug, Deserialize, Eq, Hash, PartialEq, Serialize)]
 pub enum Favorite {
     Home,
     Documents,
@@ -150,6 +153,13 @@ pub enum TypeToSearch {
 #[serde(default)]
 pub struct State {
     pub sort_names: FxOrderMap<String, (HeadingOptions, bool)>,
+    /// Operations that were queued or running by process ID, saved so they can be resumed after a
+    /// crash or logout
+    pub operation_queue: BTreeMap<u32, Vec<Operation>>,
+    /// Desktop icon positions by output name, and then by item name
+    pub desktop_positions: FxOrderMap<String, FxOrderMap<String, DesktopPosition>>,
+    /// Last folder chosen in the file chooser by the app ID of the caller
+    pub dialog_paths: FxOrderMap<String, PathBuf>,
 }
 
 impl Default for State {
@@ -161,6 +171,9 @@ impl Default for State {
                     (HeadingOptions::Modified, false),
                 )
             })),
+            operation_queue: BTreeMap::new(),
+            desktop_positions: FxOrderMap::default(),
+            dialog_paths: FxOrderMap::default(),
         }
     }
 }
@@ -207,7 +220,14 @@ pub struct Config {
     pub show_details: bool,
     pub show_recents: bool,
     pub tab: TabConfig,
+    pub tags: Vec<Tag>,
     pub type_to_search: TypeToSearch,
+    /// Compare checksums of copied files with their sources
+    pub verify_copies: bool,
+    /// Copy large files through a sidecar so that interrupted copies can be resumed
+    pub resume_copies: bool,
+    /// Metadata kept when copying and moving between filesystems
+    pub preserve: Preserve,
 }
 
 impl Config {
@@ -271,19 +291,29 @@ impl Default for Config {
             show_details: false,
             show_recents: true,
             tab: TabConfig::default(),
+            tags: Vec::new(),
             type_to_search: TypeToSearch::Recursive,
+            verify_copies: false,
+            resume_copies: false,
+            preserve: Preserve::default(),
         }
     }
 }
 
-#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq, CosmicConfigEntry, Deserialize, Serialize)]
+#[derive(Clone, Debug, Eq, Hash, PartialEq, CosmicConfigEntry, Deserialize, Serialize)]
 #[serde(default)]
 pub struct DesktopConfig {
     pub grid_spacing: NonZeroU16,
     pub icon_size: NonZeroU16,
+    /// Pack icons in the order they were placed, leaving no gaps
+    pub keep_aligned: bool,
+    /// Settings that differ on some outputs
+    pub outputs: Vec<DesktopOutputConfig>,
     pub show_content: bool,
     pub show_mounted_drives: bool,
     pub show_trash: bool,
+    /// Group files by type into stacks that expand when clicked
+    pub stacks: bool,
 }
 
 impl Default for DesktopConfig {
@@ -291,9 +321,12 @@ impl Default for DesktopConfig {
         Self {
             grid_spacing: 100.try_into().unwrap(),
             icon_size: 100.try_into().unwrap(),
+            keep_aligned: false,
+            outputs: Vec::new(),
             show_content: true,
             show_mounted_drives: false,
             show_trash: false,
+            stacks: false,
         }
     }
 }
@@ -302,6 +335,78 @@ impl DesktopConfig {
     pub fn grid_spacing_for(&self, space: u16) -> u16 {
         percent!(self.grid_spacing, space) as _
     }
+
+    pub fn output(&self, output: &str) -> Option<&DesktopOutputConfig> {
+        self.outputs.iter().find(|x| x.output == output)
+    }
+
+    /// Folder shown on an output, and the settings with its overrides applied
+    pub fn for_output(&self, output: &str) -> (PathBuf, Self) {
+        let mut path = crate::desktop_dir();
+        let mut config = self.clone();
+        if let Some(output_config) = self.output(output) {
+            match &output_config.folder {
+                DesktopFolder::Desktop => {}
+                DesktopFolder::Empty => config.show_content = false,
+                DesktopFolder::Favorite(favorite) => {
+                    if let Some(folder) = favorite.path_opt() {
+                        path = folder;
+                    }
+                }
+            }
+            if let Some(icon_size) = output_config.icon_size {
+                config.icon_size = icon_size;
+            }
+        }
+        (path, config)
+    }
+
+    /// Replaces the overrides of an output, dropping them when they match the defaults
+    pub fn set_output(&mut self, output_config: DesktopOutputConfig) {
+        let is_default = output_config
+            == DesktopOutputConfig {
+                output: output_config.output.clone(),
+                ..Default::default()
+            };
+        match self
+            .outputs
+            .iter()
+            .position(|x| x.output == output_config.output)
+        {
+            Some(i) if is_default => {
+                self.outputs.remove(i);
+            }
+            Some(i) => self.outputs[i] = output_config,
+            None if is_default => {}
+            None => self.outputs.push(output_config),
+        }
+    }
+}
+
+#[derive(Clone, Debug, Default, Deserialize, Eq, Hash, PartialEq, Serialize)]
+#[serde(default)]
+pub struct DesktopOutputConfig {
+    /// Name of the output, such as `DP-1`
+    pub output: String,
+    pub folder: DesktopFolder,
+    pub icon_size: Option<NonZeroU16>,
+    pub sort: Option<(HeadingOptions, bool)>,
+}
+
+#[derive(Clone, Debug, Default, Deserialize, Eq, Hash, PartialEq, Serialize)]
+pub enum DesktopFolder {
+    #[default]
+    Desktop,
+    /// Show no folder content, only drives and trash if enabled
+    Empty,
+    Favorite(Favorite),
+}
+
+/// Cell of a desktop icon, counted from the top left corner of the output
+#[derive(Clone, Copy, Debug, Default, Deserialize, Eq, Hash, PartialEq, Serialize)]
+pub struct DesktopPosition {
+    pub col: usize,
+    pub row: usize,
 }
 
 #[derive(Clone, Copy, Debug, Eq, PartialEq, CosmicConfigEntry, Deserialize, Serialize)]
@@ -468,6 +573,39 @@ mod tests {
         );
     }
 
+    #[test]
+    fn desktop_output_overrides() {
+        let mut config = DesktopConfig::default();
+        config.set_output(DesktopOutputConfig {
+            output: "DP-2".to_string(),
+            folder: DesktopFolder::Favorite(Favorite::Path(PathBuf::from("/some/dir"))),
+            icon_size: NonZeroU16::new(200),
+            sort: Some((HeadingOptions::Modified, false)),
+        });
+        config.set_output(DesktopOutputConfig {
+            output: "HDMI-1".to_string(),
+            folder: DesktopFolder::Empty,
+            ..Default::default()
+        });
+
+        let (path, output_config) = config.for_output("DP-2");
+        assert_eq!(path, PathBuf::from("/some/dir"));
+        assert_eq!(output_config.icon_size.get(), 200);
+        assert!(output_config.show_content);
+
+        let (path, output_config) = config.for_output("HDMI-1");
+        assert_eq!(path, crate::desktop_dir());
+        assert!(!output_config.show_content);
+
+        // Resetting an output to the defaults forgets it
+        config.set_output(DesktopOutputConfig {
+            output: "DP-2".to_string(),
+            ..Default::default()
+        });
+        assert!(config.output("DP-2").is_none());
+        assert_eq!(config.outputs.len(), 1);
+    }
+
     #[test]
     fn favorite_display_name() {
         assert_eq!(
@@ -1,10 +1,18 @@
 // SPDX-License-Identifier: GPL-3.0-only
 
+use std::io::{self, Read};
 use std::path::PathBuf;
+use std::process::{Child, Stdio};
+use std::sync::{Arc, Mutex, mpsc};
+use std::thread;
+use std::time::{Duration, Instant};
 
+use mime_guess::{Mime, mime};
 use serde::{Deserialize, Serialize};
 
 use crate::mime_app;
+use crate::mime_icon;
+use crate::operation::{Controller, ControllerState, OperationError};
 use crate::spawn_detached::spawn_detached;
 
 #[derive(Clone, Copy, Debug, Default, Deserialize, Eq, PartialEq, Serialize)]
@@ -18,26 +26,89 @@ pub enum ContextActionSelection {
     Folders,
 }
 
+/// Selected item that the conditions of context actions are checked against
+#[derive(Clone, Copy, Debug)]
+pub struct SelectedItem<'a> {
+    pub name: &'a str,
+    pub mime: &'a Mime,
+    pub is_dir: bool,
+}
+
 #[derive(Clone, Debug, Default, Deserialize, Eq, PartialEq, Serialize)]
 #[serde(default)]
 pub struct ContextActionPreset {
     pub name: String,
     pub confirm: bool,
     pub selection: ContextActionSelection,
+    /// MIME types such as `image/png` or `image/*`. Every selected item has to match one of these
+    /// or one of the globs, unless both are empty.
+    pub mime_types: Vec<String>,
+    /// Patterns such as `*.png` that file names are matched against
+    pub globs: Vec<String>,
+    pub min_selected: Option<usize>,
+    pub max_selected: Option<usize>,
+    /// Name of the submenu the action is grouped in, shown at the top level if empty
+    pub submenu: String,
+    /// Run as an operation that can be cancelled and shows the output of the steps, instead of
+    /// starting them in the background
+    pub track: bool,
     pub steps: Vec<String>,
 }
 
+/// Output captured from the steps of a tracked context action
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+pub struct ContextActionOutput {
+    pub stdout: String,
+    pub stderr: String,
+}
+
+impl ContextActionOutput {
+    pub fn is_empty(&self) -> bool {
+        self.stdout.trim().is_empty() && self.stderr.trim().is_empty()
+    }
+}
+
 impl ContextActionPreset {
-    pub fn matches_selection(&self, selected: usize, selected_dir: usize) -> bool {
-        if selected == 0 {
+    pub fn matches_selection(&self, selected: &[SelectedItem<'_>]) -> bool {
+        if selected.is_empty()
+            || self.min_selected.is_some_and(|min| selected.len() < min)
+            || self.max_selected.is_some_and(|max| selected.len() > max)
+        {
             return false;
         }
 
-        match self.selection {
+        let selected_dir = selected.iter().filter(|item| item.is_dir).count();
+        let selection = match self.selection {
             ContextActionSelection::Any => true,
             ContextActionSelection::Files => selected_dir == 0,
-            ContextActionSelection::Folders => selected_dir == selected,
+            ContextActionSelection::Folders => selected_dir == selected.len(),
+        };
+        if !selection {
+            return false;
         }
+
+        if self.mime_types.is_empty() && self.globs.is_empty() {
+            return true;
+        }
+        let mimes: Vec<Mime> = self
+            .mime_types
+            .iter()
+            .filter_map(|mime_type| mime_type.parse().ok())
+            .collect();
+        let globs: Vec<glob::Pattern> = self
+            .globs
+            .iter()
+            .filter_map(|glob| glob::Pattern::new(glob).ok())
+            .collect();
+        selected.iter().all(|item| {
+            mimes.iter().any(|mime| {
+                if mime.subtype() == mime::STAR {
+                    mime.type_() == item.mime.type_()
+                } else {
+                    mime == item.mime || mime_icon::is_mime_subclass_of(item.mime, mime)
+                }
+            }) || globs.iter().any(|glob| glob.matches(item.name))
+        })
     }
 
     pub fn run(&self, paths: &[PathBuf]) {
@@ -71,10 +142,268 @@ impl ContextActionPreset {
     }
 }
 
-pub fn run(actions: &[ContextActionPreset], action: usize, paths: &[PathBuf]) {
-    if let Some(preset) = actions.get(action) {
-        preset.run(paths);
-    } else {
-        log::warn!("invalid context action index `{action}`");
+/// Time to wait for the output of a step after it exits, as processes it started in the background
+/// may keep its pipes open
+const PIPE_TIMEOUT: Duration = Duration::from_secs(1);
+
+/// Run the steps of a context action one after another, waiting for each to exit and capturing
+/// their output. A step that fails to start or exits with a non-zero status fails the action, and
+/// cancelling the controller kills the step that is running along with the processes it started.
+pub fn run_captured(
+    name: &str,
+    steps: &[String],
+    paths: &[PathBuf],
+    controller: &Controller,
+) -> Result<ContextActionOutput, OperationError> {
+    let mut output = ContextActionOutput::default();
+    for (i, step) in steps.iter().enumerate() {
+        controller.set_progress(i as f32 / steps.len() as f32);
+
+        let Some(commands) = mime_app::exec_to_command(step, name, None, paths) else {
+            return Err(OperationError::from_err(
+                format!("invalid Exec {step:?}"),
+                controller,
+            ));
+        };
+
+        for mut command in commands {
+            // Each step gets its own process group, so that cancelling also stops its children
+            #[cfg(unix)]
+            {
+                use std::os::unix::process::CommandExt;
+                command.process_group(0);
+            }
+            let mut child = command
+                .stdin(Stdio::null())
+                .stdout(Stdio::piped())
+                .stderr(Stdio::piped())
+                .spawn()
+                .map_err(|err| OperationError::from_err(format!("{step:?}: {err}"), controller))?;
+
+            // Read both pipes while waiting so that a step filling one of them does not block
+            let stdout = PipeReader::spawn(child.stdout.take());
+            let stderr = PipeReader::spawn(child.stderr.take());
+
+            let status = loop {
+                if controller.is_cancelled() {
+                    kill_group(&mut child);
+                    let _ = child.wait();
+                    return Err(OperationError::from_state(
+                        ControllerState::Cancelled,
+                        controller,
+                    ));
+                }
+                match child.try_wait() {
+                    Ok(Some(status)) => break status,
+                    Ok(None) => thread::sleep(Duration::from_millis(100)),
+                    Err(err) => {
+                        return Err(OperationError::from_err(
+                            format!("{step:?}: {err}"),
+                            controller,
+                        ));
+                    }
+                }
+            };
+
+            let deadline = Instant::now() + PIPE_TIMEOUT;
+            output.stdout.push_str(&stdout.finish(deadline));
+            output.stderr.push_str(&stderr.finish(deadline));
+            if !status.success() {
+                let mut message = format!("{step:?}: {status}");
+                for text in [&output.stdout, &output.stderr] {
+                    if !text.trim().is_empty() {
+                        message.push('\n');
+                        message.push_str(text.trim_end());
+                    }
+                }
+                return Err(OperationError::from_err(message, controller));
+            }
+        }
+    }
+    Ok(output)
+}
+
+/// Kill a step and the processes in its group.
+fn kill_group(child: &mut Child) {
+    #[cfg(unix)]
+    if let Ok(pid) = libc::pid_t::try_from(child.id()) {
+        // SAFETY: the group was created for this step, and the step has not been waited on yet
+        unsafe { libc::kill(-pid, libc::SIGKILL) };
+        return;
+    }
+    let _ = child.kill();
+}
+
+/// Output of a pipe, read on another thread
+struct PipeReader {
+    data: Arc<Mutex<Vec<u8>>>,
+    done: mpsc::Receiver<()>,
+}
+
+impl PipeReader {
+    fn spawn<R: Read + Send + 'static>(pipe: Option<R>) -> Self {
+        let data = Arc::new(Mutex::new(Vec::new()));
+        let (done_tx, done) = mpsc::channel();
+        let thread_data = data.clone();
+        thread::spawn(move || {
+            if let Some(mut pipe) = pipe {
+                let mut buf = [0; 8192];
+                loop {
+                    match pipe.read(&mut buf) {
+                        Ok(0) => break,
+                        Ok(count) => {
+                            if let Ok(mut data) = thread_data.lock() {
+                                data.extend_from_slice(&buf[..count]);
+                            }
+                        }
+                        Err(err) if err.kind() == io::ErrorKind::Interrupted => {}
+                        Err(_) => break,
+                    }
+                }
+            }
+            let _ = done_tx.send(());
+        });
+        Self { data, done }
+    }
+
+    /// Output read so far, after waiting until `deadline` for the pipe to be closed.
+    fn finish(self, deadline: Instant) -> String {
+        let _ = self
+            .done
+            .recv_timeout(deadline.saturating_duration_since(Instant::now()));
+        let data = self
+            .data
+            .lock()
+            .map(|data| data.clone())
+            .unwrap_or_default();
+        String::from_utf8_lossy(&data).into_owned()
+    }
+}
+
+/// Indexes of the actions matching the selection, grouped by submenu in the order they are first
+/// used. Actions outside of submenus are in the group with an empty name.
+pub fn grouped(
+    actions: &[ContextActionPreset],
+    selected: &[SelectedItem<'_>],
+) -> Vec<(String, Vec<usize>)> {
+    let mut groups: Vec<(String, Vec<usize>)> = Vec::new();
+    for (i, action) in actions.iter().enumerate() {
+        if !action.matches_selection(selected) {
+            continue;
+        }
+        match groups.iter_mut().find(|(name, _)| *name == action.submenu) {
+            Some((_, indexes)) => indexes.push(i),
+            None => groups.push((action.submenu.clone(), vec![i])),
+        }
+    }
+    groups
+}
+
+#[cfg(test)]
+mod tests {
+    use mime_guess::Mime;
+
+    use super::{ContextActionPreset, ContextActionSelection, SelectedItem, grouped, run_captured};
+    use crate::operation::{Controller, ControllerState};
+
+    fn item<'a>(name: &'a str, mime: &'a Mime) -> SelectedItem<'a> {
+        SelectedItem {
+            name,
+            mime,
+            is_dir: false,
+        }
+    }
+
+    #[test]
+    fn matches_mime_types_globs_and_counts() {
+        let png: Mime = "image/png".parse().unwrap();
+        let text: Mime = "text/plain".parse().unwrap();
+        let dir: Mime = "inode/directory".parse().unwrap();
+
+        let convert = ContextActionPreset {
+            mime_types: vec!["image/*".to_string()],
+            ..Default::default()
+        };
+        assert!(convert.matches_selection(&[item("a.png", &png)]));
+        assert!(!convert.matches_selection(&[item("a.png", &png), item("b.txt", &text)]));
+        assert!(!convert.matches_selection(&[]));
+
+        let compare = ContextActionPreset {
+            selection: ContextActionSelection::Files,
+            min_selected: Some(2),
+            max_selected: Some(2),
+            ..Default::default()
+        };
+        assert!(!compare.matches_selection(&[item("a.txt", &text)]));
+        assert!(compare.matches_selection(&[item("a.txt", &text), item("b.txt", &text)]));
+        let folder = SelectedItem {
+            name: "c",
+            mime: &dir,
+            is_dir: true,
+        };
+        assert!(!compare.matches_selection(&[item("a.txt", &text), folder]));
+
+        // Globs are alternatives to MIME types
+        let notes = ContextActionPreset {
+            mime_types: vec!["text/markdown".to_string()],
+            globs: vec!["*.md".to_string(), "*.txt".to_string()],
+            ..Default::default()
+        };
+        assert!(notes.matches_selection(&[item("a.txt", &text)]));
+        assert!(!notes.matches_selection(&[item("a.png", &png)]));
+    }
+
+    #[test]
+    fn groups_by_submenu() {
+        let text: Mime = "text/plain".parse().unwrap();
+        let action = |submenu: &str| ContextActionPreset {
+            submenu: submenu.to_string(),
+            ..Default::default()
+        };
+        let actions = [action(""), action("Convert"), action(""), action("Convert")];
+        assert_eq!(
+            grouped(&actions, &[item("a.txt", &text)]),
+            [
+                (String::new(), vec![0, 2]),
+                ("Convert".to_string(), vec![1, 3])
+            ]
+        );
+    }
+
+    #[test]
+    fn captures_output_and_fails_on_exit_status() {
+        let controller = Controller::default();
+        let steps = [
+            "sh -c 'echo first'".to_string(),
+            "sh -c 'echo second; echo warning >&2'".to_string(),
+        ];
+        let output = run_captured("Test", &steps, &[], &controller).unwrap();
+        assert_eq!(output.stdout, "first\nsecond\n");
+        assert_eq!(output.stderr, "warning\n");
+
+        let controller = Controller::default();
+        let steps = ["sh -c 'echo broken >&2; exit 3'".to_string()];
+        let err = run_captured("Test", &steps, &[], &controller).unwrap_err();
+        assert!(err.to_string().contains("broken"));
+        assert_eq!(controller.state(), ControllerState::Failed);
+    }
+
+    #[test]
+    fn cancelling_kills_the_step() {
+        let controller = Controller::default();
+        controller.cancel();
+        let steps = ["sleep 10".to_string()];
+        assert!(run_captured("Test", &steps, &[], &controller).is_err());
+        assert!(controller.is_cancelled());
+    }
+
+    #[test]
+    fn background_processes_do_not_block_the_step() {
+        let controller = Controller::default();
+        let steps = ["sh -c 'sleep 10 & echo started'".to_string()];
+        let start = std::time::Instant::now();
+        let output = run_captured("Test", &steps, &[], &controller).unwrap();
+        assert_eq!(output.stdout, "started\n");
+        assert!(start.elapsed() < std::time::Duration::from_secs(5));
     }
 }
@@ -0,0 +1,414 @@
+// SPDX-License-Identifier: GPL-3.0-only
+
+//! Session bus service of the running file manager, so other programs and later launches reuse it
+//! instead of starting another process.
+//!
+//! The service implements <https://www.freedesktop.org/wiki/Specifications/file-manager-interface/>
+//! under the application ID, and the desktop applet forwards `org.freedesktop.FileManager1` to it.
+//! Scripts and other components drive the file manager through the `com.system76.CosmicFiles`
+//! interface at [`AUTOMATION_PATH`].
+
+use std::path::PathBuf;
+use tokio::sync::mpsc;
+
+use crate::app::{Message, ShowMode};
+use crate::operation::{Operation, Preserve};
+use crate::tab;
+
+pub const DBUS_NAME: &str = "com.system76.CosmicFiles";
+pub const DBUS_PATH: &str = "/org/freedesktop/FileManager1";
+pub const FILE_MANAGER_INTERFACE: &str = "org.freedesktop.FileManager1";
+pub const AUTOMATION_PATH: &str = "/com/system76/CosmicFiles";
+pub const AUTOMATION_INTERFACE: &str = "com.system76.CosmicFiles";
+
+/// Pending operation as returned by `PendingOperations`: ID, description, progress from 0 to 1
+/// and state
+pub type OperationStatus = (u64, String, f64, String);
+
+/// Requests from the automation interface that the app answers
+#[derive(Clone, Debug)]
+pub enum Request {
+    /// Open a path in a new window
+    OpenWindow(PathBuf),
+    /// Start an operation and reply with its ID. Copies and moves are verified according to the
+    /// app config.
+    Operation(Operation, mpsc::Sender<u64>),
+    /// Reply with the status of pending operations
+    PendingOperations(mpsc::Sender<Vec<OperationStatus>>),
+}
+
+/// Paths of file URIs, and plain paths for callers that do not send URIs
+pub fn uri_paths<S: AsRef<str>>(uris: &[S]) -> Vec<PathBuf> {
+    uris.iter()
+        .filter_map(|uri| {
+            let uri = uri.as_ref();
+            match url::Url::parse(uri) {
+                Ok(url) if url.scheme() == "file" => url.to_file_path().ok(),
+                Ok(_) => {
+                    log::warn!("unsupported URI {uri:?}");
+                    None
+                }
+                Err(_) => Some(PathBuf::from(uri)),
+            }
+        })
+        .collect()
+}
+
+pub struct FileManager {
+    messages: mpsc::UnboundedSender<Message>,
+}
+
+impl FileManager {
+    pub const fn new(messages: mpsc::UnboundedSender<Message>) -> Self {
+        Self { messages }
+    }
+
+    fn show(&self, mode: ShowMode, uris: &[String], startup_id: String) {
+        let message = Message::Show(mode, uri_paths(uris), startup_id);
+        if let Err(err) = self.messages.send(message) {
+            log::warn!("failed to send {mode:?} request: {err}");
+        }
+    }
+}
+
+#[zbus::interface(name = "org.freedesktop.FileManager1")]
+impl FileManager {
+    #[zbus(name = "ShowFolders")]
+    fn show_folders(&self, uris: Vec<String>, startup_id: String) {
+        self.show(ShowMode::Folders, &uris, startup_id);
+    }
+
+    #[zbus(name = "ShowItems")]
+    fn show_items(&self, uris: Vec<String>, startup_id: String) {
+        self.show(ShowMode::Items, &uris, startup_id);
+    }
+
+    #[zbus(name = "ShowItemProperties")]
+    fn show_item_properties(&self, uris: Vec<String>, startup_id: String) {
+        self.show(ShowMode::ItemProperties, &uris, startup_id);
+    }
+}
+
+pub struct Automation {
+    messages: mpsc::UnboundedSender<Message>,
+}
+
+impl Automation {
+    pub const fn new(messages: mpsc::UnboundedSender<Message>) -> Self {
+        Self { messages }
+    }
+
+    fn send(&self, message: Message) -> zbus::fdo::Result<()> {
+        self.messages
+            .send(message)
+            .map_err(|_| zbus::fdo::Error::Failed("file manager is closing".to_string()))
+    }
+
+    async fn request<T>(
+        &self,
+        request: impl FnOnce(mpsc::Sender<T>) -> Request,
+    ) -> zbus::fdo::Result<T> {
+        let (tx, mut rx) = mpsc::channel(1);
+        self.send(Message::Dbus(request(tx)))?;
+        rx.recv()
+            .await
+            .ok_or_else(|| zbus::fdo::Error::Failed("request was not answered".to_string()))
+    }
+
+    async fn operation(&self, operation: Operation) -> zbus::fdo::Result<u64> {
+        self.request(|tx| Request::Operation(operation, tx)).await
+    }
+}
+
+/// Single path of a location URI
+fn uri_path(uri: &str) -> zbus::fdo::Result<PathBuf> {
+    uri_paths(&[uri])
+        .pop()
+        .ok_or_else(|| zbus::fdo::Error::InvalidArgs(format!("unsupported URI {uri:?}")))
+}
+
+/// Paths of source URIs, none of which may be skipped
+fn source_paths(uris: &[String]) -> zbus::fdo::Result<Vec<PathBuf>> {
+    let paths = uri_paths(uris);
+    if paths.is_empty() || paths.len() != uris.len() {
+        return Err(zbus::fdo::Error::InvalidArgs(
+            "sources must be file URIs or paths".to_string(),
+        ));
+    }
+    Ok(paths)
+}
+
+#[zbus::interface(name = "com.system76.CosmicFiles")]
+impl Automation {
+    /// Opens a location in a new tab of the running window
+    #[zbus(name = "OpenTab")]
+    fn open_tab(&self, uri: String) -> zbus::fdo::Result<()> {
+        let path = uri_path(&uri)?;
+        self.send(Message::Show(ShowMode::Folders, vec![path], String::new()))
+    }
+
+    /// Opens a location in a new window
+    #[zbus(name = "OpenWindow")]
+    fn open_window(&self, uri: String) -> zbus::fdo::Result<()> {
+        let path = uri_path(&uri)?;
+        self.send(Message::Dbus(Request::OpenWindow(path)))
+    }
+
+    /// Selects items in the active tab
+    #[zbus(name = "SelectPaths")]
+    fn select_paths(&self, uris: Vec<String>) -> zbus::fdo::Result<()> {
+        self.send(Message::TabMessage(
+            None,
+            tab::Message::SelectPaths(uri_paths(&uris)),
+        ))
+    }
+
+    /// Copies sources into a folder, returning the operation ID
+    #[zbus(name = "Copy")]
+    async fn copy(&self, sources: Vec<String>, destination: String) -> zbus::fdo::Result<u64> {
+        self.operation(Operation::Copy {
+            paths: source_paths(&sources)?,
+            to: uri_path(&destination)?,
+            preserve: Preserve::default(),
+            verify: false,
+            resumable: false,
+        })
+        .await
+    }
+
+    /// Moves sources into a folder, returning the operation ID
+    #[zbus(name = "Move")]
+    async fn move_(&self, sources: Vec<String>, destination: String) -> zbus::fdo::Result<u64> {
+        self.operation(Operation::Move {
+            paths: source_paths(&sources)?,
+            to: uri_path(&destination)?,
+            cross_device_copy: false,
+            preserve: Preserve::default(),
+            verify: false,
+            resumable: false,
+        })
+        .await
+    }
+
+    /// Extracts archives into a folder, returning the operation ID
+    #[zbus(name = "Extract")]
+    async fn extract(&self, sources: Vec<String>, destination: String) -> zbus::fdo::Result<u64> {
+        self.operation(Operation::Extract {
+            paths: source_paths(&sources)?.into_boxed_slice(),
+            to: uri_path(&destination)?,
+            password: None,
+        })
+        .await
+    }
+
+    /// Operations that are queued or running
+    #[zbus(name = "PendingOperations")]
+    async fn pending_operations(&self) -> zbus::fdo::Result<Vec<OperationStatus>> {
+        self.request(Request::PendingOperations).await
+    }
+}
+
+/// Claims the service name on the connection built by `builder` and serves the file manager.
+///
+/// Fails if another instance owns the name. The connection must be kept alive to keep serving.
+pub async fn serve(
+    builder: zbus::connection::Builder<'_>,
+    messages: mpsc::UnboundedSender<Message>,
+) -> zbus::Result<zbus::Connection> {
+    builder
+        .name(DBUS_NAME)?
+        .serve_at(DBUS_PATH, FileManager::new(messages.clone()))?
+        .serve_at(AUTOMATION_PATH, Automation::new(messages))?
+        .build()
+        .await
+}
+
+/// Sends paths to the running instance, returning false if there is none
+pub async fn forward(
+    connection: &zbus::Connection,
+    mode: ShowMode,
+    paths: &[PathBuf],
+    startup_id: &str,
+) -> zbus::Result<bool> {
+    let dbus = zbus::fdo::DBusProxy::new(connection).await?;
+    if !dbus.name_has_owner(DBUS_NAME.try_into()?).await? {
+        return Ok(false);
+    }
+
+    let uris: Vec<String> = paths
+        .iter()
+        .filter_map(|path| url::Url::from_file_path(path).ok())
+        .map(String::from)
+        .collect();
+    let method = match mode {
+        ShowMode::Folders => "ShowFolders",
+        ShowMode::Items => "ShowItems",
+        ShowMode::ItemProperties => "ShowItemProperties",
+    };
+    connection
+        .call_method(
+            Some(DBUS_NAME),
+            DBUS_PATH,
+            Some(FILE_MANAGER_INTERFACE),
+            method,
+            &(uris, startup_id),
+        )
+        .await?;
+    Ok(true)
+}
+
+/// Blocking [`forward`] on a new session bus connection, for use before the app starts
+pub fn forward_blocking(mode: ShowMode, paths: &[PathBuf], startup_id: &str) -> zbus::Result<bool> {
+    let runtime = tokio::runtime::Builder::new_current_thread()
+        .enable_all()
+        .build()?;
+    runtime.block_on(async {
+        let connection = zbus::Connection::session().await?;
+        forward(&connection, mode, paths, startup_id).await
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use std::path::PathBuf;
+    use tokio::sync::mpsc;
+
+    use super::{
+        AUTOMATION_INTERFACE, AUTOMATION_PATH, DBUS_NAME, OperationStatus, Request, serve,
+        uri_paths,
+    };
+    use crate::app::{Message, ShowMode};
+    use crate::operation::Operation;
+    use crate::tab;
+    use crate::test_bus::PrivateBus;
+
+    async fn proxy(bus: &PrivateBus) -> zbus::Result<zbus::Proxy<'static>> {
+        let client = bus.connect()?.build().await?;
+        zbus::Proxy::new(&client, DBUS_NAME, AUTOMATION_PATH, AUTOMATION_INTERFACE).await
+    }
+
+    #[test]
+    fn uri_paths_accepts_file_uris_and_paths() {
+        let paths = uri_paths(&["file:///home/user/a%20b.txt", "/tmp/c", "sftp://host/file"]);
+        assert_eq!(
+            paths,
+            [PathBuf::from("/home/user/a b.txt"), PathBuf::from("/tmp/c")]
+        );
+    }
+
+    #[tokio::test]
+    async fn open_and_select_over_private_bus() -> zbus::Result<()> {
+        let Some(bus) = PrivateBus::start() else {
+            return Ok(());
+        };
+        let (messages, mut messages_rx) = mpsc::unbounded_channel();
+        let _server = serve(bus.connect()?, messages).await?;
+        let proxy = proxy(&bus).await?;
+
+        proxy.call::<_, _, ()>("OpenTab", &("file:///tmp",)).await?;
+        match messages_rx.recv().await {
+            Some(Message::Show(ShowMode::Folders, paths, _)) => {
+                assert_eq!(paths, [PathBuf::from("/tmp")]);
+            }
+            message => panic!("unexpected message {message:?}"),
+        }
+
+        proxy
+            .call::<_, _, ()>("OpenWindow", &("/home/user",))
+            .await?;
+        match messages_rx.recv().await {
+            Some(Message::Dbus(Request::OpenWindow(path))) => {
+                assert_eq!(path, PathBuf::from("/home/user"));
+            }
+            message => panic!("unexpected message {message:?}"),
+        }
+
+        proxy
+            .call::<_, _, ()>("SelectPaths", &(vec!["file:///tmp/a", "/tmp/b"],))
+            .await?;
+        match messages_rx.recv().await {
+            Some(Message::TabMessage(None, tab::Message::SelectPaths(paths))) => {
+                assert_eq!(paths, [PathBuf::from("/tmp/a"), PathBuf::from("/tmp/b")]);
+            }
+            message => panic!("unexpected message {message:?}"),
+        }
+
+        // Remote locations are rejected instead of opening the home folder
+        assert!(
+            proxy
+                .call::<_, _, ()>("OpenTab", &("sftp://host/",))
+                .await
+                .is_err()
+        );
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn operations_over_private_bus() -> zbus::Result<()> {
+        let Some(bus) = PrivateBus::start() else {
+            return Ok(());
+        };
+        let (messages, mut messages_rx) = mpsc::unbounded_channel();
+        let _server = serve(bus.connect()?, messages).await?;
+
+        // Answers like the app, which numbers operations in the order they start
+        let responder = tokio::spawn(async move {
+            let mut operations = Vec::new();
+            while let Some(message) = messages_rx.recv().await {
+                match message {
+                    Message::Dbus(Request::Operation(operation, tx)) => {
+                        tx.send(operations.len() as u64).await.unwrap();
+                        operations.push(operation);
+                    }
+                    Message::Dbus(Request::PendingOperations(tx)) => {
+                        let statuses = operations
+                            .iter()
+                            .enumerate()
+                            .map(|(id, _)| (id as u64, String::new(), 0.5, "Running".to_string()))
+                            .collect();
+                        tx.send(statuses).await.unwrap();
+                        return operations;
+                    }
+                    message => panic!("unexpected message {message:?}"),
+                }
+            }
+            operations
+        });
+
+        let proxy = proxy(&bus).await?;
+        let copy: u64 = proxy
+            .call(
+                "Copy",
+                &(vec!["file:///tmp/a", "/tmp/b"], "file:///tmp/out"),
+            )
+            .await?;
+        let move_: u64 = proxy.call("Move", &(vec!["/tmp/c"], "/tmp/out")).await?;
+        let extract: u64 = proxy
+            .call("Extract", &(vec!["/tmp/d.zip"], "/tmp/out"))
+            .await?;
+        assert_eq!((copy, move_, extract), (0, 1, 2));
+
+        // Sources that are not local files fail without starting anything
+        assert!(
+            proxy
+                .call::<_, _, u64>("Copy", &(vec!["sftp://host/e"], "/tmp/out"))
+                .await
+                .is_err()
+        );
+
+        let pending: Vec<OperationStatus> = proxy.call("PendingOperations", &()).await?;
+        assert_eq!(pending.len(), 3);
+        assert_eq!(pending[2].0, 2);
+
+        let operations = responder.await.unwrap();
+        assert!(matches!(
+            &operations[0],
+            Operation::Copy { paths, to, .. }
+                if paths == &[PathBuf::from("/tmp/a"), PathBuf::from("/tmp/b")]
+                    && to == &PathBuf::from("/tmp/out")
+        ));
+        assert!(matches!(&operations[1], Operation::Move { paths, .. } if paths.len() == 1));
+        assert!(matches!(&operations[2], Operation::Extract { paths, .. } if paths.len() == 1));
+        Ok(())
+    }
+}
@@ -0,0 +1,300 @@
+// Copyright 2026 System76 <info@system76.com>
+// SPDX-License-Identifier: GPL-3.0-only
+
+//! Placement of desktop icons on a grid of cells.
+//!
+//! Icons without a stored position flow top to bottom, then left to right, continuing on pages
+//! below the first screen. Stored positions are kept where they fit the current grid, so icons
+//! return to their cells when an output comes back at its previous resolution.
+
+use cosmic::iced::{Point, Size};
+use rustc_hash::FxHashSet;
+
+use crate::FxOrderMap;
+use crate::config::DesktopPosition;
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Grid {
+    pub cols: usize,
+    pub rows: usize,
+    /// Distance between the origins of neighbouring cells
+    pub step: Size,
+    pub padding: f32,
+}
+
+impl Grid {
+    fn cols(&self) -> usize {
+        self.cols.max(1)
+    }
+
+    fn rows(&self) -> usize {
+        self.rows.max(1)
+    }
+
+    /// Cell used by the icon at `index` when icons are packed in order
+    pub fn flow_cell(&self, index: usize) -> DesktopPosition {
+        let page_len = self.rows() * self.cols();
+        let page = index / page_len;
+        let index = index % page_len;
+        DesktopPosition {
+            col: index / self.rows(),
+            row: page * self.rows() + index % self.rows(),
+        }
+    }
+
+    /// Inverse of [`Grid::flow_cell`]
+    pub fn flow_index(&self, pos: DesktopPosition) -> usize {
+        let page = pos.row / self.rows();
+        let col = pos.col.min(self.cols() - 1);
+        page * self.rows() * self.cols() + col * self.rows() + pos.row % self.rows()
+    }
+
+    /// Moves a cell that is off the grid to the closest cell on the first page
+    pub fn clamp(&self, pos: DesktopPosition) -> DesktopPosition {
+        DesktopPosition {
+            col: pos.col.min(self.cols() - 1),
+            row: pos.row.min(self.rows() - 1),
+        }
+    }
+
+    /// Cell under a point relative to the top left corner of the grid
+    pub fn cell_at(&self, point: Point) -> DesktopPosition {
+        let cell = |offset: f32, step: f32| {
+            if step > 0.0 {
+                ((offset - self.padding).max(0.0) / step) as usize
+            } else {
+                0
+            }
+        };
+        self.clamp(DesktopPosition {
+            col: cell(point.x, self.step.width),
+            row: cell(point.y, self.step.height),
+        })
+    }
+
+    /// Closest free cell to `pos`, falling back to the first free cell in flow order
+    fn nearest_free(
+        &self,
+        pos: DesktopPosition,
+        occupied: &FxHashSet<DesktopPosition>,
+    ) -> DesktopPosition {
+        let pos = self.clamp(pos);
+        for distance in 0..self.cols().max(self.rows()) {
+            let mut best: Option<(usize, DesktopPosition)> = None;
+            let rows = pos.row.saturating_sub(distance)..=(pos.row + distance).min(self.rows() - 1);
+            for row in rows {
+                let cols =
+                    pos.col.saturating_sub(distance)..=(pos.col + distance).min(self.cols() - 1);
+                for col in cols {
+                    let (dx, dy) = (col.abs_diff(pos.col), row.abs_diff(pos.row));
+                    let cell = DesktopPosition { col, row };
+                    if dx.max(dy) != distance || occupied.contains(&cell) {
+                        continue;
+                    }
+                    let score = dx * dx + dy * dy;
+                    if best.is_none_or(|(best_score, _)| score < best_score) {
+                        best = Some((score, cell));
+                    }
+                }
+            }
+            if let Some((_, cell)) = best {
+                return cell;
+            }
+        }
+        (0..)
+            .map(|index| self.flow_cell(index))
+            .find(|cell| !occupied.contains(cell))
+            .unwrap()
+    }
+}
+
+/// Computes the cell of every named icon, in the order of `names`.
+///
+/// With `keep_aligned`, stored positions only decide the order and icons are packed without gaps.
+pub fn layout<'a>(
+    names: impl IntoIterator<Item = &'a str>,
+    positions: &FxOrderMap<String, DesktopPosition>,
+    grid: Grid,
+    keep_aligned: bool,
+) -> Vec<DesktopPosition> {
+    let names: Vec<&str> = names.into_iter().collect();
+    let mut cells = vec![DesktopPosition::default(); names.len()];
+
+    if keep_aligned {
+        let mut order: Vec<usize> = (0..names.len()).collect();
+        order.sort_by_key(|&i| {
+            let stored = positions.get(names[i]).map(|pos| grid.flow_index(*pos));
+            (stored.unwrap_or(usize::MAX), i)
+        });
+        for (index, i) in order.into_iter().enumerate() {
+            cells[i] = grid.flow_cell(index);
+        }
+        return cells;
+    }
+
+    let mut occupied = FxHashSet::default();
+    let mut unplaced = Vec::new();
+    for (i, name) in names.iter().enumerate() {
+        match positions.get(*name) {
+            Some(pos) => {
+                let cell = grid.nearest_free(*pos, &occupied);
+                occupied.insert(cell);
+                cells[i] = cell;
+            }
+            None => unplaced.push(i),
+        }
+    }
+
+    let mut flow = (0..).map(|index| grid.flow_cell(index));
+    for i in unplaced {
+        let cell = flow.find(|cell| !occupied.contains(cell)).unwrap();
+        occupied.insert(cell);
+        cells[i] = cell;
+    }
+    cells
+}
+
+/// Moves the `moved` icons so the first of them lands on `target`, keeping their arrangement.
+///
+/// `cells` holds the current cell of every icon. Icons in `moved` that are not on the desktop
+/// yet are placed around `target`. Every icon is pinned to its cell in the returned positions.
+pub fn drop_items(
+    cells: &[(&str, DesktopPosition)],
+    moved: &[&str],
+    target: DesktopPosition,
+    grid: Grid,
+) -> FxOrderMap<String, DesktopPosition> {
+    let mut positions = FxOrderMap::default();
+    let mut occupied = FxHashSet::default();
+    for &(name, cell) in cells {
+        if !moved.contains(&name) {
+            positions.insert(name.to_string(), cell);
+            occupied.insert(cell);
+        }
+    }
+
+    let anchor = cells
+        .iter()
+        .filter(|(name, _)| moved.contains(name))
+        .map(|(_, cell)| *cell)
+        .min_by_key(|cell| grid.flow_index(*cell));
+    for name in moved {
+        let desired = match (anchor, cells.iter().find(|(n, _)| n == name)) {
+            (Some(anchor), Some((_, cell))) => DesktopPosition {
+                col: (target.col + cell.col).saturating_sub(anchor.col),
+                row: (target.row + cell.row).saturating_sub(anchor.row),
+            },
+            _ => target,
+        };
+        let cell = grid.nearest_free(desired, &occupied);
+        occupied.insert(cell);
+        positions.insert(name.to_string(), cell);
+    }
+    positions
+}
+
+/// Packs icons in the given order, pinning each one to its cell
+pub fn arrange<'a>(
+    names: impl IntoIterator<Item = &'a str>,
+    grid: Grid,
+) -> FxOrderMap<String, DesktopPosition> {
+    names
+        .into_iter()
+        .enumerate()
+        .map(|(index, name)| (name.to_string(), grid.flow_cell(index)))
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use cosmic::iced::{Point, Size};
+
+    use super::{Grid, arrange, drop_items, layout};
+    use crate::FxOrderMap;
+    use crate::config::DesktopPosition;
+
+    const fn pos(col: usize, row: usize) -> DesktopPosition {
+        DesktopPosition { col, row }
+    }
+
+    fn grid(cols: usize, rows: usize) -> Grid {
+        Grid {
+            cols,
+            rows,
+            step: Size::new(100.0, 100.0),
+            padding: 10.0,
+        }
+    }
+
+    #[test]
+    fn flow_fills_columns_then_pages() {
+        let grid = grid(2, 3);
+        let cells: Vec<_> = (0..8).map(|index| grid.flow_cell(index)).collect();
+        assert_eq!(
+            cells,
+            [
+                pos(0, 0),
+                pos(0, 1),
+                pos(0, 2),
+                pos(1, 0),
+                pos(1, 1),
+                pos(1, 2),
+                pos(0, 3),
+                pos(0, 4),
+            ]
+        );
+        for index in 0..20 {
+            assert_eq!(grid.flow_index(grid.flow_cell(index)), index);
+        }
+        assert_eq!(grid.cell_at(Point::new(115.0, 5.0)), pos(1, 0));
+        assert_eq!(grid.cell_at(Point::new(900.0, 150.0)), pos(1, 1));
+    }
+
+    #[test]
+    fn layout_keeps_positions_and_resolves_collisions() {
+        let mut positions = FxOrderMap::default();
+        positions.insert("a".to_string(), pos(3, 2));
+        positions.insert("b".to_string(), pos(9, 9));
+
+        // Both fit on a large output
+        let cells = layout(["a", "b", "c"], &positions, grid(10, 10), false);
+        assert_eq!(cells, [pos(3, 2), pos(9, 9), pos(0, 0)]);
+
+        // A smaller output clamps them into the same cell, so one moves aside
+        let cells = layout(["a", "b", "c"], &positions, grid(4, 3), false);
+        assert_eq!(cells[0], pos(3, 2));
+        assert_ne!(cells[1], cells[0]);
+        assert!(cells[1].col < 4 && cells[1].row < 3);
+        assert_eq!(cells[2], pos(0, 0));
+    }
+
+    #[test]
+    fn layout_keep_aligned_packs_in_stored_order() {
+        let mut positions = FxOrderMap::default();
+        positions.insert("a".to_string(), pos(2, 0));
+        positions.insert("b".to_string(), pos(0, 1));
+        let cells = layout(["a", "b", "c"], &positions, grid(4, 3), true);
+        assert_eq!(cells, [pos(0, 1), pos(0, 0), pos(0, 2)]);
+    }
+
+    #[test]
+    fn drop_keeps_arrangement_of_moved_items() {
+        let grid = grid(5, 4);
+        let cells = [("a", pos(0, 0)), ("b", pos(0, 1)), ("c", pos(2, 1))];
+        let positions = drop_items(&cells, &["a", "b", "new"], pos(2, 0), grid);
+        assert_eq!(positions.get("a"), Some(&pos(2, 0)));
+        // The moved item would land on "c", so it takes the nearest free cell
+        assert_ne!(positions.get("b"), Some(&pos(2, 1)));
+        assert_eq!(positions.get("c"), Some(&pos(2, 1)));
+        let new = positions.get("new").unwrap();
+        assert!(positions.values().filter(|cell| *cell == new).count() == 1);
+    }
+
+    #[test]
+    fn arrange_packs_in_order() {
+        let positions = arrange(["b", "a", "c"], grid(2, 2));
+        let names: Vec<_> = positions.keys().map(String::as_str).collect();
+        assert_eq!(names, ["b", "a", "c"]);
+        assert_eq!(positions.get("c"), Some(&pos(1, 0)));
+    }
+}
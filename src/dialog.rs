@@ -25,14 +25,16 @@ use recently_used_xbel::update_recently_used;
 use rustc_hash::{FxHashMap, FxHashSet};
 use std::any::TypeId;
 use std::collections::{HashMap, VecDeque};
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 use std::time::{self, Instant};
 use std::{env, fmt, fs};
 
 use crate::app::{
     Action, ContextPage, Message as AppMessage, PreviewItem, PreviewKind, REPLACE_BUTTON_ID,
 };
-use crate::config::{Config, DialogConfig, TIME_CONFIG_ID, ThumbCfg, TimeConfig, TypeToSearch};
+use crate::config::{
+    Config, DialogConfig, State, TIME_CONFIG_ID, ThumbCfg, TimeConfig, TypeToSearch,
+};
 use crate::key_bind::key_binds;
 use crate::localize::LANGUAGE_SORTER;
 use crate::mounter::{MOUNTERS, MounterItem, MounterItems, MounterKey, MounterMessage};
@@ -55,7 +57,13 @@ pub enum DialogKind {
     OpenFolder,
     OpenMultipleFiles,
     OpenMultipleFolders,
-    SaveFile { filename: String },
+    SaveFile {
+        filename: String,
+    },
+    /// Saves every file name into the chosen folder
+    SaveFiles {
+        filenames: Vec<String>,
+    },
 }
 
 impl DialogKind {
@@ -66,12 +74,13 @@ impl DialogKind {
             Self::OpenMultipleFiles => fl!("open-multiple-files"),
             Self::OpenMultipleFolders => fl!("open-multiple-folders"),
             Self::SaveFile { .. } => fl!("save-file"),
+            Self::SaveFiles { .. } => fl!("save-files"),
         }
     }
 
     pub fn accept_label(&self) -> String {
         match self {
-            Self::SaveFile { .. } => fl!("save"),
+            Self::SaveFile { .. } | Self::SaveFiles { .. } => fl!("save"),
             _ => fl!("open"),
         }
     }
@@ -85,7 +94,7 @@ impl DialogKind {
     }
 
     pub const fn save(&self) -> bool {
-        matches!(self, Self::SaveFile { .. })
+        matches!(self, Self::SaveFile { .. } | Self::SaveFiles { .. })
     }
 }
 
@@ -128,6 +137,28 @@ pub struct DialogFilter {
     pub patterns: Vec<DialogFilterPattern>,
 }
 
+impl DialogFilter {
+    /// Extension of the first glob pattern that matches a plain extension, like `*.txt`
+    pub fn extension(&self) -> Option<&str> {
+        self.patterns.iter().find_map(|pattern| match pattern {
+            DialogFilterPattern::Glob(glob) => glob
+                .strip_prefix("*.")
+                .filter(|ext| !ext.is_empty() && !ext.contains(['*', '?', '[', '/'])),
+            DialogFilterPattern::Mime(_) => None,
+        })
+    }
+
+    /// Appends the filter extension to a file name that has none
+    pub fn apply_extension(&self, filename: &str) -> String {
+        match self.extension() {
+            Some(ext) if Path::new(filename).extension().is_none() => {
+                format!("{filename}.{ext}")
+            }
+            _ => filename.to_string(),
+        }
+    }
+}
+
 impl AsRef<str> for DialogFilter {
     fn as_ref(&self) -> &str {
         &self.label
@@ -202,6 +233,7 @@ pub struct DialogSettings {
     app_id: String,
     kind: DialogKind,
     path_opt: Option<PathBuf>,
+    preview: bool,
 }
 
 impl DialogSettings {
@@ -223,6 +255,12 @@ impl DialogSettings {
         self.path_opt = Some(path);
         self
     }
+
+    /// Shows a preview of the selected file next to the file list while details are hidden
+    pub fn preview(mut self, preview: bool) -> Self {
+        self.preview = preview;
+        self
+    }
 }
 
 impl Default for DialogSettings {
@@ -231,6 +269,7 @@ impl Default for DialogSettings {
             app_id: App::APP_ID.to_string(),
             kind: DialogKind::OpenFile,
             path_opt: None,
+            preview: false,
         }
     }
 }
@@ -251,6 +290,7 @@ impl<M: Send + 'static> Dialog<M> {
         crate::localize::localize();
 
         let (config_handler, config) = Config::load();
+        let (state_handler, state) = State::load();
 
         let mut settings = window::Settings {
             decorations: false,
@@ -264,27 +304,42 @@ impl<M: Send + 'static> Dialog<M> {
 
         #[cfg(target_os = "linux")]
         {
-            settings.platform_specific.application_id = dialog_settings.app_id;
+            settings
+                .platform_specific
+                .application_id
+                .clone_from(&dialog_settings.app_id);
         }
 
         let (window_id, window_command) = window::open(settings);
 
         let mut core = Core::default();
         core.set_main_window_id(Some(window_id));
+        // Start where this app last chose a file, unless it asks for a folder
+        let path_opt = dialog_settings.path_opt.or_else(|| {
+            state
+                .dialog_paths
+                .get(&dialog_settings.app_id)
+                .filter(|path| path.is_dir())
+                .cloned()
+        });
         let flags = Flags {
+            app_id: dialog_settings.app_id,
             kind: dialog_settings.kind,
-            path_opt: dialog_settings.path_opt.as_ref().and_then(|path| {
-                match fs::canonicalize(path) {
+            path_opt: path_opt
+                .as_ref()
+                .and_then(|path| match fs::canonicalize(path) {
                     Ok(ok) => Some(ok),
                     Err(err) => {
                         log::warn!("failed to canonicalize {}: {}", path.display(), err);
                         None
                     }
-                }
-            }),
+                }),
+            preview: dialog_settings.preview,
             window_id,
             config_handler,
             config,
+            state_handler,
+            state,
         };
 
         let (cosmic, cosmic_command) = Cosmic::<App>::init((core, flags));
@@ -428,17 +483,21 @@ impl<M: Send + 'static> Dialog<M> {
 #[derive(Clone, Debug)]
 enum DialogPage {
     NewFolder { parent: PathBuf, name: String },
-    Replace { filename: String },
+    Replace { filenames: Vec<String> },
 }
 
 #[derive(Clone, Debug)]
 struct Flags {
+    app_id: String,
     kind: DialogKind,
     path_opt: Option<PathBuf>,
+    preview: bool,
     window_id: window::Id,
     #[allow(dead_code)]
     config_handler: Option<cosmic_config::Config>,
     config: Config,
+    state_handler: Option<cosmic_config::Config>,
+    state: State,
 }
 
 /// Messages that are used specifically by our [`App`].
@@ -586,6 +645,9 @@ impl App {
                     .on_submit(|_| Message::Save(false)),
             );
         }
+        if let DialogKind::SaveFiles { filenames } = &self.flags.kind {
+            col = col.push(widget::text::body(filenames.join(", ")));
+        }
 
         let mut row = widget::row::with_capacity(
             usize::from(!self.filters.is_empty())
@@ -656,10 +718,14 @@ impl App {
             )
             .padding(0)
             .on_press_maybe(if self.flags.kind.save() {
-                if let DialogKind::SaveFile { filename } = &self.flags.kind {
-                    (!filename.is_empty()).then_some(Message::Save(false))
-                } else {
-                    None
+                match &self.flags.kind {
+                    DialogKind::SaveFile { filename } => {
+                        (!filename.is_empty()).then_some(Message::Save(false))
+                    }
+                    DialogKind::SaveFiles { filenames } => {
+                        (!filenames.is_empty()).then_some(Message::Save(false))
+                    }
+                    _ => None,
                 }
             } else if has_selected || self.flags.kind.is_dir() {
                 Some(Message::Open)
@@ -727,6 +793,28 @@ impl App {
         widget::column::with_children(children).into()
     }
 
+    /// Thumbnail and metadata of the only selected item, or of the current folder
+    fn preview_column_view(&self) -> Element<'_, Message> {
+        let military_time = self.tab.config.military_time;
+        let mut selected = self
+            .tab
+            .items_opt()
+            .into_iter()
+            .flatten()
+            .filter(|item| item.selected);
+        let item_opt = match (selected.next(), selected.next()) {
+            (Some(item), None) => Some(item),
+            (Some(_), Some(_)) => None,
+            _ => self.tab.parent_item_opt.as_ref(),
+        };
+        match item_opt {
+            Some(item) => item
+                .preview_view(None, military_time)
+                .map(Message::TabMessage),
+            None => widget::space::horizontal().into(),
+        }
+    }
+
     fn rescan_tab(&self, selection_paths: Option<Vec<PathBuf>>) -> Task<Message> {
         let location = self.tab.location.clone();
         let icon_sizes = self.tab.config.icon_sizes;
@@ -821,6 +909,32 @@ impl App {
         Task::none()
     }
 
+    /// Whether the preview column is shown next to the file list
+    fn preview_column(&self) -> bool {
+        self.flags.preview && !self.core.window.show_context
+    }
+
+    /// Remembers the current folder for the calling app, so its next dialog starts there
+    fn save_dialog_path(&mut self) {
+        /// Apps that are remembered, the least recently used are forgotten first
+        const MAX_DIALOG_PATHS: usize = 64;
+
+        let Location::Path(path) = &self.tab.location else {
+            return;
+        };
+        let dialog_paths = &mut self.flags.state.dialog_paths;
+        dialog_paths.remove(&self.flags.app_id);
+        dialog_paths.insert(self.flags.app_id.clone(), path.clone());
+        while dialog_paths.len() > MAX_DIALOG_PATHS {
+            dialog_paths.remove_index(0);
+        }
+        if let Some(state_handler) = self.flags.state_handler.as_ref()
+            && let Err(err) = state_handler.set("dialog_paths", dialog_paths)
+        {
+            log::warn!("failed to save state \"dialog_paths\": {err:?}");
+        }
+    }
+
     fn update_config(&mut self) -> Task<Message> {
         self.core.window.show_context = self.flags.config.dialog.show_details;
         let config = self.flags.config.dialog_tab();
@@ -877,7 +991,8 @@ impl App {
     fn update_nav_model(&mut self) {
         let mut nav_model = segmented_button::ModelBuilder::default();
 
-        if self.flags.config.show_recents {
+        // Recent files cannot be saved into or chosen as folders
+        if self.flags.config.show_recents && !self.flags.kind.save() && !self.flags.kind.is_dir() {
             nav_model = nav_model.insert(|b| {
                 b.text(fl!("recents"))
                     .icon(widget::icon::from_name("document-open-recent-symbolic"))
@@ -1187,18 +1302,31 @@ impl Application for App {
                         .spacing(space_xxs),
                     )
             }
-            DialogPage::Replace { filename } => widget::dialog()
-                .title(fl!("replace-title", filename = filename.as_str()))
-                .icon(widget::icon::from_name("dialog-question").size(64))
-                .body(fl!("replace-warning"))
-                .primary_action(
-                    widget::button::suggested(fl!("replace"))
-                        .on_press(Message::DialogComplete)
-                        .id(REPLACE_BUTTON_ID.clone()),
-                )
-                .secondary_action(
-                    widget::button::standard(fl!("cancel")).on_press(Message::DialogCancel),
-                ),
+            DialogPage::Replace { filenames } => {
+                let dialog = match filenames.as_slice() {
+                    [filename] => widget::dialog()
+                        .title(fl!("replace-title", filename = filename.as_str()))
+                        .body(fl!("replace-warning")),
+                    _ => widget::dialog()
+                        .title(fl!("replace-title-multiple", count = filenames.len()))
+                        .body(fl!("replace-warning-multiple"))
+                        .control(widget::column::with_children(
+                            filenames
+                                .iter()
+                                .map(|filename| widget::text::body(filename).into()),
+                        )),
+                };
+                dialog
+                    .icon(widget::icon::from_name("dialog-question").size(64))
+                    .primary_action(
+                        widget::button::suggested(fl!("replace"))
+                            .on_press(Message::DialogComplete)
+                            .id(REPLACE_BUTTON_ID.clone()),
+                    )
+                    .secondary_action(
+                        widget::button::standard(fl!("cancel")).on_press(Message::DialogCancel),
+                    )
+            }
         };
 
         Some(dialog.into())
@@ -1435,6 +1563,19 @@ impl Application for App {
                 }
             }
             Message::Filter(filter_i) => {
+                // Swap the extension of the previous filter for the one of the new filter
+                if let DialogKind::SaveFile { filename } = &mut self.flags.kind
+                    && let Some(old_ext) = self
+                        .filter_selected
+                        .and_then(|i| self.filters.get(i))
+                        .and_then(DialogFilter::extension)
+                    && let Some(new_ext) =
+                        self.filters.get(filter_i).and_then(DialogFilter::extension)
+                    && let Some(stem) = filename.strip_suffix(old_ext)
+                    && let Some(stem) = stem.strip_suffix('.')
+                {
+                    *filename = format!("{stem}.{new_ext}");
+                }
                 if filter_i < self.filters.len() {
                     self.filter_selected = Some(filter_i);
                 } else {
@@ -1648,7 +1789,7 @@ impl Application for App {
                             if self.flags.config.show_recents {
                                 let _ = update_recently_used(
                                     path,
-                                    Self::APP_ID.to_string(),
+                                    self.flags.app_id.clone(),
                                     "cosmic-files".to_string(),
                                     None,
                                 );
@@ -1677,6 +1818,7 @@ impl Application for App {
 
                 // If there are proper matching items, return them
                 if !paths.is_empty() {
+                    self.save_dialog_path();
                     self.result_opt = Some(DialogResult::Open(paths));
                     return window::close(self.flags.window_id);
                 }
@@ -1685,7 +1827,9 @@ impl Application for App {
                 if self.flags.kind.is_dir()
                     && let Location::Path(tab_path) = &self.tab.location
                 {
-                    self.result_opt = Some(DialogResult::Open(vec![tab_path.clone()]));
+                    let result = DialogResult::Open(vec![tab_path.clone()]);
+                    self.save_dialog_path();
+                    self.result_opt = Some(result);
                     return window::close(self.flags.window_id);
                 }
             }
@@ -1700,7 +1844,11 @@ impl Application for App {
                     && !filename.is_empty()
                     && let Some(tab_path) = self.tab.location.path_opt()
                 {
-                    let path = tab_path.join(filename);
+                    let filename = match self.filter_selected.and_then(|i| self.filters.get(i)) {
+                        Some(filter) => filter.apply_extension(filename),
+                        None => filename.clone(),
+                    };
+                    let path = tab_path.join(&filename);
                     if path.is_dir() {
                         // cd to directory
                         let message =
@@ -1708,13 +1856,41 @@ impl Application for App {
                         return self.update(message);
                     } else if !replace && path.exists() {
                         self.dialog_pages.push_back(DialogPage::Replace {
-                            filename: filename.clone(),
+                            filenames: vec![filename],
                         });
                         return widget::button::focus(REPLACE_BUTTON_ID.clone());
                     }
+                    self.save_dialog_path();
                     self.result_opt = Some(DialogResult::Open(vec![path]));
                     return window::close(self.flags.window_id);
                 }
+
+                if let DialogKind::SaveFiles { filenames } = &self.flags.kind
+                    && !filenames.is_empty()
+                    && let Some(tab_path) = self.tab.location.path_opt()
+                {
+                    let paths: Vec<PathBuf> = filenames
+                        .iter()
+                        .map(|filename| tab_path.join(filename))
+                        .collect();
+                    if !replace {
+                        let existing: Vec<String> = filenames
+                            .iter()
+                            .zip(&paths)
+                            .filter(|(_, path)| path.exists())
+                            .map(|(filename, _)| filename.clone())
+                            .collect();
+                        if !existing.is_empty() {
+                            self.dialog_pages.push_back(DialogPage::Replace {
+                                filenames: existing,
+                            });
+                            return widget::button::focus(REPLACE_BUTTON_ID.clone());
+                        }
+                    }
+                    self.save_dialog_path();
+                    self.result_opt = Some(DialogResult::Open(paths));
+                    return window::close(self.flags.window_id);
+                }
             }
             Message::ScrollTab(scroll_speed) => {
                 return self.update(Message::TabMessage(tab::Message::ScrollTab(
@@ -1838,6 +2014,7 @@ impl Application for App {
                                                             &app.modifiers,
                                                             false, // Paste not used in dialogs
                                                             &app.flags.config.context_actions,
+                                                            &app.flags.config.tags,
                                                         )
                                                         .map(Message::TabMessage)
                                                         .map(cosmic::Action::App),
@@ -1925,9 +2102,10 @@ impl Application for App {
                             }
                         }
 
+                        let is_recents = location == Location::Recents;
                         items.retain(|item| {
-                            // Directories are always shown
-                            item.metadata.is_dir()
+                            // Directories are always shown, except in recent files
+                            (item.metadata.is_dir() && !is_recents)
                                 // Check for mime type match (first because it is faster)
                                     || mimes.iter().any(|filter_mime| {
                                         if filter_mime.subtype() == mime::STAR {
@@ -2041,11 +2219,29 @@ impl Application for App {
             );
         }
 
-        col = col.push(
-            self.tab
-                .view(&self.key_binds, &self.modifiers, false, &[])
-                .map(Message::TabMessage),
-        );
+        let tab_view = self
+            .tab
+            .view(
+                &self.key_binds,
+                &self.modifiers,
+                false,
+                &[],
+                &self.flags.config.tags,
+            )
+            .map(Message::TabMessage);
+        if self.preview_column() {
+            col = col.push(widget::row::with_children([
+                tab_view,
+                widget::scrollable(
+                    widget::container(self.preview_column_view()).padding(space_xxs),
+                )
+                .width(Length::Fixed(240.0))
+                .height(Length::Fill)
+                .into(),
+            ]));
+        } else {
+            col = col.push(tab_view);
+        }
 
         col.into()
     }
@@ -2183,11 +2379,12 @@ impl Application for App {
             }),
             self.tab
                 .subscription(
-                    self.core.window.show_context
-                        && matches!(
-                            self.context_page,
-                            ContextPage::Preview(_, PreviewKind::Selected)
-                        ),
+                    self.preview_column()
+                        || (self.core.window.show_context
+                            && matches!(
+                                self.context_page,
+                                ContextPage::Preview(_, PreviewKind::Selected)
+                            )),
                 )
                 .map(Message::TabMessage),
         ];
@@ -2217,3 +2414,30 @@ impl Application for App {
         Subscription::batch(subscriptions)
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::{DialogFilter, DialogFilterPattern};
+
+    #[test]
+    fn filter_extension() {
+        let filter = DialogFilter {
+            label: "Text".to_string(),
+            patterns: vec![
+                DialogFilterPattern::Mime("text/plain".to_string()),
+                DialogFilterPattern::Glob("*.[tT][xX][tT]".to_string()),
+                DialogFilterPattern::Glob("*.txt".to_string()),
+            ],
+        };
+        assert_eq!(filter.extension(), Some("txt"));
+        assert_eq!(filter.apply_extension("notes"), "notes.txt");
+        assert_eq!(filter.apply_extension("notes.md"), "notes.md");
+
+        let any = DialogFilter {
+            label: "Any file".to_string(),
+            patterns: vec![DialogFilterPattern::Glob("*".to_string())],
+        };
+        assert_eq!(any.extension(), None);
+        assert_eq!(any.apply_extension("notes"), "notes");
+    }
+}
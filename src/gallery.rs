@@ -0,0 +1,309 @@
+// Copyright 2026 System76 <info@system76.com>
+// SPDX-License-Identifier: GPL-3.0-only
+
+use cosmic::iced::mouse::ScrollDelta;
+use cosmic::iced::{Point, Size, Vector};
+use cosmic::widget;
+use std::fs::File;
+use std::io::BufReader;
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+
+pub const SLIDESHOW_INTERVAL: Duration = Duration::from_secs(5);
+
+const MIN_SCALE: f32 = 0.05;
+const MAX_SCALE: f32 = 16.0;
+const ZOOM_STEP: f32 = 1.1;
+/// Pixels of smooth scrolling that count as one wheel step.
+const PIXELS_PER_STEP: f32 = 20.0;
+
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
+pub enum GalleryFit {
+    /// Shrink large images to fit, never enlarging small ones
+    #[default]
+    Fit,
+    /// Fill the view, cropping the image
+    Fill,
+    /// Show image pixels at their original size
+    Original,
+}
+
+/// Scroll over the image, with the geometry needed to keep the pointer in place.
+#[derive(Clone, Copy, Debug)]
+pub struct ZoomEvent {
+    pub delta: ScrollDelta,
+    /// Pointer position relative to the image
+    pub point: Point,
+    pub image: Size,
+    pub viewport: Size,
+    pub scale: f32,
+}
+
+/// Centers content that is smaller than the viewport.
+pub fn padding(content: Size, viewport: Size) -> Vector {
+    Vector::new(
+        ((viewport.width - content.width) / 2.0).max(0.0),
+        ((viewport.height - content.height) / 2.0).max(0.0),
+    )
+}
+
+fn max_offset(content: Size, viewport: Size) -> Vector {
+    Vector::new(
+        (content.width - viewport.width).max(0.0),
+        (content.height - viewport.height).max(0.0),
+    )
+}
+
+fn clamp_offset(offset: Vector, max: Vector) -> Vector {
+    Vector::new(offset.x.clamp(0.0, max.x), offset.y.clamp(0.0, max.y))
+}
+
+#[derive(Debug)]
+pub struct GalleryState {
+    pub scrollable_id: widget::Id,
+    pub slideshow: bool,
+    pub fit: GalleryFit,
+    /// Zoom chosen by the user, overriding the fit mode
+    pub scale_opt: Option<f32>,
+    /// Scroll offset of the zoomed image
+    pub offset: Vector,
+    /// Last pointer position and maximum offset while panning
+    pan_opt: Option<(Point, Vector)>,
+    pub show_info: bool,
+    pub info_opt: Option<(PathBuf, Result<ImageInfo, String>)>,
+}
+
+impl Default for GalleryState {
+    fn default() -> Self {
+        Self {
+            scrollable_id: widget::Id::unique(),
+            slideshow: false,
+            fit: GalleryFit::default(),
+            scale_opt: None,
+            offset: Vector::ZERO,
+            pan_opt: None,
+            show_info: false,
+            info_opt: None,
+        }
+    }
+}
+
+impl GalleryState {
+    pub fn reset_zoom(&mut self) {
+        self.scale_opt = None;
+        self.offset = Vector::ZERO;
+        self.pan_opt = None;
+    }
+
+    /// Scale of the image for the current zoom or fit mode.
+    pub fn scale(&self, image: Size, viewport: Size) -> f32 {
+        if let Some(scale) = self.scale_opt {
+            return scale;
+        }
+        if image.width <= 0.0 || image.height <= 0.0 {
+            return 1.0;
+        }
+        let scale_x = viewport.width / image.width;
+        let scale_y = viewport.height / image.height;
+        match self.fit {
+            GalleryFit::Fit => scale_x.min(scale_y).min(1.0),
+            GalleryFit::Fill => scale_x.max(scale_y),
+            GalleryFit::Original => 1.0,
+        }
+    }
+
+    /// Zoom around the pointer, returning the new scroll offset.
+    pub fn zoom(&mut self, event: ZoomEvent) -> Vector {
+        let steps = match event.delta {
+            ScrollDelta::Lines { y, .. } => y,
+            ScrollDelta::Pixels { y, .. } => y / PIXELS_PER_STEP,
+        };
+        let scale = (event.scale * ZOOM_STEP.powf(steps)).clamp(MIN_SCALE, MAX_SCALE);
+        self.scale_opt = Some(scale);
+
+        let old_size = event.image * event.scale;
+        let new_size = event.image * scale;
+        // Pointer position within the viewport stays the same
+        let cursor = Vector::new(event.point.x, event.point.y) + padding(old_size, event.viewport)
+            - self.offset;
+        let point = Vector::new(event.point.x, event.point.y) * (scale / event.scale);
+        let offset = point + padding(new_size, event.viewport) - cursor;
+        self.offset = clamp_offset(offset, max_offset(new_size, event.viewport));
+        self.offset
+    }
+
+    pub fn pan_start(&mut self, point: Point, content: Size, viewport: Size) {
+        self.pan_opt = Some((point - self.offset, max_offset(content, viewport)));
+    }
+
+    /// Drag the image with the pointer, returning the new scroll offset.
+    pub fn pan(&mut self, point: Point) -> Option<Vector> {
+        let (last, max) = self.pan_opt.as_mut()?;
+        // Positions are relative to the image, which moves with the offset
+        let current = point - self.offset;
+        let offset = self.offset - (current - *last);
+        self.offset = clamp_offset(offset, *max);
+        *last = current;
+        Some(self.offset)
+    }
+}
+
+/// Details from the EXIF data of an image.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct ImageInfo {
+    pub camera: Option<String>,
+    pub exposure: Option<String>,
+    pub location: Option<String>,
+    pub date_taken: Option<String>,
+}
+
+impl ImageInfo {
+    pub fn load(path: &Path) -> Result<Self, String> {
+        let file = File::open(path).map_err(|err| err.to_string())?;
+        let exif = match exif::Reader::new().read_from_container(&mut BufReader::new(file)) {
+            Ok(exif) => exif,
+            Err(exif::Error::NotFound(_)) => return Ok(Self::default()),
+            Err(err) => return Err(err.to_string()),
+        };
+
+        let ascii = |tag| {
+            let field = exif.get_field(tag, exif::In::PRIMARY)?;
+            let exif::Value::Ascii(values) = &field.value else {
+                return None;
+            };
+            let value = String::from_utf8_lossy(values.first()?)
+                .trim_matches(|c: char| c == '\0' || c.is_whitespace())
+                .to_string();
+            (!value.is_empty()).then_some(value)
+        };
+        let with_unit = |tag| {
+            exif.get_field(tag, exif::In::PRIMARY)
+                .map(|field| field.display_value().with_unit(&exif).to_string())
+        };
+        let coordinate = |tag, ref_tag| {
+            let field = exif.get_field(tag, exif::In::PRIMARY)?;
+            let exif::Value::Rational(values) = &field.value else {
+                return None;
+            };
+            let [degrees, minutes, seconds, ..] = values.as_slice() else {
+                return None;
+            };
+            let value = degrees.to_f64() + minutes.to_f64() / 60.0 + seconds.to_f64() / 3600.0;
+            Some(match ascii(ref_tag).as_deref() {
+                Some("S" | "W") => -value,
+                _ => value,
+            })
+        };
+
+        let camera = match (ascii(exif::Tag::Make), ascii(exif::Tag::Model)) {
+            // Models commonly include the make already
+            (Some(make), Some(model)) if !model.starts_with(&make) => {
+                Some(format!("{make} {model}"))
+            }
+            (make, model) => model.or(make),
+        };
+
+        let exposure = [
+            with_unit(exif::Tag::ExposureTime),
+            with_unit(exif::Tag::FNumber),
+            exif.get_field(exif::Tag::PhotographicSensitivity, exif::In::PRIMARY)
+                .map(|field| format!("ISO {}", field.display_value())),
+            with_unit(exif::Tag::FocalLength),
+        ]
+        .into_iter()
+        .flatten()
+        .collect::<Vec<_>>();
+
+        let location = coordinate(exif::Tag::GPSLatitude, exif::Tag::GPSLatitudeRef)
+            .zip(coordinate(
+                exif::Tag::GPSLongitude,
+                exif::Tag::GPSLongitudeRef,
+            ))
+            .map(|(latitude, longitude)| format!("{latitude:.5}, {longitude:.5}"));
+
+        let date_taken = [exif::Tag::DateTimeOriginal, exif::Tag::DateTime]
+            .into_iter()
+            .find_map(|tag| {
+                let field = exif.get_field(tag, exif::In::PRIMARY)?;
+                let exif::Value::Ascii(values) = &field.value else {
+                    return None;
+                };
+                exif::DateTime::from_ascii(values.first()?)
+                    .ok()
+                    .map(|date_time| date_time.to_string())
+            });
+
+        Ok(Self {
+            camera,
+            exposure: (!exposure.is_empty()).then(|| exposure.join(", ")),
+            location,
+            date_taken,
+        })
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self == &Self::default()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use cosmic::iced::mouse::ScrollDelta;
+    use cosmic::iced::{Point, Size, Vector};
+
+    use super::{GalleryFit, GalleryState, ZoomEvent, padding};
+
+    #[test]
+    fn fit_modes_scale_image() {
+        let mut state = GalleryState::default();
+        let image = Size::new(400.0, 200.0);
+        let viewport = Size::new(200.0, 200.0);
+        assert_eq!(state.scale(image, viewport), 0.5);
+        // Small images are not enlarged to fit
+        assert_eq!(state.scale(Size::new(100.0, 50.0), viewport), 1.0);
+        state.fit = GalleryFit::Fill;
+        assert_eq!(state.scale(image, viewport), 1.0);
+        state.fit = GalleryFit::Original;
+        assert_eq!(state.scale(Size::new(100.0, 50.0), viewport), 1.0);
+    }
+
+    #[test]
+    fn zoom_keeps_pointer_in_place() {
+        let mut state = GalleryState::default();
+        let event = ZoomEvent {
+            delta: ScrollDelta::Lines { x: 0.0, y: 1.0 },
+            point: Point::new(300.0, 150.0),
+            image: Size::new(1000.0, 1000.0),
+            viewport: Size::new(400.0, 400.0),
+            scale: 1.0,
+        };
+        let offset = state.zoom(event);
+        let scale = state.scale_opt.expect("zoom should set a scale");
+        assert!(scale > 1.0);
+        // The image pixel under the pointer is still at the same viewport position
+        let before = Vector::new(300.0, 150.0);
+        let after = Vector::new(300.0 * scale, 150.0 * scale) - offset;
+        assert!((before.x - after.x).abs() < 0.01);
+        assert!((before.y - after.y).abs() < 0.01);
+    }
+
+    #[test]
+    fn pan_is_clamped_to_image() {
+        let mut state = GalleryState::default();
+        let image = Size::new(1000.0, 500.0);
+        let viewport = Size::new(400.0, 400.0);
+        state.pan_start(Point::new(500.0, 200.0), image, viewport);
+        assert_eq!(
+            state.pan(Point::new(400.0, 180.0)),
+            Some(Vector::new(100.0, 20.0))
+        );
+        assert_eq!(
+            state.pan(Point::new(0.0, 0.0)),
+            Some(Vector::new(600.0, 100.0))
+        );
+        assert_eq!(
+            padding(Size::new(200.0, 500.0), viewport),
+            Vector::new(100.0, 0.0)
+        );
+    }
+}
@@ -77,6 +77,7 @@ pub fn key_binds(mode: &tab::Mode) -> HashMap<KeyBind, Action> {
         bind!([Shift], Key::Named(Named::Delete), PermanentlyDelete);
         bind!([Shift], Key::Named(Named::Enter), OpenInNewWindow);
         bind!([Ctrl], Key::Character("v".into()), Paste);
+        bind!([Ctrl, Shift], Key::Character("v".into()), PasteLink);
         bind!([], Key::Named(Named::F2), Rename);
     }
 
@@ -1,6 +1,7 @@
 use cosmic::widget;
-use image::ImageReader;
+use image::{DynamicImage, ImageDecoder, ImageReader, ImageResult, metadata::Orientation};
 use std::collections::{HashMap, HashSet};
+use std::io::{BufRead, Seek};
 use std::path::{Path, PathBuf};
 
 /// Bytes per pixel in RGBA format (Red, Green, Blue, Alpha = 4 bytes)
@@ -154,26 +155,44 @@ pub fn should_use_dedicated_worker(
     }
 }
 
-/// Get the dimensions of an image without fully decoding it
+/// Decode an image, rotating or flipping it as its EXIF orientation says. Lossless rotations of
+/// JPEG files only change the orientation tag, so it has to be applied everywhere images are shown.
+pub fn decode_oriented<R: BufRead + Seek>(reader: ImageReader<R>) -> ImageResult<DynamicImage> {
+    let mut decoder = reader.into_decoder()?;
+    let orientation = decoder.orientation().unwrap_or(Orientation::NoTransforms);
+    let mut image = DynamicImage::from_decoder(decoder)?;
+    image.apply_orientation(orientation);
+    Ok(image)
+}
+
+/// Get the dimensions of an image as it is shown, after applying its EXIF orientation, without
+/// decoding it
+pub fn oriented_dimensions(path: &Path) -> ImageResult<(u32, u32)> {
+    let mut decoder = ImageReader::open(path)?.into_decoder()?;
+    let (width, height) = decoder.dimensions();
+    match decoder.orientation().unwrap_or(Orientation::NoTransforms) {
+        Orientation::Rotate90
+        | Orientation::Rotate270
+        | Orientation::Rotate90FlipH
+        | Orientation::Rotate270FlipH => Ok((height, width)),
+        _ => Ok((width, height)),
+    }
+}
+
+/// Get the oriented dimensions of an image, logging any failure
 pub fn get_image_dimensions(path: &Path) -> Option<(u32, u32)> {
-    match ImageReader::open(path) {
-        Ok(reader) => match reader.into_dimensions() {
-            Ok((width, height)) => {
-                log::debug!(
-                    "Image dimensions: {}x{} for {}",
-                    width,
-                    height,
-                    path.display()
-                );
-                Some((width, height))
-            }
-            Err(e) => {
-                log::warn!("Failed to get dimensions for {}: {}", path.display(), e);
-                None
-            }
-        },
+    match oriented_dimensions(path) {
+        Ok((width, height)) => {
+            log::debug!(
+                "Image dimensions: {}x{} for {}",
+                width,
+                height,
+                path.display()
+            );
+            Some((width, height))
+        }
         Err(e) => {
-            log::warn!("Failed to open image reader for {}: {}", path.display(), e);
+            log::warn!("Failed to get dimensions for {}: {}", path.display(), e);
             None
         }
     }
@@ -278,7 +297,7 @@ pub async fn decode_large_image(
                         limits.max_alloc = Some(GALLERY_MEMORY_LIMIT_MB * DECIMAL_MB_TO_BYTES);
                         reader.limits(limits);
 
-                        match reader.decode() {
+                        match decode_oriented(reader) {
                             Ok(img) => {
                                 let rgba = img.into_rgba8();
                                 let orig_width = rgba.width();
@@ -8,21 +8,28 @@ use std::{env, fs, process};
 use tracing_subscriber::layer::SubscriberExt;
 use tracing_subscriber::util::SubscriberInitExt;
 
-use crate::app::{App, Flags};
+use crate::app::{App, Flags, ShowMode};
 use crate::config::{Config, State};
 use crate::tab::Location;
 
 pub mod app;
 mod archive;
 pub mod channel;
+mod cli;
 pub mod clipboard;
 pub mod config;
 mod context_action;
+#[cfg(feature = "dbus")]
+pub mod dbus;
+mod desktop_layout;
 pub mod dialog;
+pub(crate) mod gallery;
 mod key_bind;
 pub(crate) mod large_image;
 pub(crate) mod load_image;
 mod localize;
+#[cfg(feature = "media")]
+pub(crate) mod media;
 mod menu;
 mod mime_app;
 pub mod mime_icon;
@@ -31,6 +38,10 @@ mod mouse_area;
 pub mod operation;
 mod spawn_detached;
 pub mod tab;
+mod tag;
+#[cfg(all(feature = "dbus", any(test, feature = "test-util")))]
+pub mod test_bus;
+pub(crate) mod text_preview;
 mod thumbnail_cacher;
 mod thumbnailer;
 pub(crate) mod trash;
@@ -110,7 +121,11 @@ pub fn desktop() -> Result<(), Box<dyn std::error::Error>> {
         settings = settings.no_main_window(true);
     }
 
-    let locations = vec![tab::Location::Desktop(desktop_dir(), String::new(), config.desktop)];
+    let locations = vec![tab::Location::Desktop(
+        desktop_dir(),
+        String::new(),
+        config.desktop.clone(),
+    )];
     let flags = Flags {
         config_handler,
         config,
@@ -118,7 +133,8 @@ pub fn desktop() -> Result<(), Box<dyn std::error::Error>> {
         state,
         mode: app::Mode::Desktop,
         locations,
-        uris: Vec::new()
+        uris: Vec::new(),
+        show_mode: ShowMode::Folders,
     };
     cosmic::app::run::<App>(settings, flags)?;
 
@@ -149,13 +165,35 @@ pub fn main() -> Result<(), Box<dyn std::error::Error>> {
     let (config_handler, config) = Config::load();
     let (state_handler, state) = State::load();
 
+    // Subcommands perform file operations without starting the app. Existing paths named like a
+    // subcommand are opened instead.
+    let args: Vec<String> = env::args().skip(1).collect();
+    if let Some(command) = args
+        .first()
+        .filter(|arg| fs::symlink_metadata(arg).is_err())
+        .and_then(|arg| cli::Command::from_name(arg))
+    {
+        process::exit(cli::run(command, &args[1..], &config));
+    }
+
     let mut daemonize = true;
+    let mut new_window = false;
+    let mut show_mode = ShowMode::Folders;
     let mut locations = Vec::new();
     let mut uris = Vec::new();
-    for arg in env::args().skip(1) {
+    for arg in args {
         let location = if &arg == "--no-daemon" {
             daemonize = false;
             continue;
+        } else if &arg == "--new-window" {
+            new_window = true;
+            continue;
+        } else if &arg == "--select" {
+            show_mode = ShowMode::Items;
+            continue;
+        } else if &arg == "--properties" {
+            show_mode = ShowMode::ItemProperties;
+            continue;
         } else if &arg == "--trash" {
             Location::Trash
         } else if &arg == "--recents" {
@@ -191,6 +229,27 @@ pub fn main() -> Result<(), Box<dyn std::error::Error>> {
         locations.push(location);
     }
 
+    // Reuse the running instance when it can show every location
+    #[cfg(feature = "dbus")]
+    if !new_window
+        && uris.is_empty()
+        && let Some(paths) = locations
+            .iter()
+            .map(|location| location.path_opt().cloned())
+            .collect::<Option<Vec<_>>>()
+    {
+        let startup_id = env::var("XDG_ACTIVATION_TOKEN")
+            .or_else(|_| env::var("DESKTOP_STARTUP_ID"))
+            .unwrap_or_default();
+        match dbus::forward_blocking(show_mode, &paths, &startup_id) {
+            Ok(true) => return Ok(()),
+            Ok(false) => {}
+            Err(err) => log::warn!("failed to reach running instance: {err}"),
+        }
+    }
+    #[cfg(not(feature = "dbus"))]
+    let _ = new_window;
+
     if daemonize {
         #[cfg(all(unix, not(any(target_os = "macos", target_os = "redox"))))]
         match fork::daemon(true, true) {
@@ -220,7 +279,8 @@ pub fn main() -> Result<(), Box<dyn std::error::Error>> {
         state,
         mode: app::Mode::App,
         locations,
-        uris
+        uris,
+        show_mode,
     };
     cosmic::app::run::<App>(settings, flags)?;
 
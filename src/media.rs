@@ -0,0 +1,594 @@
+// Copyright 2026 System76 <info@system76.com>
+// SPDX-License-Identifier: GPL-3.0-only
+
+//! Inline audio and video playback using GStreamer.
+//!
+//! Hardware decoders are demoted so that playback only depends on software decoding, and video
+//! frames are converted to RGBA on the CPU before being shown as images.
+
+use cosmic::iced::{Alignment, Length};
+use cosmic::widget;
+use cosmic::{Element, theme};
+use gstreamer as gst;
+use gstreamer::prelude::*;
+use gstreamer_app as gst_app;
+use gstreamer_pbutils as gst_pbutils;
+use mime_guess::{Mime, mime};
+use std::fmt;
+use std::path::{Path, PathBuf};
+use std::rc::Rc;
+use std::sync::{Arc, LazyLock, Mutex};
+use std::time::Duration;
+
+use crate::tab::format_duration;
+
+/// Interval between UI updates while media is playing.
+pub const TICK_INTERVAL: Duration = Duration::from_millis(33);
+
+const DISCOVER_TIMEOUT: Duration = Duration::from_secs(5);
+const DEFAULT_VOLUME: f32 = 1.0;
+
+static INIT: LazyLock<Result<(), String>> = LazyLock::new(|| {
+    gst::init().map_err(|err| err.to_string())?;
+
+    let registry = gst::Registry::get();
+    for feature in registry.features(gst::ElementFactory::static_type()) {
+        if let Ok(factory) = feature.downcast::<gst::ElementFactory>()
+            && factory.has_type(gst::ElementFactoryType::HARDWARE)
+        {
+            factory.set_rank(gst::Rank::NONE);
+        }
+    }
+
+    Ok(())
+});
+
+fn init() -> Result<(), String> {
+    INIT.clone()
+}
+
+/// Returns true if the MIME type can be played inline.
+pub fn is_media_mime(mime: &Mime) -> bool {
+    mime.type_() == mime::AUDIO || mime.type_() == mime::VIDEO
+}
+
+fn uri(path: &Path) -> Result<String, String> {
+    url::Url::from_file_path(path)
+        .map(String::from)
+        .map_err(|()| format!("invalid path {}", path.display()))
+}
+
+const fn to_duration(time: gst::ClockTime) -> Duration {
+    Duration::from_nanos(time.nseconds())
+}
+
+/// Duration of a media file and whether it contains video.
+pub fn probe(path: &Path) -> Result<(Option<Duration>, bool), String> {
+    init()?;
+    let discoverer = gst_pbutils::Discoverer::new(gst::ClockTime::from_nseconds(
+        DISCOVER_TIMEOUT.as_nanos() as u64,
+    ))
+    .map_err(|err| err.to_string())?;
+    let info = discoverer
+        .discover_uri(&uri(path)?)
+        .map_err(|err| err.to_string())?;
+    Ok((
+        info.duration().map(to_duration),
+        !info.video_streams().is_empty(),
+    ))
+}
+
+struct Frame {
+    width: u32,
+    height: u32,
+    pixels: Vec<u8>,
+}
+
+/// A playbin pipeline decoding one file.
+pub struct MediaPlayer {
+    pipeline: gst::Element,
+    frame: Arc<Mutex<Option<Frame>>>,
+    paused: bool,
+}
+
+impl fmt::Debug for MediaPlayer {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("MediaPlayer")
+            .field("paused", &self.paused)
+            .finish_non_exhaustive()
+    }
+}
+
+impl MediaPlayer {
+    pub fn new(path: &Path, has_video: bool, volume: f32) -> Result<Self, String> {
+        init()?;
+
+        let frame = Arc::new(Mutex::new(None));
+        let video_sink = if has_video {
+            let frame = frame.clone();
+            let sink = gst_app::AppSink::builder()
+                .caps(
+                    &gst::Caps::builder("video/x-raw")
+                        .field("format", "RGBA")
+                        .field("pixel-aspect-ratio", gst::Fraction::new(1, 1))
+                        .build(),
+                )
+                .max_buffers(1)
+                .drop(true)
+                .build();
+            sink.set_callbacks(
+                gst_app::AppSinkCallbacks::builder()
+                    .new_sample(move |sink| {
+                        let sample = sink.pull_sample().map_err(|_| gst::FlowError::Eos)?;
+                        let structure = sample
+                            .caps()
+                            .and_then(|caps| caps.structure(0))
+                            .ok_or(gst::FlowError::Error)?;
+                        let width = structure
+                            .get::<i32>("width")
+                            .map_err(|_| gst::FlowError::Error)?;
+                        let height = structure
+                            .get::<i32>("height")
+                            .map_err(|_| gst::FlowError::Error)?;
+                        let buffer = sample.buffer().ok_or(gst::FlowError::Error)?;
+                        let map = buffer.map_readable().map_err(|_| gst::FlowError::Error)?;
+                        // RGBA rows are always four byte aligned, so there is no padding
+                        *frame.lock().unwrap() = Some(Frame {
+                            width: width as u32,
+                            height: height as u32,
+                            pixels: map.as_slice().to_vec(),
+                        });
+                        Ok(gst::FlowSuccess::Ok)
+                    })
+                    .build(),
+            );
+            sink.upcast::<gst::Element>()
+        } else {
+            gst::ElementFactory::make("fakesink")
+                .build()
+                .map_err(|err| err.to_string())?
+        };
+
+        let pipeline = gst::ElementFactory::make("playbin")
+            .property("uri", uri(path)?)
+            .property("video-sink", &video_sink)
+            .property("volume", f64::from(volume))
+            .build()
+            .map_err(|err| err.to_string())?;
+        pipeline
+            .set_state(gst::State::Playing)
+            .map_err(|err| err.to_string())?;
+
+        Ok(Self {
+            pipeline,
+            frame,
+            paused: false,
+        })
+    }
+
+    pub const fn is_paused(&self) -> bool {
+        self.paused
+    }
+
+    pub fn set_paused(&mut self, paused: bool) {
+        let state = if paused {
+            gst::State::Paused
+        } else {
+            gst::State::Playing
+        };
+        match self.pipeline.set_state(state) {
+            Ok(_) => self.paused = paused,
+            Err(err) => log::warn!("failed to set media state to {state:?}: {err}"),
+        }
+    }
+
+    pub fn seek(&self, position: Duration) {
+        if let Err(err) = self.pipeline.seek_simple(
+            gst::SeekFlags::FLUSH | gst::SeekFlags::KEY_UNIT,
+            gst::ClockTime::from_nseconds(position.as_nanos() as u64),
+        ) {
+            log::warn!("failed to seek media to {position:?}: {err}");
+        }
+    }
+
+    pub fn set_volume(&self, volume: f32) {
+        self.pipeline.set_property("volume", f64::from(volume));
+    }
+
+    pub fn position(&self) -> Option<Duration> {
+        self.pipeline
+            .query_position::<gst::ClockTime>()
+            .map(to_duration)
+    }
+
+    fn take_frame(&self) -> Option<Frame> {
+        self.frame.lock().unwrap().take()
+    }
+
+    /// Handle pipeline messages, returning true when the end of the stream was reached.
+    fn poll_bus(&self) -> Result<bool, String> {
+        let Some(bus) = self.pipeline.bus() else {
+            return Ok(false);
+        };
+        while let Some(message) =
+            bus.pop_filtered(&[gst::MessageType::Eos, gst::MessageType::Error])
+        {
+            match message.view() {
+                gst::MessageView::Eos(_) => return Ok(true),
+                gst::MessageView::Error(err) => return Err(err.error().to_string()),
+                _ => {}
+            }
+        }
+        Ok(false)
+    }
+}
+
+impl Drop for MediaPlayer {
+    fn drop(&mut self) {
+        if let Err(err) = self.pipeline.set_state(gst::State::Null) {
+            log::warn!("failed to stop media: {err}");
+        }
+    }
+}
+
+#[derive(Clone, Debug)]
+pub enum MediaMessage {
+    Play,
+    Pause,
+    Seek(f32),
+    Volume(f32),
+}
+
+/// Playback state for an audio or video file in the preview pane or gallery.
+#[derive(Debug)]
+pub struct MediaPreview {
+    pub path: PathBuf,
+    pub poster_opt: Option<widget::image::Handle>,
+    pub duration_opt: Option<Duration>,
+    pub has_video: bool,
+    probed: bool,
+    player_opt: Option<MediaPlayer>,
+    frame_opt: Option<widget::image::Handle>,
+    position: Duration,
+    volume: f32,
+    error_opt: Option<String>,
+}
+
+impl Clone for MediaPreview {
+    fn clone(&self) -> Self {
+        // Pipelines are not shared, clones start stopped
+        Self {
+            path: self.path.clone(),
+            poster_opt: self.poster_opt.clone(),
+            duration_opt: self.duration_opt,
+            has_video: self.has_video,
+            probed: self.probed,
+            player_opt: None,
+            frame_opt: None,
+            position: Duration::ZERO,
+            volume: self.volume,
+            error_opt: self.error_opt.clone(),
+        }
+    }
+}
+
+impl MediaPreview {
+    /// Preview of a media file that has not been probed yet. Files with a poster frame are
+    /// assumed to contain video until then.
+    pub fn new(path: &Path, poster_opt: Option<widget::image::Handle>) -> Self {
+        Self {
+            path: path.to_path_buf(),
+            has_video: poster_opt.is_some(),
+            poster_opt,
+            duration_opt: None,
+            probed: false,
+            player_opt: None,
+            frame_opt: None,
+            position: Duration::ZERO,
+            volume: DEFAULT_VOLUME,
+            error_opt: None,
+        }
+    }
+
+    /// Returns true until the result of [`probe`] has been set.
+    pub const fn needs_probe(&self) -> bool {
+        !self.probed
+    }
+
+    /// Set the result of [`probe`], which blocks and so is run only once the preview is shown.
+    pub fn set_probe(&mut self, result: Result<(Option<Duration>, bool), String>) {
+        self.probed = true;
+        match result {
+            Ok((duration_opt, has_video)) => {
+                self.duration_opt = duration_opt;
+                self.has_video = has_video;
+            }
+            Err(err) => {
+                log::warn!("failed to probe media {}: {}", self.path.display(), err);
+                self.error_opt = Some(err);
+            }
+        }
+    }
+
+    pub fn is_playing(&self) -> bool {
+        self.player_opt
+            .as_ref()
+            .is_some_and(|player| !player.is_paused())
+    }
+
+    pub fn stop(&mut self) {
+        self.player_opt = None;
+        self.frame_opt = None;
+        self.position = Duration::ZERO;
+    }
+
+    pub fn update(&mut self, message: MediaMessage) {
+        match message {
+            MediaMessage::Play => {
+                if let Some(player) = &mut self.player_opt {
+                    player.set_paused(false);
+                } else {
+                    match MediaPlayer::new(&self.path, self.has_video, self.volume) {
+                        Ok(player) => {
+                            if !self.position.is_zero() {
+                                player.seek(self.position);
+                            }
+                            self.player_opt = Some(player);
+                            self.error_opt = None;
+                        }
+                        Err(err) => {
+                            log::warn!("failed to play {}: {}", self.path.display(), err);
+                            self.error_opt = Some(err);
+                        }
+                    }
+                }
+            }
+            MediaMessage::Pause => {
+                if let Some(player) = &mut self.player_opt {
+                    player.set_paused(true);
+                }
+            }
+            MediaMessage::Seek(seconds) => {
+                self.position = Duration::from_secs_f32(seconds.max(0.0));
+                if let Some(player) = &self.player_opt {
+                    player.seek(self.position);
+                }
+            }
+            MediaMessage::Volume(volume) => {
+                self.volume = volume.clamp(0.0, 1.0);
+                if let Some(player) = &self.player_opt {
+                    player.set_volume(self.volume);
+                }
+            }
+        }
+    }
+
+    /// Pick up the latest frame and position from a running player.
+    pub fn tick(&mut self) {
+        let Some(player) = &mut self.player_opt else {
+            return;
+        };
+        if let Some(frame) = player.take_frame() {
+            self.frame_opt = Some(widget::image::Handle::from_rgba(
+                frame.width,
+                frame.height,
+                frame.pixels,
+            ));
+        }
+        if let Some(position) = player.position() {
+            self.position = position;
+        }
+        match player.poll_bus() {
+            Ok(false) => {}
+            Ok(true) => {
+                // Rewind so that play starts from the beginning again
+                player.set_paused(true);
+                player.seek(Duration::ZERO);
+                self.position = Duration::ZERO;
+            }
+            Err(err) => {
+                log::warn!("failed to play {}: {}", self.path.display(), err);
+                self.error_opt = Some(err);
+                self.stop();
+            }
+        }
+    }
+
+    pub fn view<'a, M: Clone + 'a>(
+        &'a self,
+        on_message: impl Fn(MediaMessage) -> M + 'a,
+    ) -> Element<'a, M> {
+        let cosmic::cosmic_theme::Spacing {
+            space_xxs,
+            space_xs,
+            ..
+        } = theme::spacing();
+        let on_message = Rc::new(on_message);
+
+        let picture: Element<'a, M> = match self.frame_opt.as_ref().or(self.poster_opt.as_ref()) {
+            Some(handle) => widget::image(handle.clone())
+                .width(Length::Fill)
+                .height(Length::Fill)
+                .into(),
+            None => widget::icon::from_name(if self.has_video {
+                "video-x-generic"
+            } else {
+                "audio-x-generic"
+            })
+            .size(128)
+            .icon()
+            .into(),
+        };
+
+        let play_button = if self.is_playing() {
+            widget::button::icon(widget::icon::from_name("media-playback-pause-symbolic"))
+                .on_press(on_message(MediaMessage::Pause))
+        } else {
+            widget::button::icon(widget::icon::from_name("media-playback-start-symbolic"))
+                .on_press(on_message(MediaMessage::Play))
+        };
+
+        let duration = self.duration_opt.unwrap_or_default();
+        let mut controls = widget::row::with_capacity(5)
+            .align_y(Alignment::Center)
+            .spacing(space_xxs)
+            .push(play_button)
+            .push(widget::text::caption(format!(
+                "{} / {}",
+                format_duration(self.position),
+                format_duration(duration)
+            )));
+        if !duration.is_zero() {
+            controls = controls.push(
+                widget::slider(
+                    0.0..=duration.as_secs_f32(),
+                    self.position.as_secs_f32().min(duration.as_secs_f32()),
+                    {
+                        let on_message = on_message.clone();
+                        move |seconds| on_message(MediaMessage::Seek(seconds))
+                    },
+                )
+                .step(0.1)
+                .width(Length::Fill),
+            );
+        } else {
+            controls = controls.push(widget::space::horizontal());
+        }
+        controls = controls
+            .push(widget::icon::from_name("audio-volume-high-symbolic").size(16))
+            .push(
+                widget::slider(0.0..=1.0, self.volume, move |volume| {
+                    on_message(MediaMessage::Volume(volume))
+                })
+                .step(0.05)
+                .width(Length::Fixed(80.0)),
+            );
+
+        let mut column = widget::column::with_capacity(3)
+            .spacing(space_xs)
+            .align_x(Alignment::Center)
+            .push(widget::container(picture).center(Length::Fill))
+            .push(controls);
+        if let Some(err) = &self.error_opt {
+            column = column.push(widget::text::caption(format!("⚠ {err}")));
+        }
+        column.into()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::fs;
+    use std::io;
+    use std::time::Duration;
+
+    use gstreamer as gst;
+    use mime_guess::Mime;
+    use tempfile::TempDir;
+
+    use super::{MediaMessage, MediaPreview, is_media_mime, probe, to_duration, uri};
+
+    /// One second of 8 kHz mono 16 bit silence
+    fn wav() -> Vec<u8> {
+        let rate = 8000u32;
+        let data_len = rate * 2;
+        let mut wav = Vec::with_capacity(44 + data_len as usize);
+        wav.extend_from_slice(b"RIFF");
+        wav.extend_from_slice(&(36 + data_len).to_le_bytes());
+        wav.extend_from_slice(b"WAVEfmt ");
+        wav.extend_from_slice(&16u32.to_le_bytes());
+        // PCM, one channel
+        wav.extend_from_slice(&1u16.to_le_bytes());
+        wav.extend_from_slice(&1u16.to_le_bytes());
+        wav.extend_from_slice(&rate.to_le_bytes());
+        wav.extend_from_slice(&(rate * 2).to_le_bytes());
+        wav.extend_from_slice(&2u16.to_le_bytes());
+        wav.extend_from_slice(&16u16.to_le_bytes());
+        wav.extend_from_slice(b"data");
+        wav.extend_from_slice(&data_len.to_le_bytes());
+        wav.resize(44 + data_len as usize, 0);
+        wav
+    }
+
+    #[test]
+    fn media_mime_types() {
+        for (mime, media) in [
+            ("audio/mpeg", true),
+            ("video/webm", true),
+            ("image/png", false),
+            ("application/ogg", false),
+        ] {
+            let mime: Mime = mime.parse().unwrap();
+            assert_eq!(is_media_mime(&mime), media, "{mime}");
+        }
+    }
+
+    #[test]
+    fn clock_time_to_duration() {
+        assert_eq!(to_duration(gst::ClockTime::ZERO), Duration::ZERO);
+        assert_eq!(
+            to_duration(gst::ClockTime::from_mseconds(61_500)),
+            Duration::from_millis(61_500)
+        );
+    }
+
+    #[test]
+    fn uri_escapes_paths() {
+        assert_eq!(
+            uri("/tmp/my song #1.ogg".as_ref()).unwrap(),
+            "file:///tmp/my%20song%20%231.ogg"
+        );
+        assert!(uri("relative.ogg".as_ref()).is_err());
+    }
+
+    #[test]
+    fn probe_rejects_other_files() -> io::Result<()> {
+        let dir = TempDir::new()?;
+        let path = dir.path().join("notes.txt");
+        fs::write(&path, "not media")?;
+        assert!(probe(&path).is_err());
+
+        let path = dir.path().join("missing.ogg");
+        let mut preview = MediaPreview::new(&path, None);
+        assert!(preview.needs_probe());
+        preview.set_probe(probe(&path));
+        assert!(!preview.needs_probe());
+        assert_eq!(preview.duration_opt, None);
+        assert!(!preview.has_video);
+        assert!(preview.error_opt.is_some());
+        Ok(())
+    }
+
+    #[test]
+    #[ignore = "needs the GStreamer wavparse plugin"]
+    fn probe_reads_duration() -> io::Result<()> {
+        let dir = TempDir::new()?;
+        let path = dir.path().join("silence.wav");
+        fs::write(&path, wav())?;
+
+        let (duration_opt, has_video) = probe(&path).map_err(io::Error::other)?;
+        assert!(!has_video);
+        let duration = duration_opt.expect("duration");
+        assert!(duration.abs_diff(Duration::from_secs(1)) < Duration::from_millis(10));
+
+        let mut preview = MediaPreview::new(&path, None);
+        preview.set_probe(probe(&path));
+        assert_eq!(preview.duration_opt, duration_opt);
+        assert!(preview.error_opt.is_none());
+        Ok(())
+    }
+
+    #[test]
+    fn seek_and_volume_without_playing() -> io::Result<()> {
+        let dir = TempDir::new()?;
+        let mut preview = MediaPreview::new(&dir.path().join("missing.ogg"), None);
+        preview.update(MediaMessage::Seek(-1.0));
+        assert_eq!(preview.position, Duration::ZERO);
+        preview.update(MediaMessage::Seek(2.5));
+        assert_eq!(preview.position, Duration::from_millis(2500));
+        preview.update(MediaMessage::Volume(1.5));
+        assert_eq!(preview.volume, 1.0);
+        assert!(!preview.is_playing());
+        Ok(())
+    }
+}
@@ -16,7 +16,8 @@ use std::collections::HashMap;
 use std::sync::LazyLock;
 
 use crate::app::{Action, Message};
-use crate::config::{Config, ContextActionPreset};
+use crate::config::{Config, ContextActionPreset, Tag};
+use crate::context_action::{self, SelectedItem};
 use crate::fl;
 use crate::tab::{
     self, HeadingOptions, ItemMetadata, Location, LocationMenuAction, SearchLocation, Tab,
@@ -59,6 +60,7 @@ pub fn context_menu<'a>(
     modifiers: &Modifiers,
     clipboard_paste_available: bool,
     context_actions: &[ContextActionPreset],
+    tags: &[Tag],
 ) -> Element<'a, tab::Message> {
     let find_key = |action: &Action| -> String {
         for (key_bind, key_action) in key_binds {
@@ -131,21 +133,57 @@ pub fn context_menu<'a>(
         .into()
     };
 
+    // Tags are checked when all selected items have them
+    let tag_item = |tag: &Tag, i: usize, checked: bool| {
+        let trailing: Element<'a, tab::Message> = if checked {
+            widget::icon::from_name("object-select-symbolic")
+                .size(14)
+                .into()
+        } else {
+            space::horizontal().width(Length::Fixed(14.0)).into()
+        };
+        menu_button!(
+            widget::icon(tag.color.icon(14)).size(14),
+            space::horizontal().width(Length::Fixed(theme::spacing().space_xxs.into())),
+            text::body(tag.name.clone()),
+            space::horizontal(),
+            trailing
+        )
+        .on_press(tab::Message::ContextAction(Action::ToggleTag(i)))
+        .into()
+    };
+
     let mut selected_dir = 0;
     let mut selected = 0;
     let mut selected_trash_only = false;
     let mut selected_desktop_entry = None;
     let mut selected_types: Vec<Mime> = vec![];
     let mut selected_mount_point = 0;
+    let mut selected_link = 0;
     let mut any_trash_item = false;
+    let mut tag_counts = vec![0; tags.len()];
+    let mut selected_items = Vec::new();
     if let Some(items) = tab.items_opt() {
         for item in items {
             if item.selected {
                 selected += 1;
+                selected_items.push(SelectedItem {
+                    name: &item.name,
+                    mime: &item.mime,
+                    is_dir: item.metadata.is_dir(),
+                });
+                for (tag, count) in tags.iter().zip(tag_counts.iter_mut()) {
+                    if item.tags.contains(&tag.name) {
+                        *count += 1;
+                    }
+                }
                 if item.metadata.is_dir() {
                     selected_mount_point += i32::from(item.is_mount_point);
                     selected_dir += 1;
                 }
+                if item.link_opt.as_ref().is_some_and(|link| !link.broken) {
+                    selected_link += 1;
+                }
                 match &item.location_opt {
                     Some(Location::Trash) | Some(Location::Search(SearchLocation::Trash, ..)) => {
                         selected_trash_only = true
@@ -168,13 +206,28 @@ pub fn context_menu<'a>(
     selected_types.sort_unstable();
     selected_types.dedup();
     selected_trash_only = selected_trash_only && selected == 1;
-    let context_action_items = |selected: usize, selected_dir: usize| {
-        context_actions
-            .iter()
-            .enumerate()
-            .filter(|(_, action)| action.matches_selection(selected, selected_dir))
-            .map(|(i, action)| menu_item(action.name.clone(), Action::RunContextAction(i)).into())
-            .collect::<Vec<Element<'a, tab::Message>>>()
+    let context_action_groups = context_action::grouped(context_actions, &selected_items);
+    // Submenus are opened in place of the context menu
+    let context_action_items = || {
+        let mut items = Vec::<Element<'a, tab::Message>>::new();
+        for (submenu, indexes) in &context_action_groups {
+            if submenu.is_empty() {
+                items.extend(indexes.iter().map(|&i| {
+                    menu_item(context_actions[i].name.clone(), Action::RunContextAction(i)).into()
+                }));
+            } else {
+                items.push(
+                    menu_button!(
+                        text::body(submenu.clone()),
+                        space::horizontal(),
+                        widget::icon::from_name("go-next-symbolic").size(14)
+                    )
+                    .on_press(tab::Message::ContextSubmenu(Some(submenu.clone())))
+                    .into(),
+                );
+            }
+        }
+        items
     };
     // Parse the desktop entry if it is the only selection
     #[cfg(feature = "desktop")]
@@ -191,6 +244,27 @@ pub fn context_menu<'a>(
 
     let mut children: Vec<Element<_>> = Vec::new();
     match (&tab.mode, &tab.location) {
+        (_, _) if tab.context_submenu.is_some() => {
+            let submenu = tab.context_submenu.as_deref().unwrap_or_default();
+            children.push(
+                menu_button!(
+                    widget::icon::from_name("go-previous-symbolic").size(14),
+                    space::horizontal().width(Length::Fixed(theme::spacing().space_xxs.into())),
+                    text::body(submenu.to_string())
+                )
+                .on_press(tab::Message::ContextSubmenu(None))
+                .into(),
+            );
+            children.push(divider::horizontal::light().into());
+            if let Some((_, indexes)) = context_action_groups
+                .iter()
+                .find(|(name, _)| name == submenu)
+            {
+                children.extend(indexes.iter().map(|&i| {
+                    menu_item(context_actions[i].name.clone(), Action::RunContextAction(i)).into()
+                }));
+            }
+        }
         (
             tab::Mode::App | tab::Mode::Desktop,
             Location::Desktop(..)
@@ -223,7 +297,7 @@ pub fn context_menu<'a>(
                 }
                 // Should this simply bypass trash and remove the shortcut?
                 children.push(menu_item(fl!("move-to-trash"), Action::Delete).into());
-                let action_items = context_action_items(selected, selected_dir);
+                let action_items = context_action_items();
                 if !action_items.is_empty() {
                     children.push(divider::horizontal::light().into());
                     children.extend(action_items);
@@ -244,13 +318,16 @@ pub fn context_menu<'a>(
                         menu_item(fl!("open-item-location"), Action::OpenItemLocation).into(),
                     );
                 }
+                if selected == 1 && selected_link == 1 {
+                    children.push(menu_item(fl!("follow-link"), Action::FollowLink).into());
+                }
                 // All selected items are directories
                 if selected == selected_dir && matches!(tab.mode, tab::Mode::App) {
                     children.push(menu_item(fl!("open-in-new-tab"), Action::OpenInNewTab).into());
                     children
                         .push(menu_item(fl!("open-in-new-window"), Action::OpenInNewWindow).into());
                 }
-                let action_items = context_action_items(selected, selected_dir);
+                let action_items = context_action_items();
                 if !action_items.is_empty() {
                     children.push(divider::horizontal::light().into());
                     children.extend(action_items);
@@ -282,6 +359,9 @@ pub fn context_menu<'a>(
 
                 //TODO: Print?
                 children.push(menu_item(fl!("show-details"), Action::Preview).into());
+                if !any_trash_item {
+                    children.push(menu_item(fl!("properties"), Action::Properties).into());
+                }
                 if any_trash_item {
                     children.push(divider::horizontal::light().into());
                     children.push(
@@ -296,6 +376,11 @@ pub fn context_menu<'a>(
                             .push(menu_item(fl!("add-to-sidebar"), Action::AddToSidebar).into());
                     }
                     children.push(divider::horizontal::light().into());
+                    for (i, (tag, count)) in tags.iter().zip(&tag_counts).enumerate() {
+                        children.push(tag_item(tag, i, *count == selected));
+                    }
+                    children.push(menu_item(fl!("new-tag"), Action::NewTag).into());
+                    children.push(divider::horizontal::light().into());
                     if tab.location.is_recents() {
                         children.push(
                             menu_item(fl!("remove-from-recents"), Action::RemoveFromRecents).into(),
@@ -330,8 +415,14 @@ pub fn context_menu<'a>(
                 }
                 if can_paste {
                     children.push(menu_item(fl!("paste"), Action::Paste).into());
+                    children.push(menu_item(fl!("paste-link"), Action::PasteLink).into());
+                    children.push(menu_item(fl!("paste-hard-link"), Action::PasteHardLink).into());
                 } else {
                     children.push(menu_item_disabled(fl!("paste"), Action::Paste).into());
+                    children.push(menu_item_disabled(fl!("paste-link"), Action::PasteLink).into());
+                    children.push(
+                        menu_item_disabled(fl!("paste-hard-link"), Action::PasteHardLink).into(),
+                    );
                 }
 
                 //TODO: only show if cosmic-settings is found?
@@ -353,7 +444,45 @@ pub fn context_menu<'a>(
                 children.push(sort_item(fl!("sort-by-name"), HeadingOptions::Name));
                 children.push(sort_item(fl!("sort-by-modified"), HeadingOptions::Modified));
                 children.push(sort_item(fl!("sort-by-size"), HeadingOptions::Size));
-                if matches!(tab.location, Location::Desktop(..)) {
+                if let Location::Desktop(_, _, desktop_config) = &tab.location {
+                    children.push(divider::horizontal::light().into());
+                    children.push(
+                        menu_item(
+                            fl!("arrange-by-name"),
+                            Action::DesktopArrange(tab::DesktopArrange::Name),
+                        )
+                        .into(),
+                    );
+                    children.push(
+                        menu_item(
+                            fl!("arrange-by-type"),
+                            Action::DesktopArrange(tab::DesktopArrange::Type),
+                        )
+                        .into(),
+                    );
+                    children.push(
+                        menu_item(
+                            fl!("arrange-by-modified"),
+                            Action::DesktopArrange(tab::DesktopArrange::Modified),
+                        )
+                        .into(),
+                    );
+                    let trailing: Element<'a, tab::Message> = if desktop_config.keep_aligned {
+                        widget::icon::from_name("object-select-symbolic")
+                            .size(14)
+                            .into()
+                    } else {
+                        space::horizontal().width(Length::Fixed(14.0)).into()
+                    };
+                    children.push(
+                        menu_button!(
+                            text::body(fl!("keep-aligned")),
+                            space::horizontal(),
+                            trailing
+                        )
+                        .on_press(tab::Message::ContextAction(Action::DesktopKeepAligned))
+                        .into(),
+                    );
                     children.push(divider::horizontal::light().into());
                     children.push(
                         menu_item(fl!("desktop-view-options"), Action::DesktopViewOptions).into(),
@@ -683,6 +812,7 @@ pub fn menu_bar<'a>(
                         menu_button_optional(fl!("rename"), Action::Rename, selected > 0),
                         menu::Item::Divider,
                         menu::Item::Button(fl!("reload-folder"), None, Action::Reload),
+                        menu::Item::Button(fl!("sync-folders"), None, Action::SyncFolders),
                         menu::Item::Divider,
                         menu_button_optional(
                             fl!("add-to-sidebar"),
@@ -709,6 +839,7 @@ pub fn menu_bar<'a>(
                         menu_button_optional(fl!("move-to"), Action::MoveTo, selected > 0),
                         menu_button_optional(fl!("copy-to"), Action::CopyTo, selected > 0),
                         menu_button_optional(fl!("paste"), Action::Paste, can_paste),
+                        menu_button_optional(fl!("paste-link"), Action::PasteLink, can_paste),
                         menu::Item::Button(fl!("select-all"), None, Action::SelectAll),
                         menu::Item::Divider,
                         menu::Item::Button(fl!("history"), None, Action::EditHistory),
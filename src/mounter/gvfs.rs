@@ -216,6 +216,8 @@ fn network_scan(uri: &str, sizes: IconSizes) -> Result<Vec<tab::Item>, String> {
             //TODO: scan directory size on gvfs mounts?
             dir_size: DirSize::NotDirectory,
             cut: false,
+            tags: Vec::new(),
+            link_opt: None,
             checksums: ChecksumState::default(),
         });
     }
@@ -38,6 +38,8 @@ pub struct MouseArea<'a, Message> {
     on_forward_press: Option<Box<dyn OnMouseButton<'a, Message>>>,
     on_forward_release: Option<Box<dyn OnMouseButton<'a, Message>>>,
     on_scroll: Option<Box<dyn OnScroll<'a, Message>>>,
+    on_scroll_at: Option<Box<dyn OnScrollAt<'a, Message>>>,
+    on_pan: Option<Box<dyn OnPan<'a, Message>>>,
     on_enter: Option<Box<dyn OnEnterExit<'a, Message>>>,
     on_exit: Option<Box<dyn OnEnterExit<'a, Message>>>,
     show_drag_rect: bool,
@@ -180,6 +182,20 @@ impl<'a, Message> MouseArea<'a, Message> {
         self
     }
 
+    /// The message to emit on a scroll, with the cursor position inside the area.
+    #[must_use]
+    pub fn on_scroll_at(mut self, message: impl OnScrollAt<'a, Message>) -> Self {
+        self.on_scroll_at = Some(Box::new(message));
+        self
+    }
+
+    /// The message to emit when the cursor moves while the left button is held.
+    #[must_use]
+    pub fn on_pan(mut self, message: impl OnPan<'a, Message>) -> Self {
+        self.on_pan = Some(Box::new(message));
+        self
+    }
+
     /// The message to emit when a mouse enters the area.
     #[must_use]
     pub fn on_enter(mut self, message: impl OnEnterExit<'a, Message>) -> Self {
@@ -226,6 +242,15 @@ impl<'a, Message, F> OnScroll<'a, Message> for F where
 {
 }
 
+pub trait OnScrollAt<'a, Message>: Fn(mouse::ScrollDelta, Point) -> Option<Message> + 'a {}
+impl<'a, Message, F> OnScrollAt<'a, Message> for F where
+    F: Fn(mouse::ScrollDelta, Point) -> Option<Message> + 'a
+{
+}
+
+pub trait OnPan<'a, Message>: Fn(Point) -> Message + 'a {}
+impl<'a, Message, F> OnPan<'a, Message> for F where F: Fn(Point) -> Message + 'a {}
+
 pub trait OnEnterExit<'a, Message>: Fn() -> Message + 'a {}
 impl<'a, Message, F> OnEnterExit<'a, Message> for F where F: Fn() -> Message + 'a {}
 
@@ -311,6 +336,8 @@ impl<'a, Message> MouseArea<'a, Message> {
             on_enter: None,
             on_exit: None,
             on_scroll: None,
+            on_scroll_at: None,
+            on_pan: None,
             show_drag_rect: false,
         }
     }
@@ -589,6 +616,15 @@ fn update<Message: Clone>(
                 state.last_auto_scroll = auto_scroll;
             }
         }
+
+        if let Some(message) = widget.on_pan.as_ref()
+            && state.drag_initiated.is_some()
+        {
+            shell.publish(message(Point::new(
+                position.x - layout_bounds.x,
+                position.y - layout_bounds.y,
+            )));
+        }
     }
 
     if state.drag_initiated.is_none() && !cursor.is_over(layout_bounds) {
@@ -617,7 +653,7 @@ fn update<Message: Clone>(
                 }
             }
         }
-        if widget.on_drag.is_some() {
+        if widget.on_drag.is_some() || widget.on_pan.is_some() {
             state.drag_initiated = cursor.position();
         }
 
@@ -627,6 +663,17 @@ fn update<Message: Clone>(
         }
     }
 
+    // Panning ends on any release, even without movement
+    if widget.on_pan.is_some()
+        && matches!(
+            event,
+            Event::Mouse(mouse::Event::ButtonReleased(mouse::Button::Left))
+                | Event::Touch(touch::Event::FingerLifted { .. })
+        )
+    {
+        state.drag_initiated = None;
+    }
+
     let distance_dragged = state
         .drag_initiated
         .map(|initiated| initiated.distance(cursor.position().unwrap_or_default()))
@@ -784,6 +831,16 @@ fn update<Message: Clone>(
         return;
     }
 
+    if let Some(on_scroll_at) = widget.on_scroll_at.as_ref()
+        && let Event::Mouse(mouse::Event::WheelScrolled { delta }) = event
+        && let Some(position) = cursor.position_in(layout_bounds)
+        && let Some(message) = on_scroll_at(*delta, position)
+    {
+        shell.publish(message);
+        shell.capture_event();
+        return;
+    }
+
     if let Some((message, drag_rect)) = widget.on_drag.as_ref().zip(state.drag_rect(cursor)) {
         shell.publish(message(drag_rect.intersection(&layout_bounds).map(
             |mut rect| {
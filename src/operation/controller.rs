@@ -1,9 +1,14 @@
 use atomic_float::AtomicF32;
 use num_enum::{IntoPrimitive, TryFromPrimitive};
-use std::sync::Arc;
-use std::sync::atomic::{self, AtomicU16};
+use std::path::Path;
+use std::sync::atomic::{self, AtomicU16, AtomicU64};
+use std::sync::{Arc, Mutex};
+use std::time::Instant;
 use tokio::sync::Notify;
 
+use super::recursive::Progress;
+use super::stats::TransferStats;
+
 #[derive(Clone, Copy, Debug, PartialEq, Eq, IntoPrimitive, TryFromPrimitive)]
 #[repr(u16)]
 pub enum ControllerState {
@@ -17,6 +22,9 @@ pub enum ControllerState {
 struct ControllerInner {
     state: AtomicU16,
     progress: AtomicF32,
+    stats: Mutex<Option<TransferStats>>,
+    /// Bytes per second, or zero when unlimited
+    bandwidth_limit: AtomicU64,
     notify: Notify,
 }
 
@@ -33,6 +41,8 @@ impl Default for Controller {
             inner: Arc::new(ControllerInner {
                 state: AtomicU16::new(ControllerState::Running.into()),
                 progress: AtomicF32::new(0.0),
+                stats: Mutex::new(None),
+                bandwidth_limit: AtomicU64::new(0),
                 notify: Notify::new(),
             }),
         }
@@ -63,6 +73,33 @@ impl Controller {
             .swap(progress, atomic::Ordering::Relaxed);
     }
 
+    /// Bytes per second that copies are limited to.
+    pub fn bandwidth_limit(&self) -> Option<u64> {
+        match self.inner.bandwidth_limit.load(atomic::Ordering::Relaxed) {
+            0 => None,
+            limit => Some(limit),
+        }
+    }
+
+    pub fn set_bandwidth_limit(&self, limit: Option<u64>) {
+        self.inner
+            .bandwidth_limit
+            .store(limit.unwrap_or(0), atomic::Ordering::Relaxed);
+    }
+
+    /// Statistics of the transfer, if the operation copies or moves files.
+    pub fn with_stats<T>(&self, f: impl FnOnce(&TransferStats) -> T) -> Option<T> {
+        self.inner.stats.lock().ok()?.as_ref().map(f)
+    }
+
+    pub fn update_stats(&self, file: Option<&Path>, progress: &Progress) {
+        if let Ok(mut stats) = self.inner.stats.lock() {
+            stats
+                .get_or_insert_default()
+                .update(Instant::now(), file, progress);
+        }
+    }
+
     pub fn state(&self) -> ControllerState {
         ControllerState::try_from(self.inner.state.load(atomic::Ordering::Relaxed))
             .unwrap_or(ControllerState::Failed)
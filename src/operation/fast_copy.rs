@@ -0,0 +1,105 @@
+// Copyright 2026 System76 <info@system76.com>
+// SPDX-License-Identifier: GPL-3.0-only
+
+//! Copies performed by the kernel, which avoid moving file contents through userspace.
+//!
+//! A reflink shares the extents of the source on copy-on-write filesystems such as Btrfs and
+//! XFS, making the copy instant. Otherwise `copy_file_range` copies the ranges of the source that
+//! contain data, so that holes in sparse files are not written.
+
+use std::io;
+use std::os::fd::RawFd;
+
+/// `_IOW(0x94, 9, int)` from linux/fs.h
+const FICLONE: libc::c_ulong = 0x4004_9409;
+
+/// Clone all extents of `from` into `to`.
+pub fn reflink(from: RawFd, to: RawFd) -> io::Result<()> {
+    // SAFETY: both descriptors are kept open by the caller for the duration of the call
+    if unsafe { libc::ioctl(to, FICLONE as _, from) } == -1 {
+        return Err(io::Error::last_os_error());
+    }
+    Ok(())
+}
+
+fn seek(fd: RawFd, offset: u64, whence: libc::c_int) -> io::Result<u64> {
+    // SAFETY: the descriptor is kept open by the caller for the duration of the call
+    let ret = unsafe { libc::lseek(fd, offset as libc::off_t, whence) };
+    if ret < 0 {
+        return Err(io::Error::last_os_error());
+    }
+    Ok(ret as u64)
+}
+
+/// Ranges of the first `len` bytes of a file that contain data. Filesystems without support for
+/// holes report the whole file as data.
+pub fn data_segments(fd: RawFd, len: u64) -> io::Result<Vec<(u64, u64)>> {
+    let mut segments = Vec::new();
+    let mut pos = 0;
+    while pos < len {
+        let start = match seek(fd, pos, libc::SEEK_DATA) {
+            Ok(start) => start,
+            // There is only a hole after pos
+            Err(err) if err.raw_os_error() == Some(libc::ENXIO) => break,
+            Err(err) if err.raw_os_error() == Some(libc::EINVAL) && segments.is_empty() => {
+                return Ok(vec![(0, len)]);
+            }
+            Err(err) => return Err(err),
+        };
+        let end = seek(fd, start, libc::SEEK_HOLE)?.min(len);
+        if start >= end {
+            break;
+        }
+        segments.push((start, end));
+        pos = end;
+    }
+    Ok(segments)
+}
+
+/// Copy up to `len` bytes at `offset` from `from` to the same offset in `to`, returning the
+/// number of bytes copied.
+pub fn copy_range(from: RawFd, to: RawFd, offset: u64, len: u64) -> io::Result<u64> {
+    let mut off_in = offset as libc::loff_t;
+    let mut off_out = offset as libc::loff_t;
+    // SAFETY: both descriptors are kept open by the caller, and the offsets outlive the call
+    let ret = unsafe {
+        libc::copy_file_range(
+            from,
+            &mut off_in,
+            to,
+            &mut off_out,
+            usize::try_from(len).unwrap_or(usize::MAX),
+            0,
+        )
+    };
+    if ret < 0 {
+        return Err(io::Error::last_os_error());
+    }
+    Ok(ret as u64)
+}
+
+/// Set the length of a file, which leaves a hole when extending it.
+pub fn set_len(fd: RawFd, len: u64) -> io::Result<()> {
+    // SAFETY: the descriptor is kept open by the caller for the duration of the call
+    if unsafe { libc::ftruncate(fd, len as libc::off_t) } == -1 {
+        return Err(io::Error::last_os_error());
+    }
+    Ok(())
+}
+
+/// Errors returned when a copy method is not possible between two files, for example because
+/// they are on different filesystems, rather than because the copy itself failed.
+pub fn is_unsupported(err: &io::Error) -> bool {
+    matches!(
+        err.raw_os_error(),
+        Some(
+            libc::EXDEV
+                | libc::EINVAL
+                | libc::ENOSYS
+                | libc::EOPNOTSUPP
+                | libc::ENOTTY
+                | libc::EBADF
+                | libc::EPERM
+        )
+    )
+}
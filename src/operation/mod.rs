@@ -1,9 +1,10 @@
 use crate::app::{ArchiveType, DialogPage, Message, REPLACE_BUTTON_ID};
 use crate::config::IconSizes;
 use crate::spawn_detached::spawn_detached;
-use crate::{archive, fl, tab};
+use crate::{archive, context_action, fl, tab};
 use cosmic::iced::futures::channel::mpsc::Sender;
 use cosmic::iced::futures::{self, SinkExt, StreamExt, stream};
+use serde::{Deserialize, Serialize};
 use std::borrow::Cow;
 use std::fmt::Formatter;
 use std::fs;
@@ -17,15 +18,36 @@ use zip::AesMode::Aes256;
 pub use self::controller::{Controller, ControllerState};
 pub mod controller;
 
+#[cfg(target_os = "linux")]
+mod fast_copy;
+
 pub use notifiers::*;
 mod notifiers;
 
 pub use self::reader::OpReader;
 pub mod reader;
 
+pub use self::permissions::{ModeChange, PermissionsChange};
+pub mod permissions;
+
+pub use self::queue::OperationQueue;
+pub mod queue;
+
+pub use self::recursive::Preserve;
 use self::recursive::{Context, Method};
 pub mod recursive;
 
+pub mod resume;
+
+pub use self::stats::TransferStats;
+pub mod stats;
+
+pub use self::sync::{SyncAction, SyncMode};
+pub mod sync;
+
+pub use self::transform::ImageTransform;
+pub mod transform;
+
 async fn handle_replace(
     msg_tx: Arc<TokioMutex<Sender<Message>>>,
     file_from: PathBuf,
@@ -60,6 +82,8 @@ async fn handle_replace(
                 multiple,
                 apply_to_all: false,
                 conflict_count,
+                compare: false,
+                merge_policy: MergePolicy::default(),
                 tx,
             },
             Some(REPLACE_BUTTON_ID.clone()),
@@ -81,15 +105,85 @@ fn get_directory_name(file_name: &str) -> &str {
 #[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
 pub enum ReplaceResult {
     Replace(bool),
+    /// Replace if the source was modified more recently, otherwise skip
+    ReplaceIfNewer(bool),
+    /// Replace if the sizes differ, otherwise skip
+    ReplaceIfSizeDiffers(bool),
+    /// Move the existing file to a unique name, then copy
+    RenameExisting(bool),
     KeepBoth,
     Skip(bool),
+    /// Merge a folder into the existing one
+    Merge(MergePolicy),
     Cancel,
 }
 
+impl ReplaceResult {
+    pub const fn apply_to_all(self) -> bool {
+        match self {
+            Self::Replace(apply_to_all)
+            | Self::ReplaceIfNewer(apply_to_all)
+            | Self::ReplaceIfSizeDiffers(apply_to_all)
+            | Self::RenameExisting(apply_to_all)
+            | Self::Skip(apply_to_all) => apply_to_all,
+            Self::KeepBoth | Self::Merge(_) | Self::Cancel => false,
+        }
+    }
+}
+
+/// How conflicting files inside a merged folder are resolved
+#[derive(Clone, Copy, Debug, Default, Eq, Hash, PartialEq)]
+pub enum MergePolicy {
+    #[default]
+    Ask,
+    Replace,
+    ReplaceIfNewer,
+    ReplaceIfSizeDiffers,
+    KeepBoth,
+    Skip,
+}
+
+impl MergePolicy {
+    pub const ALL: [Self; 6] = [
+        Self::Ask,
+        Self::Replace,
+        Self::ReplaceIfNewer,
+        Self::ReplaceIfSizeDiffers,
+        Self::KeepBoth,
+        Self::Skip,
+    ];
+
+    /// Result used for every conflict in the folder, or `None` to ask for each one
+    pub const fn replace_result(self) -> Option<ReplaceResult> {
+        match self {
+            Self::Ask => None,
+            Self::Replace => Some(ReplaceResult::Replace(true)),
+            Self::ReplaceIfNewer => Some(ReplaceResult::ReplaceIfNewer(true)),
+            Self::ReplaceIfSizeDiffers => Some(ReplaceResult::ReplaceIfSizeDiffers(true)),
+            Self::KeepBoth => Some(ReplaceResult::KeepBoth),
+            Self::Skip => Some(ReplaceResult::Skip(true)),
+        }
+    }
+
+    pub fn label(self) -> String {
+        match self {
+            Self::Ask => fl!("merge-ask"),
+            Self::Replace => fl!("replace"),
+            Self::ReplaceIfNewer => fl!("replace-if-newer"),
+            Self::ReplaceIfSizeDiffers => fl!("replace-if-size-differs"),
+            Self::KeepBoth => fl!("keep-both"),
+            Self::Skip => fl!("skip"),
+        }
+    }
+}
+
 async fn copy_or_move(
     paths: Vec<PathBuf>,
     to: PathBuf,
     method: Method,
+    preserve: Preserve,
+    verify: bool,
+    resumable: bool,
     msg_tx: &Arc<TokioMutex<Sender<Message>>>,
     controller: Controller,
 ) -> Result<OperationSelection, OperationError> {
@@ -168,26 +262,12 @@ async fn copy_or_move(
             from_to_pairs_iter.collect()
         };
 
-        let mut context = Context::new(controller.clone());
+        let mut context = Context::new(controller.clone())
+            .preserve(preserve)
+            .resumable(resumable.then_some(resume::MIN_SIZE))
+            .verify(verify);
 
-        {
-            let controller = controller.clone();
-            context = context.on_progress(move |_op, progress| {
-                let item_progress = match progress.total_bytes {
-                    Some(total_bytes) => {
-                        if total_bytes == 0 {
-                            1.0
-                        } else {
-                            progress.current_bytes as f32 / total_bytes as f32
-                        }
-                    }
-                    None => 0.0,
-                };
-                let total_progress =
-                    (item_progress + progress.current_ops as f32) / progress.total_ops as f32;
-                controller.set_progress(total_progress);
-            });
-        }
+        context = context.on_progress(track_progress(controller.clone()));
 
         {
             let msg_tx = msg_tx.clone();
@@ -213,6 +293,27 @@ async fn copy_or_move(
     .map_err(wrap_compio_spawn_error)?
 }
 
+/// Report the progress of a recursive copy or move to the controller.
+fn track_progress(controller: Controller) -> impl recursive::OnProgress {
+    move |op: &recursive::Op, progress: &recursive::Progress| {
+        let file = op.is_file_transfer().then_some(op.from.as_path());
+        controller.update_stats(file, progress);
+        let item_progress = match progress.total_bytes {
+            Some(total_bytes) => {
+                if total_bytes == 0 {
+                    1.0
+                } else {
+                    progress.current_bytes as f32 / total_bytes as f32
+                }
+            }
+            None => 0.0,
+        };
+        let total_progress =
+            (item_progress + progress.current_ops as f32) / progress.total_ops as f32;
+        controller.set_progress(total_progress);
+    }
+}
+
 pub async fn sync_to_disk(
     written_files: Vec<PathBuf>,
     target_dirs: std::collections::HashSet<PathBuf>,
@@ -239,6 +340,15 @@ pub async fn sync_to_disk(
 }
 
 pub fn copy_unique_path(from: &Path, to: &Path) -> PathBuf {
+    unique_path(from, to, &fl!("copy_noun"))
+}
+
+/// Unique path for a link to `from` in `to`, named like a copy but as a link.
+fn link_unique_path(from: &Path, to: &Path) -> PathBuf {
+    unique_path(from, to, &fl!("link_noun"))
+}
+
+fn unique_path(from: &Path, to: &Path, noun: &str) -> PathBuf {
     // List of compound extensions to check
     const COMPOUND_EXTENSIONS: &[&str] = &[
         ".tar.gz",
@@ -289,8 +399,8 @@ pub fn copy_unique_path(from: &Path, to: &Path) -> PathBuf {
                 file_name.to_string()
             } else {
                 match ext {
-                    Some(ref ext) => format!("{} ({} {}).{}", stem, fl!("copy_noun"), n, ext),
-                    None => format!("{} ({} {})", stem, fl!("copy_noun"), n),
+                    Some(ref ext) => format!("{} ({} {}).{}", stem, noun, n, ext),
+                    None => format!("{} ({} {})", stem, noun, n),
                 }
             };
 
@@ -306,6 +416,25 @@ pub fn copy_unique_path(from: &Path, to: &Path) -> PathBuf {
     to
 }
 
+/// Create a link at `link` that points to `target`, or a hard link to it when `hard` is set.
+fn create_link(target: &Path, link: &Path, hard: bool) -> io::Result<()> {
+    if hard {
+        return fs::hard_link(target, link);
+    }
+    #[cfg(unix)]
+    {
+        std::os::unix::fs::symlink(target, link)
+    }
+    #[cfg(windows)]
+    {
+        if target.is_dir() {
+            std::os::windows::fs::symlink_dir(target, link)
+        } else {
+            std::os::windows::fs::symlink_file(target, link)
+        }
+    }
+}
+
 fn file_name(path: &Path) -> Cow<'_, str> {
     path.file_name()
         .map_or_else(|| fl!("unknown-folder").into(), |x| x.to_string_lossy())
@@ -346,7 +475,9 @@ pub struct OperationSelection {
     pub selected: Vec<PathBuf>,
 }
 
-#[derive(Clone, Debug, Eq, Hash, PartialEq)]
+/// Operations that are still queued or running when the app exits are saved so that they can be
+/// resumed. Variants that only make sense within a session are skipped.
+#[derive(Clone, Debug, Deserialize, Eq, Hash, PartialEq, Serialize)]
 pub enum Operation {
     /// Compress files
     Compress {
@@ -355,20 +486,31 @@ pub enum Operation {
         archive_type: ArchiveType,
         password: Option<String>,
     },
+    /// Run the steps of a context action, capturing their output
+    #[serde(skip)]
+    ContextAction {
+        name: String,
+        steps: Vec<String>,
+        paths: Vec<PathBuf>,
+    },
     /// Copy items
     Copy {
         paths: Vec<PathBuf>,
         to: PathBuf,
+        preserve: Preserve,
+        /// Compare checksums of the copies with their sources
+        verify: bool,
+        /// Copy large files so that the copy can be resumed if interrupted
+        resumable: bool,
     },
     /// Move items to the trash
-    Delete {
-        paths: Vec<PathBuf>,
-    },
+    #[serde(skip)]
+    Delete { paths: Vec<PathBuf> },
     /// Delete a path from the trash
-    DeleteTrash {
-        items: Vec<trash::TrashItem>,
-    },
+    #[serde(skip)]
+    DeleteTrash { items: Vec<trash::TrashItem> },
     /// Empty the trash
+    #[serde(skip)]
     EmptyTrash,
     /// Uncompress files
     Extract {
@@ -376,41 +518,69 @@ pub enum Operation {
         to: PathBuf,
         password: Option<String>,
     },
+    /// Create symbolic links, or hard links, to items
+    #[serde(skip)]
+    Link {
+        paths: Vec<PathBuf>,
+        to: PathBuf,
+        hard: bool,
+    },
     /// Move items
     Move {
         paths: Vec<PathBuf>,
         to: PathBuf,
         cross_device_copy: bool,
+        preserve: Preserve,
+        /// Compare checksums of copies made across devices with their sources
+        verify: bool,
+        /// Copy large files across devices so that the copy can be resumed if interrupted
+        resumable: bool,
     },
-    NewFile {
-        path: PathBuf,
-    },
-    NewFolder {
-        path: PathBuf,
-    },
+    #[serde(skip)]
+    NewFile { path: PathBuf },
+    #[serde(skip)]
+    NewFolder { path: PathBuf },
     /// Permanently delete items, skipping the trash
-    PermanentlyDelete {
-        paths: Box<[PathBuf]>,
-    },
-    RemoveFromRecents {
-        paths: Box<[PathBuf]>,
-    },
-    Rename {
-        from: PathBuf,
-        to: PathBuf,
-    },
+    #[serde(skip)]
+    PermanentlyDelete { paths: Box<[PathBuf]> },
+    #[serde(skip)]
+    RemoveFromRecents { paths: Box<[PathBuf]> },
+    #[serde(skip)]
+    Rename { from: PathBuf, to: PathBuf },
     /// Restore a path from the trash
-    Restore {
-        items: Vec<trash::TrashItem>,
-    },
+    #[serde(skip)]
+    Restore { items: Vec<trash::TrashItem> },
     /// Set executable and launch
-    SetExecutableAndLaunch {
-        path: PathBuf,
-    },
+    #[serde(skip)]
+    SetExecutableAndLaunch { path: PathBuf },
     /// Set permissions
-    SetPermissions {
+    #[serde(skip)]
+    SetPermissions { path: PathBuf, mode: u32 },
+    /// Change ownership and permissions, optionally including the contents of directories
+    SetProperties {
+        paths: Vec<PathBuf>,
+        change: PermissionsChange,
+    },
+    /// Add or remove a tag
+    #[serde(skip)]
+    SetTag {
+        paths: Vec<PathBuf>,
+        tag: String,
+        enabled: bool,
+    },
+    /// Synchronize two folders by running a reviewed plan
+    #[serde(skip)]
+    Sync {
+        source: PathBuf,
+        destination: PathBuf,
+        mode: SyncMode,
+        actions: Vec<SyncAction>,
+    },
+    /// Losslessly rotate or flip an image
+    #[serde(skip)]
+    TransformImage {
         path: PathBuf,
-        mode: u32,
+        transform: ImageTransform,
     },
 }
 
@@ -418,6 +588,8 @@ pub enum Operation {
 pub enum OperationErrorType {
     Generic(String),
     PasswordRequired,
+    /// Sources and copies with checksums that did not match
+    VerificationFailed(Vec<(PathBuf, PathBuf)>),
 }
 #[derive(Clone, Debug)]
 pub struct OperationError {
@@ -466,6 +638,13 @@ impl std::fmt::Display for OperationError {
         match &self.kind {
             OperationErrorType::Generic(s) => s.fmt(f),
             OperationErrorType::PasswordRequired => f.write_str("Password required"),
+            OperationErrorType::VerificationFailed(files) => {
+                f.write_str(&fl!("verification-failed", items = files.len()))?;
+                for (from, to) in files {
+                    write!(f, "\n{} → {}", from.display(), to.display())?;
+                }
+                Ok(())
+            }
         }
     }
 }
@@ -487,7 +666,13 @@ impl Operation {
                 to = file_name(to),
                 progress = progress()
             ),
-            Self::Copy { paths, to } => fl!(
+            Self::ContextAction { name, paths, .. } => fl!(
+                "running-context-action",
+                name = name.as_str(),
+                items = paths.len(),
+                progress = progress()
+            ),
+            Self::Copy { paths, to, .. } => fl!(
                 "copying",
                 items = paths.len(),
                 from = paths_parent_name(paths),
@@ -516,6 +701,14 @@ impl Operation {
                 to = file_name(to),
                 progress = progress()
             ),
+            Self::Link { paths, to, hard } => fl!(
+                "linking",
+                items = paths.len(),
+                from = paths_parent_name(paths),
+                to = file_name(to),
+                hard = hard.to_string(),
+                progress = progress()
+            ),
             Self::Move { paths, to, .. } => fl!(
                 "moving",
                 items = paths.len(),
@@ -549,6 +742,36 @@ impl Operation {
                     mode = format!("{:#03o}", mode)
                 )
             }
+            Self::SetProperties { paths, .. } => fl!(
+                "setting-properties",
+                items = paths.len(),
+                progress = progress()
+            ),
+            Self::SetTag {
+                paths,
+                tag,
+                enabled,
+            } => fl!(
+                "setting-tag",
+                items = paths.len(),
+                tag = tag.as_str(),
+                enabled = enabled.to_string()
+            ),
+            Self::Sync {
+                source,
+                destination,
+                ..
+            } => fl!(
+                "synchronizing",
+                from = file_name(source),
+                to = file_name(destination),
+                progress = progress()
+            ),
+            Self::TransformImage { path, transform } => fl!(
+                "transforming-image",
+                name = file_name(path),
+                transform = transform.id()
+            ),
         }
     }
 
@@ -560,7 +783,12 @@ impl Operation {
                 from = paths_parent_name(paths),
                 to = file_name(to)
             ),
-            Self::Copy { paths, to } => fl!(
+            Self::ContextAction { name, paths, .. } => fl!(
+                "ran-context-action",
+                name = name.as_str(),
+                items = paths.len()
+            ),
+            Self::Copy { paths, to, .. } => fl!(
                 "copied",
                 items = paths.len(),
                 from = paths_parent_name(paths),
@@ -584,6 +812,13 @@ impl Operation {
                 from = paths_parent_name(paths),
                 to = file_name(to)
             ),
+            Self::Link { paths, to, hard } => fl!(
+                "linked",
+                items = paths.len(),
+                from = paths_parent_name(paths),
+                to = file_name(to),
+                hard = hard.to_string()
+            ),
             Self::Move { paths, to, .. } => fl!(
                 "moved",
                 items = paths.len(),
@@ -614,6 +849,31 @@ impl Operation {
                     mode = format!("{:#03o}", mode)
                 )
             }
+            Self::SetProperties { paths, .. } => fl!("set-properties", items = paths.len()),
+            Self::SetTag {
+                paths,
+                tag,
+                enabled,
+            } => fl!(
+                "set-tag",
+                items = paths.len(),
+                tag = tag.as_str(),
+                enabled = enabled.to_string()
+            ),
+            Self::Sync {
+                source,
+                destination,
+                ..
+            } => fl!(
+                "synchronized",
+                from = file_name(source),
+                to = file_name(destination)
+            ),
+            Self::TransformImage { path, transform } => fl!(
+                "transformed-image",
+                name = file_name(path),
+                transform = transform.id()
+            ),
         }
     }
 
@@ -628,13 +888,97 @@ impl Operation {
             | Self::Extract { .. }
             | Self::Move { .. }
             | Self::PermanentlyDelete { .. }
-            | Self::Restore { .. } => true,
-            Self::NewFile { .. }
+            | Self::Restore { .. }
+            | Self::SetProperties { .. }
+            | Self::Sync { .. } => true,
+            Self::ContextAction { .. }
+            | Self::Link { .. }
+            | Self::NewFile { .. }
+            | Self::NewFolder { .. }
+            | Self::RemoveFromRecents { .. }
+            | Self::Rename { .. }
+            | Self::SetExecutableAndLaunch { .. }
+            | Self::SetPermissions { .. }
+            | Self::SetTag { .. }
+            | Self::TransformImage { .. } => false,
+        }
+    }
+
+    /// Block devices used by the operation, so that operations on the same drive can be queued.
+    pub fn devices(&self) -> Box<[u64]> {
+        let paths: Vec<&Path> = match self {
+            Self::Compress { paths, to, .. }
+            | Self::Copy { paths, to, .. }
+            | Self::Move { paths, to, .. } => paths
+                .iter()
+                .map(PathBuf::as_path)
+                .chain([to.as_path()])
+                .collect(),
+            Self::Extract { paths, to, .. } => paths
+                .iter()
+                .map(PathBuf::as_path)
+                .chain([to.as_path()])
+                .collect(),
+            Self::Delete { paths } | Self::SetProperties { paths, .. } => {
+                paths.iter().map(PathBuf::as_path).collect()
+            }
+            Self::PermanentlyDelete { paths } => paths.iter().map(PathBuf::as_path).collect(),
+            Self::Sync {
+                source,
+                destination,
+                ..
+            } => vec![source.as_path(), destination.as_path()],
+            _ => Vec::new(),
+        };
+        let mut devices: Vec<u64> = paths.into_iter().filter_map(queue::device).collect();
+        devices.sort_unstable();
+        devices.dedup();
+        devices.into_boxed_slice()
+    }
+
+    /// Remove the sidecars kept to resume this operation, when it will not be resumed.
+    pub fn discard_resume_state(&self) {
+        if let Self::Copy {
+            paths,
+            to,
+            resumable: true,
+            ..
+        }
+        | Self::Move {
+            paths,
+            to,
+            resumable: true,
+            ..
+        } = self
+        {
+            resume::discard_sidecars(paths, to);
+        }
+    }
+
+    /// Whether the operation is saved so that it can be resumed after the app exits. Passwords are
+    /// never saved.
+    pub const fn persistent(&self) -> bool {
+        match self {
+            Self::Compress { password, .. } | Self::Extract { password, .. } => password.is_none(),
+            Self::Copy { .. } | Self::Move { .. } | Self::SetProperties { .. } => true,
+            // Resuming a stale deletion could remove items that were created since. Plans of
+            // synchronizations are stale as well.
+            Self::ContextAction { .. }
+            | Self::Delete { .. }
+            | Self::DeleteTrash { .. }
+            | Self::EmptyTrash
+            | Self::Link { .. }
+            | Self::NewFile { .. }
             | Self::NewFolder { .. }
+            | Self::PermanentlyDelete { .. }
             | Self::RemoveFromRecents { .. }
             | Self::Rename { .. }
+            | Self::Restore { .. }
             | Self::SetExecutableAndLaunch { .. }
-            | Self::SetPermissions { .. } => false,
+            | Self::SetPermissions { .. }
+            | Self::SetTag { .. }
+            | Self::Sync { .. }
+            | Self::TransformImage { .. } => false,
         }
     }
 
@@ -843,8 +1187,51 @@ impl Operation {
                 .await
                 .map_err(wrap_compio_spawn_error)?
             }
-            Self::Copy { paths, to } => {
-                copy_or_move(paths, to, Method::Copy, msg_tx, controller).await
+            Self::ContextAction { name, steps, paths } => {
+                controller
+                    .check()
+                    .await
+                    .map_err(|s| OperationError::from_state(s, &controller))?;
+
+                let controller_c = controller.clone();
+                let output_name = name.clone();
+                let output = compio::runtime::spawn_blocking(move || {
+                    context_action::run_captured(&name, &steps, &paths, &controller_c)
+                })
+                .await
+                .map_err(wrap_compio_spawn_error)??;
+
+                let _ = msg_tx
+                    .lock()
+                    .await
+                    .send(Message::DialogPush(
+                        DialogPage::ContextActionOutput {
+                            name: output_name,
+                            output,
+                        },
+                        None,
+                    ))
+                    .await;
+                Ok(OperationSelection::default())
+            }
+            Self::Copy {
+                paths,
+                to,
+                preserve,
+                verify,
+                resumable,
+            } => {
+                copy_or_move(
+                    paths,
+                    to,
+                    Method::Copy,
+                    preserve,
+                    verify,
+                    resumable,
+                    msg_tx,
+                    controller,
+                )
+                .await
             }
             Self::Delete { paths } => {
                 let total = paths.len();
@@ -1027,16 +1414,62 @@ impl Operation {
                 paths,
                 to,
                 cross_device_copy,
+                preserve,
+                verify,
+                resumable,
             } => {
                 copy_or_move(
                     paths,
                     to,
                     Method::Move { cross_device_copy },
+                    preserve,
+                    verify,
+                    resumable,
                     msg_tx,
                     controller,
                 )
                 .await
             }
+            Self::Link { paths, to, hard } => {
+                // Folders cannot be hard linked, so fail before creating any links
+                if hard
+                    && let Some(path) = paths
+                        .iter()
+                        .find(|path| fs::symlink_metadata(path).is_ok_and(|m| m.is_dir()))
+                {
+                    return Err(OperationError::from_err(
+                        fl!(
+                            "hard-link-folder",
+                            name = path.file_name().unwrap_or_default().to_string_lossy()
+                        ),
+                        &controller,
+                    ));
+                }
+
+                let total = paths.len();
+                let mut selected = Vec::with_capacity(total);
+                for (i, path) in paths.into_iter().enumerate() {
+                    controller
+                        .check()
+                        .await
+                        .map_err(|s| OperationError::from_state(s, &controller))?;
+
+                    controller.set_progress((i as f32) / (total as f32));
+
+                    let link = link_unique_path(&path, &to);
+                    let link_c = link.clone();
+                    tokio::task::spawn_blocking(move || create_link(&path, &link_c, hard))
+                        .await
+                        .map_err(|e| OperationError::from_err(e, &controller))?
+                        .map_err(|e| OperationError::from_err(e, &controller))?;
+                    selected.push(link);
+                }
+
+                Ok(OperationSelection {
+                    ignored: Vec::new(),
+                    selected,
+                })
+            }
             Self::NewFolder { path } => {
                 let controller_clone = controller.clone();
                 compio::runtime::spawn(async move {
@@ -1247,6 +1680,111 @@ impl Operation {
                     selected: vec![path],
                 })
             }
+            Self::SetProperties { paths, change } => {
+                controller
+                    .check()
+                    .await
+                    .map_err(|s| OperationError::from_state(s, &controller))?;
+
+                let paths_clone = paths.clone();
+                let (mut entries, mut errors) =
+                    compio::runtime::spawn_blocking(move || change.entries(&paths_clone))
+                        .await
+                        .map_err(wrap_compio_spawn_error)?;
+
+                // Apply in batches so that progress is updated and cancellation is responsive
+                let total = entries.len();
+                let mut done = 0;
+                while !entries.is_empty() {
+                    controller
+                        .check()
+                        .await
+                        .map_err(|s| OperationError::from_state(s, &controller))?;
+
+                    controller.set_progress((done as f32) / (total as f32));
+
+                    let batch: Vec<PathBuf> = entries.drain(..entries.len().min(256)).collect();
+                    done += batch.len();
+                    let batch_errors = compio::runtime::spawn_blocking(move || {
+                        batch
+                            .into_iter()
+                            .filter_map(|path| change.apply(&path).err().map(|err| (path, err)))
+                            .collect::<Vec<_>>()
+                    })
+                    .await
+                    .map_err(wrap_compio_spawn_error)?;
+                    errors.extend(batch_errors);
+                }
+
+                if !errors.is_empty() {
+                    // Show a limited number of errors, one per line
+                    let limit = 10;
+                    let mut lines: Vec<String> = errors
+                        .iter()
+                        .take(limit)
+                        .map(|(path, err)| format!("{}: {}", path.display(), err))
+                        .collect();
+                    if errors.len() > limit {
+                        lines.push("...".to_string());
+                    }
+                    return Err(OperationError::from_err(lines.join("\n"), &controller));
+                }
+
+                Ok(OperationSelection {
+                    ignored: Vec::new(),
+                    selected: paths,
+                })
+            }
+            Self::SetTag {
+                paths,
+                tag,
+                enabled,
+            } => {
+                controller
+                    .check()
+                    .await
+                    .map_err(|s| OperationError::from_state(s, &controller))?;
+
+                let controller_clone = controller.clone();
+                let paths_clone = paths.clone();
+                compio::runtime::spawn_blocking(move || {
+                    for path in &paths_clone {
+                        crate::tag::set(path, &tag, enabled).map_err(|err| {
+                            OperationError::from_err(
+                                format!("failed to set tag of {}: {}", path.display(), err),
+                                &controller_clone,
+                            )
+                        })?;
+                    }
+                    Ok(())
+                })
+                .await
+                .map_err(wrap_compio_spawn_error)??;
+                Ok(OperationSelection {
+                    ignored: Vec::new(),
+                    selected: paths,
+                })
+            }
+            Self::Sync { actions, .. } => sync::execute(actions, controller).await,
+            Self::TransformImage { path, transform } => {
+                controller
+                    .check()
+                    .await
+                    .map_err(|s| OperationError::from_state(s, &controller))?;
+
+                let controller_clone = controller.clone();
+                let path_clone = path.clone();
+                compio::runtime::spawn_blocking(move || {
+                    transform::transform_image(&path_clone, transform)
+                        .map_err(|e| OperationError::from_err(e, &controller_clone))
+                })
+                .await
+                .map_err(wrap_compio_spawn_error)??;
+                Ok(OperationSelection {
+                    ignored: Vec::new(),
+                    selected: vec![path],
+                })
+            }
         };
 
         controller_clone.set_progress(1.0);
@@ -1282,7 +1820,11 @@ mod tests {
     use test_log::test;
     use tokio::sync;
 
-    use super::{Controller, Operation, OperationError, OperationSelection, ReplaceResult};
+    use super::permissions::{AclEntry, AclQualifier, parse_acl};
+    use super::{
+        Controller, ImageTransform, MergePolicy, ModeChange, Operation, OperationError,
+        OperationErrorType, OperationSelection, PermissionsChange, Preserve, ReplaceResult,
+    };
     use crate::app::test_utils::{
         NAME_LEN, NUM_DIRS, NUM_FILES, NUM_HIDDEN, NUM_NESTED, empty_fs, filter_dirs, filter_files,
         simple_fs,
@@ -1294,6 +1836,15 @@ mod tests {
     pub async fn operation_copy(
         paths: Vec<PathBuf>,
         to: PathBuf,
+    ) -> Result<OperationSelection, OperationError> {
+        operation_copy_preserve(paths, to, Preserve::default()).await
+    }
+
+    /// Wrapper around `[Operation::Copy]` with a metadata preservation policy
+    pub async fn operation_copy_preserve(
+        paths: Vec<PathBuf>,
+        to: PathBuf,
+        preserve: Preserve,
     ) -> Result<OperationSelection, OperationError> {
         let id = fastrand::u64(0..u64::MAX);
         let (tx, mut rx) = mpsc::channel(1);
@@ -1305,6 +1856,9 @@ mod tests {
             Operation::Copy {
                 paths: paths_clone,
                 to: to_clone,
+                preserve,
+                verify: false,
+                resumable: false,
             }
             .perform(&sync::Mutex::new(tx).into(), Controller::default())
             .await
@@ -1500,4 +2054,832 @@ mod tests {
 
         Ok(())
     }
+
+    /// Create a tree with distinct times and permissions, returning the directory and file
+    fn metadata_fixture(path: &std::path::Path) -> io::Result<(PathBuf, PathBuf)> {
+        use std::os::unix::fs::PermissionsExt;
+
+        let dir = path.join("cosmic");
+        fs::create_dir(&dir)?;
+        let file = dir.join("ferris");
+        fs::write(&file, b"crab")?;
+        fs::set_permissions(&file, fs::Permissions::from_mode(0o640))?;
+        filetime::set_file_times(
+            &file,
+            filetime::FileTime::from_unix_time(1_000_000, 0),
+            filetime::FileTime::from_unix_time(2_000_000, 0),
+        )?;
+        // Set directory metadata last, as writing the file changes it
+        fs::set_permissions(&dir, fs::Permissions::from_mode(0o750))?;
+        filetime::set_file_mtime(&dir, filetime::FileTime::from_unix_time(3_000_000, 0))?;
+        Ok((dir, file))
+    }
+
+    #[test(compio::test)]
+    async fn copy_preserves_metadata() -> io::Result<()> {
+        use std::os::unix::fs::PermissionsExt;
+
+        let fs = empty_fs()?;
+        let path = fs.path();
+        let (dir, _file) = metadata_fixture(path)?;
+        let to = path.join("to");
+        fs::create_dir(&to)?;
+
+        operation_copy(vec![dir], to.clone())
+            .await
+            .expect("Copy operation should have succeeded");
+
+        let dir_metadata = fs::metadata(to.join("cosmic"))?;
+        assert_eq!(dir_metadata.permissions().mode() & 0o7777, 0o750);
+        assert_eq!(
+            filetime::FileTime::from_last_modification_time(&dir_metadata).unix_seconds(),
+            3_000_000
+        );
+
+        let file_metadata = fs::metadata(to.join("cosmic").join("ferris"))?;
+        assert_eq!(file_metadata.permissions().mode() & 0o7777, 0o640);
+        assert_eq!(
+            filetime::FileTime::from_last_access_time(&file_metadata).unix_seconds(),
+            1_000_000
+        );
+        assert_eq!(
+            filetime::FileTime::from_last_modification_time(&file_metadata).unix_seconds(),
+            2_000_000
+        );
+
+        Ok(())
+    }
+
+    #[test(compio::test)]
+    async fn copy_without_preserving_metadata() -> io::Result<()> {
+        let fs = empty_fs()?;
+        let path = fs.path();
+        let (dir, file) = metadata_fixture(path)?;
+        let to = path.join("to");
+        fs::create_dir(&to)?;
+        let xattrs = xattr::set(&file, "user.cosmic", b"ferris").is_ok();
+
+        operation_copy_preserve(vec![dir], to.clone(), Preserve::NONE)
+            .await
+            .expect("Copy operation should have succeeded");
+
+        let copied = to.join("cosmic").join("ferris");
+        assert_eq!(fs::read(&copied)?, b"crab");
+        let file_metadata = fs::metadata(&copied)?;
+        assert_ne!(
+            filetime::FileTime::from_last_modification_time(&file_metadata).unix_seconds(),
+            2_000_000
+        );
+        if xattrs {
+            assert!(xattr::get(&copied, "user.cosmic")?.is_none());
+        }
+
+        Ok(())
+    }
+
+    #[test(compio::test)]
+    async fn copy_keeps_sparse_files_sparse() -> io::Result<()> {
+        use std::io::{Seek, SeekFrom, Write};
+        use std::os::unix::fs::MetadataExt;
+
+        const LEN: u64 = 64 * 1024 * 1024;
+
+        let fs = empty_fs()?;
+        let path = fs.path();
+        let from = path.join("sparse");
+        {
+            let mut file = File::create(&from)?;
+            file.set_len(LEN)?;
+            file.seek(SeekFrom::Start(LEN / 2))?;
+            file.write_all(b"crab")?;
+        }
+        let to = path.join("to");
+        fs::create_dir(&to)?;
+
+        operation_copy(vec![from.clone()], to.clone())
+            .await
+            .expect("Copy operation should have succeeded");
+
+        let copied = to.join("sparse");
+        assert_eq!(fs::read(&copied)?, fs::read(&from)?);
+        // Only check allocation if the filesystem supports holes
+        if fs::metadata(&from)?.blocks() * 512 < LEN {
+            assert!(fs::metadata(&copied)?.blocks() * 512 < LEN);
+        }
+
+        Ok(())
+    }
+
+    #[test(compio::test)]
+    async fn copy_reports_progress() -> io::Result<()> {
+        use super::recursive::{Context, Method};
+        use std::cell::Cell;
+        use std::rc::Rc;
+
+        let fs = empty_fs()?;
+        let path = fs.path();
+        let from = path.join("ferris");
+        fs::write(&from, vec![0x42; 1024 * 1024])?;
+        let to = path.join("crab");
+
+        let bytes = Rc::new(Cell::new((0, None)));
+        let bytes_clone = bytes.clone();
+        let mut context = Context::new(Controller::default()).on_progress(move |_op, progress| {
+            bytes_clone.set((progress.current_bytes, progress.total_bytes));
+        });
+        context
+            .recursive_copy_or_move([(from.clone(), to.clone())], Method::Copy)
+            .await
+            .expect("Copy should have succeeded");
+
+        assert_eq!(bytes.get(), (1024 * 1024, Some(1024 * 1024)));
+        assert_eq!(fs::read(&to)?, fs::read(&from)?);
+
+        Ok(())
+    }
+
+    /// Copy `ferris` to `crab` after a previous attempt left `partial` in the sidecar.
+    async fn resume_copy(path: &std::path::Path, partial: &[u8]) -> io::Result<Vec<u8>> {
+        use super::recursive::{Context, Method};
+
+        let from = path.join("ferris");
+        let to = path.join("crab");
+        let sidecar = super::resume::partial_path(&to);
+        fs::write(&sidecar, partial)?;
+
+        let mut context = Context::new(Controller::default()).resumable(Some(0));
+        context
+            .recursive_copy_or_move([(from, to.clone())], Method::Copy)
+            .await
+            .expect("Copy should have succeeded");
+
+        assert!(!sidecar.exists());
+        fs::read(&to)
+    }
+
+    fn resume_fixture(path: &std::path::Path) -> io::Result<Vec<u8>> {
+        let contents: Vec<u8> = (0..4 * 1024 * 1024).map(|i| (i % 251) as u8).collect();
+        fs::write(path.join("ferris"), &contents)?;
+        Ok(contents)
+    }
+
+    #[test(compio::test)]
+    async fn copy_resumes_from_partial_file() -> io::Result<()> {
+        let fs = empty_fs()?;
+        let contents = resume_fixture(fs.path())?;
+
+        // Only the start and end of the copied prefix are verified, so a change in the middle
+        // shows that it was not copied again
+        let mut partial = contents[..3 * 1024 * 1024].to_vec();
+        partial[1536 * 1024] ^= 0xFF;
+        let copied = resume_copy(fs.path(), &partial).await?;
+
+        assert_eq!(copied.len(), contents.len());
+        assert_eq!(copied[..3 * 1024 * 1024], partial[..]);
+        assert_eq!(copied[3 * 1024 * 1024..], contents[3 * 1024 * 1024..]);
+
+        Ok(())
+    }
+
+    #[test(compio::test)]
+    async fn copy_restarts_mismatched_partial_file() -> io::Result<()> {
+        let fs = empty_fs()?;
+        let contents = resume_fixture(fs.path())?;
+
+        let mut partial = contents[..3 * 1024 * 1024].to_vec();
+        *partial.last_mut().unwrap() ^= 0xFF;
+        let copied = resume_copy(fs.path(), &partial).await?;
+
+        assert_eq!(copied, contents);
+
+        Ok(())
+    }
+
+    #[test]
+    fn partial_file_does_not_replace_new_target() -> io::Result<()> {
+        use super::resume::Partial;
+
+        let fs = empty_fs()?;
+        let from = fs.path().join("ferris");
+        let to = fs.path().join("crab");
+        fs::write(&from, b"ferris")?;
+        let partial = Partial::new(&to, &fs::metadata(&from)?);
+        partial.prepare(&from)?;
+        fs::write(&partial.path, b"ferris")?;
+        fs::write(&to, b"crab")?;
+
+        let err = partial.finish(&to).unwrap_err();
+        assert_eq!(err.kind(), io::ErrorKind::AlreadyExists);
+        assert_eq!(fs::read(&to)?, b"crab");
+
+        Ok(())
+    }
+
+    #[test(compio::test)]
+    async fn copy_verifies_checksums() -> io::Result<()> {
+        use super::recursive::{Context, Method, verify_copy};
+
+        let fs = empty_fs()?;
+        let path = fs.path();
+        let from = path.join("ferris");
+        fs::write(&from, b"DCIM/100CANON/IMG_0001.CR3")?;
+        let to = path.join("crab");
+
+        let mut context = Context::new(Controller::default()).verify(true);
+        context
+            .recursive_copy_or_move([(from.clone(), to.clone())], Method::Copy)
+            .await
+            .expect("Copy should have been verified");
+        assert!(verify_copy(&from, &to)?);
+
+        fs::write(&to, b"DCIM/100CANON/IMG_0001.CR2")?;
+        assert!(!verify_copy(&from, &to)?);
+
+        Ok(())
+    }
+
+    /// Copy or move `from` to `to` with verification, replacing `from` once it has been opened so
+    /// that the copy no longer matches it.
+    async fn verify_mismatch(
+        from: &std::path::Path,
+        to: &std::path::Path,
+        method: super::recursive::Method,
+    ) -> Result<OperationSelection, OperationError> {
+        use super::recursive::Context;
+        use std::cell::Cell;
+
+        fs::write(from, b"DCIM/100CANON/IMG_0001.CR3").unwrap();
+        let replaced = Cell::new(false);
+        let from_clone = from.to_path_buf();
+        let mut context = Context::new(Controller::default())
+            .verify(true)
+            .on_progress(move |op, progress| {
+                if op.from == from_clone && progress.total_bytes.is_some() && !replaced.get() {
+                    // The open file keeps its contents
+                    fs::remove_file(&from_clone).unwrap();
+                    fs::write(&from_clone, b"DCIM/100CANON/IMG_0001.CR2").unwrap();
+                    replaced.set(true);
+                }
+            });
+        context
+            .recursive_copy_or_move([(from.to_path_buf(), to.to_path_buf())], method)
+            .await?;
+        Ok(context.op_sel)
+    }
+
+    #[test(compio::test)]
+    async fn copy_reports_checksum_mismatch() -> io::Result<()> {
+        use super::recursive::Method;
+
+        let fs = empty_fs()?;
+        let from = fs.path().join("ferris");
+        let to = fs.path().join("crab");
+
+        let err = verify_mismatch(&from, &to, Method::Copy)
+            .await
+            .expect_err("Copy should have failed verification");
+        let OperationErrorType::VerificationFailed(files) = err.kind else {
+            panic!("Copy should have reported the mismatched file");
+        };
+        assert_eq!(files, [(from, to)]);
+
+        Ok(())
+    }
+
+    #[cfg(target_os = "linux")]
+    #[test(compio::test)]
+    async fn move_keeps_source_when_checksum_mismatches() -> io::Result<()> {
+        use super::recursive::Method;
+        use std::os::unix::fs::MetadataExt;
+
+        // Files are only copied, and so verified, when moving to another filesystem
+        let fs = empty_fs()?;
+        let other_fs = tempfile::tempdir_in("/dev/shm")?;
+        assert_ne!(
+            fs::metadata(fs.path())?.dev(),
+            fs::metadata(other_fs.path())?.dev()
+        );
+        let from = fs.path().join("ferris");
+        let to = other_fs.path().join("crab");
+
+        let err = verify_mismatch(
+            &from,
+            &to,
+            Method::Move {
+                cross_device_copy: false,
+            },
+        )
+        .await
+        .expect_err("Move should have failed verification");
+        assert!(matches!(
+            err.kind,
+            OperationErrorType::VerificationFailed(_)
+        ));
+        assert!(from.exists());
+        assert_eq!(fs::read(&to)?, b"DCIM/100CANON/IMG_0001.CR3");
+
+        Ok(())
+    }
+
+    #[test(compio::test)]
+    async fn copy_is_throttled() -> io::Result<()> {
+        use super::recursive::{Context, Method};
+        use std::time::{Duration, Instant};
+
+        let fs = empty_fs()?;
+        let contents = resume_fixture(fs.path())?;
+        let from = fs.path().join("ferris");
+        let to = fs.path().join("crab");
+
+        let controller = Controller::default();
+        controller.set_bandwidth_limit(Some(8 * 1024 * 1024));
+        let start = Instant::now();
+        let mut context = Context::new(controller);
+        context
+            .recursive_copy_or_move([(from, to.clone())], Method::Copy)
+            .await
+            .expect("Copy should have succeeded");
+
+        // 4 MiB at 8 MiB/s, less the first chunk that starts the throttling window
+        assert!(start.elapsed() >= Duration::from_millis(350));
+        assert_eq!(fs::read(&to)?, contents);
+
+        Ok(())
+    }
+
+    /// Copy the folder `cosmic` into `target`, which already contains a folder of that name.
+    async fn merge_copy(
+        path: &std::path::Path,
+        result: ReplaceResult,
+    ) -> io::Result<OperationSelection> {
+        use super::recursive::{Context, Method};
+
+        let mut context = Context::new(Controller::default()).on_replace(move |op, _count| {
+            let result = if op.from.is_dir() {
+                result
+            } else {
+                ReplaceResult::Cancel
+            };
+            Box::pin(async move { result })
+        });
+        context
+            .recursive_copy_or_move(
+                [(path.join("cosmic"), path.join("target").join("cosmic"))],
+                Method::Copy,
+            )
+            .await
+            .expect("Copy should have succeeded");
+        Ok(context.op_sel)
+    }
+
+    fn merge_fixture(path: &std::path::Path) -> io::Result<()> {
+        let from = path.join("cosmic");
+        let to = path.join("target").join("cosmic");
+        fs::create_dir_all(&from)?;
+        fs::create_dir_all(&to)?;
+        fs::write(from.join("newer"), b"new")?;
+        fs::write(from.join("older"), b"old")?;
+        fs::write(to.join("newer"), b"existing")?;
+        fs::write(to.join("older"), b"existing")?;
+        let old = std::time::SystemTime::now() - std::time::Duration::from_secs(3600);
+        File::options()
+            .write(true)
+            .open(to.join("newer"))?
+            .set_modified(old)?;
+        File::options()
+            .write(true)
+            .open(from.join("older"))?
+            .set_modified(old)?;
+        Ok(())
+    }
+
+    #[test(compio::test)]
+    async fn copy_merges_folders_replacing_newer_files() -> io::Result<()> {
+        let fs = empty_fs()?;
+        let path = fs.path();
+        merge_fixture(path)?;
+
+        merge_copy(path, ReplaceResult::Merge(MergePolicy::ReplaceIfNewer)).await?;
+
+        let to = path.join("target").join("cosmic");
+        assert_eq!(fs::read(to.join("newer"))?, b"new");
+        assert_eq!(fs::read(to.join("older"))?, b"existing");
+
+        Ok(())
+    }
+
+    #[test(compio::test)]
+    async fn copy_replacing_folder_replaces_its_files() -> io::Result<()> {
+        let fs = empty_fs()?;
+        let path = fs.path();
+        merge_fixture(path)?;
+
+        merge_copy(path, ReplaceResult::Replace(false)).await?;
+
+        let to = path.join("target").join("cosmic");
+        assert_eq!(fs::read(to.join("newer"))?, b"new");
+        assert_eq!(fs::read(to.join("older"))?, b"old");
+
+        Ok(())
+    }
+
+    #[test(compio::test)]
+    async fn copy_skips_folders() -> io::Result<()> {
+        let fs = empty_fs()?;
+        let path = fs.path();
+        merge_fixture(path)?;
+
+        let op_sel = merge_copy(path, ReplaceResult::Skip(false)).await?;
+
+        let to = path.join("target").join("cosmic");
+        assert_eq!(fs::read(to.join("newer"))?, b"existing");
+        assert!(op_sel.selected.is_empty());
+
+        Ok(())
+    }
+
+    #[test(compio::test)]
+    async fn copy_keeps_both_folders() -> io::Result<()> {
+        let fs = empty_fs()?;
+        let path = fs.path();
+        merge_fixture(path)?;
+
+        merge_copy(path, ReplaceResult::KeepBoth).await?;
+
+        let target = path.join("target");
+        assert_eq!(fs::read(target.join("cosmic").join("newer"))?, b"existing");
+        let copy = target.join(format!("cosmic ({} 1)", fl!("copy_noun")));
+        assert_eq!(fs::read(copy.join("newer"))?, b"new");
+
+        Ok(())
+    }
+
+    #[test(compio::test)]
+    async fn copy_preserves_tags() -> io::Result<()> {
+        let fs = empty_fs()?;
+        let path = fs.path();
+
+        let dir_path = path.join("cosmic");
+        fs::create_dir(&dir_path)?;
+        let file_path = path.join("ferris");
+        File::create(&file_path)?;
+        let tags = vec!["work".to_string()];
+        match crate::tag::write(&file_path, &tags) {
+            Ok(()) => {}
+            // Extended attributes are not available on every filesystem
+            Err(err) if err.kind() == io::ErrorKind::Unsupported => return Ok(()),
+            Err(err) => return Err(err),
+        }
+
+        operation_copy(vec![file_path], dir_path.clone())
+            .await
+            .expect("Copy operation should have succeeded");
+
+        assert_eq!(crate::tag::read(&dir_path.join("ferris")), tags);
+
+        Ok(())
+    }
+
+    async fn operation_transform(path: PathBuf, transform: ImageTransform) {
+        let (tx, _rx) = mpsc::channel(1);
+        Operation::TransformImage {
+            path: path.clone(),
+            transform,
+        }
+        .perform(&sync::Mutex::new(tx).into(), Controller::default())
+        .await
+        .expect("Transform operation should have succeeded");
+    }
+
+    fn exif_orientation(path: &std::path::Path) -> Option<u32> {
+        let file = File::open(path).ok()?;
+        let exif = exif::Reader::new()
+            .read_from_container(&mut io::BufReader::new(file))
+            .ok()?;
+        exif.get_field(exif::Tag::Orientation, exif::In::PRIMARY)?
+            .value
+            .get_uint(0)
+    }
+
+    #[test]
+    fn image_transform_orientation_round_trips() {
+        for orientation in 1..=8 {
+            let mut rotated = orientation;
+            for _ in 0..4 {
+                rotated = ImageTransform::RotateRight.apply_orientation(rotated);
+            }
+            assert_eq!(rotated, orientation);
+            assert_eq!(
+                ImageTransform::RotateLeft
+                    .apply_orientation(ImageTransform::RotateRight.apply_orientation(orientation)),
+                orientation
+            );
+            for flip in [ImageTransform::FlipHorizontal, ImageTransform::FlipVertical] {
+                assert_eq!(
+                    flip.apply_orientation(flip.apply_orientation(orientation)),
+                    orientation
+                );
+            }
+        }
+        assert_eq!(ImageTransform::RotateRight.apply_orientation(1), 6);
+        assert_eq!(ImageTransform::FlipVertical.apply_orientation(1), 4);
+    }
+
+    #[test(compio::test)]
+    async fn transform_png_rotates_pixels() -> io::Result<()> {
+        let fs = empty_fs()?;
+        let path = fs.path().join("image.png");
+        let mut image = image::RgbImage::new(2, 1);
+        image.put_pixel(0, 0, image::Rgb([255, 0, 0]));
+        image.save(&path).map_err(io::Error::other)?;
+
+        operation_transform(path.clone(), ImageTransform::RotateRight).await;
+
+        let rotated = image::open(&path).map_err(io::Error::other)?.to_rgb8();
+        assert_eq!(rotated.dimensions(), (1, 2));
+        // The left pixel is now at the top
+        assert_eq!(rotated.get_pixel(0, 0), &image::Rgb([255, 0, 0]));
+
+        Ok(())
+    }
+
+    #[test(compio::test)]
+    async fn transform_jpeg_sets_orientation() -> io::Result<()> {
+        let fs = empty_fs()?;
+        let path = fs.path().join("image.jpg");
+        image::RgbImage::new(4, 2)
+            .save(&path)
+            .map_err(io::Error::other)?;
+        let original = image::open(&path).map_err(io::Error::other)?.to_rgb8();
+
+        operation_transform(path.clone(), ImageTransform::RotateRight).await;
+        assert_eq!(exif_orientation(&path), Some(6));
+        assert_eq!(
+            crate::large_image::oriented_dimensions(&path).map_err(io::Error::other)?,
+            (2, 4)
+        );
+        operation_transform(path.clone(), ImageTransform::FlipHorizontal).await;
+        assert_eq!(exif_orientation(&path), Some(5));
+
+        // Image data is not re-encoded
+        let transformed = image::open(&path).map_err(io::Error::other)?.to_rgb8();
+        assert_eq!(transformed, original);
+
+        Ok(())
+    }
+
+    #[test(compio::test)]
+    async fn transform_keeps_links_and_attributes() -> io::Result<()> {
+        let fs = empty_fs()?;
+        let path = fs.path().join("image.jpg");
+        image::RgbImage::new(4, 2)
+            .save(&path)
+            .map_err(io::Error::other)?;
+        let tagged = xattr::set(&path, "user.xdg.tags", b"holiday").is_ok();
+        let link = fs.path().join("link.jpg");
+        std::os::unix::fs::symlink(&path, &link)?;
+
+        operation_transform(link.clone(), ImageTransform::RotateRight).await;
+
+        assert!(fs::symlink_metadata(&link)?.is_symlink());
+        assert_eq!(exif_orientation(&path), Some(6));
+        if tagged {
+            assert_eq!(
+                xattr::get(&path, "user.xdg.tags")?.as_deref(),
+                Some(&b"holiday"[..])
+            );
+        }
+
+        Ok(())
+    }
+
+    #[test(compio::test)]
+    async fn transform_jpeg_adds_orientation_to_exif() -> io::Result<()> {
+        let fs = empty_fs()?;
+        let path = fs.path().join("image.jpg");
+        image::RgbImage::new(4, 2)
+            .save(&path)
+            .map_err(io::Error::other)?;
+
+        // EXIF with a make and software tag around where the orientation tag belongs
+        let mut tiff = b"MM\0\x2a\0\0\0\x08\0\x02".to_vec();
+        for (tag, value) in [(0x010Fu16, b"Abc\0"), (0x0131, b"Xyz\0")] {
+            tiff.extend_from_slice(&tag.to_be_bytes());
+            tiff.extend_from_slice(&[0, 2, 0, 0, 0, 4]);
+            tiff.extend_from_slice(value);
+        }
+        tiff.extend_from_slice(&[0, 0, 0, 0]);
+        let mut segment = vec![0xFF, 0xE1];
+        segment.extend_from_slice(&(8 + tiff.len() as u16).to_be_bytes());
+        segment.extend_from_slice(b"Exif\0\0");
+        segment.extend_from_slice(&tiff);
+        let mut data = fs::read(&path)?;
+        data.splice(2..2, segment);
+        fs::write(&path, &data)?;
+
+        operation_transform(path.clone(), ImageTransform::RotateRight).await;
+        assert_eq!(exif_orientation(&path), Some(6));
+
+        let file = File::open(&path)?;
+        let exif = exif::Reader::new()
+            .read_from_container(&mut io::BufReader::new(file))
+            .map_err(io::Error::other)?;
+        for (tag, value) in [(exif::Tag::Make, "Abc"), (exif::Tag::Software, "Xyz")] {
+            let field = exif.get_field(tag, exif::In::PRIMARY).expect("tag kept");
+            assert!(
+                matches!(&field.value, exif::Value::Ascii(values) if values[0] == value.as_bytes())
+            );
+        }
+
+        Ok(())
+    }
+
+    fn mode(path: &std::path::Path) -> io::Result<u32> {
+        use std::os::unix::fs::PermissionsExt;
+        Ok(fs::symlink_metadata(path)?.permissions().mode() & 0o7777)
+    }
+
+    #[test(compio::test)]
+    async fn set_properties_applies_separate_masks() -> io::Result<()> {
+        let fs = empty_fs()?;
+        let dir = fs.path().join("cosmic");
+        let nested = dir.join("nested");
+        fs::create_dir_all(&nested)?;
+        let file = nested.join("ferris");
+        File::create(&file)?;
+        fs::set_permissions(&dir, std::os::unix::fs::PermissionsExt::from_mode(0o1755))?;
+        fs::set_permissions(&file, std::os::unix::fs::PermissionsExt::from_mode(0o644))?;
+
+        let change = PermissionsChange {
+            files: ModeChange {
+                mode: 0o600,
+                mask: 0o077,
+            },
+            dirs: ModeChange::default().with_part(0, 0),
+            recursive: true,
+            ..Default::default()
+        };
+        let (tx, _rx) = mpsc::channel(1);
+        Operation::SetProperties {
+            paths: vec![dir.clone()],
+            change,
+        }
+        .perform(&sync::Mutex::new(tx).into(), Controller::default())
+        .await
+        .expect("Set properties operation should have succeeded");
+
+        // Special bits and bits outside of the masks are kept
+        assert_eq!(mode(&dir)?, 0o1750);
+        assert_eq!(mode(&nested)?, 0o750);
+        assert_eq!(mode(&file)?, 0o600);
+
+        Ok(())
+    }
+
+    #[test(compio::test)]
+    async fn set_properties_only_applies_toggled_bits() -> io::Result<()> {
+        let fs = empty_fs()?;
+        let dir = fs.path().join("cosmic");
+        fs::create_dir(&dir)?;
+        let first = dir.join("first");
+        let second = dir.join("second");
+        File::create(&first)?;
+        File::create(&second)?;
+        fs::set_permissions(&first, std::os::unix::fs::PermissionsExt::from_mode(0o644))?;
+        fs::set_permissions(&second, std::os::unix::fs::PermissionsExt::from_mode(0o755))?;
+
+        let dir_mode = mode(&dir)?;
+
+        // The owner of the top item can read and write, the user removes write access
+        let files = ModeChange::default().with_toggled_part(6, 0o4, Some(0o6));
+        assert_eq!(files.shown_part(6, Some(0o6)), Some(0o4));
+        assert_eq!(files.shown_part(3, Some(0o4)), Some(0o4));
+        let (tx, _rx) = mpsc::channel(1);
+        Operation::SetProperties {
+            paths: vec![dir.clone()],
+            change: PermissionsChange {
+                files,
+                recursive: true,
+                ..Default::default()
+            },
+        }
+        .perform(&sync::Mutex::new(tx).into(), Controller::default())
+        .await
+        .expect("Set properties operation should have succeeded");
+
+        // Execute bits and other access are kept
+        assert_eq!(mode(&first)?, 0o444);
+        assert_eq!(mode(&second)?, 0o555);
+        assert_eq!(mode(&dir)?, dir_mode);
+
+        Ok(())
+    }
+
+    #[test(compio::test)]
+    async fn set_properties_cancelled() -> io::Result<()> {
+        let fs = empty_fs()?;
+        let file = fs.path().join("ferris");
+        File::create(&file)?;
+        fs::set_permissions(&file, std::os::unix::fs::PermissionsExt::from_mode(0o644))?;
+
+        let controller = Controller::default();
+        controller.cancel();
+        let (tx, _rx) = mpsc::channel(1);
+        let result = Operation::SetProperties {
+            paths: vec![file.clone()],
+            change: PermissionsChange {
+                files: ModeChange::default().with_part(6, 0o7),
+                ..Default::default()
+            },
+        }
+        .perform(&sync::Mutex::new(tx).into(), controller)
+        .await;
+
+        assert!(result.is_err(), "Cancelled operation should fail");
+        assert_eq!(mode(&file)?, 0o644);
+
+        Ok(())
+    }
+
+    #[test(compio::test)]
+    async fn link_creates_symbolic_and_hard_links() -> io::Result<()> {
+        let fs = empty_fs()?;
+        let path = fs.path();
+        let file = path.join("ferris");
+        fs::write(&file, "crab")?;
+        let to = path.join("links");
+        fs::create_dir(&to)?;
+
+        for hard in [false, true] {
+            let (tx, _rx) = mpsc::channel(1);
+            Operation::Link {
+                paths: vec![file.clone()],
+                to: to.clone(),
+                hard,
+            }
+            .perform(&sync::Mutex::new(tx).into(), Controller::default())
+            .await
+            .expect("Link operation should have succeeded");
+        }
+
+        // The second link gets a unique name
+        let link = to.join("ferris");
+        assert_eq!(fs::read_link(&link)?, file);
+        let hard_link = to.join(format!("ferris ({} 1)", fl!("link_noun")));
+        assert!(!hard_link.is_symlink());
+        assert_eq!(fs::read_to_string(&hard_link)?, "crab");
+        fs::write(&file, "ferris")?;
+        assert_eq!(fs::read_to_string(&hard_link)?, "ferris");
+
+        // Folders cannot be hard linked, and nothing is linked when one is included
+        let dir = path.join("dir");
+        fs::create_dir(&dir)?;
+        let (tx, _rx) = mpsc::channel(1);
+        let result = Operation::Link {
+            paths: vec![file.clone(), dir],
+            to: to.clone(),
+            hard: true,
+        }
+        .perform(&sync::Mutex::new(tx).into(), Controller::default())
+        .await;
+        assert!(result.is_err());
+        assert!(!to.join(format!("ferris ({} 2)", fl!("link_noun"))).exists());
+
+        Ok(())
+    }
+
+    #[test]
+    fn parse_acl_entries() {
+        let mut value = 2u32.to_le_bytes().to_vec();
+        for (tag, perm, id) in [(0x01u16, 6u16, u32::MAX), (0x02, 4, 0), (0x20, 0, u32::MAX)] {
+            value.extend(tag.to_le_bytes());
+            value.extend(perm.to_le_bytes());
+            value.extend(id.to_le_bytes());
+        }
+
+        let entries = parse_acl(&value).expect("ACL should be valid");
+        assert_eq!(
+            entries,
+            [
+                AclEntry {
+                    qualifier: AclQualifier::UserObj,
+                    perm: 6
+                },
+                AclEntry {
+                    qualifier: AclQualifier::User(0),
+                    perm: 4
+                },
+                AclEntry {
+                    qualifier: AclQualifier::Other,
+                    perm: 0
+                },
+            ]
+        );
+        assert_eq!(entries[0].to_string(), "user::rw-");
+        assert_eq!(entries[2].to_string(), "other::---");
+
+        // Wrong version or truncated entries
+        assert!(parse_acl(&1u32.to_le_bytes()).is_none());
+        assert!(parse_acl(&value[..value.len() - 1]).is_none());
+    }
 }
@@ -0,0 +1,248 @@
+// Copyright 2026 System76 <info@system76.com>
+// SPDX-License-Identifier: GPL-3.0-only
+
+//! Ownership and permission changes, optionally applied to the contents of directories, and
+//! read-only access to POSIX ACLs.
+
+use serde::{Deserialize, Serialize};
+use std::fmt;
+use std::fs;
+use std::io;
+use std::os::unix::fs::{MetadataExt, PermissionsExt};
+use std::path::{Path, PathBuf};
+use walkdir::WalkDir;
+
+pub const MODE_SETUID: u32 = 0o4000;
+pub const MODE_SETGID: u32 = 0o2000;
+pub const MODE_STICKY: u32 = 0o1000;
+
+const ACL_ACCESS: &str = "system.posix_acl_access";
+const ACL_DEFAULT: &str = "system.posix_acl_default";
+const ACL_VERSION: u32 = 2;
+// Entry tags from linux/posix_acl.h
+const ACL_USER_OBJ: u16 = 0x01;
+const ACL_USER: u16 = 0x02;
+const ACL_GROUP_OBJ: u16 = 0x04;
+const ACL_GROUP: u16 = 0x08;
+const ACL_MASK: u16 = 0x10;
+const ACL_OTHER: u16 = 0x20;
+
+/// Permission bits to change, leaving bits outside of `mask` as they are.
+#[derive(Clone, Copy, Debug, Default, Deserialize, Eq, Hash, PartialEq, Serialize)]
+pub struct ModeChange {
+    pub mode: u32,
+    pub mask: u32,
+}
+
+impl ModeChange {
+    pub const fn apply(self, mode: u32) -> u32 {
+        (mode & !self.mask) | (self.mode & self.mask)
+    }
+
+    /// Access bits at `shift`, if they are all being changed.
+    pub const fn part(self, shift: u32) -> Option<u32> {
+        if (self.mask >> shift) & 0o7 == 0o7 {
+            Some((self.mode >> shift) & 0o7)
+        } else {
+            None
+        }
+    }
+
+    pub const fn with_part(self, shift: u32, bits: u32) -> Self {
+        Self {
+            mode: (self.mode & !(0o7 << shift)) | ((bits & 0o7) << shift),
+            mask: self.mask | (0o7 << shift),
+        }
+    }
+
+    /// Change the access bits at `shift` from `current` to `bits`. Only the bits that differ are
+    /// masked, so other items keep the rest of theirs. All of them are masked without a current
+    /// value.
+    pub const fn with_toggled_part(self, shift: u32, bits: u32, current: Option<u32>) -> Self {
+        let toggled = match current {
+            Some(current) => (bits ^ current) & 0o7,
+            None => 0o7,
+        };
+        Self {
+            mode: (self.mode & !(0o7 << shift)) | ((bits & 0o7) << shift),
+            mask: (self.mask & !(0o7 << shift)) | (toggled << shift),
+        }
+    }
+
+    /// Access bits at `shift` after applying the change to `current`, which are only known if
+    /// there is a current value or the change replaces all of them.
+    pub const fn shown_part(self, shift: u32, current: Option<u32>) -> Option<u32> {
+        match current {
+            Some(current) => Some((self.apply(current << shift) >> shift) & 0o7),
+            None => self.part(shift),
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, Default, Deserialize, Eq, Hash, PartialEq, Serialize)]
+pub struct PermissionsChange {
+    pub owner: Option<u32>,
+    pub group: Option<u32>,
+    /// Applied to everything that is not a directory
+    pub files: ModeChange,
+    pub dirs: ModeChange,
+    /// Also apply to the contents of directories
+    pub recursive: bool,
+}
+
+impl PermissionsChange {
+    /// Apply to a single path. Symbolic links are not followed.
+    pub fn apply(&self, path: &Path) -> io::Result<()> {
+        let mut metadata = fs::symlink_metadata(path)?;
+
+        // Only change ownership when it differs, as it may clear setuid and setgid bits
+        let owner = self.owner.filter(|&uid| uid != metadata.uid());
+        let group = self.group.filter(|&gid| gid != metadata.gid());
+        if owner.is_some() || group.is_some() {
+            std::os::unix::fs::lchown(path, owner, group)?;
+            metadata = fs::symlink_metadata(path)?;
+        }
+
+        // Permissions of symbolic links are never used
+        if metadata.is_symlink() {
+            return Ok(());
+        }
+
+        let change = if metadata.is_dir() {
+            self.dirs
+        } else {
+            self.files
+        };
+        let mode = metadata.mode() & 0o7777;
+        let new_mode = change.apply(mode);
+        if new_mode != mode {
+            fs::set_permissions(path, fs::Permissions::from_mode(new_mode))?;
+        }
+
+        Ok(())
+    }
+
+    /// List every path to apply to, along with errors from reading directories.
+    pub fn entries(&self, paths: &[PathBuf]) -> (Vec<PathBuf>, Vec<(PathBuf, io::Error)>) {
+        let mut entries = Vec::with_capacity(paths.len());
+        let mut errors = Vec::new();
+        for path in paths {
+            if !self.recursive {
+                entries.push(path.clone());
+                continue;
+            }
+            for entry_res in WalkDir::new(path) {
+                match entry_res {
+                    Ok(entry) => entries.push(entry.into_path()),
+                    Err(err) => {
+                        let path = err.path().unwrap_or(path).to_path_buf();
+                        errors.push((path, err.into()));
+                    }
+                }
+            }
+        }
+        (entries, errors)
+    }
+}
+
+/// Names of the setuid, setgid, and sticky bits that are set.
+pub fn special_bits(mode: u32) -> Vec<&'static str> {
+    [
+        (MODE_SETUID, "setuid"),
+        (MODE_SETGID, "setgid"),
+        (MODE_STICKY, "sticky"),
+    ]
+    .into_iter()
+    .filter(|(bit, _)| mode & bit != 0)
+    .map(|(_, name)| name)
+    .collect()
+}
+
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum AclQualifier {
+    UserObj,
+    User(u32),
+    GroupObj,
+    Group(u32),
+    Mask,
+    Other,
+}
+
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct AclEntry {
+    pub qualifier: AclQualifier,
+    pub perm: u32,
+}
+
+impl fmt::Display for AclEntry {
+    /// Formatted like the output of getfacl, such as `user:alice:rw-`
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let user_name = |uid| {
+            uzers::get_user_by_uid(uid)
+                .and_then(|user| user.name().to_str().map(ToOwned::to_owned))
+                .unwrap_or_else(|| uid.to_string())
+        };
+        let group_name = |gid| {
+            uzers::get_group_by_gid(gid)
+                .and_then(|group| group.name().to_str().map(ToOwned::to_owned))
+                .unwrap_or_else(|| gid.to_string())
+        };
+        match self.qualifier {
+            AclQualifier::UserObj => write!(f, "user::")?,
+            AclQualifier::User(uid) => write!(f, "user:{}:", user_name(uid))?,
+            AclQualifier::GroupObj => write!(f, "group::")?,
+            AclQualifier::Group(gid) => write!(f, "group:{}:", group_name(gid))?,
+            AclQualifier::Mask => write!(f, "mask::")?,
+            AclQualifier::Other => write!(f, "other::")?,
+        }
+        for (bit, c) in [(0o4, 'r'), (0o2, 'w'), (0o1, 'x')] {
+            write!(f, "{}", if self.perm & bit != 0 { c } else { '-' })?;
+        }
+        Ok(())
+    }
+}
+
+/// Parse the extended attribute representation of an ACL.
+pub fn parse_acl(value: &[u8]) -> Option<Vec<AclEntry>> {
+    let (header, entries) = value.split_first_chunk::<4>()?;
+    if u32::from_le_bytes(*header) != ACL_VERSION || entries.len() % 8 != 0 {
+        return None;
+    }
+    entries
+        .chunks_exact(8)
+        .map(|entry| {
+            let tag = u16::from_le_bytes([entry[0], entry[1]]);
+            let perm = u16::from_le_bytes([entry[2], entry[3]]).into();
+            let id = u32::from_le_bytes([entry[4], entry[5], entry[6], entry[7]]);
+            let qualifier = match tag {
+                ACL_USER_OBJ => AclQualifier::UserObj,
+                ACL_USER => AclQualifier::User(id),
+                ACL_GROUP_OBJ => AclQualifier::GroupObj,
+                ACL_GROUP => AclQualifier::Group(id),
+                ACL_MASK => AclQualifier::Mask,
+                ACL_OTHER => AclQualifier::Other,
+                _ => return None,
+            };
+            Some(AclEntry { qualifier, perm })
+        })
+        .collect()
+}
+
+/// Read the access ACL of a path, or the default ACL given to new items in a directory. This is
+/// empty if the path only has regular permissions.
+pub fn read_acl(path: &Path, default: bool) -> Vec<AclEntry> {
+    let name = if default { ACL_DEFAULT } else { ACL_ACCESS };
+    match xattr::get(path, name) {
+        Ok(Some(value)) => parse_acl(&value).unwrap_or_else(|| {
+            log::warn!("invalid ACL {} for {}", name, path.display());
+            Vec::new()
+        }),
+        Ok(None) => Vec::new(),
+        Err(err) => {
+            if err.kind() != io::ErrorKind::Unsupported {
+                log::debug!("failed to read ACL of {}: {}", path.display(), err);
+            }
+            Vec::new()
+        }
+    }
+}
@@ -0,0 +1,213 @@
+// Copyright 2026 System76 <info@system76.com>
+// SPDX-License-Identifier: GPL-3.0-only
+
+//! Serializes operations that use the same block devices, so that large transfers to the same
+//! drive run one after another instead of competing with each other.
+
+use rustc_hash::FxHashMap;
+use std::fs;
+use std::path::Path;
+use tokio::sync::oneshot;
+
+/// Whether a process with this ID is still running.
+pub fn process_running(pid: u32) -> bool {
+    #[cfg(unix)]
+    {
+        let Ok(pid) = libc::pid_t::try_from(pid) else {
+            return false;
+        };
+        pid > 0
+            && (unsafe { libc::kill(pid, 0) } == 0
+                || std::io::Error::last_os_error().raw_os_error() == Some(libc::EPERM))
+    }
+    #[cfg(not(unix))]
+    {
+        let _ = pid;
+        false
+    }
+}
+
+/// Device of a path, or of its closest existing ancestor if it does not exist yet.
+pub fn device(path: &Path) -> Option<u64> {
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::MetadataExt;
+        path.ancestors()
+            .find_map(|ancestor| fs::metadata(ancestor).ok())
+            .map(|metadata| metadata.dev())
+    }
+    #[cfg(not(unix))]
+    {
+        let _ = path;
+        None
+    }
+}
+
+struct Queued {
+    id: u64,
+    devices: Box<[u64]>,
+    start_tx: oneshot::Sender<()>,
+}
+
+#[derive(Default)]
+pub struct OperationQueue {
+    /// Devices used by running operations
+    running: FxHashMap<u64, Box<[u64]>>,
+    /// Operations waiting for their devices, in the order they will start
+    queued: Vec<Queued>,
+}
+
+impl OperationQueue {
+    fn conflicts(
+        &self,
+        devices: &[u64],
+        queued_before: usize,
+        is_paused: &impl Fn(u64) -> bool,
+    ) -> bool {
+        let overlaps = |other: &[u64]| devices.iter().any(|device| other.contains(device));
+        self.running.values().any(|other| overlaps(other))
+            || self.queued[..queued_before]
+                .iter()
+                .any(|queued| !is_paused(queued.id) && overlaps(&queued.devices))
+    }
+
+    /// Add an operation, returning a receiver to wait on before starting if its devices are busy.
+    /// Operations without devices are never queued.
+    pub fn push(
+        &mut self,
+        id: u64,
+        devices: Box<[u64]>,
+        is_paused: impl Fn(u64) -> bool,
+    ) -> Option<oneshot::Receiver<()>> {
+        if self.conflicts(&devices, self.queued.len(), &is_paused) {
+            let (start_tx, start_rx) = oneshot::channel();
+            self.queued.push(Queued {
+                id,
+                devices,
+                start_tx,
+            });
+            Some(start_rx)
+        } else {
+            self.running.insert(id, devices);
+            None
+        }
+    }
+
+    /// Remove a finished operation, freeing its devices.
+    pub fn finish(&mut self, id: u64) {
+        self.running.remove(&id);
+        self.queued.retain(|queued| queued.id != id);
+    }
+
+    /// Remove an operation that has not started, which drops its start signal. Returns false if
+    /// the operation was not queued.
+    pub fn cancel(&mut self, id: u64) -> bool {
+        let len = self.queued.len();
+        self.queued.retain(|queued| queued.id != id);
+        self.queued.len() != len
+    }
+
+    pub fn is_queued(&self, id: u64) -> bool {
+        self.queued.iter().any(|queued| queued.id == id)
+    }
+
+    /// Position in the queue and the length of the queue.
+    pub fn position(&self, id: u64) -> Option<(usize, usize)> {
+        self.queued
+            .iter()
+            .position(|queued| queued.id == id)
+            .map(|index| (index, self.queued.len()))
+    }
+
+    /// Move a queued operation one place earlier or later.
+    pub fn reorder(&mut self, id: u64, earlier: bool) {
+        if let Some(index) = self.queued.iter().position(|queued| queued.id == id) {
+            let other = if earlier {
+                index.checked_sub(1)
+            } else {
+                Some(index + 1).filter(|&other| other < self.queued.len())
+            };
+            if let Some(other) = other {
+                self.queued.swap(index, other);
+            }
+        }
+    }
+
+    /// Start queued operations whose devices are free, in order. Paused operations are skipped and
+    /// do not hold back the ones after them.
+    pub fn start_ready(&mut self, is_paused: impl Fn(u64) -> bool) {
+        let mut index = 0;
+        while index < self.queued.len() {
+            let queued = &self.queued[index];
+            if is_paused(queued.id) || self.conflicts(&queued.devices, index, &is_paused) {
+                index += 1;
+                continue;
+            }
+            let queued = self.queued.remove(index);
+            if queued.start_tx.send(()).is_ok() {
+                self.running.insert(queued.id, queued.devices);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::OperationQueue;
+
+    #[test]
+    fn serializes_per_device() {
+        let mut queue = OperationQueue::default();
+        let not_paused = |_| false;
+        assert!(queue.push(0, Box::new([1]), not_paused).is_none());
+        let mut second = queue
+            .push(1, Box::new([1, 2]), not_paused)
+            .expect("should be queued");
+        // Different devices run concurrently
+        assert!(queue.push(2, Box::new([3]), not_paused).is_none());
+        // Operations without devices are never queued
+        assert!(queue.push(3, Box::new([]), not_paused).is_none());
+        // Waits behind the queued operation that uses the same device
+        let mut fourth = queue
+            .push(4, Box::new([2]), not_paused)
+            .expect("should be queued");
+
+        queue.start_ready(not_paused);
+        assert!(second.try_recv().is_err());
+
+        queue.finish(0);
+        queue.start_ready(not_paused);
+        assert!(second.try_recv().is_ok());
+        assert!(fourth.try_recv().is_err());
+
+        queue.finish(1);
+        queue.start_ready(not_paused);
+        assert!(fourth.try_recv().is_ok());
+    }
+
+    #[test]
+    fn reorder_and_pause() {
+        let mut queue = OperationQueue::default();
+        let not_paused = |_| false;
+        assert!(queue.push(0, Box::new([1]), not_paused).is_none());
+        let mut first = queue
+            .push(1, Box::new([1]), not_paused)
+            .expect("should be queued");
+        let mut second = queue
+            .push(2, Box::new([1]), not_paused)
+            .expect("should be queued");
+
+        queue.reorder(2, true);
+        assert_eq!(queue.position(2), Some((0, 2)));
+
+        // A paused operation does not hold back the next one
+        queue.finish(0);
+        queue.start_ready(|id| id == 2);
+        assert!(second.try_recv().is_err());
+        assert!(first.try_recv().is_ok());
+
+        assert!(queue.cancel(2));
+        assert!(!queue.is_queued(2));
+        assert!(second.try_recv().is_err());
+    }
+}
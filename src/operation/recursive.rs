@@ -1,8 +1,12 @@
 // Copyright 2023 System76 <info@system76.com>
 // SPDX-License-Identifier: GPL-3.0-only
 
-use super::{Controller, OperationSelection, ReplaceResult, copy_unique_path};
-use crate::operation::{OperationError, sync_to_disk};
+#[cfg(target_os = "linux")]
+use super::fast_copy;
+use super::resume::{self, Partial};
+use super::{Controller, MergePolicy, OperationSelection, ReplaceResult, copy_unique_path};
+use crate::operation::{OperationError, OperationErrorType, sync_to_disk};
+use crate::tab;
 use anyhow::Context as AnyhowContext;
 use compio::BufResult;
 use compio::buf::{IntoInner, IoBuf};
@@ -11,20 +15,147 @@ use compio::driver::op::AsyncifyFd;
 use compio::io::{AsyncReadAt, AsyncWriteAt};
 use cosmic::iced::futures;
 use futures::{FutureExt, StreamExt};
+use serde::{Deserialize, Serialize};
 use std::cell::Cell;
 use std::error::Error;
 use std::fs;
 use std::future::Future;
+use std::io;
 use std::ops::ControlFlow;
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 use std::pin::Pin;
 use std::rc::Rc;
-use std::time::Instant;
+use std::time::{Duration, Instant};
 use walkdir::WalkDir;
 
 #[cfg(feature = "gvfs")]
 use gio::prelude::FileExtManual;
 
+/// Metadata to preserve when copying, in addition to the contents.
+#[derive(Clone, Copy, Debug, Deserialize, Eq, Hash, PartialEq, Serialize)]
+pub struct Preserve {
+    /// Modification and access times
+    pub times: bool,
+    /// Permission bits, including setuid, setgid, and sticky bits
+    pub permissions: bool,
+    /// Owner and group, which is only possible when running with privilege
+    pub ownership: bool,
+    /// Extended attributes in the user namespace
+    pub xattrs: bool,
+}
+
+impl Preserve {
+    pub const ALL: Self = Self {
+        times: true,
+        permissions: true,
+        ownership: true,
+        xattrs: true,
+    };
+
+    pub const NONE: Self = Self {
+        times: false,
+        permissions: false,
+        ownership: false,
+        xattrs: false,
+    };
+}
+
+impl Default for Preserve {
+    fn default() -> Self {
+        Self::ALL
+    }
+}
+
+fn set_times(path: &Path, metadata: &fs::Metadata) {
+    let atime = filetime::FileTime::from_last_access_time(metadata);
+    let mtime = filetime::FileTime::from_last_modification_time(metadata);
+    let result = if metadata.is_symlink() {
+        filetime::set_symlink_file_times(path, atime, mtime)
+    } else {
+        filetime::set_file_times(path, atime, mtime)
+    };
+    if let Err(why) = result
+        && !matches!(why.kind(), std::io::ErrorKind::Unsupported)
+    {
+        tracing::warn!(?why, "failed to set times for {}", path.display());
+    }
+}
+
+/// Changing the owner requires privilege, so this is skipped for other users. This must happen
+/// before setting permissions, as changing the owner clears the setuid and setgid bits.
+pub(super) fn set_ownership(path: &Path, metadata: &fs::Metadata) {
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::MetadataExt;
+
+        if uzers::get_effective_uid() != 0 {
+            return;
+        }
+        if let Err(why) =
+            std::os::unix::fs::lchown(path, Some(metadata.uid()), Some(metadata.gid()))
+        {
+            tracing::warn!(?why, "failed to set ownership for {}", path.display());
+        }
+    }
+}
+
+fn set_permissions(path: &Path, metadata: &fs::Metadata) {
+    if let Err(why) = fs::set_permissions(path, metadata.permissions()) {
+        // This error is not propagated upwards as some filesystems do not support setting permissions
+        if !matches!(why.kind(), std::io::ErrorKind::Unsupported) {
+            tracing::warn!(?why, "failed to set permissions for {}", path.display());
+        }
+    }
+}
+
+/// Copy extended attributes in the user namespace, such as tags, which are lost when only the
+/// contents are copied. Other namespaces are reserved for the system.
+pub(super) fn copy_xattrs(from: &Path, to: &Path) {
+    let names = match xattr::list(from) {
+        Ok(names) => names,
+        Err(why) => {
+            if !matches!(why.kind(), std::io::ErrorKind::Unsupported) {
+                tracing::warn!(
+                    ?why,
+                    "failed to list extended attributes of {}",
+                    from.display()
+                );
+            }
+            return;
+        }
+    };
+    for name in names.filter(|name| name.as_encoded_bytes().starts_with(b"user.")) {
+        let value = match xattr::get(from, &name) {
+            Ok(Some(value)) => value,
+            Ok(None) => continue,
+            Err(why) => {
+                tracing::warn!(?why, "failed to read {:?} of {}", name, from.display());
+                continue;
+            }
+        };
+        if let Err(why) = xattr::set(to, &name, &value) {
+            if !matches!(why.kind(), std::io::ErrorKind::Unsupported) {
+                tracing::warn!(?why, "failed to set {:?} for {}", name, to.display());
+            }
+        }
+    }
+}
+
+/// Compare the checksums of a copied file and its source. The copy is flushed and dropped from the
+/// page cache first, so that it is read back from the device rather than from memory.
+pub fn verify_copy(from: &Path, to: &Path) -> io::Result<bool> {
+    let file = fs::File::open(to)?;
+    file.sync_all()?;
+    #[cfg(target_os = "linux")]
+    {
+        use std::os::fd::AsRawFd;
+        // SAFETY: the descriptor is kept open for the duration of the call
+        unsafe { libc::posix_fadvise(file.as_raw_fd(), 0, 0, libc::POSIX_FADV_DONTNEED) };
+    }
+    drop(file);
+    Ok(tab::file_checksums(from)?.sha256 == tab::file_checksums(to)?.sha256)
+}
+
 #[derive(thiserror::Error, Debug)]
 pub enum GioCopyError {
     #[error("controller state")]
@@ -47,6 +178,46 @@ pub struct Context {
     pub(crate) op_sel: OperationSelection,
     replace_result_opt: Option<ReplaceResult>,
     remaining_conflicts: usize,
+    preserve: Preserve,
+    /// Files of at least this size are copied through a sidecar so that the copy can be resumed
+    resumable: Option<u64>,
+    /// Compare the checksums of copied files with their sources
+    verify: bool,
+    /// Sources and copies with checksums that did not match
+    verify_failures: Vec<(PathBuf, PathBuf)>,
+    /// Created directories with the metadata of their source, restored once their contents have
+    /// been written
+    created_dirs: Vec<(PathBuf, fs::Metadata)>,
+    /// Existing folders that conflicting folders are merged into, and how conflicts inside them
+    /// are resolved
+    merged_dirs: Vec<(PathBuf, MergePolicy)>,
+    /// Source folders that were skipped along with their contents
+    skipped_dirs: Vec<PathBuf>,
+    /// Source folders that are copied to a unique name, with the new target
+    renamed_dirs: Vec<(PathBuf, PathBuf)>,
+    throttle: Throttle,
+}
+
+/// Bytes written since the start of the current throttling window.
+#[derive(Debug)]
+struct Throttle {
+    start: Instant,
+    bytes: u64,
+    limit: Option<u64>,
+}
+
+impl Throttle {
+    /// Longest window that bytes are averaged over, so that a slow start or a pause does not allow
+    /// a burst afterwards.
+    const WINDOW: Duration = Duration::from_secs(2);
+    /// Longest sleep before checking whether the limit changed or the operation was cancelled
+    const MAX_SLEEP: Duration = Duration::from_millis(100);
+
+    fn reset(&mut self, limit: Option<u64>) {
+        self.start = Instant::now();
+        self.bytes = 0;
+        self.limit = limit;
+    }
 }
 
 pub trait OnProgress: Fn(&Op, &Progress) + 'static {}
@@ -72,6 +243,47 @@ impl Context {
             op_sel: OperationSelection::default(),
             replace_result_opt: None,
             remaining_conflicts: 0,
+            preserve: Preserve::default(),
+            resumable: None,
+            verify: false,
+            verify_failures: Vec::new(),
+            created_dirs: Vec::new(),
+            merged_dirs: Vec::new(),
+            skipped_dirs: Vec::new(),
+            renamed_dirs: Vec::new(),
+            throttle: Throttle {
+                start: Instant::now(),
+                bytes: 0,
+                limit: None,
+            },
+        }
+    }
+
+    /// Wait after writing `count` bytes until the transfer is within the bandwidth limit of the
+    /// controller.
+    async fn throttle(&mut self, count: u64) {
+        self.throttle.bytes += count;
+        loop {
+            let limit = self.controller.bandwidth_limit();
+            if limit != self.throttle.limit {
+                self.throttle.reset(limit);
+                return;
+            }
+            let Some(limit) = limit else {
+                return;
+            };
+            let expected = Duration::from_secs_f64(self.throttle.bytes as f64 / limit as f64);
+            let elapsed = self.throttle.start.elapsed();
+            if elapsed >= expected {
+                if elapsed >= Throttle::WINDOW {
+                    self.throttle.reset(Some(limit));
+                }
+                return;
+            }
+            if self.controller.is_cancelled() || self.controller.is_failed() {
+                return;
+            }
+            compio::time::sleep((expected - elapsed).min(Throttle::MAX_SLEEP)).await;
         }
     }
 
@@ -81,6 +293,8 @@ impl Context {
         method: Method,
     ) -> Result<bool, OperationError> {
         let mut ops = Vec::new();
+        // Size of the file copied or moved by each op
+        let mut sizes = Vec::new();
         let mut cleanup_ops = Vec::new();
         let mut written_files = Vec::new();
         let mut target_dirs = std::collections::HashSet::new();
@@ -112,6 +326,11 @@ impl Context {
                     )
                 })?;
                 let file_type = entry.file_type();
+                let size = if file_type.is_file() {
+                    entry.metadata().map_or(0, |metadata| metadata.len())
+                } else {
+                    0
+                };
                 let from = entry.into_path();
                 let kind = if file_type.is_dir() {
                     OpKind::Mkdir
@@ -172,6 +391,7 @@ impl Context {
                     target_dirs.insert(parent.to_path_buf());
                 }
                 ops.push(op);
+                sizes.push(size);
             }
 
             self.op_sel.ignored.push(from_parent);
@@ -180,6 +400,7 @@ impl Context {
         // Add cleanup ops after standard ops, in reverse
         cleanup_ops.reverse();
         ops.append(&mut cleanup_ops);
+        sizes.resize(ops.len(), 0);
 
         // Count potential conflicts (files that would need replacement)
         self.remaining_conflicts = ops
@@ -193,17 +414,26 @@ impl Context {
             .count();
 
         let total_ops = ops.len();
-        for (current_ops, mut op) in ops.into_iter().enumerate() {
+        let files_total = ops.iter().filter(|op| op.is_file_transfer()).count();
+        let bytes_total = sizes.iter().sum();
+        let mut files_done = 0;
+        let mut bytes_done = 0;
+        for (current_ops, (mut op, size)) in ops.into_iter().zip(sizes).enumerate() {
             self.controller
                 .check()
                 .await
                 .map_err(|s| OperationError::from_state(s, &self.controller))?;
 
+            self.redirect(&mut op);
             let progress = Progress {
                 current_ops,
                 total_ops,
                 current_bytes: 0,
                 total_bytes: None,
+                files_done,
+                files_total,
+                bytes_done,
+                bytes_total,
             };
             (self.on_progress)(&op, &progress);
             if op.run(self, progress).await.map_err(|err| {
@@ -218,6 +448,10 @@ impl Context {
                     &self.controller,
                 )
             })? {
+                if op.is_file_transfer() {
+                    files_done += 1;
+                    bytes_done += size;
+                }
                 if matches!(
                     op.kind,
                     OpKind::Copy
@@ -228,7 +462,7 @@ impl Context {
                     written_files.push(op.to.clone());
                 }
                 // The from path is ignored in the operation selection if it is a top level item
-                if self.op_sel.ignored.contains(&op.from) {
+                if self.op_sel.ignored.contains(&op.from) && !op.skipped.normal.get() {
                     // So add the to path to the selection
                     self.op_sel.selected.push(op.to);
                 }
@@ -238,9 +472,27 @@ impl Context {
             }
         }
 
+        // Restore directories last, as writing their contents changes their modification time and
+        // they may not be writable. Children are restored before their parents.
+        for (to, metadata) in self.created_dirs.drain(..).rev() {
+            if self.preserve.permissions {
+                set_permissions(&to, &metadata);
+            }
+            if self.preserve.times {
+                set_times(&to, &metadata);
+            }
+        }
+
         // Flush files to disk
         sync_to_disk(written_files, target_dirs).await;
 
+        if !self.verify_failures.is_empty() {
+            return Err(OperationError::from_kind(
+                OperationErrorType::VerificationFailed(std::mem::take(&mut self.verify_failures)),
+                &self.controller,
+            ));
+        }
+
         Ok(true)
     }
 
@@ -254,33 +506,139 @@ impl Context {
         self
     }
 
+    pub const fn preserve(mut self, preserve: Preserve) -> Self {
+        self.preserve = preserve;
+        self
+    }
+
+    /// Copy files of at least `min_size` bytes so that an interrupted copy can be resumed.
+    pub const fn resumable(mut self, min_size: Option<u64>) -> Self {
+        self.resumable = min_size;
+        self
+    }
+
+    /// Verify the checksums of copied files. Sources of moved files are only removed once their
+    /// copies are verified.
+    pub const fn verify(mut self, verify: bool) -> Self {
+        self.verify = verify;
+        self
+    }
+
+    /// Apply the resolution of folder conflicts to the contents of the folders.
+    fn redirect(&self, op: &mut Op) {
+        if self
+            .skipped_dirs
+            .iter()
+            .any(|dir| op.from.starts_with(dir) && op.from != *dir)
+        {
+            op.skipped.normal.set(true);
+        }
+        for (from_dir, to_dir) in &self.renamed_dirs {
+            if op.from != *from_dir
+                && let Ok(relative) = op.from.strip_prefix(from_dir)
+            {
+                op.to = to_dir.join(relative);
+            }
+        }
+    }
+
     async fn replace(&mut self, op: &Op) -> Result<ControlFlow<bool, PathBuf>, Box<dyn Error>> {
+        // Conflicts inside merged folders use the policy of the innermost folder
+        let merge_result = self
+            .merged_dirs
+            .iter()
+            .filter(|(dir, _)| op.to.starts_with(dir))
+            .max_by_key(|(dir, _)| dir.components().count())
+            .and_then(|(_, policy)| policy.replace_result());
+        let replace_result = match merge_result.or(self.replace_result_opt) {
+            Some(result) => result,
+            None => (self.on_replace)(op, self.remaining_conflicts).await,
+        };
+        if replace_result.apply_to_all() {
+            self.replace_result_opt = Some(replace_result);
+        }
+
+        let replace = match replace_result {
+            ReplaceResult::Replace(_) => true,
+            ReplaceResult::ReplaceIfNewer(_) => {
+                let modified = |path: &Path| fs::metadata(path).and_then(|m| m.modified()).ok();
+                modified(&op.from) > modified(&op.to)
+            }
+            ReplaceResult::ReplaceIfSizeDiffers(_) => {
+                let len = |path: &Path| fs::metadata(path).map(|m| m.len()).ok();
+                len(&op.from) != len(&op.to)
+            }
+            ReplaceResult::RenameExisting(_) => {
+                let Some(to_parent) = op.to.parent() else {
+                    return Err(format!("failed to get parent of {}", op.to.display()).into());
+                };
+                let renamed = copy_unique_path(&op.to, to_parent);
+                compio::fs::rename(&op.to, &renamed).await?;
+                return Ok(ControlFlow::Continue(op.to.clone()));
+            }
+            ReplaceResult::KeepBoth => {
+                return match op.to.parent() {
+                    Some(to_parent) => {
+                        Ok(ControlFlow::Continue(copy_unique_path(&op.from, to_parent)))
+                    }
+                    None => Err(format!("failed to get parent of {}", op.to.display()).into()),
+                };
+            }
+            // Files are not merged
+            ReplaceResult::Skip(_) | ReplaceResult::Merge(_) => false,
+            ReplaceResult::Cancel => return Ok(ControlFlow::Break(false)),
+        };
+
+        if replace {
+            compio::fs::remove_file(&op.to).await?;
+            Ok(ControlFlow::Continue(op.to.clone()))
+        } else {
+            op.skipped.normal.set(true);
+            Ok(ControlFlow::Break(true))
+        }
+    }
+
+    /// Resolve a conflict between a copied folder and an existing folder.
+    async fn merge(&mut self, op: &Op) -> Result<ControlFlow<bool, PathBuf>, Box<dyn Error>> {
         let replace_result = match self.replace_result_opt {
             Some(result) => result,
             None => (self.on_replace)(op, self.remaining_conflicts).await,
         };
+        if replace_result.apply_to_all() {
+            self.replace_result_opt = Some(replace_result);
+        }
 
-        match replace_result {
-            ReplaceResult::Replace(apply_to_all) => {
-                if apply_to_all {
-                    self.replace_result_opt = Some(replace_result);
-                }
-                compio::fs::remove_file(&op.to).await?;
-                Ok(ControlFlow::Continue(op.to.clone()))
+        // Replacing a folder replaces the conflicting files inside it
+        let policy = match replace_result {
+            ReplaceResult::Merge(policy) => policy,
+            ReplaceResult::Replace(_) => MergePolicy::Replace,
+            ReplaceResult::ReplaceIfNewer(_) => MergePolicy::ReplaceIfNewer,
+            ReplaceResult::ReplaceIfSizeDiffers(_) => MergePolicy::ReplaceIfSizeDiffers,
+            ReplaceResult::RenameExisting(_) => {
+                let Some(to_parent) = op.to.parent() else {
+                    return Err(format!("failed to get parent of {}", op.to.display()).into());
+                };
+                let renamed = copy_unique_path(&op.to, to_parent);
+                compio::fs::rename(&op.to, &renamed).await?;
+                return Ok(ControlFlow::Continue(op.to.clone()));
             }
-            ReplaceResult::KeepBoth => match op.to.parent() {
-                Some(to_parent) => Ok(ControlFlow::Continue(copy_unique_path(&op.from, to_parent))),
-                None => Err(format!("failed to get parent of {}", op.to.display()).into()),
-            },
-            ReplaceResult::Skip(apply_to_all) => {
-                if apply_to_all {
-                    self.replace_result_opt = Some(replace_result);
-                }
+            ReplaceResult::KeepBoth => {
+                let Some(to_parent) = op.to.parent() else {
+                    return Err(format!("failed to get parent of {}", op.to.display()).into());
+                };
+                let to = copy_unique_path(&op.from, to_parent);
+                self.renamed_dirs.push((op.from.clone(), to.clone()));
+                return Ok(ControlFlow::Continue(to));
+            }
+            ReplaceResult::Skip(_) => {
                 op.skipped.normal.set(true);
-                Ok(ControlFlow::Break(true))
+                self.skipped_dirs.push(op.from.clone());
+                return Ok(ControlFlow::Break(true));
             }
-            ReplaceResult::Cancel => Ok(ControlFlow::Break(false)),
-        }
+            ReplaceResult::Cancel => return Ok(ControlFlow::Break(false)),
+        };
+        self.merged_dirs.push((op.to.clone(), policy));
+        Ok(ControlFlow::Continue(op.to.clone()))
     }
 }
 
@@ -290,6 +648,12 @@ pub struct Progress {
     pub total_ops: usize,
     pub current_bytes: u64,
     pub total_bytes: Option<u64>,
+    /// Files copied or moved so far
+    pub files_done: usize,
+    pub files_total: usize,
+    /// Bytes of the files copied or moved so far, not including the current file
+    pub bytes_done: u64,
+    pub bytes_total: u64,
 }
 
 #[derive(Debug)]
@@ -320,6 +684,11 @@ pub struct Op {
 }
 
 impl Op {
+    /// Whether this op copies or moves a file, as counted in [`Progress`]
+    pub const fn is_file_transfer(&self) -> bool {
+        matches!(self.kind, OpKind::Copy | OpKind::Move { .. })
+    }
+
     fn move_cleanup_op(&self) -> Option<Self> {
         let kind = match self.kind {
             OpKind::Copy | OpKind::Move { .. } | OpKind::Symlink { .. } => OpKind::Remove,
@@ -343,10 +712,21 @@ impl Op {
         match self.kind {
             OpKind::Copy => {
                 crate::operation::actively_writing_add(self.to.clone());
-                let result = self.copy(ctx, progress).await;
+                let mut created = false;
+                let result = self.copy(ctx, progress, &mut created).await;
+                if ctx.verify && matches!(result, Ok(true)) && !self.skipped.normal.get() {
+                    self.verify(ctx).await;
+                }
 
                 if result.is_err() {
-                    _ = compio::fs::remove_file(&self.to).await;
+                    // `to` may belong to another process if it appeared while copying
+                    if created {
+                        _ = compio::fs::remove_file(&self.to).await;
+                    }
+                    // Cancelled copies are not resumed
+                    if ctx.resumable.is_some() && ctx.controller.is_cancelled() {
+                        _ = compio::fs::remove_file(resume::partial_path(&self.to)).await;
+                    }
                 }
 
                 crate::operation::actively_writing_remove(&self.to);
@@ -399,13 +779,43 @@ impl Op {
                 }
             }
             OpKind::Mkdir => {
+                // Ask how to resolve conflicts of the folders being copied. Folders inside them
+                // are merged.
+                if self.to.is_dir() && ctx.op_sel.ignored.contains(&self.from) {
+                    match ctx.merge(self).await? {
+                        ControlFlow::Continue(to) => {
+                            self.to = to;
+                        }
+                        ControlFlow::Break(ret) => {
+                            return Ok(ret);
+                        }
+                    }
+                }
+                // Metadata of directories that are merged into is left as is
+                let created = !self.to.is_dir();
                 compio::fs::create_dir_all(&self.to).await?;
+                if created && let Ok(metadata) = fs::symlink_metadata(&self.from) {
+                    if ctx.preserve.ownership {
+                        set_ownership(&self.to, &metadata);
+                    }
+                    if ctx.preserve.xattrs {
+                        copy_xattrs(&self.from, &self.to);
+                    }
+                    ctx.created_dirs.push((self.to.clone(), metadata));
+                }
             }
             OpKind::Remove => {
                 compio::fs::remove_file(&self.from).await?;
             }
             OpKind::Rmdir => {
-                compio::fs::remove_dir(&self.from).await?;
+                // Directories still containing sources of unverified copies are kept
+                if !ctx
+                    .verify_failures
+                    .iter()
+                    .any(|(from, _)| from.starts_with(&self.from))
+                {
+                    compio::fs::remove_dir(&self.from).await?;
+                }
             }
             OpKind::Symlink { ref target } => {
                 // Remove `to` if overwriting and it is an existing file
@@ -431,15 +841,49 @@ impl Op {
                         std::os::windows::fs::symlink_file(target, &self.to)?;
                     }
                 }
+                if let Ok(metadata) = fs::symlink_metadata(&self.from) {
+                    if ctx.preserve.ownership {
+                        set_ownership(&self.to, &metadata);
+                    }
+                    if ctx.preserve.times {
+                        set_times(&self.to, &metadata);
+                    }
+                }
             }
         }
         Ok(true)
     }
 
+    /// Verify a copied file, keeping its source if the copy does not match.
+    async fn verify(&self, ctx: &mut Context) {
+        let (from, to) = (self.from.clone(), self.to.clone());
+        let verified = match compio::runtime::spawn_blocking(move || verify_copy(&from, &to)).await
+        {
+            Ok(Ok(verified)) => verified,
+            Ok(Err(why)) => {
+                tracing::error!(?why, "failed to verify {}", self.to.display());
+                false
+            }
+            Err(_) => false,
+        };
+        if !verified {
+            tracing::error!(
+                "{} does not match {} after copying",
+                self.to.display(),
+                self.from.display()
+            );
+            self.skipped.cleanup.set(true);
+            ctx.verify_failures
+                .push((self.from.clone(), self.to.clone()));
+        }
+    }
+
+    /// Copy `from` to `to`, setting `created` once `to` has been created by this copy.
     async fn copy(
         &mut self,
         ctx: &mut Context,
         mut progress: Progress,
+        created: &mut bool,
     ) -> Result<bool, Box<dyn Error>> {
         // Remove `to` if overwriting and it is an existing file
         if self.to.is_file() {
@@ -453,7 +897,7 @@ impl Op {
             }
         }
 
-        let (from_file_open_result, metadata, to_file_open_result) = cosmic::iced::futures::join!(
+        let (from_file_open_result, metadata) = cosmic::iced::futures::join!(
             async {
                 compio::fs::OpenOptions::new()
                     .read(true)
@@ -462,28 +906,74 @@ impl Op {
                     .with_context(|| format!("failed to open {} for reading", self.from.display(),))
             },
             async { compio::fs::metadata(&self.from).await.ok() },
+        );
+
+        let from_file = from_file_open_result?;
+
+        // Large files are written to a sidecar, continuing from any previous attempt
+        let partial = metadata
+            .as_ref()
+            .filter(|metadata| {
+                ctx.resumable
+                    .is_some_and(|min_size| metadata.len() >= min_size)
+            })
+            .map(|metadata| Partial::new(&self.to, metadata));
+        let mut start = 0;
+        if let Some(partial) = partial.as_ref() {
+            let (from, prepare_partial) = (self.from.clone(), partial.clone());
+            start = match compio::runtime::spawn_blocking(move || prepare_partial.prepare(&from))
+                .await
+            {
+                Ok(Ok(start)) => start,
+                Ok(Err(why)) => {
+                    _ = from_file.close().await;
+                    return Err(why).with_context(|| {
+                        format!("failed to prepare {} for writing", partial.path.display())
+                    })?;
+                }
+                Err(_) => {
+                    _ = from_file.close().await;
+                    return Err("prepare task failed".into());
+                }
+            };
+        }
+
+        let to_file_open_result = match partial.as_ref() {
+            Some(partial) => {
+                compio::fs::OpenOptions::new()
+                    .write(true)
+                    .open(&partial.path)
+                    .await
+            }
             // This is atomic and ensures `to` is not created by any other process
-            async {
+            None => {
                 compio::fs::OpenOptions::new()
                     .create_new(true)
                     .write(true)
                     .open(&self.to)
                     .await
             }
-        );
-
-        let from_file = from_file_open_result?;
+        };
 
         let mut to_file = match to_file_open_result {
-            Ok(file) => file,
+            Ok(file) => {
+                *created = partial.is_none();
+                file
+            }
             #[cfg(not(feature = "gvfs"))]
             Err(why) => {
                 _ = from_file.close().await;
-                return Err(why).with_context(|| format!("failed to open {} for writing", self.to.display())).map_err(Into::into);
+                return Err(why)
+                    .with_context(|| format!("failed to open {} for writing", self.to.display()))
+                    .map_err(Into::into);
             }
             #[cfg(feature = "gvfs")]
             Err(_why) => {
                 _ = from_file.close().await;
+                if let Some(partial) = partial.as_ref() {
+                    partial.discard();
+                }
+                *created = true;
                 return self
                     .gio_file_copy(ctx, progress)
                     .await
@@ -492,25 +982,51 @@ impl Op {
             }
         };
         progress.total_bytes = metadata.as_ref().map(|m| m.len());
+        progress.current_bytes = start;
         (ctx.on_progress)(self, &progress);
 
-        if let Some(metadata) = metadata.as_ref()
-            && let Err(why) = to_file.set_permissions(metadata.permissions()).await
-        {
-            // This error is not propagated upwards as some filesystems do not support setting permissions
-            if !matches!(why.kind(), std::io::ErrorKind::Unsupported) {
-                tracing::warn!(?why, "failed to set permissions for {}", self.to.display(),);
+        if let Some(metadata) = metadata.as_ref() {
+            if ctx.preserve.ownership {
+                set_ownership(
+                    partial.as_ref().map_or(&self.to, |partial| &partial.path),
+                    metadata,
+                );
+            }
+            // Sidecars stay writable so that they can be opened again when resuming
+            if ctx.preserve.permissions
+                && partial.is_none()
+                && let Err(why) = to_file.set_permissions(metadata.permissions()).await
+            {
+                // This error is not propagated upwards as some filesystems do not support setting permissions
+                if !matches!(why.kind(), std::io::ErrorKind::Unsupported) {
+                    tracing::warn!(?why, "failed to set permissions for {}", self.to.display(),);
+                }
             }
         }
 
+        #[cfg(target_os = "linux")]
+        let copied = match self
+            .fast_copy(ctx, &from_file, &to_file, &mut progress, partial.as_ref())
+            .await
+        {
+            Ok(copied) => copied,
+            Err(why) => {
+                _ = futures::future::join(from_file.close(), to_file.close()).await;
+                return Err(why);
+            }
+        };
+        #[cfg(not(target_os = "linux"))]
+        let copied = false;
+
         // Prevent spamming the progress callbacks.
         let mut last_progress_update = Instant::now();
         // io_uring/IOCP requires transferring ownership of the buffer to the kernel.
         let mut buf_in = std::mem::take(&mut ctx.buf);
         // Track where the current read/write position is at.
-        let mut pos = 0;
+        let mut pos = progress.current_bytes;
 
-        loop {
+        // The buffered copy is used when the kernel is unable to copy between these files
+        while !copied {
             let BufResult(result, buf_out) = from_file.read_at(buf_in, pos).await;
 
             let count = match result {
@@ -536,6 +1052,10 @@ impl Op {
                 if let std::io::ErrorKind::Unsupported = why.kind() {
                     ctx.buf = buf_out;
                     _ = futures::future::join(from_file.close(), to_file.close()).await;
+                    if let Some(partial) = partial.as_ref() {
+                        partial.discard();
+                    }
+                    *created = true;
                     return self
                         .gio_file_copy(ctx, progress)
                         .await
@@ -551,12 +1071,16 @@ impl Op {
 
             progress.current_bytes += count as u64;
             pos += count as u64;
+            ctx.throttle(count as u64).await;
 
             // Avoid spamming progress messages too early.
             let current = Instant::now();
             if current.duration_since(last_progress_update).as_millis() > 49 {
                 last_progress_update = current;
                 (ctx.on_progress)(self, &progress);
+                if let Some(partial) = partial.as_ref() {
+                    partial.record(pos);
+                }
 
                 // Also check if the progress was cancelled.
                 if let Err(state) = ctx.controller.check().await {
@@ -576,7 +1100,9 @@ impl Op {
 
         ctx.buf = buf_in;
 
-        if let Some(metadata) = metadata.as_ref() {
+        if let Some(metadata) = metadata.as_ref()
+            && ctx.preserve.times
+        {
             let mut times = fs::FileTimes::new();
             if let Ok(time) = metadata.modified() {
                 times = times.set_modified(time);
@@ -602,6 +1128,143 @@ impl Op {
 
         _ = to_file.close().await;
 
+        if let Some(partial) = partial.as_ref() {
+            partial
+                .finish(&self.to)
+                .with_context(|| format!("failed to move {} into place", partial.path.display()))?;
+            if let Some(metadata) = metadata.as_ref()
+                && ctx.preserve.permissions
+            {
+                set_permissions(&self.to, metadata);
+            }
+        }
+
+        if ctx.preserve.xattrs {
+            copy_xattrs(&self.from, &self.to);
+        }
+
+        Ok(true)
+    }
+
+    /// Copy using a reflink, or with `copy_file_range` while skipping holes. Returns false without
+    /// writing anything if neither is supported for these files. Copying starts from the current
+    /// bytes of `progress` when resuming into `partial`.
+    #[cfg(target_os = "linux")]
+    async fn fast_copy(
+        &self,
+        ctx: &mut Context,
+        from_file: &compio::fs::File,
+        to_file: &compio::fs::File,
+        progress: &mut Progress,
+        partial: Option<&Partial>,
+    ) -> Result<bool, Box<dyn Error>> {
+        use std::os::fd::AsRawFd;
+
+        // Larger ranges are copied in chunks so that progress is reported and cancellation works
+        const CHUNK_SIZE: u64 = 16 * 1024 * 1024;
+
+        let Some(len) = progress.total_bytes else {
+            return Ok(false);
+        };
+        if len == 0 {
+            return Ok(true);
+        }
+        let from_fd = from_file.as_raw_fd();
+        let to_fd = to_file.as_raw_fd();
+        let resume_from = progress.current_bytes;
+
+        // Copy-on-write clones share all extents, including holes. They are skipped when throttled,
+        // as network filesystems may clone by copying on the server.
+        if resume_from == 0 && ctx.controller.bandwidth_limit().is_none() {
+            match compio::runtime::spawn_blocking(move || fast_copy::reflink(from_fd, to_fd))
+                .await
+                .map_err(|_| "reflink task failed")?
+            {
+                Ok(()) => {
+                    tracing::debug!("reflinked {} to {}", self.from.display(), self.to.display());
+                    progress.current_bytes = len;
+                    (ctx.on_progress)(self, progress);
+                    return Ok(true);
+                }
+                Err(why) => {
+                    if !fast_copy::is_unsupported(&why) {
+                        tracing::warn!(?why, "failed to reflink {}", self.from.display());
+                    }
+                }
+            }
+        }
+
+        let segments =
+            compio::runtime::spawn_blocking(move || fast_copy::data_segments(from_fd, len))
+                .await
+                .map_err(|_| "seek task failed")??;
+
+        let mut last_progress_update = Instant::now();
+        let mut written = false;
+        for (start, end) in segments {
+            // Segments before the resumed offset were copied by a previous attempt
+            let mut pos = start.max(resume_from);
+            while pos < end {
+                // Smaller chunks keep a throttled transfer smooth
+                let chunk_size = ctx
+                    .controller
+                    .bandwidth_limit()
+                    .map_or(CHUNK_SIZE, |limit| {
+                        (limit / 10).clamp(64 * 1024, CHUNK_SIZE)
+                    });
+                let chunk = (end - pos).min(chunk_size);
+                let result = compio::runtime::spawn_blocking(move || {
+                    fast_copy::copy_range(from_fd, to_fd, pos, chunk)
+                })
+                .await
+                .map_err(|_| "copy task failed")?;
+                let count = match result {
+                    // The source was truncated while copying, padding the copy would hide it
+                    Ok(0) => {
+                        return Err(io::Error::from(io::ErrorKind::UnexpectedEof)).with_context(
+                            || format!("{} was truncated while copying", self.from.display()),
+                        )?;
+                    }
+                    Ok(count) => count,
+                    Err(why) if !written && fast_copy::is_unsupported(&why) => {
+                        tracing::debug!(?why, "copy_file_range unsupported");
+                        return Ok(false);
+                    }
+                    Err(why) => return Err(why).context("failed to copy range")?,
+                };
+                written = true;
+                pos += count;
+                // Holes count as copied
+                progress.current_bytes = pos;
+                ctx.throttle(count).await;
+
+                let current = Instant::now();
+                if current.duration_since(last_progress_update).as_millis() > 49 {
+                    last_progress_update = current;
+                    (ctx.on_progress)(self, progress);
+                    if let Some(partial) = partial {
+                        partial.record(pos);
+                    }
+
+                    if let Err(state) = ctx.controller.check().await {
+                        tracing::warn!(
+                            "operation to copy from {:?} to {:?} cancelled",
+                            self.from,
+                            self.to
+                        );
+                        return Err(OperationError::from_state(state, &ctx.controller).into());
+                    }
+                }
+            }
+        }
+
+        // Extend the file over any trailing hole
+        compio::runtime::spawn_blocking(move || fast_copy::set_len(to_fd, len))
+            .await
+            .map_err(|_| "truncate task failed")??;
+        progress.current_bytes = len;
+        (ctx.on_progress)(self, progress);
+
         Ok(true)
     }
 
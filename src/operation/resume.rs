@@ -0,0 +1,239 @@
+// Copyright 2026 System76 <info@system76.com>
+// SPDX-License-Identifier: GPL-3.0-only
+
+//! Resumable copies of large files.
+//!
+//! Large files are written to a hidden sidecar next to the target and only renamed into place
+//! once complete. The sidecar is kept when the copy fails or the app exits, along with the number
+//! of bytes copied, so that retrying the copy can continue where it stopped. Cancelling the copy
+//! removes it. Before continuing,
+//! the start and end of the copied prefix are hashed in both files to make sure the sidecar still
+//! matches the source.
+
+use sha2::{Digest, Sha256};
+use std::ffi::OsString;
+use std::fs;
+use std::io::{self, Read, Seek, SeekFrom};
+use std::path::{Path, PathBuf};
+use std::time::UNIX_EPOCH;
+use walkdir::WalkDir;
+
+/// Files smaller than this are copied directly to the target.
+pub const MIN_SIZE: u64 = 64 * 1024 * 1024;
+
+/// Extended attribute on the sidecar that records the progress of the copy
+const PROGRESS_XATTR: &str = "user.cosmic-files.resume";
+
+/// Length of each part of the prefix that is hashed before resuming
+const VERIFY_LEN: u64 = 1024 * 1024;
+
+/// Hidden sidecar that a resumable copy to `to` is written to.
+pub fn partial_path(to: &Path) -> PathBuf {
+    let mut name = OsString::from(".");
+    name.push(to.file_name().unwrap_or_default());
+    name.push(".partial");
+    to.with_file_name(name)
+}
+
+/// Remove the sidecars left by copying `paths` into `to`, when the copy will not be resumed.
+pub fn discard_sidecars(paths: &[PathBuf], to: &Path) {
+    for path in paths {
+        let Some(name) = path.file_name() else {
+            continue;
+        };
+        let target = to.join(name);
+        for entry in WalkDir::new(path).into_iter().filter_map(Result::ok) {
+            if !entry.file_type().is_file()
+                || entry
+                    .metadata()
+                    .is_ok_and(|metadata| metadata.len() < MIN_SIZE)
+            {
+                continue;
+            }
+            let Ok(relative) = entry.path().strip_prefix(path) else {
+                continue;
+            };
+            let to = if relative.as_os_str().is_empty() {
+                target.clone()
+            } else {
+                target.join(relative)
+            };
+            _ = fs::remove_file(partial_path(&to));
+        }
+    }
+}
+
+/// Rename `from` to `to`, failing if `to` exists.
+#[cfg(target_os = "linux")]
+fn rename_noreplace(from: &Path, to: &Path) -> io::Result<()> {
+    use std::ffi::CString;
+    use std::os::unix::ffi::OsStrExt;
+
+    let from_c = CString::new(from.as_os_str().as_bytes())?;
+    let to_c = CString::new(to.as_os_str().as_bytes())?;
+    let ret = unsafe {
+        libc::renameat2(
+            libc::AT_FDCWD,
+            from_c.as_ptr(),
+            libc::AT_FDCWD,
+            to_c.as_ptr(),
+            libc::RENAME_NOREPLACE,
+        )
+    };
+    if ret == 0 {
+        return Ok(());
+    }
+    let err = io::Error::last_os_error();
+    // Some filesystems do not support the flag
+    if matches!(err.raw_os_error(), Some(libc::EINVAL | libc::ENOSYS)) {
+        return rename_checked(from, to);
+    }
+    Err(err)
+}
+
+#[cfg(not(target_os = "linux"))]
+fn rename_noreplace(from: &Path, to: &Path) -> io::Result<()> {
+    rename_checked(from, to)
+}
+
+fn rename_checked(from: &Path, to: &Path) -> io::Result<()> {
+    if fs::symlink_metadata(to).is_ok() {
+        return Err(io::ErrorKind::AlreadyExists.into());
+    }
+    fs::rename(from, to)
+}
+
+/// Hash the start and the end of the first `offset` bytes of a file.
+fn prefix_hash(path: &Path, offset: u64) -> io::Result<[u8; 32]> {
+    let mut file = fs::File::open(path)?;
+    let mut hasher = Sha256::new();
+    let mut buf = vec![0; VERIFY_LEN as usize];
+    let head = offset.min(VERIFY_LEN);
+    let tail = offset.saturating_sub(VERIFY_LEN).max(head);
+    for (start, end) in [(0, head), (tail, offset)] {
+        let len = (end - start) as usize;
+        file.seek(SeekFrom::Start(start))?;
+        file.read_exact(&mut buf[..len])?;
+        hasher.update(&buf[..len]);
+    }
+    Ok(hasher.finalize().into())
+}
+
+/// Sidecar of a resumable copy, along with the source it was copied from.
+#[derive(Clone, Debug)]
+pub struct Partial {
+    pub path: PathBuf,
+    len: u64,
+    modified: u128,
+}
+
+impl Partial {
+    pub fn new(to: &Path, metadata: &fs::Metadata) -> Self {
+        let modified = metadata
+            .modified()
+            .ok()
+            .and_then(|time| time.duration_since(UNIX_EPOCH).ok())
+            .map_or(0, |duration| duration.as_nanos());
+        Self {
+            path: partial_path(to),
+            len: metadata.len(),
+            modified,
+        }
+    }
+
+    fn record_value(&self, offset: u64) -> String {
+        format!("{} {} {}", self.len, self.modified, offset)
+    }
+
+    /// Recorded length and modification time of the source, and the number of bytes copied.
+    fn recorded(&self) -> Option<(u64, u128, u64)> {
+        let value = xattr::get(&self.path, PROGRESS_XATTR).ok()??;
+        let mut parts = std::str::from_utf8(&value).ok()?.split(' ');
+        let len = parts.next()?.parse().ok()?;
+        let modified = parts.next()?.parse().ok()?;
+        let offset = parts.next()?.parse().ok()?;
+        Some((len, modified, offset))
+    }
+
+    /// Find where to continue copying from `from`. The sidecar is emptied, or created if it does
+    /// not exist, when the copy has to start over.
+    pub fn prepare(&self, from: &Path) -> io::Result<u64> {
+        let offset = self.resume_offset(from);
+        if offset == 0 {
+            fs::File::create(&self.path)?;
+        } else {
+            log::info!("resuming copy of {} at {} bytes", from.display(), offset);
+        }
+        Ok(offset)
+    }
+
+    fn resume_offset(&self, from: &Path) -> u64 {
+        let Ok(metadata) = fs::symlink_metadata(&self.path) else {
+            return 0;
+        };
+        if !metadata.is_file() {
+            return 0;
+        }
+        let offset = match self.recorded() {
+            Some((len, modified, offset)) => {
+                // The source changed since the sidecar was written
+                if len != self.len || modified != self.modified {
+                    return 0;
+                }
+                offset.min(metadata.len())
+            }
+            // Progress could not be recorded, so rely on verifying the prefix
+            None => metadata.len(),
+        };
+        if offset == 0 || offset > self.len {
+            return 0;
+        }
+        match (prefix_hash(from, offset), prefix_hash(&self.path, offset)) {
+            (Ok(from_hash), Ok(partial_hash)) if from_hash == partial_hash => offset,
+            _ => {
+                log::info!(
+                    "{} does not match {}, copying it again",
+                    self.path.display(),
+                    from.display()
+                );
+                0
+            }
+        }
+    }
+
+    /// Record the number of bytes copied so far.
+    pub fn record(&self, offset: u64) {
+        if let Err(err) = xattr::set(
+            &self.path,
+            PROGRESS_XATTR,
+            self.record_value(offset).as_bytes(),
+        ) {
+            log::debug!(
+                "failed to record progress of {}: {}",
+                self.path.display(),
+                err
+            );
+        }
+    }
+
+    /// Move the completed sidecar to `to`, without replacing anything created there meanwhile.
+    pub fn finish(&self, to: &Path) -> io::Result<()> {
+        _ = xattr::remove(&self.path, PROGRESS_XATTR);
+        let result = rename_noreplace(&self.path, to);
+        if result
+            .as_ref()
+            .is_err_and(|err| err.kind() == io::ErrorKind::AlreadyExists)
+        {
+            return Err(io::Error::new(
+                io::ErrorKind::AlreadyExists,
+                format!("{} was created while copying", to.display()),
+            ));
+        }
+        result
+    }
+
+    /// Remove the sidecar when the copy continues another way.
+    pub fn discard(&self) {
+        _ = fs::remove_file(&self.path);
+    }
+}
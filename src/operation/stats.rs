@@ -0,0 +1,188 @@
+// Copyright 2026 System76 <info@system76.com>
+// SPDX-License-Identifier: GPL-3.0-only
+
+//! Statistics of a running transfer: speed, time remaining, file counts and the files that have
+//! been copied so far.
+
+use std::collections::VecDeque;
+use std::path::{Path, PathBuf};
+use std::time::{Duration, Instant};
+
+use super::recursive::Progress;
+use crate::fl;
+use crate::tab::{format_duration, format_size};
+
+/// Number of finished files kept in the history
+const HISTORY_LEN: usize = 256;
+
+/// Minimum time between speed samples, so that the speed does not jump around
+const SAMPLE_INTERVAL: Duration = Duration::from_millis(500);
+
+/// Samples further apart than this span a pause and are discarded
+const MAX_SAMPLE_INTERVAL: Duration = Duration::from_secs(5);
+
+/// Weight of the newest sample in the smoothed speed
+const SMOOTHING: f64 = 0.25;
+
+#[derive(Clone, Debug)]
+pub struct FileTransfer {
+    pub path: PathBuf,
+    pub bytes: u64,
+    pub duration: Duration,
+}
+
+#[derive(Clone, Debug, Default)]
+pub struct TransferStats {
+    pub files_done: usize,
+    pub files_total: usize,
+    /// Bytes transferred, including the current file
+    pub bytes_done: u64,
+    pub bytes_total: u64,
+    /// File being transferred and when it started
+    pub current: Option<(PathBuf, Instant)>,
+    /// Smoothed bytes per second
+    pub speed: Option<f64>,
+    /// Finished files, oldest first
+    pub history: VecDeque<FileTransfer>,
+    /// Bytes of finished files
+    finished_bytes: u64,
+    sample: Option<(Instant, u64)>,
+}
+
+impl TransferStats {
+    /// Update from the progress of a transfer. `file` is the file being transferred, if any.
+    pub fn update(&mut self, now: Instant, file: Option<&Path>, progress: &Progress) {
+        if progress.files_done > self.files_done
+            && let Some((path, started)) = self.current.take()
+        {
+            self.history.push_back(FileTransfer {
+                path,
+                bytes: progress.bytes_done.saturating_sub(self.finished_bytes),
+                duration: now.saturating_duration_since(started),
+            });
+            if self.history.len() > HISTORY_LEN {
+                self.history.pop_front();
+            }
+        }
+        if let Some(file) = file
+            && self.current.as_ref().is_none_or(|(path, _)| path != file)
+        {
+            self.current = Some((file.to_path_buf(), now));
+        }
+
+        self.files_done = progress.files_done;
+        self.files_total = progress.files_total;
+        self.finished_bytes = progress.bytes_done;
+        self.bytes_done = progress.bytes_done + progress.current_bytes;
+        self.bytes_total = progress.bytes_total.max(self.bytes_done);
+
+        match self.sample {
+            Some((time, bytes)) => {
+                let elapsed = now.saturating_duration_since(time);
+                if elapsed < SAMPLE_INTERVAL {
+                    return;
+                }
+                if elapsed <= MAX_SAMPLE_INTERVAL {
+                    let rate = self.bytes_done.saturating_sub(bytes) as f64 / elapsed.as_secs_f64();
+                    self.speed = Some(match self.speed {
+                        Some(speed) => speed + SMOOTHING * (rate - speed),
+                        None => rate,
+                    });
+                }
+                self.sample = Some((now, self.bytes_done));
+            }
+            None => self.sample = Some((now, self.bytes_done)),
+        }
+    }
+
+    /// Estimated time until the transfer finishes.
+    pub fn eta(&self) -> Option<Duration> {
+        let speed = self.speed.filter(|speed| *speed >= 1.0)?;
+        let remaining = self.bytes_total.saturating_sub(self.bytes_done) as f64;
+        Some(Duration::from_secs_f64(remaining / speed))
+    }
+
+    /// Speed, time remaining and files done, such as `12.3 MB/s, 1:05 left, 3 of 10 files`.
+    pub fn summary(&self) -> String {
+        let files = fl!(
+            "transfer-files",
+            done = self.files_done,
+            total = self.files_total
+        );
+        match (self.speed, self.eta()) {
+            (Some(speed), Some(eta)) => fl!(
+                "transfer-stats",
+                speed = format_size(speed as u64),
+                eta = format_duration(eta),
+                files = files
+            ),
+            _ => files,
+        }
+    }
+
+    /// Name of the file being transferred.
+    pub fn current_name(&self) -> Option<String> {
+        let (path, _) = self.current.as_ref()?;
+        Some(path.file_name()?.to_string_lossy().into_owned())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::path::Path;
+    use std::time::{Duration, Instant};
+
+    use super::TransferStats;
+    use crate::operation::recursive::Progress;
+
+    fn progress(files_done: usize, bytes_done: u64, current_bytes: u64) -> Progress {
+        Progress {
+            current_ops: files_done,
+            total_ops: 2,
+            current_bytes,
+            total_bytes: Some(1000),
+            files_done,
+            files_total: 2,
+            bytes_done,
+            bytes_total: 2000,
+        }
+    }
+
+    #[test]
+    fn speed_eta_and_history() {
+        let start = Instant::now();
+        let mut stats = TransferStats::default();
+        stats.update(start, Some(Path::new("a")), &progress(0, 0, 0));
+        assert_eq!(stats.eta(), None);
+
+        stats.update(
+            start + Duration::from_secs(1),
+            Some(Path::new("a")),
+            &progress(0, 0, 500),
+        );
+        assert_eq!(stats.speed, Some(500.0));
+        assert_eq!(stats.eta(), Some(Duration::from_secs(3)));
+        assert_eq!(stats.current_name().as_deref(), Some("a"));
+
+        stats.update(
+            start + Duration::from_secs(2),
+            Some(Path::new("b")),
+            &progress(1, 1000, 0),
+        );
+        // The speed is smoothed towards the new rate of 500 bytes per second
+        assert_eq!(stats.speed, Some(500.0));
+        assert_eq!(stats.history.len(), 1);
+        assert_eq!(stats.history[0].path, Path::new("a"));
+        assert_eq!(stats.history[0].bytes, 1000);
+        assert_eq!(stats.history[0].duration, Duration::from_secs(2));
+        assert_eq!(stats.current_name().as_deref(), Some("b"));
+
+        // Samples spanning a pause do not slow down the speed
+        stats.update(
+            start + Duration::from_secs(60),
+            Some(Path::new("b")),
+            &progress(1, 1000, 100),
+        );
+        assert_eq!(stats.speed, Some(500.0));
+    }
+}
@@ -0,0 +1,429 @@
+// Copyright 2026 System76 <info@system76.com>
+// SPDX-License-Identifier: GPL-3.0-only
+
+//! Synchronizing two folders.
+//!
+//! Both folders are scanned and compared to produce a plan of actions, which the user reviews
+//! before it is run as a single operation.
+
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+use std::time::{Duration, SystemTime};
+use walkdir::WalkDir;
+
+use super::recursive::{Context, Method};
+use super::{
+    Controller, OperationError, OperationSelection, ReplaceResult, track_progress,
+    wrap_compio_spawn_error,
+};
+use crate::tab;
+
+/// Modification times closer than this are treated as equal, as FAT stores them with a precision
+/// of two seconds
+const MODIFIED_TOLERANCE: Duration = Duration::from_secs(2);
+
+#[derive(Clone, Copy, Debug, Default, Deserialize, Eq, Hash, PartialEq, Serialize)]
+pub enum SyncMode {
+    /// Make the destination match the source, removing items that are not in the source
+    #[default]
+    Mirror,
+    /// Copy missing items both ways, and replace files with the newer version
+    TwoWay,
+}
+
+#[derive(Clone, Debug, Deserialize, Eq, Hash, PartialEq, Serialize)]
+pub enum SyncAction {
+    /// Copy a file or folder that is missing
+    Copy { from: PathBuf, to: PathBuf },
+    /// Replace a file that differs
+    Update { from: PathBuf, to: PathBuf },
+    /// Move a file or folder that is not in the source to the trash
+    Delete { path: PathBuf },
+}
+
+impl SyncAction {
+    /// Path that is changed by the action.
+    pub fn target(&self) -> &Path {
+        match self {
+            Self::Copy { to, .. } | Self::Update { to, .. } => to,
+            Self::Delete { path } => path,
+        }
+    }
+}
+
+#[derive(Debug)]
+struct Entry {
+    is_dir: bool,
+    len: u64,
+    modified: Option<SystemTime>,
+}
+
+/// Entries below `root`, by their path relative to it. Children directly follow their parents.
+fn scan(root: &Path) -> io::Result<BTreeMap<PathBuf, Entry>> {
+    let mut entries = BTreeMap::new();
+    for entry in WalkDir::new(root).min_depth(1) {
+        let entry = entry?;
+        let metadata = entry.metadata()?;
+        let Ok(relative) = entry.path().strip_prefix(root) else {
+            continue;
+        };
+        entries.insert(
+            relative.to_path_buf(),
+            Entry {
+                is_dir: metadata.is_dir(),
+                len: metadata.len(),
+                modified: metadata.modified().ok(),
+            },
+        );
+    }
+    Ok(entries)
+}
+
+fn covered(dirs: &[&Path], relative: &Path) -> bool {
+    dirs.iter().any(|dir| relative.starts_with(dir))
+}
+
+fn same_modified(a: Option<SystemTime>, b: Option<SystemTime>) -> bool {
+    match (a, b) {
+        (Some(a), Some(b)) => {
+            let diff = a.duration_since(b).or_else(|_| b.duration_since(a));
+            diff.is_ok_and(|diff| diff < MODIFIED_TOLERANCE)
+        }
+        _ => false,
+    }
+}
+
+/// Whether two files differ, by size and then by checksum or modification time.
+fn differs(a: &Entry, a_path: &Path, b: &Entry, b_path: &Path, hash: bool) -> io::Result<bool> {
+    if a.len != b.len {
+        return Ok(true);
+    }
+    if hash {
+        Ok(tab::file_checksums(a_path)?.sha256 != tab::file_checksums(b_path)?.sha256)
+    } else {
+        Ok(!same_modified(a.modified, b.modified))
+    }
+}
+
+/// Compare `source` with `destination` and list what has to change to synchronize them. Files are
+/// compared by size and modification time, or by size and checksum when `hash` is set.
+pub fn plan(
+    source: &Path,
+    destination: &Path,
+    mode: SyncMode,
+    hash: bool,
+) -> io::Result<Vec<SyncAction>> {
+    // Either folder would be changed while it is compared with itself
+    if destination.starts_with(source) || source.starts_with(destination) {
+        return Err(io::Error::new(
+            io::ErrorKind::InvalidInput,
+            format!(
+                "{} and {} cannot be synchronized, as one contains the other",
+                source.display(),
+                destination.display()
+            ),
+        ));
+    }
+    let sources = scan(source)?;
+    let destinations = scan(destination)?;
+
+    let mut deletes = Vec::new();
+    let mut changes = Vec::new();
+    // Folders that are copied or deleted as a whole, so their contents are not listed
+    let mut copied_dirs: Vec<&Path> = Vec::new();
+    let mut deleted_dirs: Vec<&Path> = Vec::new();
+
+    for (relative, from) in &sources {
+        if covered(&copied_dirs, relative) {
+            continue;
+        }
+        let from_path = source.join(relative);
+        let to_path = destination.join(relative);
+        match destinations.get(relative) {
+            None => {
+                if from.is_dir {
+                    copied_dirs.push(relative);
+                }
+                changes.push(SyncAction::Copy {
+                    from: from_path,
+                    to: to_path,
+                });
+            }
+            Some(to) if to.is_dir != from.is_dir => {
+                // A file and a folder with the same name can only be mirrored
+                if mode == SyncMode::Mirror {
+                    if to.is_dir {
+                        deleted_dirs.push(relative);
+                    } else {
+                        copied_dirs.push(relative);
+                    }
+                    deletes.push(SyncAction::Delete {
+                        path: to_path.clone(),
+                    });
+                    changes.push(SyncAction::Copy {
+                        from: from_path,
+                        to: to_path,
+                    });
+                } else {
+                    log::warn!(
+                        "not synchronizing {} with {}, as one is a folder",
+                        from_path.display(),
+                        to_path.display()
+                    );
+                }
+            }
+            Some(to) if !from.is_dir && differs(from, &from_path, to, &to_path, hash)? => {
+                match mode {
+                    SyncMode::Mirror => changes.push(SyncAction::Update {
+                        from: from_path,
+                        to: to_path,
+                    }),
+                    // Files with the same modification time that differ are left alone, as
+                    // neither is newer
+                    SyncMode::TwoWay if same_modified(from.modified, to.modified) => {}
+                    SyncMode::TwoWay if from.modified > to.modified => {
+                        changes.push(SyncAction::Update {
+                            from: from_path,
+                            to: to_path,
+                        });
+                    }
+                    SyncMode::TwoWay => changes.push(SyncAction::Update {
+                        from: to_path,
+                        to: from_path,
+                    }),
+                }
+            }
+            Some(_) => {}
+        }
+    }
+
+    for (relative, to) in &destinations {
+        if sources.contains_key(relative)
+            || covered(&deleted_dirs, relative)
+            || covered(&copied_dirs, relative)
+        {
+            continue;
+        }
+        let to_path = destination.join(relative);
+        match mode {
+            SyncMode::Mirror => {
+                if to.is_dir {
+                    deleted_dirs.push(relative);
+                }
+                deletes.push(SyncAction::Delete { path: to_path });
+            }
+            SyncMode::TwoWay => {
+                if to.is_dir {
+                    copied_dirs.push(relative);
+                }
+                changes.push(SyncAction::Copy {
+                    from: to_path,
+                    to: source.join(relative),
+                });
+            }
+        }
+    }
+
+    // Deletions come first so that files can replace folders of the same name
+    deletes.append(&mut changes);
+    Ok(deletes)
+}
+
+/// Run the actions of a plan.
+pub async fn execute(
+    actions: Vec<SyncAction>,
+    controller: Controller,
+) -> Result<OperationSelection, OperationError> {
+    compio::runtime::spawn(async move {
+        let mut pairs = Vec::new();
+        let mut deletes = Vec::new();
+        for action in actions {
+            match action {
+                SyncAction::Copy { from, to } | SyncAction::Update { from, to } => {
+                    pairs.push((from, to));
+                }
+                SyncAction::Delete { path } => deletes.push(path),
+            }
+        }
+
+        for path in deletes {
+            controller
+                .check()
+                .await
+                .map_err(|s| OperationError::from_state(s, &controller))?;
+            let controller_c = controller.clone();
+            // Items are trashed so that a mistaken plan can be undone
+            compio::runtime::spawn_blocking(move || {
+                if fs::symlink_metadata(&path).is_err() {
+                    return Ok(());
+                }
+                trash::delete(&path).map_err(|err| {
+                    OperationError::from_err(
+                        format!("failed to move {} to the trash: {}", path.display(), err),
+                        &controller_c,
+                    )
+                })
+            })
+            .await
+            .map_err(wrap_compio_spawn_error)??;
+        }
+
+        // Outdated files are replaced without asking, as the plan was reviewed
+        let mut context = Context::new(controller.clone())
+            .on_progress(track_progress(controller.clone()))
+            .on_replace(|_op, _count| Box::pin(async { ReplaceResult::Replace(true) }));
+        context.recursive_copy_or_move(pairs, Method::Copy).await?;
+
+        Ok(context.op_sel)
+    })
+    .await
+    .map_err(wrap_compio_spawn_error)?
+}
+
+#[cfg(test)]
+mod tests {
+    use std::fs::{self, File};
+    use std::io;
+    use std::path::Path;
+    use std::time::{Duration, SystemTime};
+
+    use test_log::test;
+
+    use super::{SyncAction, SyncMode, execute, plan};
+    use crate::app::test_utils::empty_fs;
+    use crate::operation::Controller;
+
+    fn write(path: &Path, contents: &str, age: u64) -> io::Result<()> {
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent)?;
+        }
+        fs::write(path, contents)?;
+        File::options()
+            .write(true)
+            .open(path)?
+            .set_modified(SystemTime::now() - Duration::from_secs(age))
+    }
+
+    /// Source and destination that share `same`, with `changed` newer in the source and
+    /// `outdated` newer in the destination.
+    fn fixture(path: &Path) -> io::Result<()> {
+        let (source, destination) = (path.join("source"), path.join("destination"));
+        write(&source.join("same"), "same", 3600)?;
+        write(&destination.join("same"), "same", 3600)?;
+        write(&source.join("changed"), "new", 60)?;
+        write(&destination.join("changed"), "old", 3600)?;
+        write(&source.join("outdated"), "old", 3600)?;
+        write(&destination.join("outdated"), "new", 60)?;
+        write(&source.join("folder").join("added"), "added", 60)?;
+        write(&destination.join("extra").join("file"), "extra", 60)?;
+        Ok(())
+    }
+
+    #[test]
+    fn plan_mirror() -> io::Result<()> {
+        let fs = empty_fs()?;
+        let path = fs.path();
+        fixture(path)?;
+        let (source, destination) = (path.join("source"), path.join("destination"));
+
+        let actions = plan(&source, &destination, SyncMode::Mirror, false)?;
+        assert_eq!(
+            actions,
+            [
+                SyncAction::Delete {
+                    path: destination.join("extra"),
+                },
+                SyncAction::Update {
+                    from: source.join("changed"),
+                    to: destination.join("changed"),
+                },
+                SyncAction::Copy {
+                    from: source.join("folder"),
+                    to: destination.join("folder"),
+                },
+                SyncAction::Update {
+                    from: source.join("outdated"),
+                    to: destination.join("outdated"),
+                },
+            ]
+        );
+
+        Ok(())
+    }
+
+    #[test]
+    fn plan_two_way() -> io::Result<()> {
+        let fs = empty_fs()?;
+        let path = fs.path();
+        fixture(path)?;
+        let (source, destination) = (path.join("source"), path.join("destination"));
+
+        let actions = plan(&source, &destination, SyncMode::TwoWay, false)?;
+        assert_eq!(
+            actions,
+            [
+                SyncAction::Update {
+                    from: source.join("changed"),
+                    to: destination.join("changed"),
+                },
+                SyncAction::Copy {
+                    from: source.join("folder"),
+                    to: destination.join("folder"),
+                },
+                SyncAction::Update {
+                    from: destination.join("outdated"),
+                    to: source.join("outdated"),
+                },
+                SyncAction::Copy {
+                    from: destination.join("extra"),
+                    to: source.join("extra"),
+                },
+            ]
+        );
+
+        Ok(())
+    }
+
+    #[test]
+    fn plan_rejects_nested_folders() -> io::Result<()> {
+        let fs = empty_fs()?;
+        let path = fs.path();
+        fixture(path)?;
+        let source = path.join("source");
+
+        for destination in [source.clone(), source.join("folder"), path.to_path_buf()] {
+            let err = plan(&source, &destination, SyncMode::Mirror, false).unwrap_err();
+            assert_eq!(err.kind(), io::ErrorKind::InvalidInput);
+        }
+
+        Ok(())
+    }
+
+    #[test(compio::test)]
+    async fn mirror_matches_source() -> io::Result<()> {
+        let fs = empty_fs()?;
+        let path = fs.path();
+        fixture(path)?;
+        let (source, destination) = (path.join("source"), path.join("destination"));
+
+        // Deletions are left out, as they would move items to the trash of the user
+        let mut actions = plan(&source, &destination, SyncMode::Mirror, false)?;
+        actions.retain(|action| !matches!(action, SyncAction::Delete { .. }));
+        execute(actions, Controller::default())
+            .await
+            .expect("Sync should have succeeded");
+
+        assert_eq!(
+            plan(&source, &destination, SyncMode::Mirror, true)?,
+            [SyncAction::Delete {
+                path: destination.join("extra"),
+            }]
+        );
+        assert_eq!(fs::read_to_string(destination.join("outdated"))?, "old");
+
+        Ok(())
+    }
+}
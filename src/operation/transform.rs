@@ -0,0 +1,346 @@
+// Copyright 2026 System76 <info@system76.com>
+// SPDX-License-Identifier: GPL-3.0-only
+
+//! Lossless image rotation and flipping.
+//!
+//! JPEG files are never re-encoded, instead their EXIF orientation tag is updated. Formats with
+//! lossless compression are decoded, transformed, and written back without their metadata.
+
+use image::{ImageFormat, ImageReader};
+use std::fs;
+use std::io::{self, BufWriter, Write};
+use std::path::Path;
+
+use super::recursive::{copy_xattrs, set_ownership};
+
+const EXIF_ORIENTATION: u16 = 0x0112;
+const JPEG_SOI: u8 = 0xD8;
+const JPEG_EOI: u8 = 0xD9;
+const JPEG_SOS: u8 = 0xDA;
+const JPEG_APP0: u8 = 0xE0;
+const JPEG_APP1: u8 = 0xE1;
+
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+pub enum ImageTransform {
+    RotateLeft,
+    RotateRight,
+    FlipHorizontal,
+    FlipVertical,
+}
+
+impl ImageTransform {
+    /// Identifier used for selecting localized text.
+    pub const fn id(self) -> &'static str {
+        match self {
+            Self::RotateLeft => "rotate-left",
+            Self::RotateRight => "rotate-right",
+            Self::FlipHorizontal => "flip-horizontal",
+            Self::FlipVertical => "flip-vertical",
+        }
+    }
+
+    /// Compose with an EXIF orientation (1-8), returning the new orientation.
+    pub const fn apply_orientation(self, orientation: u16) -> u16 {
+        // Orientation as clockwise quarter turns applied after an optional horizontal flip
+        let (turns, flip) = match orientation {
+            2 => (0, true),
+            3 => (2, false),
+            4 => (2, true),
+            5 => (3, true),
+            6 => (1, false),
+            7 => (1, true),
+            8 => (3, false),
+            _ => (0, false),
+        };
+        let (turns, flip) = match self {
+            Self::RotateRight => ((turns + 1) % 4, flip),
+            Self::RotateLeft => ((turns + 3) % 4, flip),
+            // Flipping reverses the direction of any rotation applied before it
+            Self::FlipHorizontal => ((4 - turns) % 4, !flip),
+            Self::FlipVertical => ((6 - turns) % 4, !flip),
+        };
+        match (turns, flip) {
+            (0, false) => 1,
+            (0, true) => 2,
+            (2, false) => 3,
+            (2, true) => 4,
+            (3, true) => 5,
+            (1, false) => 6,
+            (1, true) => 7,
+            _ => 8,
+        }
+    }
+}
+
+fn invalid_data(message: &str) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, message)
+}
+
+/// Minimal big endian EXIF segment containing only an orientation tag.
+fn exif_segment(orientation: u16) -> Vec<u8> {
+    let mut segment = vec![0xFF, JPEG_APP1, 0x00, 0x22];
+    segment.extend_from_slice(b"Exif\0\0");
+    // TIFF header with IFD0 directly after it
+    segment.extend_from_slice(b"MM\0\x2a\0\0\0\x08");
+    segment.extend_from_slice(&1u16.to_be_bytes());
+    segment.extend_from_slice(&EXIF_ORIENTATION.to_be_bytes());
+    // SHORT with a count of one
+    segment.extend_from_slice(&3u16.to_be_bytes());
+    segment.extend_from_slice(&1u32.to_be_bytes());
+    segment.extend_from_slice(&orientation.to_be_bytes());
+    segment.extend_from_slice(&[0, 0]);
+    // No next IFD
+    segment.extend_from_slice(&[0, 0, 0, 0]);
+    segment
+}
+
+/// Reads values in the byte order of a TIFF structure.
+struct Tiff<'a> {
+    data: &'a [u8],
+    big_endian: bool,
+}
+
+impl<'a> Tiff<'a> {
+    fn new(data: &'a [u8]) -> io::Result<Self> {
+        let big_endian = match data.get(..2) {
+            Some(b"MM") => true,
+            Some(b"II") => false,
+            _ => return Err(invalid_data("invalid TIFF byte order")),
+        };
+        Ok(Self { data, big_endian })
+    }
+
+    fn read_u16(&self, offset: usize) -> io::Result<u16> {
+        let bytes: [u8; 2] = self
+            .data
+            .get(offset..offset + 2)
+            .and_then(|bytes| bytes.try_into().ok())
+            .ok_or_else(|| invalid_data("truncated EXIF data"))?;
+        Ok(if self.big_endian {
+            u16::from_be_bytes(bytes)
+        } else {
+            u16::from_le_bytes(bytes)
+        })
+    }
+
+    fn read_u32(&self, offset: usize) -> io::Result<u32> {
+        let bytes: [u8; 4] = self
+            .data
+            .get(offset..offset + 4)
+            .and_then(|bytes| bytes.try_into().ok())
+            .ok_or_else(|| invalid_data("truncated EXIF data"))?;
+        Ok(if self.big_endian {
+            u32::from_be_bytes(bytes)
+        } else {
+            u32::from_le_bytes(bytes)
+        })
+    }
+
+    const fn u16_bytes(&self, value: u16) -> [u8; 2] {
+        if self.big_endian {
+            value.to_be_bytes()
+        } else {
+            value.to_le_bytes()
+        }
+    }
+
+    const fn u32_bytes(&self, value: u32) -> [u8; 4] {
+        if self.big_endian {
+            value.to_be_bytes()
+        } else {
+            value.to_le_bytes()
+        }
+    }
+}
+
+/// Find the offset of the orientation value in a TIFF structure.
+fn tiff_orientation_offset(tiff: &[u8]) -> io::Result<Option<(usize, bool)>> {
+    let tiff = Tiff::new(tiff)?;
+    let ifd = tiff.read_u32(4)? as usize;
+    let count = tiff.read_u16(ifd)? as usize;
+    for i in 0..count {
+        let entry = ifd + 2 + i * 12;
+        if tiff.read_u16(entry)? == EXIF_ORIENTATION {
+            // The value of a single SHORT is stored in the first bytes of the value field
+            let value = entry + 8;
+            tiff.read_u16(value)?;
+            return Ok(Some((value, tiff.big_endian)));
+        }
+    }
+    Ok(None)
+}
+
+/// Copy a TIFF structure with an orientation tag added to its first IFD. The IFD is moved to the
+/// end, so that the offsets of everything else stay valid.
+fn tiff_with_orientation(data: &[u8], orientation: u16) -> io::Result<Vec<u8>> {
+    let tiff = Tiff::new(data)?;
+    let ifd = tiff.read_u32(4)? as usize;
+    let count = tiff.read_u16(ifd)? as usize;
+    let next_ifd = tiff.read_u32(ifd + 2 + count * 12)?;
+
+    let mut entry = Vec::with_capacity(12);
+    entry.extend_from_slice(&tiff.u16_bytes(EXIF_ORIENTATION));
+    // SHORT with a count of one
+    entry.extend_from_slice(&tiff.u16_bytes(3));
+    entry.extend_from_slice(&tiff.u32_bytes(1));
+    entry.extend_from_slice(&tiff.u16_bytes(orientation));
+    entry.extend_from_slice(&[0, 0]);
+
+    let mut entries: Vec<&[u8]> = (0..count)
+        .map(|i| &data[ifd + 2 + i * 12..ifd + 14 + i * 12])
+        .collect();
+    // Entries are sorted by tag
+    let index = (0..count)
+        .find(|i| {
+            tiff.read_u16(ifd + 2 + i * 12)
+                .is_ok_and(|tag| tag > EXIF_ORIENTATION)
+        })
+        .unwrap_or(count);
+    entries.insert(index, &entry);
+
+    let mut new = data.to_vec();
+    // IFDs start on a word boundary
+    if new.len() % 2 == 1 {
+        new.push(0);
+    }
+    let new_ifd = u32::try_from(new.len()).map_err(|_| invalid_data("EXIF data is too large"))?;
+    new.extend_from_slice(&tiff.u16_bytes(entries.len() as u16));
+    for entry in entries {
+        new.extend_from_slice(entry);
+    }
+    new.extend_from_slice(&tiff.u32_bytes(next_ifd));
+    new[4..8].copy_from_slice(&tiff.u32_bytes(new_ifd));
+    Ok(new)
+}
+
+/// Rewrite the EXIF orientation of JPEG data, inserting an EXIF segment if there is none.
+pub fn jpeg_transform(data: &mut Vec<u8>, transform: ImageTransform) -> io::Result<()> {
+    if !data.starts_with(&[0xFF, JPEG_SOI]) {
+        return Err(invalid_data("invalid JPEG header"));
+    }
+
+    let mut insert_at = 2;
+    let mut pos = 2;
+    while pos + 4 <= data.len() {
+        if data[pos] != 0xFF {
+            return Err(invalid_data("invalid JPEG marker"));
+        }
+        let marker = data[pos + 1];
+        if marker == 0xFF {
+            // Fill byte
+            pos += 1;
+            continue;
+        }
+        if marker == JPEG_SOS || marker == JPEG_EOI {
+            break;
+        }
+        let len = u16::from_be_bytes([data[pos + 2], data[pos + 3]]) as usize;
+        let payload = pos + 4..pos + 2 + len;
+        if len < 2 || payload.end > data.len() {
+            return Err(invalid_data("truncated JPEG segment"));
+        }
+
+        if marker == JPEG_APP0 && insert_at == pos {
+            // JFIF requires its segment to come first
+            insert_at = payload.end;
+        } else if marker == JPEG_APP1 && data[payload.clone()].starts_with(b"Exif\0\0") {
+            let tiff_start = payload.start + 6;
+            let Some((offset, big_endian)) =
+                tiff_orientation_offset(&data[tiff_start..payload.end])?
+            else {
+                let orientation = transform.apply_orientation(1);
+                let tiff = tiff_with_orientation(&data[tiff_start..payload.end], orientation)?;
+                let len = u16::try_from(8 + tiff.len())
+                    .map_err(|_| invalid_data("EXIF segment is too large"))?;
+                let mut segment = vec![0xFF, JPEG_APP1];
+                segment.extend_from_slice(&len.to_be_bytes());
+                segment.extend_from_slice(b"Exif\0\0");
+                segment.extend_from_slice(&tiff);
+                data.splice(pos..payload.end, segment);
+                return Ok(());
+            };
+            let offset = tiff_start + offset;
+            let bytes = [data[offset], data[offset + 1]];
+            let orientation = if big_endian {
+                u16::from_be_bytes(bytes)
+            } else {
+                u16::from_le_bytes(bytes)
+            };
+            let orientation = transform.apply_orientation(orientation);
+            let bytes = if big_endian {
+                orientation.to_be_bytes()
+            } else {
+                orientation.to_le_bytes()
+            };
+            data[offset..offset + 2].copy_from_slice(&bytes);
+            return Ok(());
+        }
+        pos = payload.end;
+    }
+
+    let orientation = transform.apply_orientation(1);
+    data.splice(insert_at..insert_at, exif_segment(orientation));
+    Ok(())
+}
+
+/// Write data next to a file and atomically replace it, keeping its ownership, permissions and
+/// extended attributes. Symbolic links are resolved so that their target is replaced instead.
+fn replace_file(
+    path: &Path,
+    write: impl FnOnce(&mut BufWriter<&fs::File>) -> io::Result<()>,
+) -> io::Result<()> {
+    let path = fs::canonicalize(path)?;
+    let parent = path
+        .parent()
+        .ok_or_else(|| io::Error::other(format!("path {} has no parent", path.display())))?;
+    let metadata = fs::metadata(&path)?;
+    let temp = tempfile::NamedTempFile::new_in(parent)?;
+    {
+        let mut writer = BufWriter::new(temp.as_file());
+        write(&mut writer)?;
+        writer.flush()?;
+    }
+    temp.as_file().sync_all()?;
+    copy_xattrs(&path, temp.path());
+    // Ownership is changed first, as it clears the setuid and setgid bits
+    set_ownership(temp.path(), &metadata);
+    fs::set_permissions(temp.path(), metadata.permissions())?;
+    temp.persist(&path).map_err(|err| err.error)?;
+    Ok(())
+}
+
+/// Rotate or flip an image file in place without losing quality.
+pub fn transform_image(path: &Path, transform: ImageTransform) -> io::Result<()> {
+    let reader = ImageReader::open(path)?.with_guessed_format()?;
+    let format = reader
+        .format()
+        .ok_or_else(|| io::Error::other("unknown image format"))?;
+    match format {
+        ImageFormat::Jpeg => {
+            let mut data = fs::read(path)?;
+            jpeg_transform(&mut data, transform)?;
+            replace_file(path, |writer| writer.write_all(&data))
+        }
+        ImageFormat::Bmp
+        | ImageFormat::Png
+        | ImageFormat::Pnm
+        | ImageFormat::Qoi
+        | ImageFormat::Tga
+        | ImageFormat::Tiff => {
+            let image = reader.decode().map_err(io::Error::other)?;
+            let image = match transform {
+                ImageTransform::RotateLeft => image.rotate270(),
+                ImageTransform::RotateRight => image.rotate90(),
+                ImageTransform::FlipHorizontal => image.fliph(),
+                ImageTransform::FlipVertical => image.flipv(),
+            };
+            replace_file(path, |writer| {
+                image.write_to(writer, format).map_err(io::Error::other)
+            })
+        }
+        _ => Err(io::Error::new(
+            io::ErrorKind::Unsupported,
+            format!("cannot losslessly transform {format:?} images"),
+        )),
+    }
+}
@@ -26,7 +26,7 @@ use icu::locale::preferences::extensions::unicode::keywords::HourCycle;
 use image::{DynamicImage, ImageReader};
 use jiff_icu::ConvertFrom;
 use mime_guess::{Mime, mime};
-use rustc_hash::FxHashMap;
+use rustc_hash::{FxHashMap, FxHashSet};
 use serde::{Deserialize, Serialize};
 use sha2::{Digest, Sha256};
 use std::borrow::Cow;
@@ -37,7 +37,7 @@ use std::error::Error;
 use std::fmt::{self, Display};
 use std::fs::{self, File, Metadata};
 use std::hash::Hash;
-use std::io::{BufRead, BufReader, Read};
+use std::io::{self, BufRead, BufReader, Read};
 #[cfg(unix)]
 use std::os::unix::fs::MetadataExt;
 use std::path::{self, Path, PathBuf};
@@ -51,22 +51,26 @@ use walkdir::WalkDir;
 use crate::app::{Action, PreviewItem, PreviewKind};
 use crate::clipboard::{ClipboardCopy, ClipboardKind, ClipboardPaste};
 use crate::config::{
-    ContextActionPreset, DesktopConfig, ICON_SCALE_MAX, ICON_SIZE_GRID, IconSizes, TabConfig,
-    ThumbCfg,
+    ContextActionPreset, DesktopConfig, DesktopPosition, ICON_SCALE_MAX, ICON_SIZE_GRID, IconSizes,
+    TabConfig, Tag, ThumbCfg,
 };
 use crate::dialog::DialogKind;
+use crate::gallery::{GalleryFit, GalleryState, ImageInfo, SLIDESHOW_INTERVAL, ZoomEvent};
 use crate::large_image::{
-    LargeImageManager, decode_large_image, exceeds_memory_limit, should_use_dedicated_worker,
-    should_use_tiling,
+    LargeImageManager, decode_large_image, decode_oriented, exceeds_memory_limit,
+    oriented_dimensions, should_use_dedicated_worker, should_use_tiling,
 };
 use crate::localize::{LANGUAGE_SORTER, LOCALE};
+#[cfg(feature = "media")]
+use crate::media::{MediaMessage, MediaPreview, is_media_mime};
 use crate::mime_icon::{mime_for_path, mime_icon};
 use crate::mounter::MOUNTERS;
-use crate::operation::{Controller, OperationError};
+use crate::operation::{Controller, ImageTransform, OperationError};
+use crate::text_preview::{self, TextChunk, TextPreview, is_text_mime};
 use crate::thumbnail_cacher::{CachedThumbnail, ThumbnailCacher, ThumbnailSize};
 use crate::thumbnailer::thumbnailer;
 use crate::trash::{Trash, TrashExt};
-use crate::{FxOrderMap, fl, menu, mime_app, mouse_area};
+use crate::{FxOrderMap, desktop_layout, fl, menu, mime_app, mouse_area};
 
 pub const DOUBLE_CLICK_DURATION: Duration = Duration::from_millis(500);
 pub const HOVER_DURATION: Duration = Duration::from_millis(1600);
@@ -76,11 +80,7 @@ const MAX_SEARCH_LATENCY: Duration = Duration::from_millis(20);
 const MAX_SEARCH_RESULTS: usize = 200;
 //TODO: configurable thumbnail size?
 const THUMBNAIL_SIZE: u32 = (ICON_SIZE_GRID as u32) * (ICON_SCALE_MAX as u32);
-/// Maximum bytes of text to pass to the editor for preview; caps shaping work to avoid blocking.
-/// Files larger than this get a truncated preview (first N bytes only).
-const TEXT_PREVIEW_MAX_BYTES: usize = 256 * 1024; // 256 KiB
-/// Maximum file size (bytes) to attempt text preview; files larger than this are skipped entirely.
-const TEXT_PREVIEW_MAX_FILE_BYTES: u64 = 8 * 1000 * 1000; // 8 MiB
+const TAG_DOT_SIZE: f32 = 8.0;
 
 // Thumbnail generation semaphore - limits parallel thumbnail workers
 // Uses 4 workers for balanced throughput and memory usage
@@ -97,7 +97,7 @@ pub(crate) static SORT_OPTION_FALLBACK: LazyLock<FxHashMap<String, (HeadingOptio
         }))
     });
 
-static MODE_NAMES: LazyLock<Vec<String>> = LazyLock::new(|| {
+pub(crate) static MODE_NAMES: LazyLock<Vec<String>> = LazyLock::new(|| {
     vec![
         // Mode 0
         fl!("none"),
@@ -346,7 +346,7 @@ fn tab_complete(path: &Path) -> Result<Vec<(String, PathBuf)>, Box<dyn Error>> {
 }
 
 //TODO: translate, add more levels?
-fn format_size(size: u64) -> String {
+pub(crate) fn format_size(size: u64) -> String {
     const KB: u64 = 1000;
     const MB: u64 = 1000 * KB;
     const GB: u64 = 1000 * MB;
@@ -365,9 +365,19 @@ fn format_size(size: u64) -> String {
     }
 }
 
-const MODE_SHIFT_USER: u32 = 6;
-const MODE_SHIFT_GROUP: u32 = 3;
-const MODE_SHIFT_OTHER: u32 = 0;
+pub(crate) fn format_duration(duration: Duration) -> String {
+    let secs = duration.as_secs();
+    let (hours, minutes, secs) = (secs / 3600, (secs / 60) % 60, secs % 60);
+    if hours > 0 {
+        format!("{hours}:{minutes:02}:{secs:02}")
+    } else {
+        format!("{minutes}:{secs:02}")
+    }
+}
+
+pub(crate) const MODE_SHIFT_USER: u32 = 6;
+pub(crate) const MODE_SHIFT_GROUP: u32 = 3;
+pub(crate) const MODE_SHIFT_OTHER: u32 = 0;
 
 const fn get_mode_part(mode: u32, shift: u32) -> u32 {
     (mode >> shift) & 0o7
@@ -707,6 +717,11 @@ pub fn item_from_gvfs_info(path: PathBuf, file_info: gio::FileInfo, sizes: IconS
     }
 
     let display_name = display_name_for_file(&path, &file_info.display_name(), false, is_desktop);
+    let tags = if remote {
+        Vec::new()
+    } else {
+        crate::tag::read(&path)
+    };
     let hidden = file_name.starts_with('.');
 
     Item {
@@ -720,7 +735,7 @@ pub fn item_from_gvfs_info(path: PathBuf, file_info: gio::FileInfo, sizes: IconS
         },
         hidden,
         image_dimensions: (!remote && mime.type_() == mime::IMAGE)
-            .then(|| image::image_dimensions(&path).ok())
+            .then(|| oriented_dimensions(&path).ok())
             .flatten(),
         location_opt: Some(Location::Path(path)),
         mime,
@@ -740,6 +755,8 @@ pub fn item_from_gvfs_info(path: PathBuf, file_info: gio::FileInfo, sizes: IconS
         overlaps_drag_rect: false,
         dir_size,
         cut: false,
+        tags,
+        link_opt: None,
         checksums: ChecksumState::default(),
     }
 }
@@ -847,6 +864,17 @@ pub fn item_from_entry(
     }
 
     let display_name = display_name_for_file(&path, &name, is_gvfs, is_desktop);
+    let tags = if remote {
+        Vec::new()
+    } else {
+        crate::tag::read(&path)
+    };
+    let link_opt = fs::read_link(&path).ok().map(|target| LinkTarget {
+        path: target,
+        // The metadata passed in does not follow links for search results, so the target is
+        // checked separately
+        broken: fs::metadata(&path).is_err(),
+    });
 
     Item {
         name,
@@ -855,6 +883,7 @@ pub fn item_from_entry(
         metadata: ItemMetadata::Path {
             metadata,
             children_opt,
+            duration_opt: None,
         },
         hidden,
         location_opt: Some(Location::Path(path)),
@@ -872,6 +901,8 @@ pub fn item_from_entry(
         overlaps_drag_rect: false,
         dir_size,
         cut: false,
+        tags,
+        link_opt,
         checksums: ChecksumState::default(),
     }
 }
@@ -916,7 +947,7 @@ pub fn item_from_trash_entry(
         hidden: false,
         location_opt: location,
         image_dimensions: (mime.type_() == mime::IMAGE)
-            .then(|| image::image_dimensions(&original_path).ok())
+            .then(|| oriented_dimensions(&original_path).ok())
             .flatten(),
         mime,
         icon_handle_grid,
@@ -931,6 +962,8 @@ pub fn item_from_trash_entry(
         overlaps_drag_rect: false,
         dir_size: DirSize::NotDirectory,
         cut: false,
+        tags: Vec::new(),
+        link_opt: None,
         checksums: ChecksumState::default(),
     }
 }
@@ -992,6 +1025,7 @@ pub fn item_from_path<P: Into<PathBuf>>(path: P, sizes: IconSizes) -> Result<Ite
     let path = path.into();
     let name = get_filename_from_path(&path)?;
     let metadata = fs::metadata(&path)
+        .or_else(|_| fs::symlink_metadata(&path))
         .map_err(|err| format!("failed to read metadata for {}: {}", path.display(), err))?;
     Ok(item_from_entry(path, name, metadata, sizes))
 }
@@ -1079,7 +1113,9 @@ pub fn scan_path(tab_path: &PathBuf, sizes: IconSizes) -> Vec<Item> {
                             hidden_files = parse_hidden_file(&path);
                         }
 
+                        // Broken links are listed with the metadata of the link
                         let metadata = fs::metadata(&path)
+                            .or_else(|_| fs::symlink_metadata(&path))
                             .inspect_err(|err| {
                                 log::warn!(
                                     "failed to read metadata for entry at {}: {}",
@@ -1125,6 +1161,11 @@ pub fn scan_search<F: Fn(SearchItem) -> bool + Sync>(
         return;
     }
 
+    let (tag_opt, term) = crate::tag::parse_search(term);
+    let has_tag = |path: &Path| {
+        tag_opt.is_none_or(|tag| crate::tag::read(path).iter().any(|name| name == tag))
+    };
+
     let pattern = regex::escape(term);
     let regex = match regex::RegexBuilder::new(&pattern)
         .case_insensitive(true)
@@ -1157,7 +1198,7 @@ pub fn scan_search<F: Fn(SearchItem) -> bool + Sync>(
                             return ignore::WalkState::Skip;
                         };
 
-                        if regex.is_match(file_name) {
+                        if regex.is_match(file_name) && has_tag(entry.path()) {
                             let path = entry.path();
 
                             let metadata = match entry.metadata() {
@@ -1202,7 +1243,7 @@ pub fn scan_search<F: Fn(SearchItem) -> bool + Sync>(
                     let file_name = path.file_name();
                     if let Some(file_name) = file_name {
                         let file_name = file_name.to_string_lossy();
-                        if regex.is_match(&file_name) {
+                        if regex.is_match(&file_name) && has_tag(&path) {
                             match path.metadata() {
                                 Ok(metadata) => {
                                     if !callback(SearchItem::Path(
@@ -1227,6 +1268,10 @@ pub fn scan_search<F: Fn(SearchItem) -> bool + Sync>(
             }
         }
         SearchLocation::Trash => {
+            // Tags are not kept for items in the trash
+            if tag_opt.is_some() {
+                return;
+            }
             Trash::scan_search(callback, &regex);
         }
     }
@@ -1313,7 +1358,7 @@ pub fn scan_network(uri: &str, sizes: IconSizes) -> Vec<Item> {
 pub fn scan_desktop(
     tab_path: &PathBuf,
     _display: &str,
-    desktop_config: DesktopConfig,
+    desktop_config: &DesktopConfig,
     mut sizes: IconSizes,
 ) -> Vec<Item> {
     sizes.grid = desktop_config.icon_size;
@@ -1398,6 +1443,8 @@ pub fn scan_desktop(
             overlaps_drag_rect: false,
             dir_size: DirSize::NotDirectory,
             cut: false,
+            tags: Vec::new(),
+            link_opt: None,
             checksums: ChecksumState::default(),
         });
     }
@@ -1583,7 +1630,7 @@ impl Location {
         let path = Self::expand_tilde(path);
         match self {
             Self::Desktop(_, display, desktop_config) => {
-                Self::Desktop(path, display.clone(), *desktop_config)
+                Self::Desktop(path, display.clone(), desktop_config.clone())
             }
             Self::Path(..) => Self::Path(path),
             Self::Search(SearchLocation::Path(_), term, show_hidden, time) => Self::Search(
@@ -1608,7 +1655,7 @@ impl Location {
     pub fn scan(&self, sizes: IconSizes) -> (Option<Box<Item>>, Vec<Item>) {
         let items = match self {
             Self::Desktop(path, display, desktop_config) => {
-                scan_desktop(path, display, *desktop_config, sizes)
+                scan_desktop(path, display, desktop_config, sizes)
             }
             Self::Path(path) => scan_path(path, sizes),
             Self::Search(..) => {
@@ -1742,9 +1789,11 @@ pub enum Command {
     Preview(PreviewKind),
     RunContextAction(usize),
     SetOpenWith(Mime, String),
+    SetDesktopPositions(String, FxOrderMap<String, DesktopPosition>),
     SetPermissions(PathBuf, u32),
     SetMultiplePermissions(Vec<(PathBuf, u32)>),
     SetSort(String, HeadingOptions, bool),
+    TransformImage(PathBuf, ImageTransform),
     WindowDrag,
     WindowToggleMaximize,
 }
@@ -1759,6 +1808,9 @@ pub enum Message {
     Config(TabConfig),
     ContextAction(Action),
     ContextMenu(Option<Point>, Option<window::Id>),
+    ContextSubmenu(Option<String>),
+    DesktopArrange(DesktopArrange),
+    DesktopStack(DesktopStack),
     LocationContextMenuPoint(Option<Point>),
     LocationContextMenuIndex(Option<Point>, Option<usize>),
     LocationMenuAction(LocationMenuAction),
@@ -1778,6 +1830,15 @@ pub enum Message {
     GalleryPrevious,
     GalleryNext,
     GalleryToggle,
+    GalleryFit(GalleryFit),
+    GalleryInfo(bool),
+    GalleryInfoLoaded(PathBuf, Result<ImageInfo, String>),
+    GalleryPan(Point),
+    GalleryPanStart(Option<Point>, Size, Size),
+    GalleryScroll(Viewport),
+    GallerySlideshow(bool),
+    GalleryTransform(ImageTransform),
+    GalleryZoom(ZoomEvent),
     GoNext,
     GoPrevious,
     ItemDown,
@@ -1786,6 +1847,7 @@ pub enum Message {
     ItemPageUp,
     ItemRight,
     ItemUp,
+    FollowLink(Option<PathBuf>),
     Location(Location),
     LocationUp,
     Open(Option<PathBuf>),
@@ -1801,6 +1863,7 @@ pub enum Message {
     SelectAll,
     SelectFirst,
     SelectLast,
+    SelectPaths(Vec<PathBuf>),
     SetOpenWith(Mime, String),
     RunContextAction(usize),
     SetPermissions(PathBuf, u32),
@@ -1813,6 +1876,7 @@ pub enum Message {
     DndHover(Location),
     DndEnter(Location),
     DndLeave(Location),
+    DndMotion(Point),
     WindowDrag,
     WindowToggleMaximize,
     ZoomIn,
@@ -1823,9 +1887,87 @@ pub enum Message {
     Checksums(PathBuf, ChecksumState),
     CalculateChecksums(PathBuf),
     CopyChecksum(String),
+    TextPreviewMore(PathBuf),
+    TextPreviewChunk(PathBuf, Result<TextChunk, String>),
+    #[cfg(feature = "media")]
+    Media(PathBuf, MediaMessage),
+    #[cfg(feature = "media")]
+    MediaProbe(PathBuf, Result<(Option<Duration>, bool), String>),
+    #[cfg(feature = "media")]
+    MediaTick,
     ImageDecoded(PathBuf, u32, u32, Vec<u8>, Option<(u32, u32)>, u64), // path, width, height, pixels, display_size, generation
 }
 
+/// Order of the icons when arranging the desktop
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+pub enum DesktopArrange {
+    Modified,
+    Name,
+    Type,
+}
+
+/// Kind of files grouped into one icon when desktop stacks are enabled
+#[derive(Copy, Clone, Debug, Eq, Hash, Ord, PartialEq, PartialOrd)]
+pub enum DesktopStack {
+    Documents,
+    Images,
+    Music,
+    Videos,
+    Archives,
+    Other,
+}
+
+impl DesktopStack {
+    fn for_item(item: &Item) -> Option<Self> {
+        let essence = item.mime.essence_str();
+        if item.metadata.is_dir() || essence == "application/x-desktop" {
+            return None;
+        }
+        let kind = item.mime.type_();
+        Some(if kind == mime::IMAGE {
+            Self::Images
+        } else if kind == mime::AUDIO {
+            Self::Music
+        } else if kind == mime::VIDEO {
+            Self::Videos
+        } else if crate::archive::SUPPORTED_ARCHIVE_TYPES.contains(&essence) {
+            Self::Archives
+        } else if kind == mime::TEXT
+            || essence == "application/pdf"
+            || essence == "application/msword"
+            || essence.starts_with("application/vnd.oasis.opendocument.")
+            || essence.starts_with("application/vnd.openxmlformats-officedocument.")
+        {
+            Self::Documents
+        } else {
+            Self::Other
+        })
+    }
+
+    /// Name the stack position is stored under, which no file can have
+    const fn key(self) -> &'static str {
+        match self {
+            Self::Documents => "/stacks/documents",
+            Self::Images => "/stacks/images",
+            Self::Music => "/stacks/music",
+            Self::Videos => "/stacks/videos",
+            Self::Archives => "/stacks/archives",
+            Self::Other => "/stacks/other",
+        }
+    }
+
+    fn label(self, count: usize) -> String {
+        match self {
+            Self::Documents => fl!("stack-documents", count = count),
+            Self::Images => fl!("stack-images", count = count),
+            Self::Music => fl!("stack-music", count = count),
+            Self::Videos => fl!("stack-videos", count = count),
+            Self::Archives => fl!("stack-archives", count = count),
+            Self::Other => fl!("stack-other", count = count),
+        }
+    }
+}
+
 #[derive(Copy, Clone, Debug, Eq, PartialEq)]
 pub enum LocationMenuAction {
     OpenInNewTab(usize),
@@ -1872,6 +2014,8 @@ pub enum ItemMetadata {
     Path {
         metadata: Metadata,
         children_opt: Option<usize>,
+        /// Length of audio or video, probed when the thumbnail is loaded
+        duration_opt: Option<Duration>,
     },
     Trash {
         metadata: trash::TrashItemMetadata,
@@ -1930,6 +2074,13 @@ impl ItemMetadata {
         }
     }
 
+    pub fn duration(&self) -> Option<Duration> {
+        match self {
+            Self::Path { duration_opt, .. } => *duration_opt,
+            _ => None,
+        }
+    }
+
     pub fn children_count(&self) -> Option<&usize> {
         match &self {
             ItemMetadata::Path { children_opt, .. } => children_opt.as_ref(),
@@ -1945,7 +2096,9 @@ pub enum ItemThumbnail {
     NotImage,
     Image(widget::image::Handle, Option<(u32, u32)>),
     Svg(widget::svg::Handle),
-    Text(widget::text_editor::Content),
+    Text(Box<TextPreview>),
+    #[cfg(feature = "media")]
+    Media(Box<MediaPreview>),
 }
 
 impl Clone for ItemThumbnail {
@@ -1954,10 +2107,9 @@ impl Clone for ItemThumbnail {
             Self::NotImage => Self::NotImage,
             Self::Image(handle, size_opt) => Self::Image(handle.clone(), *size_opt),
             Self::Svg(handle) => Self::Svg(handle.clone()),
-            // Content cannot be cloned simply
-            Self::Text(content) => {
-                Self::Text(widget::text_editor::Content::with_text(&content.text()))
-            }
+            Self::Text(preview) => Self::Text(preview.clone()),
+            #[cfg(feature = "media")]
+            Self::Media(preview) => Self::Media(preview.clone()),
         }
     }
 }
@@ -1979,7 +2131,7 @@ impl ItemThumbnail {
                 CachedThumbnail::Valid((thumbnail_path, size)) => {
                     // Check original image dimensions even when loading cached thumbnail
                     // This prevents trying to load huge images in preview mode
-                    let original_dims = match image::image_dimensions(path) {
+                    let original_dims = match oriented_dimensions(path) {
                         Ok((width, height)) => Some((width, height)),
                         Err(_) => size.map(|s| (s.pixel_size(), s.pixel_size())),
                     };
@@ -2074,7 +2226,7 @@ impl ItemThumbnail {
                     let max_ram = max_mem * 1000 * 1000 / jobs as u64;
                     limits.max_alloc = Some(max_ram);
                     reader.limits(limits);
-                    match reader.decode() {
+                    match decode_oriented(reader) {
                         Ok(reader) => Some(reader),
                         Err(err) => {
                             log::warn!("failed to decode {}: {}", path.display(), err);
@@ -2155,31 +2307,13 @@ impl ItemThumbnail {
                     log::warn!("failed to read {}: {}", path.display(), err);
                 }
             }
-        } else if mime.type_() == mime::TEXT && check_size("text", TEXT_PREVIEW_MAX_FILE_BYTES) {
+        } else if is_text_mime(&mime) {
             tried_supported_file = true;
+            // Only the first chunk is read here, the rest is loaded on request
             if size > 0 {
-                // Reuse size from metadata above; cap allocation and read
-                let read_cap = (size.min(TEXT_PREVIEW_MAX_BYTES as u64)) as usize;
-                let mut buf = vec![0u8; read_cap];
-                match File::open(path).and_then(|f| {
-                    let n = Read::read(&mut f.take(read_cap as u64), &mut buf)?;
-                    buf.truncate(n);
-                    Ok(())
-                }) {
-                    Ok(()) => {
-                        let text = match std::str::from_utf8(&buf) {
-                            Ok(s) => s.to_string(),
-                            Err(e) => {
-                                // Use only the valid UTF-8 prefix (slice is guaranteed valid by valid_up_to())
-                                std::str::from_utf8(&buf[..e.valid_up_to()])
-                                    .unwrap_or("")
-                                    .to_string()
-                            }
-                        };
-                        if !text.is_empty() {
-                            return Self::Text(widget::text_editor::Content::with_text(&text));
-                        }
-                    }
+                match TextPreview::load(path, &mime, size) {
+                    Ok(Some(preview)) => return Self::Text(Box::new(preview)),
+                    Ok(None) => {}
                     Err(err) => {
                         log::warn!("failed to read {}: {}", path.display(), err);
                     }
@@ -2296,6 +2430,13 @@ impl ItemThumbnail {
     }
 }
 
+#[derive(Clone, Debug)]
+pub struct LinkTarget {
+    pub path: PathBuf,
+    /// The target does not exist
+    pub broken: bool,
+}
+
 #[derive(Clone, Debug)]
 pub struct Item {
     pub name: String,
@@ -2316,6 +2457,10 @@ pub struct Item {
     pub selected: bool,
     pub highlighted: bool,
     pub cut: bool,
+    /// Names of tags from extended attributes
+    pub tags: Vec<String>,
+    /// Target of a symbolic link
+    pub link_opt: Option<LinkTarget>,
     pub overlaps_drag_rect: bool,
     pub dir_size: DirSize,
     pub checksums: ChecksumState,
@@ -2349,12 +2494,28 @@ impl Item {
             )))
     }
 
+    /// Filename in list view, followed by the colored dots of its tags.
+    fn list_name_with_tags(&self, tags: &[Tag], width: Length) -> Element<'static, Message> {
+        let name = Self::list_display_name(self.display_name.clone());
+        match crate::tag::dots(&self.tags, tags, TAG_DOT_SIZE) {
+            Some(dots) => widget::row::with_children([name.width(Length::Fill).into(), dots])
+                .align_y(Alignment::Center)
+                .spacing(theme::spacing().space_xxs)
+                .into(),
+            None => name.width(width).into(),
+        }
+    }
+
     pub fn path_opt(&self) -> Option<&PathBuf> {
         self.location_opt.as_ref()?.path_opt()
     }
 
     pub fn can_gallery(&self) -> bool {
-        self.mime.type_() == mime::IMAGE || self.mime.type_() == mime::TEXT
+        #[cfg(feature = "media")]
+        if is_media_mime(&self.mime) {
+            return true;
+        }
+        self.mime.type_() == mime::IMAGE || is_text_mime(&self.mime)
     }
 
     pub fn file_metadata(&self) -> Option<Metadata> {
@@ -2369,8 +2530,34 @@ impl Item {
         }
     }
 
+    /// Icon of the item, with an emblem when it is a symbolic link.
+    fn icon_view(&self, handle: widget::icon::Handle, size: u16) -> Element<'static, Message> {
+        let icon = widget::icon::icon(handle)
+            .content_fit(ContentFit::Contain)
+            .size(size);
+        let Some(link) = &self.link_opt else {
+            return icon.into();
+        };
+        let emblem_name = if link.broken {
+            "emblem-unreadable"
+        } else {
+            "emblem-symbolic-link"
+        };
+        let emblem = widget::icon::from_name(emblem_name)
+            .size((size / 3).max(12))
+            .icon();
+        stack![
+            icon,
+            widget::container(emblem)
+                .width(Length::Fixed(f32::from(size)))
+                .height(Length::Fixed(f32::from(size)))
+                .align_x(Alignment::End)
+                .align_y(Alignment::End)
+        ]
+        .into()
+    }
+
     fn preview(&self) -> Element<'_, Message> {
-        let spacing = cosmic::theme::spacing();
         // This loads the image only if thumbnailing worked
         let icon = widget::icon::icon(self.icon_handle_grid.clone())
             .content_fit(ContentFit::Contain)
@@ -2388,12 +2575,17 @@ impl Item {
                 widget::image(handle.clone()).into()
             }
             ItemThumbnail::Svg(handle) => widget::svg(handle.clone()).into(),
-            ItemThumbnail::Text(content) => widget::text_editor::text_editor(content)
-                .style(text_editor_class)
-                .width(THUMBNAIL_SIZE as f32)
-                .height(Length::Fixed(THUMBNAIL_SIZE as f32))
-                .padding(spacing.space_xxs)
-                .into(),
+            ItemThumbnail::Text(preview) => preview.view(
+                Length::Fixed(THUMBNAIL_SIZE as f32),
+                Length::Fixed(THUMBNAIL_SIZE as f32),
+                None,
+            ),
+            #[cfg(feature = "media")]
+            ItemThumbnail::Media(preview) => widget::container(
+                preview.view(|message| Message::Media(preview.path.clone(), message)),
+            )
+            .height(Length::Fixed(THUMBNAIL_SIZE as f32))
+            .into(),
         }
     }
 
@@ -2445,6 +2637,15 @@ impl Item {
             "type",
             mime = self.mime.to_string()
         )));
+        if let Some(link) = &self.link_opt {
+            details = details.push(widget::selectable_text::body(fl!(
+                "link-target",
+                target = link.path.display().to_string()
+            )));
+            if link.broken {
+                details = details.push(widget::text::body(fl!("broken-link")));
+            }
+        }
         let mut settings = Vec::new();
         if let Some(mime_app_cache) = mime_app_cache_opt {
             let mime_apps = mime_app_cache.get_apps_for_mime(&self.mime, false);
@@ -2586,11 +2787,17 @@ impl Item {
         }
 
         if let Some(path) = self.path_opt()
-            && let Ok(img) = image::image_dimensions(path)
+            && let Ok(img) = oriented_dimensions(path)
         {
             let (width, height) = img;
             details = details.push(widget::text::body(format!("{width}x{height}")));
         }
+        if let Some(duration) = self.metadata.duration() {
+            details = details.push(widget::text::body(fl!(
+                "item-duration",
+                duration = format_duration(duration)
+            )));
+        }
         column = column.push(details);
 
         if let Some(metadata) = self.file_metadata()
@@ -2648,6 +2855,12 @@ impl Item {
             column = column.push(
                 widget::button::standard(fl!("open")).on_press(Message::Open(Some(path.clone()))),
             );
+            if self.link_opt.as_ref().is_some_and(|link| !link.broken) {
+                column = column.push(
+                    widget::button::standard(fl!("follow-link"))
+                        .on_press(Message::FollowLink(Some(path.clone()))),
+                );
+            }
         }
 
         if !settings.is_empty() {
@@ -2673,6 +2886,7 @@ impl Item {
         if let ItemMetadata::Path {
             metadata,
             children_opt,
+            ..
         } = &self.metadata
         {
             if metadata.is_dir() {
@@ -2698,6 +2912,24 @@ impl Item {
             //TODO: other metadata
         }
 
+        match &self.checksums {
+            ChecksumState::NotCalculated => {}
+            ChecksumState::Calculating => {
+                column = column.push(widget::text::body(fl!("calculating")));
+            }
+            ChecksumState::Calculated(checksums) => {
+                column = column.push(widget::text::body(fl!("checksum", kind = "SHA256")));
+                column = column.push(
+                    widget::text::caption(checksums.sha256.clone())
+                        .font(cosmic::font::mono())
+                        .wrapping(text::Wrapping::Glyph),
+                );
+            }
+            ChecksumState::Error(err) => {
+                column = column.push(widget::text::body(format!("{}: {}", fl!("error"), err)));
+            }
+        }
+
         row = row.push(column);
         row.into()
     }
@@ -2785,6 +3017,8 @@ pub struct Tab {
     pub location_context_menu_point: Option<Point>,
     pub location_context_menu_index: Option<usize>,
     pub context_menu: Option<Point>,
+    /// Submenu of context actions shown in place of the context menu
+    pub context_submenu: Option<String>,
     pub mode: Mode,
     pub scroll_opt: Option<AbsoluteOffset>,
     pub size_opt: Cell<Option<Size>>,
@@ -2800,9 +3034,16 @@ pub struct Tab {
     pub sort_name: HeadingOptions,
     pub sort_direction: bool,
     pub gallery: bool,
+    gallery_state: GalleryState,
     pub(crate) parent_item_opt: Option<Box<Item>>,
     pub(crate) items_opt: Option<Vec<Item>>,
     pub dnd_hovered: Option<(Location, Instant)>,
+    dnd_point_opt: Option<Point>,
+    /// Stored desktop icon positions for the output of a desktop tab
+    pub desktop_positions: FxOrderMap<String, DesktopPosition>,
+    desktop_grid_opt: Cell<Option<desktop_layout::Grid>>,
+    desktop_stack_cells: Cell<Vec<(DesktopStack, DesktopPosition)>>,
+    desktop_stacks_open: FxHashSet<DesktopStack>,
     pub(crate) scrollable_id: widget::Id,
     select_focus: Option<usize>,
     select_range: Option<(usize, usize)>,
@@ -2842,27 +3083,29 @@ async fn calculate_dir_size(path: &Path, controller: Controller) -> Result<u64,
 /// Calculate file checksums in a single pass over the file. To add another
 /// digest, hash it alongside `sha256_hasher` in the loop below and add a field
 /// to [`FileChecksums`]; the file is only read once.
-async fn calculate_checksums(path: &Path) -> Result<FileChecksums, String> {
-    let path = path.to_path_buf();
-    tokio::task::spawn_blocking(move || {
-        let mut file = File::open(&path).map_err(|e| e.to_string())?;
-        let mut sha256_hasher = Sha256::new();
+pub fn file_checksums(path: &Path) -> io::Result<FileChecksums> {
+    let mut file = File::open(path)?;
+    let mut sha256_hasher = Sha256::new();
 
-        let mut buffer = [0u8; 8192];
-        loop {
-            let bytes_read = file.read(&mut buffer).map_err(|e| e.to_string())?;
-            if bytes_read == 0 {
-                break;
-            }
-            sha256_hasher.update(&buffer[..bytes_read]);
+    let mut buffer = [0u8; 8192];
+    loop {
+        let bytes_read = file.read(&mut buffer)?;
+        if bytes_read == 0 {
+            break;
         }
+        sha256_hasher.update(&buffer[..bytes_read]);
+    }
 
-        Ok(FileChecksums {
-            sha256: format!("{:x}", sha256_hasher.finalize()),
-        })
+    Ok(FileChecksums {
+        sha256: format!("{:x}", sha256_hasher.finalize()),
     })
-    .await
-    .map_err(|e| e.to_string())?
+}
+
+pub(crate) async fn calculate_checksums(path: &Path) -> Result<FileChecksums, String> {
+    let path = path.to_path_buf();
+    tokio::task::spawn_blocking(move || file_checksums(&path).map_err(|e| e.to_string()))
+        .await
+        .map_err(|e| e.to_string())?
 }
 
 fn folder_name<P: AsRef<Path>>(path: P) -> (String, bool) {
@@ -2916,10 +3159,19 @@ impl Tab {
         window_id: Option<window::Id>,
     ) -> Self {
         let location_str = location.to_string();
-        let (sort_name, sort_direction) = sorting_options
-            .and_then(|opts| opts.get(&location_str))
-            .or_else(|| SORT_OPTION_FALLBACK.get(&location_str))
-            .copied()
+        let desktop_sort = match &location {
+            Location::Desktop(_, output, desktop_config) => {
+                desktop_config.output(output).and_then(|x| x.sort)
+            }
+            _ => None,
+        };
+        let (sort_name, sort_direction) = desktop_sort
+            .or_else(|| {
+                sorting_options
+                    .and_then(|opts| opts.get(&location_str))
+                    .or_else(|| SORT_OPTION_FALLBACK.get(&location_str))
+                    .copied()
+            })
             .unwrap_or((HeadingOptions::Name, true));
         let location = location.normalize();
         let location_ancestors = location.ancestors();
@@ -2930,6 +3182,7 @@ impl Tab {
             location_ancestors,
             location_title,
             context_menu: None,
+            context_submenu: None,
             location_context_menu_point: None,
             location_context_menu_index: None,
             mode: Mode::App,
@@ -2947,6 +3200,7 @@ impl Tab {
             sort_name,
             sort_direction,
             gallery: false,
+            gallery_state: GalleryState::default(),
             parent_item_opt: None,
             items_opt: None,
             scrollable_id,
@@ -2954,6 +3208,11 @@ impl Tab {
             select_range: None,
             clicked: None,
             dnd_hovered: None,
+            dnd_point_opt: None,
+            desktop_positions: FxOrderMap::default(),
+            desktop_grid_opt: Cell::new(None),
+            desktop_stack_cells: Cell::new(Vec::new()),
+            desktop_stacks_open: FxHashSet::default(),
             selected_clicked: false,
             last_right_click: None,
             search_context: None,
@@ -3397,6 +3656,78 @@ impl Tab {
         last
     }
 
+    fn gallery_path(&self) -> Option<&PathBuf> {
+        let index = self.select_focus?;
+        self.items_opt.as_ref()?.get(index)?.path_opt()
+    }
+
+    fn gallery_scroll_to(&self, offset: Vector) -> Command {
+        Command::Iced(
+            scrollable::scroll_to(
+                self.gallery_state.scrollable_id.clone(),
+                AbsoluteOffset {
+                    x: Some(offset.x),
+                    y: Some(offset.y),
+                },
+            )
+            .into(),
+        )
+    }
+
+    /// Reset the zoom and load details when the gallery shows a different image.
+    fn gallery_changed(&mut self) -> Vec<Command> {
+        self.gallery_state.reset_zoom();
+        #[cfg(feature = "media")]
+        self.stop_media(None);
+        let mut commands = self.trigger_async_decode();
+        commands.push(self.gallery_scroll_to(Vector::ZERO));
+        commands.extend(self.gallery_info_command());
+        commands
+    }
+
+    /// Stop all playing media, except for the item at `keep_opt`.
+    #[cfg(feature = "media")]
+    fn stop_media(&mut self, keep_opt: Option<&Path>) {
+        let Some(items) = &mut self.items_opt else {
+            return;
+        };
+        for item in items.iter_mut() {
+            if let Some(ItemThumbnail::Media(preview)) = &mut item.thumbnail_opt
+                && Some(preview.path.as_path()) != keep_opt
+            {
+                preview.stop();
+            }
+        }
+    }
+
+    fn gallery_info_command(&mut self) -> Option<Command> {
+        if !self.gallery || !self.gallery_state.show_info {
+            return None;
+        }
+        let path = self.gallery_path()?.clone();
+        if self
+            .gallery_state
+            .info_opt
+            .as_ref()
+            .is_some_and(|(info_path, _)| info_path == &path)
+        {
+            return None;
+        }
+        self.gallery_state.info_opt = None;
+        Some(Command::Iced(
+            cosmic::Task::future(async move {
+                let info = {
+                    let path = path.clone();
+                    tokio::task::spawn_blocking(move || ImageInfo::load(&path))
+                        .await
+                        .unwrap_or_else(|err| Err(err.to_string()))
+                };
+                Message::GalleryInfoLoaded(path, info)
+            })
+            .into(),
+        ))
+    }
+
     fn trigger_async_decode(&mut self) -> Vec<Command> {
         // Only trigger decode in gallery mode for the currently selected image
         if !self.gallery {
@@ -3419,8 +3750,9 @@ impl Tab {
             return Vec::new();
         };
 
+        let original_dims = *original_dims;
         if let Some((w, h)) = original_dims
-            && !should_use_tiling(*w, *h)
+            && !should_use_tiling(w, h)
         {
             return Vec::new();
         }
@@ -3432,11 +3764,15 @@ impl Tab {
         // Clone path to avoid borrow checker issues
         let path = path.to_path_buf();
 
-        // Get display size for adaptive resolution
-        let display_dimensions = self
-            .size_opt
-            .get()
-            .map(|size| (size.width as u32, size.height as u32));
+        // Get display size for adaptive resolution, following the gallery zoom
+        let display_dimensions = self.size_opt.get().map(|size| match original_dims {
+            Some((w, h)) => {
+                let image = Size::new(w as f32, h as f32);
+                let scale = self.gallery_state.scale(image, size);
+                ((image.width * scale) as u32, (image.height * scale) as u32)
+            }
+            None => (size.width as u32, size.height as u32),
+        });
 
         // Try to decode the image using LargeImageManager with adaptive resolution
         let (should_decode, target_dimensions, generation) = self
@@ -3752,6 +4088,7 @@ impl Tab {
             Message::ContextMenu(point_opt, _) => {
                 self.edit_location = None;
                 self.context_menu = point_opt;
+                self.context_submenu = None;
                 self.location_context_menu_index = None;
 
                 //TODO: hack for clearing selecting when right clicking empty space
@@ -3764,6 +4101,62 @@ impl Tab {
                     }
                 }
             }
+            Message::ContextSubmenu(submenu_opt) => {
+                self.context_submenu = submenu_opt;
+            }
+            Message::DesktopArrange(arrange) => {
+                if let Location::Desktop(_, output, _) = &self.location
+                    && let Some(grid) = self.desktop_grid_opt.get()
+                    && let Some(items) = &self.items_opt
+                {
+                    let stacks: Vec<_> = self
+                        .desktop_stack_cells
+                        .get_mut()
+                        .iter()
+                        .map(|(stack, _)| *stack)
+                        .collect();
+                    let mut items: Vec<_> = items
+                        .iter()
+                        .filter(|item| self.config.show_hidden || !item.hidden)
+                        .filter(|item| {
+                            !DesktopStack::for_item(item).is_some_and(|stack| {
+                                stacks.contains(&stack)
+                                    && !self.desktop_stacks_open.contains(&stack)
+                            })
+                        })
+                        .collect();
+                    items.sort_by(|a, b| {
+                        let order = match arrange {
+                            DesktopArrange::Modified => {
+                                b.metadata.modified().cmp(&a.metadata.modified())
+                            }
+                            DesktopArrange::Name => Ordering::Equal,
+                            DesktopArrange::Type => a.mime.essence_str().cmp(b.mime.essence_str()),
+                        };
+                        b.metadata
+                            .is_dir()
+                            .cmp(&a.metadata.is_dir())
+                            .then(order)
+                            .then_with(|| LANGUAGE_SORTER.compare(&a.display_name, &b.display_name))
+                    });
+                    self.desktop_positions = desktop_layout::arrange(
+                        stacks
+                            .iter()
+                            .map(|stack| stack.key())
+                            .chain(items.iter().map(|item| item.name.as_str())),
+                        grid,
+                    );
+                    commands.push(Command::SetDesktopPositions(
+                        output.clone(),
+                        self.desktop_positions.clone(),
+                    ));
+                }
+            }
+            Message::DesktopStack(stack) => {
+                if !self.desktop_stacks_open.remove(&stack) {
+                    self.desktop_stacks_open.insert(stack);
+                }
+            }
             Message::LocationContextMenuPoint(point_opt) => {
                 self.context_menu = None;
                 self.location_context_menu_point = point_opt;
@@ -3942,17 +4335,23 @@ impl Tab {
                 self.gallery = gallery;
 
                 if gallery {
-                    commands.extend(self.trigger_async_decode());
+                    commands.extend(self.gallery_changed());
+                } else {
+                    self.gallery_state.slideshow = false;
                 }
             }
             Message::GalleryPrevious | Message::GalleryNext => {
                 let mut pos_opt = None;
+                let mut first_pos_opt = None;
                 if let Some(mut indices) = self.column_sort() {
                     if matches!(message, Message::GalleryPrevious) {
                         indices.reverse();
                     }
                     let mut found = false;
                     for (index, item) in indices {
+                        if first_pos_opt.is_none() && item.can_gallery() {
+                            first_pos_opt = item.pos_opt.get();
+                        }
                         if self.select_focus.is_none() {
                             found = true;
                         }
@@ -3968,11 +4367,15 @@ impl Tab {
                         }
                     }
                 }
+                // The slideshow starts over after the last image
+                if pos_opt.is_none() && self.gallery_state.slideshow {
+                    pos_opt = first_pos_opt;
+                }
                 if let Some((row, col)) = pos_opt {
                     // Should mod_shift be available?
                     self.select_position(row, col, mod_shift);
 
-                    commands.extend(self.trigger_async_decode());
+                    commands.extend(self.gallery_changed());
                 }
                 if let Some(offset) = self.select_focus_scroll() {
                     commands.push(Command::Iced(
@@ -3997,13 +4400,61 @@ impl Tab {
                             self.gallery = !self.gallery;
 
                             if self.gallery {
-                                commands.extend(self.trigger_async_decode());
+                                commands.extend(self.gallery_changed());
+                            } else {
+                                self.gallery_state.slideshow = false;
                             }
                             break;
                         }
                     }
                 }
             }
+            Message::GalleryFit(fit) => {
+                self.gallery_state.fit = fit;
+                self.gallery_state.reset_zoom();
+                commands.extend(self.trigger_async_decode());
+                commands.push(self.gallery_scroll_to(Vector::ZERO));
+            }
+            Message::GalleryInfo(show_info) => {
+                self.gallery_state.show_info = show_info;
+                commands.extend(self.gallery_info_command());
+            }
+            Message::GalleryInfoLoaded(path, info) => {
+                if self.gallery_path() == Some(&path) {
+                    self.gallery_state.info_opt = Some((path, info));
+                }
+            }
+            Message::GalleryPan(point) => {
+                if let Some(offset) = self.gallery_state.pan(point) {
+                    commands.push(self.gallery_scroll_to(offset));
+                }
+            }
+            Message::GalleryPanStart(point_opt, content, viewport) => {
+                if let Some(point) = point_opt {
+                    self.gallery_state.pan_start(point, content, viewport);
+                }
+            }
+            Message::GalleryScroll(viewport) => {
+                let offset = viewport.absolute_offset();
+                self.gallery_state.offset = Vector::new(offset.x, offset.y);
+            }
+            Message::GallerySlideshow(slideshow) => {
+                self.gallery_state.slideshow = slideshow;
+            }
+            Message::GalleryTransform(transform) => {
+                if let Some(path) = self.gallery_path().cloned() {
+                    // Decoded images have the old orientation
+                    self.large_image_manager.clear_cache();
+                    self.gallery_state.reset_zoom();
+                    self.gallery_state.info_opt = None;
+                    commands.push(Command::TransformImage(path, transform));
+                }
+            }
+            Message::GalleryZoom(event) => {
+                let offset = self.gallery_state.zoom(event);
+                commands.push(self.gallery_scroll_to(offset));
+                commands.extend(self.trigger_async_decode());
+            }
             Message::GoNext => {
                 if let Some(history_i) = self.history_i.checked_add(1)
                     && let Some(location) = self.history.get(history_i)
@@ -4297,6 +4748,25 @@ impl Tab {
                     }
                 }
             }
+            Message::FollowLink(path_opt) => {
+                // Follow the only selected link if no path is given
+                let path_opt = path_opt.or_else(|| {
+                    let mut paths = self
+                        .selected_locations()
+                        .into_iter()
+                        .filter_map(Location::into_path_opt);
+                    paths.next().filter(|_| paths.next().is_none())
+                });
+                if let Some(path) = path_opt {
+                    match fs::canonicalize(&path) {
+                        // Folders are opened, and files are selected in their folder
+                        Ok(target) => cd = Some(Location::Path(target)),
+                        Err(err) => {
+                            log::warn!("failed to follow link {}: {}", path.display(), err);
+                        }
+                    }
+                }
+            }
             Message::Location(location) => {
                 // Workaround to support favorited files
                 match &location {
@@ -4633,6 +5103,21 @@ impl Tab {
                     }
                 }
             }
+            Message::SelectPaths(paths) => {
+                self.select_paths(paths);
+                if let Some(offset) = self.select_focus_scroll() {
+                    commands.push(Command::Iced(
+                        scrollable::scroll_to(
+                            self.scrollable_id.clone(),
+                            AbsoluteOffset {
+                                x: Some(offset.x),
+                                y: Some(offset.y),
+                            },
+                        )
+                        .into(),
+                    ));
+                }
+            }
             Message::SetOpenWith(mime, id) => {
                 commands.push(Command::SetOpenWith(mime, id));
             }
@@ -4689,19 +5174,28 @@ impl Tab {
                     let location = Location::Path(path);
                     for item in items.iter_mut() {
                         if item.location_opt.as_ref() == Some(&location) {
-                            let handle_opt = match &thumbnail {
-                                ItemThumbnail::NotImage => None,
-                                ItemThumbnail::Image(handle, _) => Some(widget::icon::Handle {
-                                    symbolic: false,
-                                    data: widget::icon::Data::Image(handle.clone()),
-                                }),
-                                ItemThumbnail::Svg(handle) => Some(widget::icon::Handle {
-                                    symbolic: false,
-                                    data: widget::icon::Data::Svg(handle.clone()),
-                                }),
-                                //TODO: text thumbnails?
-                                ItemThumbnail::Text(_text) => None,
-                            };
+                            let handle_opt =
+                                match &thumbnail {
+                                    ItemThumbnail::NotImage => None,
+                                    ItemThumbnail::Image(handle, _) => Some(widget::icon::Handle {
+                                        symbolic: false,
+                                        data: widget::icon::Data::Image(handle.clone()),
+                                    }),
+                                    ItemThumbnail::Svg(handle) => Some(widget::icon::Handle {
+                                        symbolic: false,
+                                        data: widget::icon::Data::Svg(handle.clone()),
+                                    }),
+                                    //TODO: text thumbnails?
+                                    ItemThumbnail::Text(_text) => None,
+                                    #[cfg(feature = "media")]
+                                    ItemThumbnail::Media(preview) => preview
+                                        .poster_opt
+                                        .as_ref()
+                                        .map(|handle| widget::icon::Handle {
+                                            symbolic: false,
+                                            data: widget::icon::Data::Image(handle.clone()),
+                                        }),
+                                };
                             if let Some(handle) = handle_opt {
                                 item.icon_handle_grid.clone_from(&handle);
                                 item.icon_handle_list.clone_from(&handle);
@@ -4711,6 +5205,10 @@ impl Tab {
                             break;
                         }
                     }
+                    // Images reloaded after a rescan need decoding again
+                    if self.gallery && self.gallery_path() == location.path_opt() {
+                        commands.extend(self.trigger_async_decode());
+                    }
                 }
             }
             Message::ImageDecoded(path, width, height, pixels, display_size, generation) => {
@@ -4748,11 +5246,46 @@ impl Tab {
             }
             Message::Drop(Some((to, mut from))) => {
                 self.dnd_hovered = None;
+                from.kind = from.kind.with_modifiers(modifiers);
+                // Dropping on the desktop places the icons under the pointer
+                if let Location::Desktop(desktop, output, _) = &self.location
+                    && matches!(&to, Location::Desktop(to, ..) if to == desktop)
+                    && !matches!(from.kind, ClipboardKind::Link { .. })
+                    && let Some(point) = self.dnd_point_opt.take()
+                    && let Some(grid) = self.desktop_grid_opt.get()
+                    && let Some(items) = &self.items_opt
+                {
+                    let stack_cells = self.desktop_stack_cells.get_mut();
+                    let cells: Vec<_> = items
+                        .iter()
+                        .filter_map(|item| {
+                            let (row, col) = item.pos_opt.get()?;
+                            Some((item.name.as_str(), DesktopPosition { col, row }))
+                        })
+                        .chain(stack_cells.iter().map(|(stack, cell)| (stack.key(), *cell)))
+                        .collect();
+                    let moved: Vec<_> = from
+                        .paths
+                        .iter()
+                        .filter_map(|path| path.file_name()?.to_str())
+                        .collect();
+                    let scroll_y = self.scroll_opt.map_or(0.0, |offset| offset.y);
+                    let target = grid.cell_at(Point::new(point.x, point.y + scroll_y));
+                    self.desktop_positions =
+                        desktop_layout::drop_items(&cells, &moved, target, grid);
+                    commands.push(Command::SetDesktopPositions(
+                        output.clone(),
+                        self.desktop_positions.clone(),
+                    ));
+                }
                 match to {
                     Location::Desktop(to, ..)
                     | Location::Path(to)
                     | Location::Network(_, _, Some(to)) => {
-                        if let Ok(entries) = fs::read_dir(&to) {
+                        // Links can be created next to the items they point to
+                        if !matches!(from.kind, ClipboardKind::Link { .. })
+                            && let Ok(entries) = fs::read_dir(&to)
+                        {
                             for i in entries.into_iter().filter_map(Result::ok) {
                                 let i = i.path();
                                 from.paths.retain(|p| &i != p);
@@ -4774,6 +5307,7 @@ impl Tab {
             }
             Message::Drop(None) => {
                 self.dnd_hovered = None;
+                self.dnd_point_opt = None;
             }
             Message::DndHover(loc) => {
                 if self
@@ -4800,6 +5334,12 @@ impl Tab {
                 if Some(&loc) == self.dnd_hovered.as_ref().map(|(l, _)| l) {
                     self.dnd_hovered = None;
                 }
+                if loc == self.location {
+                    self.dnd_point_opt = None;
+                }
+            }
+            Message::DndMotion(point) => {
+                self.dnd_point_opt = Some(point);
             }
             Message::WindowDrag => {
                 commands.push(Command::WindowDrag);
@@ -4875,6 +5415,96 @@ impl Tab {
             Message::CopyChecksum(value) => {
                 commands.push(Command::Iced(cosmic::iced::clipboard::write(value).into()));
             }
+            Message::TextPreviewMore(path) => {
+                let location = Location::Path(path.clone());
+                if let Some(ref mut items) = self.items_opt
+                    && let Some(item) = items
+                        .iter_mut()
+                        .find(|item| item.location_opt.as_ref() == Some(&location))
+                    && let Some(ItemThumbnail::Text(preview)) = &mut item.thumbnail_opt
+                    && preview.has_more()
+                    && !preview.loading
+                {
+                    preview.loading = true;
+                    let offset = preview.end;
+                    let encoding_opt = preview.next_encoding();
+                    commands.push(Command::Iced(
+                        cosmic::Task::future(async move {
+                            let chunk = {
+                                let path = path.clone();
+                                tokio::task::spawn_blocking(move || {
+                                    text_preview::read_chunk(&path, offset, encoding_opt)
+                                        .map_err(|err| err.to_string())
+                                })
+                                .await
+                                .unwrap_or_else(|err| Err(err.to_string()))
+                            };
+                            Message::TextPreviewChunk(path, chunk)
+                        })
+                        .into(),
+                    ));
+                }
+            }
+            Message::TextPreviewChunk(path, chunk) => {
+                let location = Location::Path(path);
+                if let Some(ref mut items) = self.items_opt
+                    && let Some(item) = items
+                        .iter_mut()
+                        .find(|item| item.location_opt.as_ref() == Some(&location))
+                    && let Some(ItemThumbnail::Text(preview)) = &mut item.thumbnail_opt
+                {
+                    match chunk {
+                        Ok(chunk) => preview.append(chunk),
+                        Err(err) => {
+                            log::warn!("failed to load more text for {:?}: {}", location, err);
+                            preview.loading = false;
+                        }
+                    }
+                }
+            }
+            #[cfg(feature = "media")]
+            Message::Media(path, message) => {
+                if matches!(message, MediaMessage::Play) {
+                    // Only one item plays at a time
+                    self.stop_media(Some(&path));
+                }
+                let location = Location::Path(path);
+                if let Some(ref mut items) = self.items_opt
+                    && let Some(item) = items
+                        .iter_mut()
+                        .find(|item| item.location_opt.as_ref() == Some(&location))
+                    && let Some(ItemThumbnail::Media(preview)) = &mut item.thumbnail_opt
+                {
+                    preview.update(message);
+                }
+            }
+            #[cfg(feature = "media")]
+            Message::MediaProbe(path, result) => {
+                let location = Location::Path(path);
+                if let Some(ref mut items) = self.items_opt
+                    && let Some(item) = items
+                        .iter_mut()
+                        .find(|item| item.location_opt.as_ref() == Some(&location))
+                    && let Some(ItemThumbnail::Media(preview)) = &mut item.thumbnail_opt
+                {
+                    preview.set_probe(result);
+                    if let ItemMetadata::Path { duration_opt, .. } = &mut item.metadata {
+                        *duration_opt = preview.duration_opt;
+                    }
+                }
+            }
+            #[cfg(feature = "media")]
+            Message::MediaTick => {
+                if let Some(ref mut items) = self.items_opt {
+                    for item in items.iter_mut() {
+                        if let Some(ItemThumbnail::Media(preview)) = &mut item.thumbnail_opt
+                            && preview.is_playing()
+                        {
+                            preview.tick();
+                        }
+                    }
+                }
+            }
         }
 
         // Scroll to top if needed
@@ -4973,6 +5603,7 @@ impl Tab {
                         ItemMetadata::Path {
                             metadata,
                             children_opt,
+                            ..
                         } => {
                             if metadata.is_dir() {
                                 (true, children_opt.unwrap_or_default() as u64)
@@ -5111,6 +5742,200 @@ impl Tab {
         container.into()
     }
 
+    fn gallery_image_view(
+        &self,
+        handle: widget::image::Handle,
+        (width, height): (u32, u32),
+    ) -> Element<'_, Message> {
+        let state = &self.gallery_state;
+        let image = Size::new(width as f32, height as f32);
+        widget::responsive(move |viewport| {
+            let scale = state.scale(image, viewport);
+            let content = image * scale;
+            let padding = crate::gallery::padding(content, viewport);
+            let double_click_fit = if state.scale_opt.is_none() && state.fit == GalleryFit::Original
+            {
+                GalleryFit::Fit
+            } else {
+                GalleryFit::Original
+            };
+            let mouse_area = mouse_area::MouseArea::new(
+                widget::image(handle.clone())
+                    .content_fit(ContentFit::Fill)
+                    .width(Length::Fixed(content.width))
+                    .height(Length::Fixed(content.height)),
+            )
+            .on_press(move |point_opt| Message::GalleryPanStart(point_opt, content, viewport))
+            .on_pan(Message::GalleryPan)
+            .on_double_click(move |_| Message::GalleryFit(double_click_fit))
+            .on_scroll_at(move |delta, point| {
+                Some(Message::GalleryZoom(ZoomEvent {
+                    delta,
+                    point,
+                    image,
+                    viewport,
+                    scale,
+                }))
+            });
+            widget::id_container(
+                widget::scrollable(widget::container(mouse_area).padding([padding.y, padding.x]))
+                    .id(state.scrollable_id.clone())
+                    .direction(scrollable::Direction::Both {
+                        vertical: scrollable::Scrollbar::default(),
+                        horizontal: scrollable::Scrollbar::default(),
+                    })
+                    .on_scroll(Message::GalleryScroll)
+                    .width(Length::Fill)
+                    .height(Length::Fill),
+                widget::Id::new(format!("{}-scrollable", state.scrollable_id)),
+            )
+            .into()
+        })
+        .into()
+    }
+
+    fn gallery_info_view(&self) -> Element<'_, Message> {
+        let cosmic_theme::Spacing {
+            space_xs, space_s, ..
+        } = theme::spacing();
+
+        let mut column = widget::column::with_capacity(9)
+            .spacing(space_xs)
+            .padding(space_s)
+            .width(Length::Fixed(280.0));
+        column = column.push(widget::text::title4(fl!("image-info")));
+        match &self.gallery_state.info_opt {
+            Some((_, Ok(info))) if !info.is_empty() => {
+                for (label, value_opt) in [
+                    (fl!("camera"), &info.camera),
+                    (fl!("exposure"), &info.exposure),
+                    (fl!("location"), &info.location),
+                    (fl!("date-taken"), &info.date_taken),
+                ] {
+                    if let Some(value) = value_opt {
+                        column = column
+                            .push(widget::text::heading(label))
+                            .push(widget::text::body(value));
+                    }
+                }
+            }
+            Some((_, Ok(_))) => {
+                column = column.push(widget::text::body(fl!("no-image-info")));
+            }
+            Some((_, Err(err))) => {
+                column = column.push(widget::text::body(format!("⚠ {err}")));
+            }
+            None => {}
+        }
+
+        widget::container(column)
+            .class(theme::Container::Card)
+            .into()
+    }
+
+    fn gallery_toolbar(&self) -> Element<'_, Message> {
+        let cosmic_theme::Spacing { space_xxs, .. } = theme::spacing();
+        let state = &self.gallery_state;
+
+        let button = |icon: &'static str, label: String, selected: bool, message: Message| {
+            widget::tooltip(
+                widget::button::icon(widget::icon::from_name(icon))
+                    .selected(selected)
+                    .padding(space_xxs)
+                    .on_press(message),
+                widget::text::body(label),
+                widget::tooltip::Position::Top,
+            )
+        };
+        let fit = |icon, label, fit| {
+            button(
+                icon,
+                label,
+                state.scale_opt.is_none() && state.fit == fit,
+                Message::GalleryFit(fit),
+            )
+        };
+        // Only JPEG files are transformed without being re-encoded
+        let keeps_metadata = self
+            .select_focus
+            .and_then(|index| self.items_opt.as_ref()?.get(index))
+            .is_none_or(|item| item.mime == mime::IMAGE_JPEG);
+        let transform = |icon, label: String, transform| {
+            let label = if keeps_metadata {
+                label
+            } else {
+                format!("{label}\n{}", fl!("transform-drops-metadata"))
+            };
+            button(icon, label, false, Message::GalleryTransform(transform))
+        };
+
+        widget::row::with_capacity(12)
+            .spacing(space_xxs)
+            .align_y(Alignment::Center)
+            .push(widget::space::horizontal())
+            .push(if state.slideshow {
+                button(
+                    "media-playback-pause-symbolic",
+                    fl!("slideshow"),
+                    true,
+                    Message::GallerySlideshow(false),
+                )
+            } else {
+                button(
+                    "media-playback-start-symbolic",
+                    fl!("slideshow"),
+                    false,
+                    Message::GallerySlideshow(true),
+                )
+            })
+            .push(widget::divider::vertical::default().height(Length::Fixed(24.0)))
+            .push(fit(
+                "zoom-fit-best-symbolic",
+                fl!("zoom-fit"),
+                GalleryFit::Fit,
+            ))
+            .push(fit(
+                "view-fullscreen-symbolic",
+                fl!("zoom-fill"),
+                GalleryFit::Fill,
+            ))
+            .push(fit(
+                "zoom-original-symbolic",
+                fl!("zoom-original"),
+                GalleryFit::Original,
+            ))
+            .push(widget::divider::vertical::default().height(Length::Fixed(24.0)))
+            .push(transform(
+                "object-rotate-left-symbolic",
+                fl!("rotate-left"),
+                ImageTransform::RotateLeft,
+            ))
+            .push(transform(
+                "object-rotate-right-symbolic",
+                fl!("rotate-right"),
+                ImageTransform::RotateRight,
+            ))
+            .push(transform(
+                "object-flip-horizontal-symbolic",
+                fl!("flip-horizontal"),
+                ImageTransform::FlipHorizontal,
+            ))
+            .push(transform(
+                "object-flip-vertical-symbolic",
+                fl!("flip-vertical"),
+                ImageTransform::FlipVertical,
+            ))
+            .push(widget::divider::vertical::default().height(Length::Fixed(24.0)))
+            .push(button(
+                "dialog-information-symbolic",
+                fl!("image-info"),
+                state.show_info,
+                Message::GalleryInfo(!state.show_info),
+            ))
+            .push(widget::space::horizontal())
+            .into()
+    }
+
     pub fn gallery_view(&self) -> Element<'_, Message> {
         let cosmic_theme::Spacing {
             space_xxs,
@@ -5121,6 +5946,7 @@ impl Tab {
 
         //TODO: display error messages when image not found?
         let mut name_opt = None;
+        let mut is_image = false;
         let mut element_opt: Option<Element<Message>> = None;
         if let Some(index) = self.select_focus
             && let Some(items) = &self.items_opt
@@ -5134,6 +5960,7 @@ impl Tab {
             {
                 ItemThumbnail::NotImage => {}
                 ItemThumbnail::Image(handle, original_dims) => {
+                    is_image = true;
                     // Determine which image to show based on async decode state
                     let mut is_loading = false;
                     let mut error_msg_opt = None;
@@ -5171,25 +5998,38 @@ impl Tab {
                         handle.clone()
                     };
 
-                    let content: cosmic::Element<'_, Message> =
-                        if let Some(error_msg) = error_msg_opt {
-                            widget::column::with_capacity(2)
-                                .push(widget::image(image_handle))
-                                .push(widget::text(format!("⚠ {}", error_msg)).size(13))
-                                .padding(space_xs)
-                                .align_x(cosmic::iced::Alignment::Center)
-                                .into()
-                        } else if is_loading {
+                    let content: cosmic::Element<'_, Message> = if let Some(dims) = original_dims
+                        && error_msg_opt.is_none()
+                    {
+                        let image = self.gallery_image_view(image_handle, *dims);
+                        if is_loading {
                             widget::column::with_capacity(2)
-                                .push(widget::image(image_handle))
+                                .push(image)
                                 .push(widget::text("Loading higher resolution...").size(14))
                                 .padding(space_xs)
                                 .align_x(cosmic::iced::Alignment::Center)
                                 .into()
                         } else {
-                            //TODO: use widget::image::viewer, when its zoom can be reset
-                            crate::load_image::loaded_image(image_handle).into()
-                        };
+                            image
+                        }
+                    } else if let Some(error_msg) = error_msg_opt {
+                        widget::column::with_capacity(2)
+                            .push(widget::image(image_handle))
+                            .push(widget::text(format!("⚠ {}", error_msg)).size(13))
+                            .padding(space_xs)
+                            .align_x(cosmic::iced::Alignment::Center)
+                            .into()
+                    } else if is_loading {
+                        widget::column::with_capacity(2)
+                            .push(widget::image(image_handle))
+                            .push(widget::text("Loading higher resolution...").size(14))
+                            .padding(space_xs)
+                            .align_x(cosmic::iced::Alignment::Center)
+                            .into()
+                    } else {
+                        //TODO: use widget::image::viewer, when its zoom can be reset
+                        crate::load_image::loaded_image(image_handle).into()
+                    };
 
                     element_opt = Some(widget::container(content).center(Length::Fill).into());
                 }
@@ -5201,12 +6041,25 @@ impl Tab {
                             .into(),
                     );
                 }
-                ItemThumbnail::Text(text) => {
+                ItemThumbnail::Text(preview) => {
                     element_opt = Some(
                         widget::container(
-                            widget::text_editor::text_editor(text)
-                                .padding(space_xxs)
-                                .style(text_editor_class),
+                            preview.view(
+                                Length::Fill,
+                                Length::Fill,
+                                item.path_opt()
+                                    .map(|path| Message::TextPreviewMore(path.clone())),
+                            ),
+                        )
+                        .center(Length::Fill)
+                        .into(),
+                    );
+                }
+                #[cfg(feature = "media")]
+                ItemThumbnail::Media(preview) => {
+                    element_opt = Some(
+                        widget::container(
+                            preview.view(|message| Message::Media(preview.path.clone(), message)),
                         )
                         .center(Length::Fill)
                         .into(),
@@ -5253,6 +6106,10 @@ impl Tab {
                 row = row.push(space::horizontal().width(Length::Fill));
                 row = row.push(space::vertical().height(Length::Fill));
             }
+            if is_image && self.gallery_state.show_info {
+                row = row.push(widget::space::horizontal().width(Length::Fixed(space_xxs.into())));
+                row = row.push(self.gallery_info_view());
+            }
             row = row.push(widget::space::horizontal().width(Length::Fixed(space_xxs.into())));
             row = row.push(
                 widget::button::icon(widget::icon::from_name("go-next-symbolic"))
@@ -5263,6 +6120,10 @@ impl Tab {
             row = row.push(widget::space::horizontal().width(Length::Fixed(space_m.into())));
             column = column.push(row);
         }
+        if is_image {
+            column = column.push(self.gallery_toolbar());
+            column = column.push(widget::space::vertical().height(Length::Fixed(space_m.into())));
+        }
 
         widget::container(column)
             .width(Length::Fill)
@@ -5683,6 +6544,7 @@ impl Tab {
 
     pub fn grid_view(
         &self,
+        tags: &[Tag],
     ) -> (
         Option<Element<'static, Message>>,
         Element<'_, Message>,
@@ -5766,7 +6628,74 @@ impl Tab {
         let mut drag_s_i = 0;
 
         let mut column = widget::column::with_capacity(2);
-        if let Some(items) = self.column_sort() {
+        if let Some(mut items) = self.column_sort() {
+            let mut desktop_cells = FxHashMap::default();
+            let mut desktop_stacks = Vec::new();
+            if let Location::Desktop(_path, _output, desktop_config) = &self.location {
+                let grid = desktop_layout::Grid {
+                    cols,
+                    rows,
+                    step: Size::new(
+                        (item_width + column_spacing as usize) as f32,
+                        (item_height + grid_spacing as usize) as f32,
+                    ),
+                    padding: space_xxs.into(),
+                };
+                self.desktop_grid_opt.set(Some(grid));
+
+                let mut stacks: Vec<(DesktopStack, Vec<(usize, &Item)>)> = Vec::new();
+                if desktop_config.stacks {
+                    for &(i, item) in &items {
+                        let Some(stack) = DesktopStack::for_item(item) else {
+                            continue;
+                        };
+                        if !show_hidden && item.hidden {
+                            continue;
+                        }
+                        match stacks.iter_mut().find(|(x, _)| *x == stack) {
+                            Some((_, members)) => members.push((i, item)),
+                            None => stacks.push((stack, vec![(i, item)])),
+                        }
+                    }
+                    stacks.sort_by_key(|(stack, _)| *stack);
+                }
+                // Files in closed stacks are not shown on their own
+                let stacked: FxHashSet<usize> = stacks
+                    .iter()
+                    .filter(|(stack, _)| !self.desktop_stacks_open.contains(stack))
+                    .flat_map(|(_, members)| members.iter().map(|(i, _)| *i))
+                    .collect();
+
+                let visible: Vec<_> = items
+                    .iter()
+                    .filter(|(i, item)| (show_hidden || !item.hidden) && !stacked.contains(i))
+                    .collect();
+                let mut cells = desktop_layout::layout(
+                    stacks
+                        .iter()
+                        .map(|(stack, _)| stack.key())
+                        .chain(visible.iter().map(|(_, item)| item.name.as_str())),
+                    &self.desktop_positions,
+                    grid,
+                    desktop_config.keep_aligned,
+                );
+                let item_cells = cells.split_off(stacks.len());
+                desktop_cells.extend(visible.into_iter().map(|(i, _)| *i).zip(item_cells));
+                desktop_stacks = stacks
+                    .into_iter()
+                    .zip(cells)
+                    .map(|((stack, members), cell)| (stack, members, cell))
+                    .collect();
+                self.desktop_stack_cells.set(
+                    desktop_stacks
+                        .iter()
+                        .map(|(stack, _, cell)| (*stack, *cell))
+                        .collect(),
+                );
+                // Visit cells in flow order so each row is built from left to right
+                items.sort_by_key(|(i, _)| desktop_cells.get(i).map(|cell| grid.flow_index(*cell)));
+            }
+
             let mut count = 0;
             let mut col = 0;
             let mut row = 0;
@@ -5780,6 +6709,16 @@ impl Tab {
                     hidden += 1;
                     continue;
                 }
+                if matches!(self.location, Location::Desktop(..)) {
+                    let Some(cell) = desktop_cells.get(&i) else {
+                        // Shown as part of a stack
+                        item.pos_opt.set(None);
+                        item.rect_opt.set(None);
+                        continue;
+                    };
+                    col = cell.col;
+                    row = cell.row;
+                }
                 item.pos_opt.set(Some((row, col)));
                 let item_rect = Rectangle::new(
                     Point::new(
@@ -5798,11 +6737,9 @@ impl Tab {
                 // Only build elements if visible (for performance)
                 if item_rect.intersects(&visible_rect) {
                     //TODO: one focus group per grid item (needs custom widget)
-                    let buttons: Vec<Element<Message>> = vec![
+                    let mut buttons: Vec<Element<Message>> = vec![
                         widget::button::custom(
-                            widget::icon::icon(item.icon_handle_grid.clone())
-                                .content_fit(ContentFit::Contain)
-                                .size(icon_sizes.grid()),
+                            item.icon_view(item.icon_handle_grid.clone(), icon_sizes.grid()),
                         )
                         .padding(space_xxxs)
                         .class(button_style(
@@ -5831,6 +6768,9 @@ impl Tab {
                         )
                         .into(),
                     ];
+                    if let Some(dots) = crate::tag::dots(&item.tags, tags, TAG_DOT_SIZE) {
+                        buttons.insert(1, dots);
+                    }
 
                     let mut column = widget::column::with_capacity(buttons.len())
                         .align_x(Alignment::Center)
@@ -5872,16 +6812,7 @@ impl Tab {
                         .on_middle_press(move |_| Message::MiddleClick(i))
                         .on_enter(move || Message::HighlightActivate(i))
                         .on_exit(move || Message::HighlightDeactivate(i));
-                    grid_elements[row].push(Element::from(mouse_area));
-                } else {
-                    // Add a spacer if the row is empty, so scroll works
-                    if grid_elements[row].is_empty() {
-                        grid_elements[row].push(Element::from(
-                            widget::column::with_capacity(0)
-                                .width(Length::Fill)
-                                .height(Length::Fixed(item_height as f32)),
-                        ));
-                    }
+                    grid_elements[row].push((col, Element::from(mouse_area)));
                 }
 
                 count += 1;
@@ -5905,9 +6836,57 @@ impl Tab {
                 }
             }
 
-            for row_elements in grid_elements {
-                for element in row_elements {
+            for (stack, members, cell) in desktop_stacks {
+                let Some((_, first)) = members.first() else {
+                    continue;
+                };
+                let open = self.desktop_stacks_open.contains(&stack);
+                let column = widget::column::with_children([
+                    widget::button::custom(
+                        first.icon_view(first.icon_handle_grid.clone(), icon_sizes.grid()),
+                    )
+                    .padding(space_xxxs)
+                    .class(button_style(open, false, false, false, false, false))
+                    .into(),
+                    widget::button::custom(Item::grid_display_name(stack.label(members.len())))
+                        .padding([0, space_xxxs])
+                        .class(button_style(open, false, false, true, true, true))
+                        .into(),
+                ])
+                .align_x(Alignment::Center)
+                .height(Length::Fixed(item_height as f32))
+                .width(Length::Fixed(item_width as f32));
+                let mouse_area = crate::mouse_area::MouseArea::new(column)
+                    .on_press(move |_| Message::DesktopStack(stack));
+                while grid_elements.len() <= cell.row {
+                    grid_elements.push(Vec::new());
+                }
+                grid_elements[cell.row].push((cell.col, Element::from(mouse_area)));
+                count += 1;
+            }
+
+            for mut row_elements in grid_elements {
+                // Add a spacer if the row is empty, so scroll works
+                if row_elements.is_empty() {
+                    grid = grid.push(
+                        widget::column::with_capacity(0)
+                            .width(Length::Fill)
+                            .height(Length::Fixed(item_height as f32)),
+                    );
+                }
+                // Freely placed desktop icons can leave empty cells between items
+                row_elements.sort_by_key(|(col, _)| *col);
+                let mut next_col = 0;
+                for (col, element) in row_elements {
+                    for _ in next_col..col {
+                        grid = grid.push(
+                            widget::column::with_capacity(0)
+                                .width(Length::Fixed(item_width as f32))
+                                .height(Length::Fixed(item_height as f32)),
+                        );
+                    }
                     grid = grid.push(element);
+                    next_col = col + 1;
                 }
                 grid = grid.insert_row();
             }
@@ -5950,6 +6929,7 @@ impl Tab {
             }
         }
 
+        dnd_items.sort_by_key(|(_, pos, _)| *pos);
         let drag_list = (!dnd_items.is_empty()).then(|| {
             let mut dnd_grid = widget::grid()
                 .column_spacing(column_spacing)
@@ -5966,9 +6946,7 @@ impl Tab {
                     if *row == r && *col == c {
                         let buttons = vec![
                             widget::button::custom(
-                                widget::icon::icon(item.icon_handle_grid.clone())
-                                    .content_fit(ContentFit::Contain)
-                                    .size(icon_sizes.grid()),
+                                item.icon_view(item.icon_handle_grid.clone(), icon_sizes.grid()),
                             )
                             .on_press(Message::Click(Some(*i)))
                             .padding(space_xxxs)
@@ -6030,6 +7008,7 @@ impl Tab {
 
     pub fn list_view(
         &self,
+        tags: &[Tag],
     ) -> (
         Option<Element<'static, Message>>,
         Element<'_, Message>,
@@ -6132,6 +7111,7 @@ impl Tab {
                         ItemMetadata::Path {
                             metadata,
                             children_opt,
+                            ..
                         } => {
                             if metadata.is_dir() {
                                 //TODO: translate
@@ -6187,12 +7167,10 @@ impl Tab {
 
                     let row = if condensed {
                         widget::row::with_children([
-                            widget::icon::icon(item.icon_handle_list_condensed.clone())
-                                .content_fit(ContentFit::Contain)
-                                .size(icon_size)
+                            item.icon_view(item.icon_handle_list_condensed.clone(), icon_size)
                                 .into(),
                             widget::column::with_children([
-                                Item::list_display_name(item.display_name.clone()).into(),
+                                item.list_name_with_tags(tags, Length::Shrink),
                                 //TODO: translate?
                                 widget::text::caption(format!("{modified_text} - {size_text}"))
                                     .into(),
@@ -6204,12 +7182,10 @@ impl Tab {
                         .spacing(space_xxs)
                     } else if is_search {
                         widget::row::with_children([
-                            widget::icon::icon(item.icon_handle_list_condensed.clone())
-                                .content_fit(ContentFit::Contain)
-                                .size(icon_size)
+                            item.icon_view(item.icon_handle_list_condensed.clone(), icon_size)
                                 .into(),
                             widget::column::with_children([
-                                Item::list_display_name(item.display_name.clone()).into(),
+                                item.list_name_with_tags(tags, Length::Shrink),
                                 widget::text::caption(match item.path_opt() {
                                     Some(path) => path.display().to_string(),
                                     None => String::new(),
@@ -6230,13 +7206,9 @@ impl Tab {
                         .spacing(space_xxs)
                     } else {
                         widget::row::with_children([
-                            widget::icon::icon(item.icon_handle_list.clone())
-                                .content_fit(ContentFit::Contain)
-                                .size(icon_size)
-                                .into(),
-                            Item::list_display_name(item.display_name.clone())
-                                .width(Length::Fill)
+                            item.icon_view(item.icon_handle_list.clone(), icon_size)
                                 .into(),
+                            item.list_name_with_tags(tags, Length::Fill),
                             widget::text::body(modified_text.clone())
                                 .width(Length::Fixed(modified_width))
                                 .into(),
@@ -6299,9 +7271,7 @@ impl Tab {
                             )
                         } else if condensed {
                             widget::row::with_children([
-                                widget::icon::icon(item.icon_handle_list_condensed.clone())
-                                    .content_fit(ContentFit::Contain)
-                                    .size(icon_size)
+                                item.icon_view(item.icon_handle_list_condensed.clone(), icon_size)
                                     .into(),
                                 widget::column::with_children([
                                     Item::list_display_name(item.display_name.clone()).into(),
@@ -6316,9 +7286,7 @@ impl Tab {
                             .into()
                         } else if is_search {
                             widget::row::with_children([
-                                widget::icon::icon(item.icon_handle_list_condensed.clone())
-                                    .content_fit(ContentFit::Contain)
-                                    .size(icon_size)
+                                item.icon_view(item.icon_handle_list_condensed.clone(), icon_size)
                                     .into(),
                                 widget::column::with_children([
                                     Item::list_display_name(item.display_name.clone()).into(),
@@ -6342,9 +7310,7 @@ impl Tab {
                             .into()
                         } else {
                             widget::row::with_children([
-                                widget::icon::icon(item.icon_handle_list.clone())
-                                    .content_fit(ContentFit::Contain)
-                                    .size(icon_size)
+                                item.icon_view(item.icon_handle_list.clone(), icon_size)
                                     .into(),
                                 Item::list_display_name(item.display_name.clone())
                                     .width(Length::Fill)
@@ -6430,6 +7396,7 @@ impl Tab {
         size: Size,
         clipboard_paste_available: bool,
         context_actions: &'a [ContextActionPreset],
+        tags: &'a [Tag],
     ) -> Element<'a, Message> {
         // Update cached size
         self.size_opt.set(Some(size));
@@ -6447,8 +7414,8 @@ impl Tab {
             Some(self.location_view())
         };
         let (drag_list, mut item_view, can_scroll) = match self.config.view {
-            View::Grid => self.grid_view(),
-            View::List => self.list_view(),
+            View::Grid => self.grid_view(tags),
+            View::List => self.list_view(tags),
         };
         item_view = widget::container(item_view).width(Length::Fill).into();
         let files = self
@@ -6523,6 +7490,7 @@ impl Tab {
                 modifiers,
                 clipboard_paste_available,
                 context_actions,
+                tags,
             );
             popover = popover
                 .popup(context_menu)
@@ -6642,6 +7610,11 @@ impl Tab {
         })
         .on_enter(move |_, _, _| Message::DndEnter(tab_location_2.clone()))
         .on_leave(move || Message::DndLeave(tab_location_3.clone()));
+        let dnd_dest = if matches!(self.location, Location::Desktop(..)) {
+            dnd_dest.on_motion(|x, y| Message::DndMotion(Point::new(x as f32, y as f32)))
+        } else {
+            dnd_dest
+        };
 
         dnd_dest.into()
     }
@@ -6912,6 +7885,7 @@ impl Tab {
         modifiers: &'a Modifiers,
         clipboard_paste_available: bool,
         context_actions: &'a [ContextActionPreset],
+        tags: &'a [Tag],
     ) -> Element<'a, Message> {
         widget::responsive(move |size| {
             widget::id_container(
@@ -6921,6 +7895,7 @@ impl Tab {
                     size,
                     clipboard_paste_available,
                     context_actions,
+                    tags,
                 ),
                 Id::new(format!(
                     "tab-{}-{}",
@@ -7043,11 +8018,12 @@ impl Tab {
                                         let path = path.clone();
 
                                         // Acquire semaphore permit
-                                        let _permit =
-                                            THUMB_SEMAPHORE.acquire().await.unwrap();
+                                        let _permit = THUMB_SEMAPHORE.acquire().await.unwrap();
 
                                         tokio::task::spawn_blocking(move || {
                                             let start = Instant::now();
+                                            #[cfg(feature = "media")]
+                                            let is_media = is_media_mime(&mime);
                                             let thumbnail = ItemThumbnail::new(
                                                 &path,
                                                 metadata,
@@ -7057,6 +8033,19 @@ impl Tab {
                                                 effective_jobs,
                                                 max_size,
                                             );
+                                            #[cfg(feature = "media")]
+                                            let thumbnail = if is_media {
+                                                // Video thumbnails become the poster frame
+                                                let poster_opt = match thumbnail {
+                                                    ItemThumbnail::Image(handle, _) => Some(handle),
+                                                    _ => None,
+                                                };
+                                                ItemThumbnail::Media(Box::new(MediaPreview::new(
+                                                    &path, poster_opt,
+                                                )))
+                                            } else {
+                                                thumbnail
+                                            };
                                             log::debug!(
                                                 "thumbnailed {} in {:?}",
                                                 path.display(),
@@ -7091,6 +8080,53 @@ impl Tab {
                 }
             }
 
+            // Media is probed once it is shown, as probing a file can block for seconds
+            #[cfg(feature = "media")]
+            {
+                let gallery_item_opt = self
+                    .select_focus
+                    .filter(|_| self.gallery)
+                    .and_then(|index| items.get(index));
+                for item in items
+                    .iter()
+                    .filter(|item| preview && item.selected)
+                    .chain(gallery_item_opt)
+                {
+                    if let Some(ItemThumbnail::Media(media)) = &item.thumbnail_opt
+                        && media.needs_probe()
+                    {
+                        subscriptions.push(Subscription::run_with(media.path.clone(), |path| {
+                            let path = path.clone();
+                            stream::channel(
+                                1,
+                                move |mut output: futures::channel::mpsc::Sender<_>| async move {
+                                    let result = {
+                                        let path = path.clone();
+                                        tokio::task::spawn_blocking(move || {
+                                            crate::media::probe(&path)
+                                        })
+                                        .await
+                                        .unwrap_or_else(|err| Err(err.to_string()))
+                                    };
+
+                                    if let Err(err) =
+                                        output.send(Message::MediaProbe(path.clone(), result)).await
+                                    {
+                                        log::warn!(
+                                            "failed to send media probe for {}: {}",
+                                            path.display(),
+                                            err
+                                        );
+                                    }
+
+                                    std::future::pending().await
+                                },
+                            )
+                        }));
+                    }
+                }
+            }
+
             if preview {
                 // Load directory size for selected items
 
@@ -7342,6 +8378,22 @@ impl Tab {
             ));
         }
 
+        if self.gallery && self.gallery_state.slideshow {
+            subscriptions
+                .push(cosmic::iced::time::every(SLIDESHOW_INTERVAL).map(|_| Message::GalleryNext));
+        }
+
+        #[cfg(feature = "media")]
+        if self.items_opt.as_ref().is_some_and(|items| {
+            items.iter().any(|item| {
+                matches!(&item.thumbnail_opt, Some(ItemThumbnail::Media(preview)) if preview.is_playing())
+            })
+        }) {
+            subscriptions.push(
+                cosmic::iced::time::every(crate::media::TICK_INTERVAL).map(|_| Message::MediaTick),
+            );
+        }
+
         Subscription::batch(subscriptions)
     }
 
@@ -7371,53 +8423,6 @@ pub fn respond_to_scroll_direction(delta: ScrollDelta, modifiers: &Modifiers) ->
     None
 }
 
-fn text_editor_class(
-    theme: &cosmic::Theme,
-    status: cosmic::widget::text_editor::Status,
-) -> cosmic::iced::widget::text_editor::Style {
-    let cosmic = theme.cosmic();
-    let container = theme.current_container();
-
-    let mut background: cosmic::iced::Color = container.component.base.into();
-    background.a = 0.25;
-    let selection = cosmic.accent.base.into();
-    let value = cosmic.palette.neutral_9.into();
-    let mut placeholder = cosmic.palette.neutral_9;
-    placeholder.alpha = 0.7;
-    let placeholder = placeholder.into();
-
-    match status {
-        cosmic::iced::widget::text_editor::Status::Active
-        | cosmic::iced::widget::text_editor::Status::Disabled => {
-            cosmic::iced::widget::text_editor::Style {
-                background: background.into(),
-                border: cosmic::iced::Border {
-                    radius: cosmic.corner_radii.radius_m.into(),
-                    width: 2.0,
-                    color: container.component.divider.into(),
-                },
-                placeholder,
-                value,
-                selection,
-            }
-        }
-        cosmic::iced::widget::text_editor::Status::Hovered
-        | cosmic::iced::widget::text_editor::Status::Focused { .. } => {
-            cosmic::iced::widget::text_editor::Style {
-                background: background.into(),
-                border: cosmic::iced::Border {
-                    radius: cosmic.corner_radii.radius_m.into(),
-                    width: 2.0,
-                    color: cosmic::iced::Color::from(cosmic.accent.base),
-                },
-                placeholder,
-                value,
-                selection,
-            }
-        }
-    }
-}
-
 #[cfg(test)]
 mod tests {
     use std::path::PathBuf;
@@ -7432,13 +8437,15 @@ mod tests {
     use test_log::test;
 
     use super::{
-        ItemMetadata, ItemThumbnail, Location, Message, Tab, respond_to_scroll_direction, scan_path,
+        ItemMetadata, ItemThumbnail, Location, Message, Tab, item_from_entry,
+        respond_to_scroll_direction, scan_path,
     };
     use crate::app::test_utils::{
         NAME_LEN, NUM_DIRS, NUM_FILES, NUM_HIDDEN, NUM_NESTED, assert_eq_tab_path, empty_fs,
         eq_path_item, filter_dirs, read_dir_sorted, simple_fs, tab_click_new,
     };
     use crate::config::{IconSizes, TabConfig, ThumbCfg};
+    use crate::text_preview;
 
     // Boilerplate for tab tests. Checks if simulated clicks selected items.
     fn tab_selects_item(
@@ -7577,6 +8584,39 @@ mod tests {
         Ok(())
     }
 
+    #[test]
+    fn scan_path_lists_links_and_broken_links() -> io::Result<()> {
+        let fs = empty_fs()?;
+        let path = fs.path();
+        let target = path.join("target");
+        fs::write(&target, "ferris")?;
+        std::os::unix::fs::symlink(&target, path.join("link"))?;
+        std::os::unix::fs::symlink(path.join("missing"), path.join("broken"))?;
+
+        let items = scan_path(&path.to_owned(), IconSizes::default());
+        let link_of = |name: &str| {
+            let item = items.iter().find(|item| item.name == name)?;
+            item.link_opt
+                .as_ref()
+                .map(|link| (link.path.clone(), link.broken))
+        };
+        assert_eq!(link_of("target"), None);
+        assert_eq!(link_of("link"), Some((target, false)));
+        assert_eq!(link_of("broken"), Some((path.join("missing"), true)));
+
+        // Search results pass metadata of the link itself
+        let link = path.join("link");
+        let item = item_from_entry(
+            link.clone(),
+            "link".to_string(),
+            fs::symlink_metadata(&link)?,
+            IconSizes::default(),
+        );
+        assert!(item.link_opt.is_some_and(|link| !link.broken));
+
+        Ok(())
+    }
+
     #[test]
     fn tab_location_changes_location() -> io::Result<()> {
         let fs = simple_fs(NUM_FILES, NUM_NESTED, NUM_DIRS, NUM_NESTED, NAME_LEN)?;
@@ -7861,6 +8901,7 @@ mod tests {
         let item_metadata = ItemMetadata::Path {
             metadata,
             children_opt: None,
+            duration_opt: None,
         };
         let thumb = ItemThumbnail::new(
             &path,
@@ -7887,6 +8928,7 @@ mod tests {
         let item_metadata = ItemMetadata::Path {
             metadata,
             children_opt: None,
+            duration_opt: None,
         };
         let thumb = ItemThumbnail::new(
             &path,
@@ -7905,7 +8947,7 @@ mod tests {
     }
 
     #[test]
-    fn item_thumbnail_text_preview_invalid_utf8_uses_valid_prefix() -> io::Result<()> {
+    fn item_thumbnail_text_preview_invalid_utf8_detects_encoding() -> io::Result<()> {
         let dir = TempDir::new()?;
         let path = dir.path().join("invalid_utf8.txt");
         // Valid UTF-8 "ab" then invalid byte sequence then "c"
@@ -7914,6 +8956,7 @@ mod tests {
         let item_metadata = ItemMetadata::Path {
             metadata,
             children_opt: None,
+            duration_opt: None,
         };
         let thumb = ItemThumbnail::new(
             &path,
@@ -7925,15 +8968,123 @@ mod tests {
             8,
         );
         match &thumb {
-            ItemThumbnail::Text(content) => {
-                // Text editor content may add a trailing newline
-                assert_eq!(content.text().trim_end(), "ab");
+            ItemThumbnail::Text(preview) => {
+                assert_ne!(preview.encoding, encoding_rs::UTF_8);
+                assert!(preview.content.text().starts_with("ab"));
             }
             _ => panic!(
-                "expected Text thumbnail with valid prefix only, got {:?}",
+                "expected Text thumbnail decoded with a legacy encoding, got {:?}",
                 thumb
             ),
         }
         Ok(())
     }
+
+    #[test]
+    fn item_thumbnail_text_preview_latin1() -> io::Result<()> {
+        let dir = TempDir::new()?;
+        let path = dir.path().join("latin1.txt");
+        fs::write(
+            &path,
+            b"Le caf\xe9 cr\xe8me br\xfbl\xe9e est d\xe9licieux, tr\xe8s fran\xe7ais.\n",
+        )?;
+        let metadata = fs::metadata(&path)?;
+        let item_metadata = ItemMetadata::Path {
+            metadata,
+            children_opt: None,
+            duration_opt: None,
+        };
+        let thumb = ItemThumbnail::new(
+            &path,
+            item_metadata,
+            mime::TEXT_PLAIN,
+            128,
+            100 * 1024 * 1024,
+            1,
+            8,
+        );
+        match &thumb {
+            ItemThumbnail::Text(preview) => {
+                assert_eq!(preview.encoding, encoding_rs::WINDOWS_1252);
+                assert!(preview.content.text().contains("café crème"));
+            }
+            _ => panic!("expected Text thumbnail, got {:?}", thumb),
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn text_preview_chunks_split_on_lines() -> io::Result<()> {
+        let dir = TempDir::new()?;
+        let path = dir.path().join("large.rs");
+        let line = "fn main() { println!(\"héllo\"); }\n";
+        let contents = line.repeat(2 * text_preview::CHUNK_BYTES / line.len());
+        fs::write(&path, &contents)?;
+
+        let mut text = String::new();
+        let mut offset = 0;
+        let mut encoding_opt = None;
+        while offset < contents.len() as u64 {
+            let chunk = text_preview::read_chunk(&path, offset, encoding_opt)?;
+            assert!(chunk.end > offset, "chunk should make progress");
+            assert!(chunk.text.ends_with('\n'), "chunk should end on a line");
+            encoding_opt = Some(chunk.encoding);
+            offset = chunk.end;
+            text.push_str(&chunk.text);
+        }
+        assert_eq!(encoding_opt, Some(encoding_rs::UTF_8));
+        assert_eq!(text, contents);
+        Ok(())
+    }
+
+    #[test]
+    fn text_preview_detects_encoding_after_ascii_chunk() -> io::Result<()> {
+        let dir = TempDir::new()?;
+        let path = dir.path().join("log.txt");
+        let line = "plain ascii line\n";
+        let mut contents = line
+            .repeat(2 * text_preview::CHUNK_BYTES / line.len())
+            .into_bytes();
+        contents.extend_from_slice(b"caf\xe9 cr\xe8me br\xfbl\xe9e, tr\xe8s fran\xe7ais\n");
+        fs::write(&path, &contents)?;
+
+        let mut preview =
+            text_preview::TextPreview::load(&path, &mime::TEXT_PLAIN, contents.len() as u64)?
+                .expect("text preview");
+        assert!(preview.ascii);
+        while preview.end < contents.len() as u64 {
+            let chunk = text_preview::read_chunk(&path, preview.end, preview.next_encoding())?;
+            preview.append(chunk);
+        }
+        assert_eq!(preview.encoding, encoding_rs::WINDOWS_1252);
+        let text = preview.content.text();
+        assert!(!text.contains('\u{FFFD}'));
+        assert!(text.contains("café crème"));
+        Ok(())
+    }
+
+    #[test]
+    fn text_preview_chunks_keep_multibyte_characters() -> io::Result<()> {
+        let dir = TempDir::new()?;
+        let path = dir.path().join("shift_jis.txt");
+        // One ASCII byte shifts the two byte characters so that a chunk boundary splits one
+        let contents = format!("a{}", "日本語".repeat(text_preview::CHUNK_BYTES / 4));
+        let (bytes, _, _) = encoding_rs::SHIFT_JIS.encode(&contents);
+        fs::write(&path, &bytes)?;
+
+        let mut text = String::new();
+        let mut offset = 0;
+        while offset < bytes.len() as u64 {
+            let chunk = text_preview::read_chunk(&path, offset, Some(encoding_rs::SHIFT_JIS))?;
+            assert!(chunk.end > offset, "chunk should make progress");
+            assert!(
+                !chunk.text.contains('\u{FFFD}'),
+                "chunk should not split a character"
+            );
+            offset = chunk.end;
+            text.push_str(&chunk.text);
+        }
+        assert_eq!(text, contents);
+        Ok(())
+    }
 }
@@ -0,0 +1,190 @@
+// Copyright 2026 System76 <info@system76.com>
+// SPDX-License-Identifier: GPL-3.0-only
+
+//! Tags stored in the `user.xdg.tags` extended attribute, as a comma separated list.
+
+use cosmic::iced::{Background, Border, Color, Length};
+use cosmic::{Element, widget};
+use serde::{Deserialize, Serialize};
+use std::io;
+use std::path::Path;
+
+pub const XATTR: &str = "user.xdg.tags";
+
+/// Prefix of search terms that filter by tag.
+pub const SEARCH_PREFIX: &str = "tag:";
+
+#[derive(Clone, Copy, Debug, Default, Deserialize, Eq, Hash, PartialEq, Serialize)]
+pub enum TagColor {
+    Red,
+    Orange,
+    Yellow,
+    Green,
+    #[default]
+    Blue,
+    Purple,
+    Gray,
+}
+
+impl TagColor {
+    pub const ALL: [Self; 7] = [
+        Self::Red,
+        Self::Orange,
+        Self::Yellow,
+        Self::Green,
+        Self::Blue,
+        Self::Purple,
+        Self::Gray,
+    ];
+
+    pub const fn color(self) -> Color {
+        match self {
+            Self::Red => Color::from_rgb8(0xe0, 0x1b, 0x24),
+            Self::Orange => Color::from_rgb8(0xff, 0x78, 0x00),
+            Self::Yellow => Color::from_rgb8(0xf6, 0xd3, 0x2d),
+            Self::Green => Color::from_rgb8(0x33, 0xd1, 0x7a),
+            Self::Blue => Color::from_rgb8(0x35, 0x84, 0xe4),
+            Self::Purple => Color::from_rgb8(0x91, 0x41, 0xac),
+            Self::Gray => Color::from_rgb8(0x9a, 0x99, 0x96),
+        }
+    }
+
+    /// Colored circle for use as an icon.
+    pub fn icon(self, size: u16) -> widget::icon::Handle {
+        let [r, g, b, _] = self.color().into_rgba8();
+        let svg = format!(
+            r##"<svg xmlns="http://www.w3.org/2000/svg" width="{size}" height="{size}" viewBox="0 0 16 16"><circle cx="8" cy="8" r="5" fill="#{r:02x}{g:02x}{b:02x}"/></svg>"##
+        );
+        widget::icon::from_svg_bytes(svg.into_bytes())
+    }
+}
+
+#[derive(Clone, Debug, Default, Deserialize, Eq, PartialEq, Serialize)]
+#[serde(default)]
+pub struct Tag {
+    pub name: String,
+    pub color: TagColor,
+}
+
+/// Find the color of a tag, falling back to gray for tags that are not configured.
+pub fn color(tags: &[Tag], name: &str) -> TagColor {
+    tags.iter()
+        .find(|tag| tag.name == name)
+        .map_or(TagColor::Gray, |tag| tag.color)
+}
+
+pub fn parse(value: &[u8]) -> Vec<String> {
+    let mut names: Vec<String> = Vec::new();
+    for name in String::from_utf8_lossy(value).split(',') {
+        let name = name.trim();
+        if !name.is_empty() && !names.iter().any(|x| x == name) {
+            names.push(name.to_string());
+        }
+    }
+    names
+}
+
+pub fn format(names: &[String]) -> Vec<u8> {
+    names.join(",").into_bytes()
+}
+
+/// Read the tags of a path, which is empty if the filesystem has no extended attributes.
+pub fn read(path: &Path) -> Vec<String> {
+    match xattr::get(path, XATTR) {
+        Ok(Some(value)) => parse(&value),
+        Ok(None) => Vec::new(),
+        Err(err) => {
+            if err.kind() != io::ErrorKind::Unsupported {
+                log::debug!("failed to read tags of {}: {}", path.display(), err);
+            }
+            Vec::new()
+        }
+    }
+}
+
+pub fn write(path: &Path, names: &[String]) -> io::Result<()> {
+    if names.is_empty() {
+        match xattr::remove(path, XATTR) {
+            Ok(()) => Ok(()),
+            // The attribute did not exist
+            Err(err) if err.raw_os_error() == Some(libc::ENODATA) => Ok(()),
+            Err(err) => Err(err),
+        }
+    } else {
+        xattr::set(path, XATTR, &format(names))
+    }
+}
+
+/// Add or remove a tag, keeping any other tags.
+pub fn set(path: &Path, name: &str, enabled: bool) -> io::Result<()> {
+    let mut names = read(path);
+    let exists = names.iter().any(|x| x == name);
+    if enabled && !exists {
+        names.push(name.to_string());
+    } else if !enabled && exists {
+        names.retain(|x| x != name);
+    } else {
+        return Ok(());
+    }
+    write(path, &names)
+}
+
+pub fn search_term(name: &str) -> String {
+    format!("{SEARCH_PREFIX}{name}")
+}
+
+/// Split a search term into a tag filter and the remaining name filter.
+pub fn parse_search(term: &str) -> (Option<&str>, &str) {
+    let Some(rest) = term.strip_prefix(SEARCH_PREFIX) else {
+        return (None, term);
+    };
+    match rest.split_once(char::is_whitespace) {
+        Some((name, rest)) => (Some(name), rest.trim_start()),
+        None => (Some(rest), ""),
+    }
+}
+
+/// Row of colored dots for the tags of an item.
+pub fn dots<M: 'static>(names: &[String], tags: &[Tag], size: f32) -> Option<Element<'static, M>> {
+    if names.is_empty() {
+        return None;
+    }
+    let mut row = widget::row::with_capacity(names.len()).spacing(size / 4.0);
+    for name in names {
+        let color = color(tags, name).color();
+        row = row.push(
+            widget::container(widget::space::horizontal())
+                .width(Length::Fixed(size))
+                .height(Length::Fixed(size))
+                .style(move |_theme| widget::container::Style {
+                    background: Some(Background::Color(color)),
+                    border: Border {
+                        radius: (size / 2.0).into(),
+                        ..Default::default()
+                    },
+                    ..Default::default()
+                }),
+        );
+    }
+    Some(row.into())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{format, parse, parse_search};
+
+    #[test]
+    fn parse_and_format_round_trip() {
+        let names = parse(b" work, photos,,work ,");
+        assert_eq!(names, ["work", "photos"]);
+        assert_eq!(format(&names), b"work,photos");
+        assert!(parse(b"").is_empty());
+    }
+
+    #[test]
+    fn search_term_filters() {
+        assert_eq!(parse_search("tag:work report"), (Some("work"), "report"));
+        assert_eq!(parse_search("tag:work"), (Some("work"), ""));
+        assert_eq!(parse_search("report"), (None, "report"));
+    }
+}
@@ -0,0 +1,52 @@
+// Copyright 2026 System76 <info@system76.com>
+// SPDX-License-Identifier: GPL-3.0-only
+
+//! Private session bus for tests of D-Bus services, shared with the portal through the `test-util`
+//! feature.
+//!
+//! Tests using it are skipped when `dbus-daemon` is not installed.
+
+use std::io::{BufRead, BufReader};
+use std::process::{Child, Command, Stdio};
+
+/// Session bus only used by one test, stopped when dropped
+pub struct PrivateBus {
+    child: Child,
+    address: String,
+}
+
+impl PrivateBus {
+    /// Start a bus, or return `None` so that the test can be skipped if `dbus-daemon` is missing.
+    pub fn start() -> Option<Self> {
+        let mut child = match Command::new("dbus-daemon")
+            .args(["--session", "--nofork", "--print-address"])
+            .stdout(Stdio::piped())
+            .spawn()
+        {
+            Ok(child) => child,
+            Err(err) => {
+                eprintln!("skipping test, failed to start dbus-daemon: {err}");
+                return None;
+            }
+        };
+        let mut address = String::new();
+        BufReader::new(child.stdout.take().expect("dbus-daemon has no stdout"))
+            .read_line(&mut address)
+            .expect("failed to read the address of dbus-daemon");
+        Some(Self {
+            child,
+            address: address.trim().to_string(),
+        })
+    }
+
+    pub fn connect(&self) -> zbus::Result<zbus::connection::Builder<'_>> {
+        zbus::connection::Builder::address(self.address.as_str())
+    }
+}
+
+impl Drop for PrivateBus {
+    fn drop(&mut self) {
+        let _ = self.child.kill();
+        let _ = self.child.wait();
+    }
+}
@@ -0,0 +1,374 @@
+// Copyright 2026 System76 <info@system76.com>
+// SPDX-License-Identifier: GPL-3.0-only
+
+use cosmic::iced::advanced::text;
+use cosmic::iced::{Alignment, Length};
+use cosmic::widget::{self, text_editor};
+use cosmic::{Element, font, theme};
+use encoding_rs::Encoding;
+use mime_guess::{Mime, mime};
+use std::fs::File;
+use std::io::{self, Read, Seek, SeekFrom};
+use std::path::Path;
+use std::sync::Arc;
+
+use crate::fl;
+
+/// Bytes read from disk per chunk. Only the first chunk is read when thumbnailing, the rest is
+/// loaded on request so large files never block the UI thread.
+pub const CHUNK_BYTES: usize = 64 * 1024;
+/// Maximum bytes of text kept in a preview; caps shaping and highlighting work.
+pub const MAX_BYTES: u64 = 1024 * 1024;
+
+const FONT_SIZE: f32 = 13.0;
+const LINE_HEIGHT: f32 = 1.4;
+
+/// MIME types outside of `text/*` that contain source code or other plain text.
+const SOURCE_MIMES: &[&str] = &[
+    "application/javascript",
+    "application/json",
+    "application/sql",
+    "application/toml",
+    "application/x-desktop",
+    "application/x-perl",
+    "application/x-php",
+    "application/x-ruby",
+    "application/x-sh",
+    "application/x-shellscript",
+    "application/x-yaml",
+    "application/xml",
+];
+
+/// Returns true if the MIME type can be previewed as text.
+pub fn is_text_mime(mime: &Mime) -> bool {
+    mime.type_() == mime::TEXT || SOURCE_MIMES.contains(&mime.essence_str())
+}
+
+/// Token used to look up the syntax definition, preferring the file extension or name.
+pub fn syntax_token(path: &Path, mime: &Mime) -> String {
+    if let Some(token) = path
+        .extension()
+        .or_else(|| path.file_name())
+        .and_then(|token| token.to_str())
+    {
+        return token.to_lowercase();
+    }
+
+    match mime.subtype().as_str() {
+        "javascript" => "js",
+        "markdown" | "x-markdown" => "md",
+        "plain" => "txt",
+        "rust" | "x-rust" => "rs",
+        "x-chdr" => "h",
+        "x-c++src" => "cpp",
+        "x-csrc" => "c",
+        "x-python" => "py",
+        "x-sh" | "x-shellscript" => "sh",
+        other => other,
+    }
+    .to_string()
+}
+
+/// Guess the encoding of a buffer without a byte order mark.
+fn detect_encoding(buf: &[u8], is_last: bool) -> &'static Encoding {
+    let utf8 = match std::str::from_utf8(buf) {
+        Ok(_) => true,
+        // A multi-byte sequence may be cut off at the end of the chunk
+        Err(err) => !is_last && err.error_len().is_none(),
+    };
+    if utf8 {
+        return encoding_rs::UTF_8;
+    }
+
+    let mut detector = chardetng::EncodingDetector::new();
+    detector.feed(buf, is_last);
+    detector.guess(None, true)
+}
+
+/// Find where a chunk can be cut without splitting a character.
+fn chunk_end(buf: &[u8], encoding: &'static Encoding, is_last: bool) -> usize {
+    if is_last {
+        return buf.len();
+    }
+
+    // Newlines are never part of a multi-byte sequence in ASCII compatible encodings
+    if encoding != encoding_rs::UTF_16LE
+        && encoding != encoding_rs::UTF_16BE
+        && let Some(i) = buf.iter().rposition(|&b| b == b'\n')
+    {
+        return i + 1;
+    }
+
+    // Leave an incomplete character at the end for the next chunk. No supported encoding has
+    // more than three bytes of a character left over.
+    let pending = (0..buf.len().min(4))
+        .find(|&len| !has_pending_bytes(&buf[..buf.len() - len], encoding))
+        .unwrap_or(0);
+    buf.len() - pending
+}
+
+/// Returns true if the decoder is left holding the start of a character after the buffer.
+fn has_pending_bytes(buf: &[u8], encoding: &'static Encoding) -> bool {
+    let mut decoder = encoding.new_decoder_without_bom_handling();
+    let mut text = String::with_capacity(
+        decoder
+            .max_utf8_buffer_length(buf.len())
+            .unwrap_or(buf.len() * 3),
+    );
+    let _ = decoder.decode_to_string(buf, &mut text, false);
+
+    // Flushing the decoder outputs a replacement character for any bytes it held back
+    let mut rest = String::with_capacity(16);
+    let _ = decoder.decode_to_string(&[], &mut rest, true);
+    !rest.is_empty()
+}
+
+/// A decoded chunk of a text file.
+#[derive(Clone, Debug)]
+pub struct TextChunk {
+    pub text: String,
+    pub encoding: &'static Encoding,
+    /// The encoding was guessed from ASCII only, so it is detected again for the next chunk
+    pub ascii: bool,
+    /// Offset in the file after the last decoded byte
+    pub end: u64,
+}
+
+/// Read and decode the chunk starting at `offset`. The encoding is detected if not provided.
+pub fn read_chunk(
+    path: &Path,
+    offset: u64,
+    encoding_opt: Option<&'static Encoding>,
+) -> io::Result<TextChunk> {
+    let mut file = File::open(path)?;
+    let file_size = file.metadata()?.len();
+    file.seek(SeekFrom::Start(offset))?;
+
+    let mut buf = Vec::with_capacity(CHUNK_BYTES);
+    file.take(CHUNK_BYTES as u64).read_to_end(&mut buf)?;
+    let is_last = offset + buf.len() as u64 >= file_size;
+
+    let (encoding, start) = match encoding_opt {
+        Some(encoding) => (encoding, 0),
+        None => match Encoding::for_bom(&buf) {
+            Some(bom) if offset == 0 => bom,
+            _ => (detect_encoding(&buf, is_last), 0),
+        },
+    };
+    let end = chunk_end(&buf, encoding, is_last).max(start);
+    let (text, _had_errors) = encoding.decode_without_bom_handling(&buf[start..end]);
+
+    Ok(TextChunk {
+        text: text.into_owned(),
+        encoding,
+        ascii: encoding_opt.is_none() && start == 0 && buf[..end].is_ascii(),
+        end: offset + end as u64,
+    })
+}
+
+/// Syntax highlighted text preview, loaded a chunk at a time.
+#[derive(Debug)]
+pub struct TextPreview {
+    pub content: text_editor::Content,
+    pub syntax: String,
+    pub encoding: &'static Encoding,
+    /// Only ASCII has been read so far
+    pub ascii: bool,
+    pub file_size: u64,
+    /// Offset in the file where the next chunk starts
+    pub end: u64,
+    pub loading: bool,
+    gutter: String,
+}
+
+impl Clone for TextPreview {
+    fn clone(&self) -> Self {
+        Self {
+            // Content cannot be cloned simply
+            content: text_editor::Content::with_text(&self.content.text()),
+            syntax: self.syntax.clone(),
+            encoding: self.encoding,
+            ascii: self.ascii,
+            file_size: self.file_size,
+            end: self.end,
+            loading: self.loading,
+            gutter: self.gutter.clone(),
+        }
+    }
+}
+
+impl TextPreview {
+    /// Load the first chunk of a file. Returns `None` if no text could be decoded.
+    pub fn load(path: &Path, mime: &Mime, file_size: u64) -> io::Result<Option<Self>> {
+        let chunk = read_chunk(path, 0, None)?;
+        if chunk.text.is_empty() {
+            return Ok(None);
+        }
+
+        let mut preview = Self {
+            content: text_editor::Content::with_text(&chunk.text),
+            syntax: syntax_token(path, mime),
+            encoding: chunk.encoding,
+            ascii: chunk.ascii,
+            file_size,
+            end: chunk.end,
+            loading: false,
+            gutter: String::new(),
+        };
+        preview.update_gutter();
+        Ok(Some(preview))
+    }
+
+    /// Returns true if there is more of the file that can be loaded.
+    pub fn has_more(&self) -> bool {
+        self.end < self.file_size && self.end < MAX_BYTES
+    }
+
+    /// Encoding to read the next chunk with, or `None` to detect it again because the file has
+    /// only contained ASCII so far.
+    pub fn next_encoding(&self) -> Option<&'static Encoding> {
+        (!self.ascii).then_some(self.encoding)
+    }
+
+    /// Append a chunk loaded with [`read_chunk`] to the end of the preview.
+    pub fn append(&mut self, chunk: TextChunk) {
+        self.loading = false;
+        if chunk.end <= self.end {
+            return;
+        }
+        if self.ascii {
+            self.encoding = chunk.encoding;
+            self.ascii = chunk.ascii;
+        }
+        self.end = chunk.end;
+        self.content
+            .perform(text_editor::Action::Move(text_editor::Motion::DocumentEnd));
+        self.content
+            .perform(text_editor::Action::Edit(text_editor::Edit::Paste(
+                Arc::new(chunk.text),
+            )));
+        self.update_gutter();
+    }
+
+    fn update_gutter(&mut self) {
+        self.gutter = (1..=self.content.line_count())
+            .map(|line| line.to_string())
+            .collect::<Vec<_>>()
+            .join("\n");
+    }
+
+    /// Line numbers and highlighted text, with a button to load more if `on_more` is set.
+    pub fn view<'a, M: Clone + 'a>(
+        &'a self,
+        width: Length,
+        height: Length,
+        on_more: Option<M>,
+    ) -> Element<'a, M> {
+        let cosmic::cosmic_theme::Spacing {
+            space_xxs,
+            space_xs,
+            ..
+        } = theme::spacing();
+
+        let line_height = text::LineHeight::Relative(LINE_HEIGHT);
+
+        let gutter = widget::container(
+            widget::text(&self.gutter)
+                .font(font::mono())
+                .size(FONT_SIZE)
+                .line_height(line_height)
+                .wrapping(text::Wrapping::None),
+        )
+        .padding(space_xxs);
+
+        let editor = widget::text_editor::text_editor(&self.content)
+            .font(font::mono())
+            .size(FONT_SIZE)
+            .line_height(line_height)
+            .wrapping(text::Wrapping::None)
+            .padding(space_xxs)
+            .style(text_editor_class);
+        #[cfg(feature = "highlighter")]
+        let editor: Element<'a, M> = editor
+            .highlight(
+                &self.syntax,
+                if theme::is_dark() {
+                    cosmic::iced::highlighter::Theme::Base16Mocha
+                } else {
+                    cosmic::iced::highlighter::Theme::InspiredGitHub
+                },
+            )
+            .into();
+        #[cfg(not(feature = "highlighter"))]
+        let editor: Element<'a, M> = editor.into();
+
+        let mut column = widget::column::with_capacity(2)
+            .spacing(space_xs)
+            .align_x(Alignment::Center)
+            .push(
+                widget::row::with_capacity(2)
+                    .push(gutter)
+                    .push(editor)
+                    .width(Length::Fill),
+            );
+        if self.has_more() {
+            let mut button = widget::button::standard(fl!("load-more"));
+            if !self.loading {
+                button = button.on_press_maybe(on_more);
+            }
+            column = column.push(button);
+        }
+
+        widget::scrollable(column)
+            .width(width)
+            .height(height)
+            .into()
+    }
+}
+
+pub fn text_editor_class(
+    theme: &cosmic::Theme,
+    status: cosmic::widget::text_editor::Status,
+) -> cosmic::iced::widget::text_editor::Style {
+    let cosmic = theme.cosmic();
+    let container = theme.current_container();
+
+    let mut background: cosmic::iced::Color = container.component.base.into();
+    background.a = 0.25;
+    let selection = cosmic.accent.base.into();
+    let value = cosmic.palette.neutral_9.into();
+    let mut placeholder = cosmic.palette.neutral_9;
+    placeholder.alpha = 0.7;
+    let placeholder = placeholder.into();
+
+    match status {
+        cosmic::iced::widget::text_editor::Status::Active
+        | cosmic::iced::widget::text_editor::Status::Disabled => {
+            cosmic::iced::widget::text_editor::Style {
+                background: background.into(),
+                border: cosmic::iced::Border {
+                    radius: cosmic.corner_radii.radius_m.into(),
+                    width: 2.0,
+                    color: container.component.divider.into(),
+                },
+                placeholder,
+                value,
+                selection,
+            }
+        }
+        cosmic::iced::widget::text_editor::Status::Hovered
+        | cosmic::iced::widget::text_editor::Status::Focused { .. } => {
+            cosmic::iced::widget::text_editor::Style {
+                background: background.into(),
+                border: cosmic::iced::Border {
+                    radius: cosmic.corner_radii.radius_m.into(),
+                    width: 2.0,
+                    color: cosmic::iced::Color::from(cosmic.accent.base),
+                },
+                placeholder,
+                value,
+                selection,
+            }
+        }
+    }
+}